@@ -21,6 +21,7 @@ pub(crate) struct ADTSHeader {
 	pub(crate) bitrate: u32,
 	pub(crate) bytes: [u8; 7],
 	pub(crate) has_crc: bool,
+	pub(crate) raw_data_blocks: u8,
 }
 
 impl ADTSHeader {
@@ -108,6 +109,11 @@ impl ADTSHeader {
 			(u16::from(byte4 & 0b11) << 11) | (u16::from(byte5) << 3) | (u16::from(byte6) >> 5);
 		let bitrate = ((u32::from(len) * sample_rate / 1024) * 8) / 1024;
 
+		// OOOOOOPP
+		let byte7 = header[6];
+
+		let raw_data_blocks = (byte7 & 0b11) + 1;
+
 		if needs_crc_skip {
 			log::debug!("Skipping CRC");
 			reader.seek(SeekFrom::Current(2))?;
@@ -124,6 +130,7 @@ impl ADTSHeader {
 			bitrate,
 			bytes: header,
 			has_crc: needs_crc_skip,
+			raw_data_blocks,
 		}))
 	}
 }
@@ -6,6 +6,7 @@ mod error;
 mod header;
 mod properties;
 mod read;
+mod sbr;
 
 use crate::id3::v1::tag::Id3v1Tag;
 use crate::id3::v2::tag::Id3v2Tag;
@@ -10,6 +10,7 @@ use std::time::Duration;
 pub struct AACProperties {
 	pub(crate) version: MpegVersion,
 	pub(crate) audio_object_type: AudioObjectType,
+	pub(crate) extension_audio_object_type: Option<AudioObjectType>,
 	pub(crate) duration: Duration,
 	pub(crate) overall_bitrate: u32,
 	pub(crate) audio_bitrate: u32,
@@ -43,6 +44,18 @@ impl AACProperties {
 		self.audio_object_type
 	}
 
+	/// Extension audio object type
+	///
+	/// ADTS streams can only signal HE-AAC implicitly, so this is determined by checking the first
+	/// few frames for Spectral Band Replication data. When present, this will be
+	/// [`AudioObjectType::SpectralBandReplication`] (HE-AAC v1).
+	///
+	/// NOTE: Parametric Stereo (HE-AAC v2) is only signalled within the SBR data itself, which cannot
+	///       be reached without decoding the stream. HE-AAC v2 streams will be reported as HE-AAC v1.
+	pub fn extension_audio_object_type(&self) -> Option<AudioObjectType> {
+		self.extension_audio_object_type
+	}
+
 	/// Duration of the audio
 	pub fn duration(&self) -> Duration {
 		self.duration
@@ -59,6 +72,8 @@ impl AACProperties {
 	}
 
 	/// Sample rate (Hz)
+	///
+	/// This is the output sample rate, which for HE-AAC streams is double that of the core AAC stream.
 	pub fn sample_rate(&self) -> u32 {
 		self.sample_rate
 	}
@@ -102,12 +117,20 @@ pub(super) fn read_properties(
 	properties: &mut AACProperties,
 	first_frame: ADTSHeader,
 	stream_len: u64,
+	sbr_present: bool,
 ) {
 	properties.version = first_frame.version;
 	properties.audio_object_type = first_frame.audio_object_ty;
 	properties.sample_rate = first_frame.sample_rate;
 	properties.channels = first_frame.channels;
 
+	if sbr_present {
+		log::debug!("Found SBR data, stream is HE-AAC");
+
+		properties.extension_audio_object_type = Some(AudioObjectType::SpectralBandReplication);
+		properties.sample_rate *= 2;
+	}
+
 	match ChannelMask::from_mp4_channels(properties.channels) {
 		Some(mask) => properties.channel_mask = Some(mask),
 		None => {
@@ -1,5 +1,6 @@
 use super::AacFile;
 use super::header::{ADTSHeader, HEADER_MASK};
+use super::sbr::SbrScanner;
 use crate::aac::error::AacParseError;
 use crate::config::{ParseOptions, ParsingMode};
 use crate::id3::v2::header::Id3v2Header;
@@ -135,6 +136,14 @@ where
 			return Err(AacParseError::message("file specifies a bitrate of 0"));
 		}
 
+		// HE-AAC can't be signalled in the ADTS header, so we need to check the frames themselves
+		let mut sbr_scanner = SbrScanner::default();
+		sbr_scanner.scan_frame(
+			reader,
+			&first_frame_header,
+			first_frame_end - u64::from(first_frame_header.len),
+		)?;
+
 		// Read as many frames as we can to try and find the average bitrate
 		reader.seek(SeekFrom::Start(first_frame_end))?;
 
@@ -144,12 +153,21 @@ where
 			first_frame_header.bitrate += header.bitrate;
 			frame_count += 1u32;
 
+			if !sbr_scanner.is_done() {
+				sbr_scanner.scan_frame(reader, &header, frame_end - u64::from(header.len))?;
+			}
+
 			reader.seek(SeekFrom::Start(frame_end))?;
 		}
 
 		first_frame_header.bitrate /= frame_count;
 
-		super::properties::read_properties(&mut file.properties, first_frame_header, stream_len);
+		super::properties::read_properties(
+			&mut file.properties,
+			first_frame_header,
+			stream_len,
+			sbr_scanner.sbr_present(),
+		);
 	}
 
 	Ok(file)
@@ -0,0 +1,278 @@
+//! Implicit SBR detection for ADTS streams
+//!
+//! ADTS headers are only able to describe the core AAC stream, so HE-AAC streams are always
+//! signalled implicitly. The SBR data is instead carried in a fill element (`ID_FIL`) with an
+//! `EXT_SBR_DATA` payload, which encoders place directly before the `ID_END` element that
+//! terminates the raw data block.
+//!
+//! We can't walk the raw data block from the start without decoding the spectral data, so instead
+//! we work backwards from the `ID_END` element, looking for a fill element that ends exactly where
+//! `ID_END` begins.
+//!
+//! Parametric Stereo (HE-AAC v2) is signalled within the SBR data itself, which can't be reached
+//! without decoding the stream, so it is never detected here.
+
+use super::header::ADTSHeader;
+use crate::util::alloc::VecFallibleCapacity;
+use crate::util::bits::BitReader;
+
+use std::io::{Read, Seek, SeekFrom};
+
+const ID_FIL: u32 = 6;
+const ID_END: u32 = 7;
+
+const EXT_SBR_DATA: u32 = 13;
+const EXT_SBR_DATA_CRC: u32 = 14;
+
+// The number of frames to check before making a decision
+const FRAMES_TO_SCAN: u8 = 16;
+
+// SBR can only be used with a core sample rate of at most 24 kHz in the HE-AAC profile
+const MAX_CORE_SAMPLE_RATE: u32 = 24000;
+
+/// Scans the first few frames of an ADTS stream for SBR payloads
+#[derive(Default)]
+pub(super) struct SbrScanner {
+	frames_scanned: u8,
+	frames_with_sbr: u8,
+	// A frame was cut short, so we can't tell whether SBR is present
+	unknown: bool,
+}
+
+impl SbrScanner {
+	pub(super) fn is_done(&self) -> bool {
+		self.unknown || self.frames_scanned >= FRAMES_TO_SCAN
+	}
+
+	/// Whether the majority of the scanned frames carried SBR data
+	///
+	/// If any of the frames were truncated, this is always `false`.
+	pub(super) fn sbr_present(&self) -> bool {
+		!self.unknown && self.frames_scanned > 0 && self.frames_with_sbr * 2 > self.frames_scanned
+	}
+
+	/// Check the frame starting at `frame_start` for an SBR payload
+	///
+	/// This will leave the reader in an unspecified position.
+	pub(super) fn scan_frame<R>(
+		&mut self,
+		reader: &mut R,
+		header: &ADTSHeader,
+		frame_start: u64,
+	) -> std::io::Result<()>
+	where
+		R: Read + Seek,
+	{
+		if self.is_done() || header.sample_rate > MAX_CORE_SAMPLE_RATE {
+			return Ok(());
+		}
+
+		// With multiple raw data blocks in a frame, we can no longer tell where the first one ends
+		if header.raw_data_blocks > 1 {
+			return Ok(());
+		}
+
+		let header_len = if header.has_crc { 9 } else { 7 };
+		let Some(payload_len) = u64::from(header.len).checked_sub(header_len) else {
+			return Ok(());
+		};
+
+		let Ok(mut payload) = Vec::try_with_capacity_stable(payload_len as usize) else {
+			return Ok(());
+		};
+
+		reader.seek(SeekFrom::Start(frame_start + header_len))?;
+		reader.take(payload_len).read_to_end(&mut payload)?;
+
+		if payload.len() as u64 != payload_len {
+			log::debug!("Encountered a truncated ADTS frame, unable to check for SBR data");
+			self.unknown = true;
+			return Ok(());
+		}
+
+		self.frames_scanned += 1;
+		if raw_data_block_has_sbr(&payload) {
+			self.frames_with_sbr += 1;
+		}
+
+		Ok(())
+	}
+}
+
+fn raw_data_block_has_sbr(payload: &[u8]) -> bool {
+	// The block is byte aligned with zeros after the `ID_END` element, so the last set bit
+	// is the end of `ID_END`.
+	let Some(last_byte_idx) = payload.iter().rposition(|b| *b != 0) else {
+		return false;
+	};
+
+	let last_set_bit = (last_byte_idx * 8) + 7 - payload[last_byte_idx].trailing_zeros() as usize;
+	let Some(end_element_start) = (last_set_bit + 1).checked_sub(3) else {
+		return false;
+	};
+
+	let mut reader = BitReader::new(payload);
+	reader.set_position(end_element_start);
+	if reader.read_bits(3).ok() != Some(ID_END) {
+		return false;
+	}
+
+	// A fill element is laid out as:
+	//
+	// ID_FIL (3)
+	// count (4)
+	// if count == 15
+	//     esc_count (8), the payload is `count + esc_count - 1` bytes long
+	// extension payload (count * 8)
+	for count in 1..15u32 {
+		let element_len = 7 + (count as usize * 8);
+		if let Some(start) = end_element_start.checked_sub(element_len)
+			&& is_sbr_fill_element(&mut reader, start, count, None)
+		{
+			return true;
+		}
+	}
+
+	for esc_count in 0..=255u32 {
+		let payload_bytes = 14 + esc_count as usize;
+		let element_len = 15 + (payload_bytes * 8);
+		let Some(start) = end_element_start.checked_sub(element_len) else {
+			break;
+		};
+
+		if is_sbr_fill_element(&mut reader, start, 15, Some(esc_count)) {
+			return true;
+		}
+	}
+
+	false
+}
+
+fn is_sbr_fill_element(
+	reader: &mut BitReader<'_>,
+	start: usize,
+	count: u32,
+	esc_count: Option<u32>,
+) -> bool {
+	fn check(reader: &mut BitReader<'_>, count: u32, esc_count: Option<u32>) -> Option<bool> {
+		if reader.read_bits(3).ok()? != ID_FIL || reader.read_bits(4).ok()? != count {
+			return Some(false);
+		}
+
+		if let Some(esc_count) = esc_count
+			&& reader.read_bits(8).ok()? != esc_count
+		{
+			return Some(false);
+		}
+
+		let extension_type = reader.read_bits(4).ok()?;
+		match extension_type {
+			EXT_SBR_DATA => {},
+			EXT_SBR_DATA_CRC => {
+				// bs_sbr_crc_bits
+				reader.skip_bits(10).ok()?;
+			},
+			_ => return Some(false),
+		}
+
+		let header_present = reader.read_bit().ok()?;
+		if header_present {
+			// Skipping 12 bits
+			// bs_amp_res (1)
+			// bs_start_freq (4)
+			// bs_stop_freq (4)
+			// bs_xover_band (3)
+			reader.skip_bits(12).ok()?;
+
+			// bs_reserved, which must be zero
+			if reader.read_bits(2).ok()? != 0 {
+				return Some(false);
+			}
+		}
+
+		Some(true)
+	}
+
+	reader.set_position(start);
+	check(reader, count, esc_count).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{SbrScanner, raw_data_block_has_sbr};
+	use crate::aac::header::ADTSHeader;
+	use crate::mp4::AudioObjectType;
+	use crate::mpeg::MpegVersion;
+
+	use std::io::Cursor;
+
+	// Packs a list of (value, bit count) pairs into a byte-aligned buffer
+	fn pack(fields: &[(u32, u8)]) -> Vec<u8> {
+		let mut bits = Vec::new();
+		for (value, count) in fields {
+			for i in (0..*count).rev() {
+				bits.push((value >> i) & 1 == 1);
+			}
+		}
+
+		bits.chunks(8)
+			.map(|chunk| {
+				chunk
+					.iter()
+					.enumerate()
+					.fold(0u8, |acc, (i, bit)| acc | (u8::from(*bit) << (7 - i)))
+			})
+			.collect()
+	}
+
+	#[test_log::test]
+	fn sbr_fill_element() {
+		// Some junk standing in for a single channel element
+		let mut fields = vec![(0b0001_0110, 8), (0xABCD, 16), (0b1, 1)];
+
+		// ID_FIL, count = 3, EXT_SBR_DATA, no header, 19 bits of SBR data
+		fields.extend([(6, 3), (3, 4), (13, 4), (0, 1), (0x5_5555, 19)]);
+
+		// ID_END
+		fields.push((7, 3));
+
+		assert!(raw_data_block_has_sbr(&pack(&fields)));
+	}
+
+	#[test_log::test]
+	fn padding_fill_element() {
+		let mut fields = vec![(0b0001_0110, 8), (0xABCD, 16), (0b1, 1)];
+
+		// ID_FIL, count = 2, EXT_FILL, followed by the padding pattern
+		fields.extend([(6, 3), (2, 4), (0, 4), (0b1010_0101_1010, 12)]);
+		fields.push((7, 3));
+
+		assert!(!raw_data_block_has_sbr(&pack(&fields)));
+	}
+
+	#[test_log::test]
+	fn truncated_frame() {
+		let header = ADTSHeader {
+			version: MpegVersion::V4,
+			audio_object_ty: AudioObjectType::AacLowComplexity,
+			sample_rate: 22050,
+			channels: 2,
+			copyright: false,
+			original: false,
+			len: 64,
+			bitrate: 0,
+			bytes: [0; 7],
+			has_crc: false,
+			raw_data_blocks: 1,
+		};
+
+		// Only half of the frame is present
+		let mut scanner = SbrScanner::default();
+		scanner
+			.scan_frame(&mut Cursor::new([0; 32]), &header, 0)
+			.unwrap();
+
+		assert!(scanner.is_done());
+		assert!(!scanner.sbr_present());
+	}
+}
@@ -5,12 +5,15 @@
 //! It is possible for an `APE` file to contain an `ID3v2` tag. For the sake of data preservation,
 //! this tag will be read, but **cannot** be written. The only tags allowed by spec are `APEv1/2` and
 //! `ID3v1`.
+//!
+//! Similarly, `Lyrics3` tags will be read, but can only be removed.
 pub(crate) mod constants;
 pub mod error;
 mod properties;
 mod read;
 pub(crate) mod tag;
 
+use crate::id3::lyrics3::tag::Lyrics3Tag;
 use crate::id3::v1::tag::Id3v1Tag;
 use crate::id3::v2::tag::Id3v2Tag;
 
@@ -20,8 +23,8 @@ use lofty_attr::LoftyFile;
 
 pub use crate::picture::APE_PICTURE_TYPES;
 pub use properties::ApeProperties;
-pub use tag::ApeTag;
 pub use tag::item::ApeItem;
+pub use tag::{ApeItemIssue, ApeItemIssueKind, ApeTag, ApeTagPlacement, ApeTagVersion};
 
 /// An APE file
 #[derive(LoftyFile)]
@@ -37,6 +40,9 @@ pub struct ApeFile {
 	/// An APEv1/v2 tag
 	#[lofty(tag_type = "Ape")]
 	pub(crate) ape_tag: Option<ApeTag>,
+	/// A Lyrics3v1/v2 tag (read only)
+	#[lofty(tag_type = "Lyrics3")]
+	pub(crate) lyrics3_tag: Option<Lyrics3Tag>,
 	/// The file's audio properties
 	pub(crate) properties: ApeProperties,
 }
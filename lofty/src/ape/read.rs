@@ -5,10 +5,12 @@ use crate::ape::tag::header::read_ape_header;
 use crate::ape::tag::read::{read_ape_tag, read_ape_tag_with_header};
 use crate::config::ParseOptions;
 use crate::error::{FakeTagError, SizeMismatchError, TagParseError};
+use crate::id3::lyrics3::read::find_lyrics3;
+use crate::id3::lyrics3::tag::Lyrics3Tag;
 use crate::id3::v1::tag::Id3v1Tag;
 use crate::id3::v2::read::parse_id3v2;
 use crate::id3::v2::tag::Id3v2Tag;
-use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1, find_id3v2, find_lyrics3v2};
+use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1, find_id3v2};
 
 use std::io::{Read, Seek, SeekFrom};
 
@@ -29,6 +31,7 @@ where
 	let mut id3v2_tag: Option<Id3v2Tag> = None;
 	let mut id3v1_tag: Option<Id3v1Tag> = None;
 	let mut ape_tag: Option<ApeTag> = None;
+	let mut lyrics3_tag: Option<Lyrics3Tag> = None;
 
 	let find_id3v2_config = if parse_options.read_tags {
 		FindId3v2Config::READ_TAG
@@ -96,7 +99,12 @@ where
 					let ape = read_ape_tag_with_header(data, ape_header, parse_options)
 						.map_err(TagParseError::from)?;
 					ape_tag = Some(ape);
+				} else {
+					// The size includes the header we already read
+					data.seek(SeekFrom::Current(i64::from(ape_header.size) - 32))?;
 				}
+
+				data.read_exact(&mut header)?;
 			},
 			_ => {
 				return Err(ApeParseError::message(
@@ -125,30 +133,56 @@ where
 		stream_len = new_stream_length;
 	}
 
-	// Next, check for a Lyrics3v2 tag, and skip over it, as it's no use to us
-	let ID3FindResults(_, lyrics3v2_size) = find_lyrics3v2(data)?;
-	let Some(new_stream_length) = stream_len.checked_sub(u64::from(lyrics3v2_size)) else {
-		return Err(SizeMismatchError.into());
-	};
+	// Next, check for a Lyrics3 tag
+	let ID3FindResults(lyrics3_header, lyrics3) =
+		find_lyrics3(data, parse_options.read_tags, parse_options.parsing_mode)?;
+	if let Some(lyrics3_header) = lyrics3_header {
+		lyrics3_tag = lyrics3;
+		let Some(new_stream_length) = stream_len.checked_sub(u64::from(lyrics3_header.size)) else {
+			return Err(SizeMismatchError.into());
+		};
 
-	stream_len = new_stream_length;
+		stream_len = new_stream_length;
+	}
 
 	// Next, search for an APE tag footer
 	//
 	// Starts with ['A', 'P', 'E', 'T', 'A', 'G', 'E', 'X']
 	// Exactly 32 bytes long
 	// Strongly recommended to be at the end of the file
+	let ape_end = data.stream_position()?;
 	data.seek(SeekFrom::Current(-32))?;
 
+	let mut ape_start = None;
 	if let (tag, Some(header)) =
 		read_ape_tag(data, true, parse_options).map_err(TagParseError::from)?
 	{
 		stream_len -= u64::from(header.size);
 		ape_tag = tag;
+		ape_start = ape_end.checked_sub(u64::from(header.size));
 	}
 
 	let file_length = data.stream_position()?;
 
+	// The Lyrics3 tag may be between the APE and ID3v1 tags
+	if lyrics3_header.is_none()
+		&& let Some(ape_start) = ape_start
+	{
+		data.seek(SeekFrom::Start(ape_start))?;
+
+		let ID3FindResults(lyrics3_header, lyrics3) =
+			find_lyrics3(data, parse_options.read_tags, parse_options.parsing_mode)?;
+		if let Some(lyrics3_header) = lyrics3_header {
+			lyrics3_tag = lyrics3;
+			let Some(new_stream_length) = stream_len.checked_sub(u64::from(lyrics3_header.size))
+			else {
+				return Err(SizeMismatchError.into());
+			};
+
+			stream_len = new_stream_length;
+		}
+	}
+
 	// Go back to the MAC header to read properties
 	data.seek(SeekFrom::Start(mac_start))?;
 
@@ -156,6 +190,7 @@ where
 		id3v1_tag,
 		id3v2_tag,
 		ape_tag,
+		lyrics3_tag,
 		properties: if parse_options.read_properties {
 			super::properties::read_properties(
 				data,
@@ -159,6 +159,7 @@ pub(crate) struct ApeTagEncodingError {
 		std::io::Error,
 		crate::util::alloc::AllocationError,
 		crate::error::TooMuchDataError,
+		crate::util::text::TextEncodingError,
 	))]
 	source: Box<dyn core::error::Error + Send + Sync + 'static>,
 }
@@ -9,6 +9,7 @@ use byteorder::{LittleEndian, ReadBytesExt};
 
 #[derive(Copy, Clone)]
 pub(crate) struct ApeHeader {
+	pub(crate) version: u32,
 	pub(crate) size: u32,
 	pub(crate) item_count: u32,
 }
@@ -28,19 +29,19 @@ where
 	}
 
 	let item_count = data.read_u32::<LittleEndian>()?;
+	let flags = data.read_u32::<LittleEndian>()?;
 
 	if footer {
 		// No point in reading the rest of the footer, just seek back to the end of the header
-		data.seek(SeekFrom::Current(i64::from(size - 12).neg()))?;
+		data.seek(SeekFrom::Current(i64::from(size - 8).neg()))?;
 	} else {
-		// There are 12 bytes remaining in the header
-		// Flags (4)
-		// Reserved (8)
-		data.seek(SeekFrom::Current(12))?;
+		// There are 8 reserved bytes remaining in the header
+		data.seek(SeekFrom::Current(8))?;
 	}
 
-	// Version 1 doesn't include a header
-	if version == 2000 {
+	// Version 1 doesn't include a header, and it's optional in version 2
+	// Bit 31 set: tag contains a header
+	if version == 2000 && (!footer || flags & (1 << 31) != 0) {
 		size = size.saturating_add(32);
 	}
 
@@ -48,5 +49,9 @@ where
 		return Err(SizeMismatchError.into());
 	}
 
-	Ok(ApeHeader { size, item_count })
+	Ok(ApeHeader {
+		version,
+		size,
+		item_count,
+	})
 }
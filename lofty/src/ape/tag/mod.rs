@@ -1,7 +1,9 @@
 pub(super) mod error;
 pub(crate) mod header;
 pub(crate) mod item;
+mod placement;
 pub(crate) mod read;
+mod version;
 mod write;
 
 use crate::ape::tag::error::ApeTagEncodingError;
@@ -24,6 +26,9 @@ use std::ops::Deref;
 
 use lofty_attr::tag;
 
+pub use placement::ApeTagPlacement;
+pub use version::{ApeItemIssue, ApeItemIssueKind, ApeTagVersion};
+
 macro_rules! impl_accessor {
 	($($name:ident => $($key:literal)|+;)+) => {
 		paste::paste! {
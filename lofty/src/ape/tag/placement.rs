@@ -0,0 +1,14 @@
+/// Where to place an APE tag at the end of a file
+///
+/// See [`WriteOptions::ape_tag_placement`](crate::config::WriteOptions::ape_tag_placement).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ApeTagPlacement {
+	/// Before any Lyrics3 and ID3v1 tags (`[audio][APE][Lyrics3][ID3v1]`)
+	///
+	/// This is the layout recommended by the APE specification.
+	#[default]
+	BeforeLyrics3,
+	/// After any Lyrics3 tag, directly before the ID3v1 tag (`[audio][Lyrics3][APE][ID3v1]`)
+	AfterLyrics3,
+}
@@ -8,7 +8,7 @@ use crate::config::ParseOptions;
 use crate::error::SizeMismatchError;
 use crate::macros::try_vec;
 use crate::tag::ItemValue;
-use crate::util::text::utf8_decode;
+use crate::util::text::{latin1_decode, utf8_decode, utf8_decode_str};
 
 use std::io::{Read, Seek, SeekFrom};
 
@@ -30,6 +30,7 @@ where
 		key: String,
 		flags: u32,
 		value_size: u32,
+		version: u32,
 	) -> Result<ApeItem, ApeTagItemParseError>
 	where
 		R: Read + Seek,
@@ -47,6 +48,11 @@ where
 		}
 
 		let parsed_value = match item_type {
+			// APEv1 predates the UTF-8 requirement, Latin-1 is the best guess
+			0 if version == 1000 => ItemValue::Text(match utf8_decode_str(&value) {
+				Ok(val) => val.to_owned(),
+				Err(_) => latin1_decode(&value),
+			}),
 			0 => ItemValue::Text(match utf8_decode(value) {
 				Ok(val) => val,
 				Err(e) => return Err(ApeTagItemParseError::from((key, e))),
@@ -99,7 +105,7 @@ where
 			continue;
 		}
 
-		let item = parse_item_with_key(reader, key, flags, value_size)?;
+		let item = parse_item_with_key(reader, key, flags, value_size, header.version)?;
 		tag.insert(item);
 	}
 
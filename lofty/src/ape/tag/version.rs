@@ -0,0 +1,124 @@
+use super::ApeTag;
+use super::item::ApeItemRef;
+use crate::tag::item::ItemValueRef;
+
+/// The version of an APE tag
+///
+/// See [`WriteOptions::ape_tag_version`](crate::config::WriteOptions::ape_tag_version).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ApeTagVersion {
+	/// APEv1 (version 1000)
+	///
+	/// This is a far more limited version, but may be required for older software and hardware
+	/// (such as Musepack SV7 players). When writing an APEv1 tag:
+	///
+	/// * No header is written, only a footer
+	/// * Only text items are written, see [`ApeItemIssueKind::NonTextValue`]
+	/// * Text is written as Latin-1
+	/// * The read only flag is discarded, for both items and the tag itself
+	V1,
+	/// APEv2 (version 2000)
+	#[default]
+	V2,
+}
+
+impl ApeTagVersion {
+	pub(crate) fn version_number(self) -> u32 {
+		match self {
+			Self::V1 => 1000,
+			Self::V2 => 2000,
+		}
+	}
+}
+
+/// The reason an [`ApeItem`](crate::ape::ApeItem) can't be represented in an APE tag version
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ApeItemIssueKind {
+	/// The item has a binary or locator value, and will be skipped
+	NonTextValue,
+	/// The item's text contains characters outside of Latin-1
+	///
+	/// If [`WriteOptions::lossy_text_encoding`] is enabled, these characters will be replaced
+	/// with `'?'`, otherwise the write will fail.
+	///
+	/// [`WriteOptions::lossy_text_encoding`]: crate::config::WriteOptions::lossy_text_encoding
+	NonLatin1Text,
+	/// The item is marked as read only, which will be discarded
+	ReadOnly,
+}
+
+/// An [`ApeItem`](crate::ape::ApeItem) that can't be fully represented in an APE tag version
+///
+/// See [`ApeTag::validate`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApeItemIssue {
+	/// The key of the item
+	pub key: String,
+	/// Why the item can't be represented
+	pub kind: ApeItemIssueKind,
+}
+
+impl ApeTag {
+	/// Check which items can't be fully represented in an APE tag version
+	///
+	/// APEv2 can represent any valid [`ApeItem`](crate::ape::ApeItem), so this is only relevant
+	/// for [`ApeTagVersion::V1`].
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::ape::{ApeItem, ApeItemIssueKind, ApeTag, ApeTagVersion};
+	/// use lofty::tag::{Accessor, ItemValue};
+	///
+	/// # fn main() -> Result<(), lofty::ape::error::ApeTagItemValidationError> {
+	/// let mut ape_tag = ApeTag::new();
+	/// ape_tag.set_title(String::from("Foo title"));
+	/// ape_tag.insert(ApeItem::new(
+	/// 	String::from("Cover Art (Front)"),
+	/// 	ItemValue::Binary(vec![1, 2, 3]),
+	/// )?);
+	///
+	/// assert!(ape_tag.validate(ApeTagVersion::V2).is_empty());
+	///
+	/// // The cover art can't be written to an APEv1 tag
+	/// let issues = ape_tag.validate(ApeTagVersion::V1);
+	/// assert_eq!(issues.len(), 1);
+	/// assert_eq!(issues[0].key, "Cover Art (Front)");
+	/// assert_eq!(issues[0].kind, ApeItemIssueKind::NonTextValue);
+	/// # Ok(()) }
+	/// ```
+	pub fn validate(&self, version: ApeTagVersion) -> Vec<ApeItemIssue> {
+		let mut issues = Vec::new();
+		for item in &self.items {
+			if let Some(kind) = item_issue(&item.into(), version) {
+				issues.push(ApeItemIssue {
+					key: item.key().to_owned(),
+					kind,
+				});
+			}
+		}
+
+		issues
+	}
+}
+
+/// The first issue preventing an item from being written as-is
+pub(super) fn item_issue(
+	item: &ApeItemRef<'_>,
+	version: ApeTagVersion,
+) -> Option<ApeItemIssueKind> {
+	if version != ApeTagVersion::V1 {
+		return None;
+	}
+
+	match &item.value {
+		ItemValueRef::Text(text) if text.chars().any(|c| c as u32 > 0xFF) => {
+			Some(ApeItemIssueKind::NonLatin1Text)
+		},
+		ItemValueRef::Text(_) if item.read_only => Some(ApeItemIssueKind::ReadOnly),
+		ItemValueRef::Text(_) => None,
+		ItemValueRef::Binary(_) | ItemValueRef::Locator(_) => Some(ApeItemIssueKind::NonTextValue),
+	}
+}
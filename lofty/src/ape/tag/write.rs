@@ -1,5 +1,7 @@
 use super::ApeTagRef;
 use super::item::ApeItemRef;
+use super::placement::ApeTagPlacement;
+use super::version::{self, ApeItemIssueKind, ApeTagVersion};
 use crate::ape::ApeTag;
 use crate::ape::constants::APE_PREAMBLE;
 use crate::ape::tag::error::ApeTagEncodingError;
@@ -9,10 +11,12 @@ use crate::error::{
 	FileEncodingError, FileParseError, SizeMismatchError, TagEncodingError, TagParseError,
 	TooMuchDataError,
 };
-use crate::id3::{FindId3v2Config, find_id3v1, find_id3v2, find_lyrics3v2};
+use crate::id3::lyrics3::read::find_lyrics3;
+use crate::id3::{FindId3v2Config, find_id3v1, find_id3v2};
 use crate::io::VerifiedFile;
 use crate::tag::item::ItemValueRef;
 use crate::util::io::FileLike;
+use crate::util::text::latin1_encode;
 
 use std::io::{Cursor, Seek, SeekFrom, Write};
 use std::ops::Range;
@@ -23,7 +27,7 @@ use byteorder::{LittleEndian, WriteBytesExt};
 pub(crate) fn write_to<'a, F, I>(
 	file: VerifiedFile<'_, F>,
 	tag_ref: &mut ApeTagRef<'a, I>,
-	write_options: WriteOptions,
+	mut write_options: WriteOptions,
 ) -> Result<(), FileEncodingError>
 where
 	I: Iterator<Item = ApeItemRef<'a>>,
@@ -33,6 +37,19 @@ where
 
 	let mut original_tags = find_existing_ape_tags(&mut file, write_options)?;
 
+	// A tag at the beginning of the file can't be found without its header, which APEv1 tags
+	// don't have
+	let mut keep_leading_tag =
+		original_tags.header.is_some() && !write_options.ape_move_leading_tag;
+	if keep_leading_tag && write_options.ape_tag_version == ApeTagVersion::V1 {
+		log::warn!("APEv1 tags can't be at the beginning of a file, moving the tag to the end");
+		keep_leading_tag = false;
+	}
+
+	if keep_leading_tag {
+		write_options.ape_tag_header = true;
+	}
+
 	// Preserve any metadata marked as read only
 	let tag;
 	if let Some(read_only) = original_tags.read_only.take() {
@@ -47,14 +64,25 @@ where
 			.map_err(TagEncodingError::from)?;
 	}
 
-	write_tag(original_tags, tag, &mut file)
+	let write_position = match original_tags.header.as_ref() {
+		Some(header) if keep_leading_tag => header.start,
+		_ => match write_options.ape_tag_placement {
+			ApeTagPlacement::BeforeLyrics3 => original_tags.lyrics3_start,
+			ApeTagPlacement::AfterLyrics3 => original_tags.id3v1_start,
+		},
+	};
+
+	write_tag(original_tags, tag, write_position, &mut file)
 }
 
 struct ApeTags {
 	read_only: Option<ApeTag>,
 	header: Option<Range<usize>>,
 	footer: Option<Range<usize>>,
-	write_position: u64,
+	/// The start of the Lyrics3 tag, or where it would be
+	lyrics3_start: usize,
+	/// The start of the ID3v1 tag, or the end of the file
+	id3v1_start: usize,
 }
 
 fn find_existing_ape_tags<F>(
@@ -67,9 +95,6 @@ where
 	// We don't actually need the ID3v2 tag, but reading it will seek to the end of it if it exists
 	find_id3v2(file, FindId3v2Config::NO_READ_TAG).map_err(TagParseError::from)?;
 
-	let mut ape_preamble = [0; 8];
-	file.read_exact(&mut ape_preamble)?;
-
 	// We have to check the APE tag for any read only items first
 	let mut read_only = None;
 
@@ -78,33 +103,30 @@ where
 	let mut header_tag_location = None;
 
 	let start = file.stream_position()? as usize;
-	match read::read_ape_tag(file, false, write_options.parse_options)
-		.map_err(TagParseError::from)?
+	if let (Some(mut existing_tag), Some(header)) =
+		read::read_ape_tag(file, false, write_options.parse_options).map_err(TagParseError::from)?
 	{
-		(Some(mut existing_tag), Some(header)) => {
-			if write_options.respect_read_only {
-				// Only keep metadata around that's marked read only
-				existing_tag.items.retain(|i| i.read_only);
+		if write_options.respect_read_only {
+			// Only keep metadata around that's marked read only
+			existing_tag.items.retain(|i| i.read_only);
 
-				if !existing_tag.items.is_empty() {
-					read_only = Some(existing_tag)
-				}
+			if !existing_tag.items.is_empty() {
+				read_only = Some(existing_tag)
 			}
+		}
 
-			header_tag_location = Some(start..start + header.size as usize);
-		},
-		_ => {
-			file.seek(SeekFrom::Current(-8))?;
-		},
+		header_tag_location = Some(start..start + header.size as usize);
 	}
 
-	// Skip over ID3v1 and Lyrics3v2 tags
+	// Skip over ID3v1 and Lyrics3 tags
 	find_id3v1(file, false, write_options.parse_options.parsing_mode)
 		.map_err(TagParseError::from)?;
-	find_lyrics3v2(file)?;
+	let id3v1_start = file.stream_position()? as usize;
+
+	let lyrics3 = find_lyrics3(file, false, write_options.parse_options.parsing_mode)?;
 
 	// In case there's no ape tag already, this is the spot it belongs
-	let ape_position = file.stream_position()?;
+	let mut lyrics3_start = file.stream_position()? as usize;
 
 	// Now search for an APE tag at the end
 	file.seek(SeekFrom::Current(-32))?;
@@ -137,17 +159,30 @@ where
 		};
 
 		footer_tag_location = Some(start..start + size as usize);
+
+		// The APE tag may be between a Lyrics3 and ID3v1 tag
+		if lyrics3.0.is_none() && start >= 15 {
+			file.seek(SeekFrom::Start(start as u64))?;
+			find_lyrics3(file, false, write_options.parse_options.parsing_mode)?;
+			lyrics3_start = file.stream_position()? as usize;
+		}
 	}
 
 	Ok(ApeTags {
 		read_only,
 		header: header_tag_location,
 		footer: footer_tag_location,
-		write_position: ape_position,
+		lyrics3_start,
+		id3v1_start,
 	})
 }
 
-fn write_tag<F>(ape_tags: ApeTags, new_tag: Vec<u8>, file: &mut F) -> Result<(), FileEncodingError>
+fn write_tag<F>(
+	ape_tags: ApeTags,
+	new_tag: Vec<u8>,
+	write_position: usize,
+	file: &mut F,
+) -> Result<(), FileEncodingError>
 where
 	F: FileLike,
 {
@@ -156,21 +191,23 @@ where
 	let mut file_bytes = Vec::new();
 	file.read_to_end(&mut file_bytes)?;
 
-	// Write the tag in the appropriate place
-	if let Some(range) = ape_tags.footer {
-		file_bytes.splice(range, new_tag);
-	} else {
-		file_bytes.splice(
-			ape_tags.write_position as usize..ape_tags.write_position as usize,
-			new_tag,
-		);
-	}
+	// Remove the existing tags, from the end of the file first so the ranges stay valid
+	let mut write_position = write_position;
+	for range in [ape_tags.footer, ape_tags.header].into_iter().flatten() {
+		if range.end > file_bytes.len() {
+			return Err(SizeMismatchError.into());
+		}
+
+		if range.end <= write_position {
+			write_position -= range.len();
+		}
 
-	// Now, if there was a tag at the beginning, remove it
-	if let Some(range) = ape_tags.header {
 		file_bytes.drain(range);
 	}
 
+	// Now write the tag in the appropriate place
+	file_bytes.splice(write_position..write_position, new_tag);
+
 	file.rewind()?;
 	file.truncate(0)?;
 	file.write_all(&file_bytes)?;
@@ -201,33 +238,51 @@ where
 		drop(read_only);
 	}
 
+	let version = write_options.ape_tag_version;
+	if version == ApeTagVersion::V1 && tag.read_only {
+		log::warn!("APEv1 tags cannot be marked read only, discarding");
+	}
+
 	let mut tag_write = Cursor::new(Vec::<u8>::new());
 
 	let mut item_count = 0_u32;
 
 	for item in peek {
-		let (mut flags, value) = match item.value {
-			ItemValueRef::Binary(value) => {
-				tag_write.write_u32::<LittleEndian>(value.len() as u32)?;
-
-				(1_u32 << 1, value)
+		match version::item_issue(&item, version) {
+			Some(ApeItemIssueKind::NonTextValue) => {
+				log::warn!(
+					"Skipping item '{}', APEv1 tags can only contain text",
+					item.key
+				);
+				continue;
 			},
-			ItemValueRef::Text(ref value) => {
-				tag_write.write_u32::<LittleEndian>(value.len() as u32)?;
-
-				(0_u32, value.as_bytes())
+			Some(ApeItemIssueKind::ReadOnly) => {
+				log::warn!(
+					"APEv1 tag items cannot be marked read only, discarding flag for '{}'",
+					item.key
+				);
 			},
-			ItemValueRef::Locator(value) => {
-				tag_write.write_u32::<LittleEndian>(value.len() as u32)?;
+			_ => {},
+		}
+
+		let v1_text;
+		let (mut flags, value) = match item.value {
+			ItemValueRef::Text(ref value) if version == ApeTagVersion::V1 => {
+				v1_text = latin1_encode(value, write_options.lossy_text_encoding)
+					.collect::<Result<Vec<u8>, _>>()?;
 
-				(2_u32 << 1, value.as_bytes())
+				(0_u32, v1_text.as_slice())
 			},
+			ItemValueRef::Binary(value) => (1_u32 << 1, value),
+			ItemValueRef::Text(ref value) => (0_u32, value.as_bytes()),
+			ItemValueRef::Locator(value) => (2_u32 << 1, value.as_bytes()),
 		};
 
-		if item.read_only {
+		if item.read_only && version == ApeTagVersion::V2 {
 			flags |= 1_u32
 		}
 
+		tag_write.write_u32::<LittleEndian>(value.len() as u32)?;
 		tag_write.write_u32::<LittleEndian>(flags)?;
 		tag_write.write_all(item.key.as_bytes())?;
 		tag_write.write_u8(0)?;
@@ -236,6 +291,11 @@ where
 		item_count += 1;
 	}
 
+	// Every item may have been skipped
+	if item_count == 0 {
+		return Ok(Vec::new());
+	}
+
 	let size = tag_write.get_ref().len();
 
 	if size as u64 + 32 > u64::from(u32::MAX) {
@@ -247,15 +307,28 @@ where
 
 	footer.write_all(APE_PREAMBLE)?;
 	// This is the APE tag version
-	// Even if we read a v1 tag, we end up adding a header anyway
-	footer.write_u32::<LittleEndian>(2000)?;
+	footer.write_u32::<LittleEndian>(version.version_number())?;
 	// The total size includes the 32 bytes of the footer
 	footer.write_u32::<LittleEndian>((size + 32) as u32)?;
 	footer.write_u32::<LittleEndian>(item_count)?;
+
+	// APEv1 has no flags, and no header
+	if version == ApeTagVersion::V1 {
+		footer.write_u32::<LittleEndian>(0)?;
+		footer.write_u64::<LittleEndian>(0)?;
+
+		tag_write.write_all(footer.get_ref())?;
+		return Ok(tag_write.into_inner());
+	}
+
 	// Bit 29 unset: this is the footer
 	// Bit 30 set: tag contains a footer
-	// Bit 31 set: tag contains a header
-	let mut footer_flags = (1_u32 << 30) | (1_u32 << 31);
+	let mut footer_flags = 1_u32 << 30;
+
+	if write_options.ape_tag_header {
+		// Bit 31 set: tag contains a header
+		footer_flags |= 1_u32 << 31;
+	}
 
 	if tag.read_only {
 		// Bit 0 set: tag is read only
@@ -270,6 +343,10 @@ where
 
 	let mut tag_write = tag_write.into_inner();
 
+	if !write_options.ape_tag_header {
+		return Ok(tag_write);
+	}
+
 	// The header is exactly the same as the footer, except for the flags
 	// Just reuse the footer and overwrite the flags
 	footer.seek(SeekFrom::Current(-12))?;
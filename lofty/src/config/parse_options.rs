@@ -1,3 +1,5 @@
+use crate::mp4::Mp4AudioTrack;
+
 /// Options to control how Lofty parses a file
 ///
 /// If you plan to write back to a file, you should reuse these options in
@@ -13,6 +15,8 @@ pub struct ParseOptions {
 	pub(crate) max_junk_bytes: usize,
 	pub(crate) read_cover_art: bool,
 	pub(crate) implicit_conversions: bool,
+	pub(crate) scan_frames: bool,
+	pub(crate) mp4_audio_track: Mp4AudioTrack,
 }
 
 impl Default for ParseOptions {
@@ -28,6 +32,8 @@ impl Default for ParseOptions {
 	///     max_junk_bytes: 1024,
 	///     read_cover_art: true,
 	///     implicit_conversions: true,
+	///     scan_frames: false,
+	///     mp4_audio_track: Mp4AudioTrack::First,
 	/// }
 	/// ```
 	fn default() -> Self {
@@ -62,6 +68,8 @@ impl ParseOptions {
 			max_junk_bytes: Self::DEFAULT_MAX_JUNK_BYTES,
 			read_cover_art: true,
 			implicit_conversions: true,
+			scan_frames: false,
+			mp4_audio_track: Mp4AudioTrack::First,
 		}
 	}
 
@@ -167,6 +175,48 @@ impl ParseOptions {
 		self.implicit_conversions = implicit_conversions;
 		*self
 	}
+
+	/// Whether or not to scan the audio frames when the stream length is unknown
+	///
+	/// Some formats allow the stream headers to leave the total sample count unset, which is common
+	/// for streamed and live encodes. Without it, the duration and bitrates can't be calculated.
+	///
+	/// With this enabled, the frames at the end of the stream will be located to recover the sample count.
+	/// This has no effect if the stream headers are complete.
+	///
+	/// NOTE: This currently only applies to FLAC.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::config::ParseOptions;
+	///
+	/// // My files are from live recordings, and are missing their durations
+	/// let parsing_options = ParseOptions::new().scan_frames(true);
+	/// ```
+	pub fn scan_frames(&mut self, scan_frames: bool) -> Self {
+		self.scan_frames = scan_frames;
+		*self
+	}
+
+	/// The audio track to read the properties from in MP4 files
+	///
+	/// By default, this is the first audio track. All of the tracks are available through
+	/// [`Mp4File::tracks`](crate::mp4::Mp4File::tracks).
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::config::ParseOptions;
+	/// use lofty::mp4::Mp4AudioTrack;
+	///
+	/// // My files have multiple audio tracks, and I want the properties of the French one
+	/// let parsing_options = ParseOptions::new().mp4_audio_track(Mp4AudioTrack::Language(*b"fra"));
+	/// ```
+	pub fn mp4_audio_track(&mut self, mp4_audio_track: Mp4AudioTrack) -> Self {
+		self.mp4_audio_track = mp4_audio_track;
+		*self
+	}
 }
 
 /// The parsing strictness mode
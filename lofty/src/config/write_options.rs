@@ -1,6 +1,9 @@
+use crate::ape::{ApeTagPlacement, ApeTagVersion};
 use crate::config::ParseOptions;
+use crate::flac::FlacBlockOrder;
 
 use std::num::NonZero;
+use std::time::Duration;
 
 /// Options to control how Lofty writes to a file
 ///
@@ -16,6 +19,14 @@ pub struct WriteOptions {
 	pub(crate) use_id3v23: bool,
 	pub(crate) lossy_text_encoding: bool,
 	pub(crate) parse_options: ParseOptions,
+	pub(crate) flac_block_order: Option<FlacBlockOrder>,
+	pub(crate) flac_seek_table_interval: Option<Duration>,
+	pub(crate) flac_strip_unknown_application_blocks: bool,
+	pub(crate) ape_tag_version: ApeTagVersion,
+	pub(crate) ape_tag_header: bool,
+	pub(crate) ape_tag_placement: ApeTagPlacement,
+	pub(crate) ape_move_leading_tag: bool,
+	pub(crate) mp4_fast_start: bool,
 }
 
 impl WriteOptions {
@@ -42,6 +53,14 @@ impl WriteOptions {
 			use_id3v23: false,
 			lossy_text_encoding: true,
 			parse_options: ParseOptions::new(),
+			flac_block_order: None,
+			flac_seek_table_interval: None,
+			flac_strip_unknown_application_blocks: false,
+			ape_tag_version: ApeTagVersion::V2,
+			ape_tag_header: true,
+			ape_tag_placement: ApeTagPlacement::BeforeLyrics3,
+			ape_move_leading_tag: true,
+			mp4_fast_start: false,
 		}
 	}
 
@@ -244,6 +263,255 @@ impl WriteOptions {
 		self.parse_options = parse_options;
 		*self
 	}
+
+	/// The order to write FLAC metadata blocks in
+	///
+	/// By default (`None`), existing blocks keep their current positions. When set, all blocks
+	/// (other than `STREAMINFO`, which is always first) will be rearranged to match the order.
+	///
+	/// This is useful for placing the Vorbis Comments and `SEEKTABLE` ahead of large pictures.
+	///
+	/// NOTE: This applies when writing to FLAC files, either through a
+	/// [`FlacFile`](crate::flac::FlacFile) or by saving its Vorbis Comments
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::WriteOptions;
+	/// use lofty::flac::{FlacBlockOrder, FlacBlockType};
+	/// use lofty::prelude::*;
+	/// use lofty::tag::{Tag, TagType};
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = Tag::new(TagType::VorbisComments);
+	///
+	/// // ...
+	///
+	/// // I want my pictures after everything else!
+	/// let order = FlacBlockOrder::with_priority(&[
+	/// 	FlacBlockType::VorbisComments,
+	/// 	FlacBlockType::SeekTable,
+	/// ]);
+	/// let options = WriteOptions::new().flac_block_order(Some(order));
+	/// tag.save_to_path("test.flac", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn flac_block_order(mut self, flac_block_order: Option<FlacBlockOrder>) -> Self {
+		self.flac_block_order = flac_block_order;
+		self
+	}
+
+	/// Regenerate the FLAC `SEEKTABLE`, with a seek point at every `interval`
+	///
+	/// When set, the audio frames will be scanned, and any existing `SEEKTABLE` will be replaced.
+	/// An interval of zero is treated as `None`.
+	///
+	/// NOTES:
+	///
+	/// * This applies when writing to FLAC files, either through a
+	///   [`FlacFile`](crate::flac::FlacFile) or by saving its Vorbis Comments
+	/// * Scanning the frames requires reading the entire stream, which can be slow for large files
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	/// use lofty::tag::{Tag, TagType};
+	/// use std::time::Duration;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = Tag::new(TagType::VorbisComments);
+	///
+	/// // ...
+	///
+	/// // My encoder didn't write a seek table, let's add one with a point every 10 seconds
+	/// let options = WriteOptions::new().flac_seek_table_interval(Some(Duration::from_secs(10)));
+	/// tag.save_to_path("test.flac", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn flac_seek_table_interval(mut self, interval: Option<Duration>) -> Self {
+		self.flac_seek_table_interval = interval.filter(|interval| !interval.is_zero());
+		self
+	}
+
+	/// Whether to remove FLAC `APPLICATION` blocks with unregistered IDs
+	///
+	/// Only `APPLICATION` blocks with an ID registered with Xiph.Org will be kept. See
+	/// [`ApplicationBlock::is_registered`](crate::flac::ApplicationBlock::is_registered).
+	///
+	/// NOTE: This applies when writing to FLAC files, either through a
+	/// [`FlacFile`](crate::flac::FlacFile) or by saving its Vorbis Comments
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	/// use lofty::tag::{Tag, TagType};
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = Tag::new(TagType::VorbisComments);
+	///
+	/// // ...
+	///
+	/// // I don't want any junk left behind by other applications
+	/// let options = WriteOptions::new().flac_strip_unknown_application_blocks(true);
+	/// tag.save_to_path("test.flac", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn flac_strip_unknown_application_blocks(mut self, strip: bool) -> Self {
+		self.flac_strip_unknown_application_blocks = strip;
+		self
+	}
+
+	/// The version of APE tags to write
+	///
+	/// By default, APEv2 tags are written. APEv1 tags can only contain text, use
+	/// [`ApeTag::validate`](crate::ape::ApeTag::validate) to check which items can't be written.
+	///
+	/// NOTE: This applies to [`ApeTag`](crate::ape::ApeTag)s and [`TagType::Ape`](crate::tag::TagType::Ape)
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::ape::{ApeTag, ApeTagVersion};
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = ApeTag::new();
+	///
+	/// // ...
+	///
+	/// // My old Musepack player only understands APEv1
+	/// let options = WriteOptions::new().ape_tag_version(ApeTagVersion::V1);
+	/// tag.save_to_path("test.mpc", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn ape_tag_version(mut self, version: ApeTagVersion) -> Self {
+		self.ape_tag_version = version;
+		self
+	}
+
+	/// Whether to write a header for APE tags
+	///
+	/// APE tags always have a footer, the header is optional. Tags at the beginning of a file
+	/// (see [`WriteOptions::ape_move_leading_tag`]) will always have a header.
+	///
+	/// NOTE: This only applies to APEv2, APEv1 tags never have a header
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::ape::ApeTag;
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = ApeTag::new();
+	///
+	/// // ...
+	///
+	/// // I only want a footer
+	/// let options = WriteOptions::new().ape_tag_header(false);
+	/// tag.save_to_path("test.mpc", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn ape_tag_header(mut self, ape_tag_header: bool) -> Self {
+		self.ape_tag_header = ape_tag_header;
+		self
+	}
+
+	/// Where to place APE tags relative to existing Lyrics3 and ID3v1 tags
+	///
+	/// By default, APE tags are placed before both. An existing APE tag will be moved to match.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::ape::{ApeTag, ApeTagPlacement};
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = ApeTag::new();
+	///
+	/// // ...
+	///
+	/// // My player expects the APE tag to directly precede the ID3v1 tag
+	/// let options = WriteOptions::new().ape_tag_placement(ApeTagPlacement::AfterLyrics3);
+	/// tag.save_to_path("test.mp3", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn ape_tag_placement(mut self, ape_tag_placement: ApeTagPlacement) -> Self {
+		self.ape_tag_placement = ape_tag_placement;
+		self
+	}
+
+	/// Whether to move an APE tag at the beginning of a file to the end
+	///
+	/// APE tags at the beginning of a file are against the specification, so by default they are
+	/// removed and the new tag is written at the end. If this is disabled, the tag will be updated
+	/// in place instead, and any APE tag at the end of the file will be removed.
+	///
+	/// APEv1 tags have no header (see [`WriteOptions::ape_tag_version`]), so they are always
+	/// written at the end.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::ape::ApeTag;
+	/// use lofty::config::WriteOptions;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = ApeTag::new();
+	///
+	/// // ...
+	///
+	/// // Leave my leading tag where it is
+	/// let options = WriteOptions::new().ape_move_leading_tag(false);
+	/// tag.save_to_path("test.mp3", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn ape_move_leading_tag(mut self, ape_move_leading_tag: bool) -> Self {
+		self.ape_move_leading_tag = ape_move_leading_tag;
+		self
+	}
+
+	/// Whether to move the `moov` atom of MP4 files in front of the `mdat` atom when writing
+	///
+	/// Files with `moov` at the end (the default for many encoders) can't be played until they're
+	/// fully downloaded. With this enabled, `moov` is moved in front of `mdat` (commonly known as
+	/// "fast start"), and the chunk offsets are updated to match. If `moov` is already in front of
+	/// `mdat`, nothing changes.
+	///
+	/// NOTE: Moving `moov` requires rewriting everything after the start of `mdat`. To avoid
+	///       doing so again on future writes, the [preferred padding](Self::preferred_padding)
+	///       is placed after `moov`.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::WriteOptions;
+	/// use lofty::mp4::Ilst;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileEncodingError> {
+	/// let mut tag = Ilst::new();
+	///
+	/// // ...
+	///
+	/// // I want my files to be playable while they're streamed
+	/// let options = WriteOptions::new().mp4_fast_start(true);
+	/// tag.save_to_path("test.m4a", options)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn mp4_fast_start(mut self, mp4_fast_start: bool) -> Self {
+		self.mp4_fast_start = mp4_fast_start;
+		self
+	}
 }
 
 impl Default for WriteOptions {
@@ -260,6 +528,14 @@ impl Default for WriteOptions {
 	///     use_id3v23: false,
 	///     lossy_text_encoding: true,
 	///     parse_options: ParseOptions::default(),
+	///     flac_block_order: None,
+	///     flac_seek_table_interval: None,
+	///     flac_strip_unknown_application_blocks: false,
+	///     ape_tag_version: ApeTagVersion::V2,
+	///     ape_tag_header: true,
+	///     ape_tag_placement: ApeTagPlacement::BeforeLyrics3,
+	///     ape_move_leading_tag: true,
+	///     mp4_fast_start: false,
 	/// }
 	/// ```
 	fn default() -> Self {
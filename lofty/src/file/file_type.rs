@@ -1,4 +1,5 @@
 use crate::config::global_options;
+use crate::iff::wav::container::{WAVE64_RIFF_GUID, WAVE64_WAVE_GUID};
 use crate::resolve::CUSTOM_RESOLVERS;
 use crate::tag::{TagSupport, TagType};
 
@@ -39,8 +40,9 @@ use std::path::Path;
 /// ```
 pub const EXTENSIONS: &[&str] = &[
 	// Also update `FileType::from_ext()` below
-	"aac", "ape", "aiff", "aif", "afc", "aifc", "mp3", "mp2", "mp1", "wav", "wv", "opus", "flac",
-	"ogg", "mp4", "m4a", "m4b", "m4p", "m4r", "m4v", "3gp", "mpc", "mp+", "mpp", "spx",
+	"aac", "ape", "aiff", "aif", "afc", "aifc", "mp3", "mp2", "mp1", "wav", "w64", "rf64", "bw64",
+	"wv", "opus", "flac", "ogg", "mp4", "m4a", "m4b", "m4p", "m4r", "m4v", "3gp", "mpc", "mp+",
+	"mpp", "spx",
 ];
 
 /// The type of file read
@@ -168,6 +170,8 @@ impl FileType {
 			(VorbisComments, crate::ogg::tag::VorbisComments),
 			(RiffInfo, crate::iff::wav::RiffInfoList),
 			(AiffText, crate::iff::aiff::AiffTextChunks),
+			(Lyrics3, crate::id3::lyrics3::Lyrics3Tag),
+			(QuickTime, crate::mp4::QuickTimeMetadata),
 		)
 	}
 
@@ -204,7 +208,7 @@ impl FileType {
 			"ape" => Some(Self::Ape),
 			"aiff" | "aif" | "afc" | "aifc" => Some(Self::Aiff),
 			"mp3" | "mp2" | "mp1" => Some(Self::Mpeg),
-			"wav" | "wave" => Some(Self::Wav),
+			"wav" | "wave" | "w64" | "rf64" | "bw64" => Some(Self::Wav),
 			"wv" => Some(Self::WavPack),
 			"opus" => Some(Self::Opus),
 			"flac" => Some(Self::Flac),
@@ -364,13 +368,20 @@ impl FileType {
 				None
 			},
 			102 if buf.starts_with(b"fLaC") => Some(Self::Flac),
-			82 if buf.len() >= 12 && &buf[..4] == b"RIFF" => {
+			82 | 66 if buf.len() >= 12 && matches!(&buf[..4], b"RIFF" | b"RF64" | b"BW64") => {
 				if &buf[8..12] == b"WAVE" {
 					return Some(Self::Wav);
 				}
 
 				None
 			},
+			// Sony Wave64, the `riff` and `wave` GUIDs
+			114 if buf.len() >= 28
+				&& buf[..16] == WAVE64_RIFF_GUID
+				&& buf[24..28] == WAVE64_WAVE_GUID[..4] =>
+			{
+				Some(Self::Wav)
+			},
 			119 if buf.len() >= 4 && &buf[..4] == b"wvpk" => Some(Self::WavPack),
 			_ if buf.len() >= 8 && &buf[4..8] == b"ftyp" => Some(Self::Mp4),
 			_ if buf.starts_with(b"MPCK") || buf.starts_with(b"MP+") => Some(Self::Mpc),
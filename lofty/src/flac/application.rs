@@ -0,0 +1,69 @@
+use crate::error::TooMuchDataError;
+use crate::flac::block::Block;
+use crate::flac::error::{FlacEncodingError, FlacParseError};
+use crate::util::alloc::VecFallibleCapacity;
+
+use std::io::Write;
+
+// IDs from the registry at <https://xiph.org/flac/id.html>
+const REGISTERED_IDS: [&[u8; 4]; 24] = [
+	b"ATCH", b"BSOL", b"BUGS", b"Cues", b"Fica", b"Ftol", b"MOTB", b"MPSE", b"MuML", b"RIFF",
+	b"SFFL", b"SONY", b"SQEZ", b"TtWv", b"UITS", b"aiff", b"imag", b"peem", b"qfst", b"riff",
+	b"tune", b"w64 ", b"xbat", b"xmcd",
+];
+
+/// A FLAC `APPLICATION` block
+///
+/// The contents of the block are defined by the application that wrote it, identified by
+/// a registered ID. See <https://www.rfc-editor.org/rfc/rfc9639.html#name-application>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplicationBlock {
+	/// The registered application ID
+	pub id: [u8; 4],
+	/// The application data
+	pub data: Vec<u8>,
+}
+
+impl ApplicationBlock {
+	/// Create a new `ApplicationBlock`
+	pub fn new(id: [u8; 4], data: Vec<u8>) -> Self {
+		Self { id, data }
+	}
+
+	/// Whether the block's ID is in the registry of known applications
+	///
+	/// See <https://xiph.org/flac/id.html>
+	pub fn is_registered(&self) -> bool {
+		is_registered_id(self.id)
+	}
+
+	pub(super) fn parse(content: &[u8]) -> Result<Self, FlacParseError> {
+		let Some((id, data)) = content.split_first_chunk::<4>() else {
+			return Err(FlacParseError::message(
+				"APPLICATION block is too small to contain an ID",
+			));
+		};
+
+		Ok(Self {
+			id: *id,
+			data: data.to_vec(),
+		})
+	}
+
+	pub(super) fn as_bytes(&self) -> Result<Vec<u8>, FlacEncodingError> {
+		let size = self.id.len() + self.data.len();
+		if size > Block::MAX_CONTENT_SIZE as usize {
+			return Err(TooMuchDataError.into());
+		}
+
+		let mut content = Vec::try_with_capacity_stable(size)?;
+		content.write_all(&self.id)?;
+		content.write_all(&self.data)?;
+
+		Ok(content)
+	}
+}
+
+pub(super) fn is_registered_id(id: [u8; 4]) -> bool {
+	REGISTERED_IDS.contains(&&id)
+}
@@ -11,8 +11,10 @@ use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 
 pub(in crate::flac) const BLOCK_ID_STREAMINFO: u8 = 0;
 pub(in crate::flac) const BLOCK_ID_PADDING: u8 = 1;
+pub(in crate::flac) const BLOCK_ID_APPLICATION: u8 = 2;
 pub(in crate::flac) const BLOCK_ID_SEEKTABLE: u8 = 3;
 pub(in crate::flac) const BLOCK_ID_VORBIS_COMMENTS: u8 = 4;
+pub(in crate::flac) const BLOCK_ID_CUESHEET: u8 = 5;
 pub(in crate::flac) const BLOCK_ID_PICTURE: u8 = 6;
 
 const BLOCK_HEADER_SIZE: u64 = 4;
@@ -67,6 +69,16 @@ impl Block {
 		(Self::BLOCK_HEADER_SIZE as u32) + self.content.len() as u32
 	}
 
+	pub(super) fn new(ty: u8, content: Vec<u8>) -> Self {
+		Self {
+			ty,
+			last: false,
+			content,
+			start: 0,
+			end: 0,
+		}
+	}
+
 	pub(super) fn new_padding(size: usize) -> Result<Self, FlacEncodingError> {
 		let block_size = core::cmp::min(size, Self::MAX_CONTENT_SIZE as usize);
 		let content = try_vec![0; block_size]?;
@@ -0,0 +1,144 @@
+use super::block::{
+	BLOCK_ID_APPLICATION, BLOCK_ID_CUESHEET, BLOCK_ID_PADDING, BLOCK_ID_PICTURE,
+	BLOCK_ID_SEEKTABLE, BLOCK_ID_VORBIS_COMMENTS,
+};
+
+/// The types of FLAC metadata blocks, used in [`FlacBlockOrder`]
+///
+/// `STREAMINFO` is not included, as it is always the first block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FlacBlockType {
+	/// `SEEKTABLE`
+	SeekTable,
+	/// `CUESHEET`
+	CueSheet,
+	/// `APPLICATION`
+	Application,
+	/// `VORBIS_COMMENT`
+	VorbisComments,
+	/// `PICTURE`
+	Picture,
+	/// `PADDING`
+	Padding,
+	/// Any reserved or unrecognized block types
+	Unknown,
+}
+
+impl FlacBlockType {
+	const COUNT: usize = 7;
+
+	pub(super) fn from_id(id: u8) -> Self {
+		match id {
+			BLOCK_ID_SEEKTABLE => Self::SeekTable,
+			BLOCK_ID_CUESHEET => Self::CueSheet,
+			BLOCK_ID_APPLICATION => Self::Application,
+			BLOCK_ID_VORBIS_COMMENTS => Self::VorbisComments,
+			BLOCK_ID_PICTURE => Self::Picture,
+			BLOCK_ID_PADDING => Self::Padding,
+			_ => Self::Unknown,
+		}
+	}
+}
+
+/// The order to write FLAC metadata blocks in
+///
+/// Blocks are grouped by type, with blocks of the same type keeping their relative order.
+///
+/// This is set with [`WriteOptions::flac_block_order`](crate::config::WriteOptions::flac_block_order).
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::flac::{FlacBlockOrder, FlacBlockType};
+///
+/// // Put the comments and seek table ahead of any (potentially large) pictures, so that players
+/// // streaming the file can start sooner. Any unlisted types follow in the default order.
+/// let order = FlacBlockOrder::with_priority(&[FlacBlockType::VorbisComments, FlacBlockType::SeekTable]);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlacBlockOrder {
+	order: [FlacBlockType; FlacBlockType::COUNT],
+}
+
+impl FlacBlockOrder {
+	/// The default block order
+	///
+	/// This places the blocks needed for playback first, followed by the tag and pictures:
+	///
+	/// `SEEKTABLE`, `CUESHEET`, `APPLICATION`, `VORBIS_COMMENT`, `PICTURE`, unknown blocks, `PADDING`
+	pub const DEFAULT: Self = Self {
+		order: [
+			FlacBlockType::SeekTable,
+			FlacBlockType::CueSheet,
+			FlacBlockType::Application,
+			FlacBlockType::VorbisComments,
+			FlacBlockType::Picture,
+			FlacBlockType::Unknown,
+			FlacBlockType::Padding,
+		],
+	};
+
+	/// Create a block order with `priority` placed first, in the given order
+	///
+	/// Any types not in `priority` will follow in the order of [`FlacBlockOrder::DEFAULT`].
+	/// Duplicate entries are ignored.
+	pub fn with_priority(priority: &[FlacBlockType]) -> Self {
+		let mut order = [FlacBlockType::Unknown; FlacBlockType::COUNT];
+
+		let mut len = 0;
+		for ty in priority.iter().chain(Self::DEFAULT.order.iter()) {
+			if !order[..len].contains(ty) {
+				order[len] = *ty;
+				len += 1;
+			}
+		}
+
+		Self { order }
+	}
+
+	/// The block types, in the order they will be written
+	pub fn order(&self) -> &[FlacBlockType] {
+		&self.order
+	}
+
+	pub(super) fn position(self, id: u8) -> usize {
+		let ty = FlacBlockType::from_id(id);
+		self.order
+			.iter()
+			.position(|t| *t == ty)
+			.unwrap_or(FlacBlockType::COUNT)
+	}
+}
+
+impl Default for FlacBlockOrder {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{FlacBlockOrder, FlacBlockType};
+
+	#[test_log::test]
+	fn with_priority() {
+		let order = FlacBlockOrder::with_priority(&[
+			FlacBlockType::Padding,
+			FlacBlockType::VorbisComments,
+			FlacBlockType::Padding,
+		]);
+
+		assert_eq!(
+			order.order(),
+			&[
+				FlacBlockType::Padding,
+				FlacBlockType::VorbisComments,
+				FlacBlockType::SeekTable,
+				FlacBlockType::CueSheet,
+				FlacBlockType::Application,
+				FlacBlockType::Picture,
+				FlacBlockType::Unknown,
+			]
+		);
+	}
+}
@@ -0,0 +1,278 @@
+use crate::config::ParsingMode;
+use crate::error::TooMuchDataError;
+use crate::flac::block::Block;
+use crate::flac::error::{FlacEncodingError, FlacParseError};
+use crate::util::alloc::VecFallibleCapacity;
+
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+const MEDIA_CATALOG_NUMBER_LEN: usize = 128;
+const ISRC_LEN: usize = 12;
+
+// 1 bit for the CD-DA flag, followed by 7 + (258 * 8) reserved bits
+const CUE_SHEET_RESERVED_LEN: usize = 259;
+// 1 bit for the track type, 1 bit for the pre-emphasis flag, followed by 6 + (13 * 8) reserved bits
+const TRACK_RESERVED_LEN: usize = 14;
+const INDEX_RESERVED_LEN: usize = 3;
+
+const INVALID_MCN_MESSAGE: &str =
+	"CUESHEET media catalog number must be at most 128 ASCII characters";
+const INVALID_ISRC_MESSAGE: &str = "CUESHEET track ISRC must be exactly 12 ASCII characters";
+
+/// An index point within a [`CueSheetTrack`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CueSheetTrackIndex {
+	/// The offset in samples, relative to the track offset
+	pub offset: u64,
+	/// The index point number
+	pub number: u8,
+}
+
+/// A single track in a [`CueSheet`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CueSheetTrack {
+	/// The offset in samples, relative to the beginning of the stream
+	pub offset: u64,
+	/// The track number
+	///
+	/// The lead-out track is [`CueSheetTrack::CD_DA_LEAD_OUT`] for CD-DA cue sheets, and
+	/// [`CueSheetTrack::LEAD_OUT`] otherwise.
+	pub number: u8,
+	/// The track's International Standard Recording Code, if any
+	pub isrc: Option<String>,
+	/// Whether the track contains audio
+	pub is_audio: bool,
+	/// Whether the track was recorded with pre-emphasis
+	pub pre_emphasis: bool,
+	/// The track's index points
+	///
+	/// The lead-out track has none.
+	pub indices: Vec<CueSheetTrackIndex>,
+}
+
+impl CueSheetTrack {
+	/// The lead-out track number for CD-DA cue sheets
+	pub const CD_DA_LEAD_OUT: u8 = 170;
+	/// The lead-out track number for non CD-DA cue sheets
+	pub const LEAD_OUT: u8 = 255;
+
+	const HEADER_SIZE: usize = 36;
+	const INDEX_SIZE: usize = 12;
+}
+
+/// A FLAC `CUESHEET` block
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9639.html#name-cuesheet>
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CueSheet {
+	/// The media catalog number
+	///
+	/// For CD-DA, this is a 13 digit number. It will be empty if unknown.
+	pub media_catalog_number: String,
+	/// The number of lead-in samples
+	///
+	/// This is only meaningful for CD-DA cue sheets.
+	pub lead_in_samples: u64,
+	/// Whether the cue sheet corresponds to a compact disc
+	pub is_cd: bool,
+	/// The tracks, including the lead-out track
+	pub tracks: Vec<CueSheetTrack>,
+}
+
+impl CueSheet {
+	pub(super) fn parse(content: &[u8], parse_mode: ParsingMode) -> Result<Self, FlacParseError> {
+		let reader = &mut &content[..];
+
+		let media_catalog_number = read_ascii(reader, MEDIA_CATALOG_NUMBER_LEN, parse_mode)?;
+		let lead_in_samples = reader.read_u64::<BigEndian>()?;
+
+		let mut reserved = [0; CUE_SHEET_RESERVED_LEN];
+		reader.read_exact(&mut reserved)?;
+		let is_cd = reserved[0] & 0x80 != 0;
+
+		let track_count = reader.read_u8()?;
+		if usize::from(track_count) * CueSheetTrack::HEADER_SIZE > reader.len() {
+			return Err(FlacParseError::message(
+				"CUESHEET track count exceeds the block size",
+			));
+		}
+
+		let mut tracks = Vec::try_with_capacity_stable(usize::from(track_count))?;
+		for _ in 0..track_count {
+			let offset = reader.read_u64::<BigEndian>()?;
+			let number = reader.read_u8()?;
+
+			let isrc = read_ascii(reader, ISRC_LEN, parse_mode)?;
+
+			let mut reserved = [0; TRACK_RESERVED_LEN];
+			reader.read_exact(&mut reserved)?;
+			let is_audio = reserved[0] & 0x80 == 0;
+			let pre_emphasis = reserved[0] & 0x40 != 0;
+
+			let index_count = reader.read_u8()?;
+			if usize::from(index_count) * CueSheetTrack::INDEX_SIZE > reader.len() {
+				return Err(FlacParseError::message(
+					"CUESHEET index count exceeds the block size",
+				));
+			}
+
+			let mut indices = Vec::try_with_capacity_stable(usize::from(index_count))?;
+			for _ in 0..index_count {
+				let offset = reader.read_u64::<BigEndian>()?;
+				let number = reader.read_u8()?;
+
+				let mut reserved = [0; INDEX_RESERVED_LEN];
+				reader.read_exact(&mut reserved)?;
+
+				indices.push(CueSheetTrackIndex { offset, number });
+			}
+
+			tracks.push(CueSheetTrack {
+				offset,
+				number,
+				isrc: (!isrc.is_empty()).then_some(isrc),
+				is_audio,
+				pre_emphasis,
+				indices,
+			});
+		}
+
+		if !reader.is_empty() {
+			if parse_mode == ParsingMode::Strict {
+				return Err(FlacParseError::message("CUESHEET has trailing data"));
+			}
+
+			log::warn!("CUESHEET has trailing data, discarding");
+		}
+
+		Ok(Self {
+			media_catalog_number,
+			lead_in_samples,
+			is_cd,
+			tracks,
+		})
+	}
+
+	pub(super) fn as_bytes(&self) -> Result<Vec<u8>, FlacEncodingError> {
+		let Ok(track_count) = u8::try_from(self.tracks.len()) else {
+			return Err(FlacEncodingError::message(
+				"CUESHEET cannot contain more than 255 tracks",
+			));
+		};
+
+		let tracks_size = self
+			.tracks
+			.iter()
+			.map(|track| {
+				CueSheetTrack::HEADER_SIZE + (track.indices.len() * CueSheetTrack::INDEX_SIZE)
+			})
+			.sum::<usize>();
+
+		// Media catalog number + lead-in samples + reserved + track count + tracks
+		let size = MEDIA_CATALOG_NUMBER_LEN + 8 + CUE_SHEET_RESERVED_LEN + 1 + tracks_size;
+		if size > Block::MAX_CONTENT_SIZE as usize {
+			return Err(TooMuchDataError.into());
+		}
+
+		let mut content = Vec::try_with_capacity_stable(size)?;
+
+		write_ascii(
+			&mut content,
+			&self.media_catalog_number,
+			MEDIA_CATALOG_NUMBER_LEN,
+			INVALID_MCN_MESSAGE,
+		)?;
+		content.write_u64::<BigEndian>(self.lead_in_samples)?;
+
+		let mut reserved = [0; CUE_SHEET_RESERVED_LEN];
+		if self.is_cd {
+			reserved[0] = 0x80;
+		}
+		content.write_all(&reserved)?;
+
+		content.write_u8(track_count)?;
+		for track in &self.tracks {
+			let Ok(index_count) = u8::try_from(track.indices.len()) else {
+				return Err(FlacEncodingError::message(
+					"CUESHEET track cannot contain more than 255 index points",
+				));
+			};
+
+			content.write_u64::<BigEndian>(track.offset)?;
+			content.write_u8(track.number)?;
+
+			match &track.isrc {
+				Some(isrc) if isrc.len() != ISRC_LEN => {
+					return Err(FlacEncodingError::message(INVALID_ISRC_MESSAGE));
+				},
+				Some(isrc) => write_ascii(&mut content, isrc, ISRC_LEN, INVALID_ISRC_MESSAGE)?,
+				None => content.write_all(&[0; ISRC_LEN])?,
+			}
+
+			let mut reserved = [0; TRACK_RESERVED_LEN];
+			if !track.is_audio {
+				reserved[0] |= 0x80;
+			}
+			if track.pre_emphasis {
+				reserved[0] |= 0x40;
+			}
+			content.write_all(&reserved)?;
+
+			content.write_u8(index_count)?;
+			for index in &track.indices {
+				content.write_u64::<BigEndian>(index.offset)?;
+				content.write_u8(index.number)?;
+				content.write_all(&[0; INDEX_RESERVED_LEN])?;
+			}
+		}
+
+		Ok(content)
+	}
+}
+
+// Reads a NUL-padded ASCII string
+fn read_ascii(
+	reader: &mut &[u8],
+	len: usize,
+	parse_mode: ParsingMode,
+) -> Result<String, FlacParseError> {
+	let Some((raw, rest)) = reader.split_at_checked(len) else {
+		return Err(FlacParseError::message("CUESHEET block is too small"));
+	};
+	*reader = rest;
+
+	let end = raw.iter().position(|b| *b == 0).unwrap_or(len);
+	let raw = &raw[..end];
+
+	if !raw.is_ascii() {
+		if parse_mode == ParsingMode::Strict {
+			return Err(FlacParseError::message(
+				"CUESHEET contains a non-ASCII string",
+			));
+		}
+
+		log::warn!("CUESHEET contains a non-ASCII string, discarding");
+		return Ok(String::new());
+	}
+
+	Ok(String::from_utf8_lossy(raw).into_owned())
+}
+
+// Writes a NUL-padded ASCII string
+fn write_ascii(
+	content: &mut Vec<u8>,
+	value: &str,
+	len: usize,
+	error_message: &'static str,
+) -> Result<(), FlacEncodingError> {
+	if value.len() > len || !value.is_ascii() {
+		return Err(FlacEncodingError::message(error_message));
+	}
+
+	content.write_all(value.as_bytes())?;
+	content.resize(content.len() + (len - value.len()), 0);
+
+	Ok(())
+}
@@ -0,0 +1,318 @@
+//! A minimal FLAC frame decoder
+//!
+//! This only produces the raw PCM samples needed to verify the STREAMINFO MD5 signature.
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc9639.html#name-frame-structure>
+
+use super::frame::{ChannelAssignment, FrameHeader, MAX_FRAME_HEADER_SIZE, crc16};
+use crate::util::bits::BitReader;
+
+use std::io::ErrorKind;
+
+const SUBFRAME_CONSTANT: u32 = 0;
+const SUBFRAME_VERBATIM: u32 = 1;
+
+const FIXED_PREDICTION_COEFFICIENTS: [&[i64]; 5] =
+	[&[], &[1], &[2, -1], &[3, -3, 1], &[4, -6, 4, -1]];
+
+pub(super) enum FrameError {
+	/// The frame extends past the end of the provided data
+	Truncated,
+	/// The data does not start with a valid frame header
+	InvalidHeader,
+	/// The frame is invalid
+	Malformed,
+	/// The frame decoded, but the CRC-16 did not match
+	CrcMismatch,
+}
+
+impl From<std::io::Error> for FrameError {
+	fn from(err: std::io::Error) -> Self {
+		if err.kind() == ErrorKind::UnexpectedEof {
+			return FrameError::Truncated;
+		}
+
+		FrameError::Malformed
+	}
+}
+
+pub(super) struct Decoder {
+	channels: u8,
+	bits_per_sample: u8,
+	// The samples of the last decoded frame, per channel
+	samples: Vec<Vec<i64>>,
+	block_size: usize,
+}
+
+impl Decoder {
+	pub(super) fn new(channels: u8, bits_per_sample: u8) -> Self {
+		Self {
+			channels,
+			bits_per_sample,
+			samples: vec![Vec::new(); usize::from(channels)],
+			block_size: 0,
+		}
+	}
+
+	/// Decode the frame at the start of `data`, returning its size
+	pub(super) fn decode_frame(&mut self, data: &[u8]) -> Result<usize, FrameError> {
+		let Some(header) = FrameHeader::parse(data) else {
+			if data.len() < MAX_FRAME_HEADER_SIZE {
+				return Err(FrameError::Truncated);
+			}
+
+			return Err(FrameError::InvalidHeader);
+		};
+
+		if header.channel_assignment.channels() != self.channels {
+			return Err(FrameError::Malformed);
+		}
+
+		if let Some(bits_per_sample) = header.bits_per_sample
+			&& bits_per_sample != self.bits_per_sample
+		{
+			return Err(FrameError::Malformed);
+		}
+
+		let block_size = header.block_size as usize;
+
+		let mut reader = BitReader::new(data);
+		reader.set_position(header.len * 8);
+
+		for channel in 0..self.channels {
+			let side_channel = match header.channel_assignment {
+				ChannelAssignment::LeftSide | ChannelAssignment::MidSide => channel == 1,
+				ChannelAssignment::SideRight => channel == 0,
+				ChannelAssignment::Independent(_) => false,
+			};
+
+			let bits_per_sample = self.bits_per_sample + u8::from(side_channel);
+
+			let samples = &mut self.samples[usize::from(channel)];
+			samples.clear();
+			decode_subframe(&mut reader, bits_per_sample, block_size, samples)?;
+		}
+
+		reader.align();
+
+		let frame_len = reader.position() / 8;
+		let crc = reader.read_bits(16)? as u16;
+		if crc16(&data[..frame_len]) != crc {
+			return Err(FrameError::CrcMismatch);
+		}
+
+		self.block_size = block_size;
+		self.decorrelate(header.channel_assignment)?;
+
+		Ok(frame_len + 2)
+	}
+
+	/// The number of samples per channel in the last decoded frame
+	pub(super) fn block_size(&self) -> usize {
+		self.block_size
+	}
+
+	/// The sample at `index` in `channel` of the last decoded frame
+	pub(super) fn sample(&self, channel: u8, index: usize) -> i64 {
+		self.samples[usize::from(channel)][index]
+	}
+
+	fn decorrelate(&mut self, channel_assignment: ChannelAssignment) -> Result<(), FrameError> {
+		let [first, second, ..] = &mut self.samples[..] else {
+			return Ok(());
+		};
+
+		match channel_assignment {
+			ChannelAssignment::Independent(_) => {},
+			ChannelAssignment::LeftSide => {
+				for (left, side) in first.iter().zip(second.iter_mut()) {
+					*side = left.checked_sub(*side).ok_or(FrameError::Malformed)?;
+				}
+			},
+			ChannelAssignment::SideRight => {
+				for (side, right) in first.iter_mut().zip(second.iter()) {
+					*side = side.checked_add(*right).ok_or(FrameError::Malformed)?;
+				}
+			},
+			ChannelAssignment::MidSide => {
+				for (mid, side) in first.iter_mut().zip(second.iter_mut()) {
+					let full_mid = mid.checked_mul(2).ok_or(FrameError::Malformed)? | (*side & 1);
+					let (Some(left), Some(right)) =
+						(full_mid.checked_add(*side), full_mid.checked_sub(*side))
+					else {
+						return Err(FrameError::Malformed);
+					};
+
+					*mid = left >> 1;
+					*side = right >> 1;
+				}
+			},
+		}
+
+		Ok(())
+	}
+}
+
+fn decode_subframe(
+	reader: &mut BitReader<'_>,
+	bits_per_sample: u8,
+	block_size: usize,
+	samples: &mut Vec<i64>,
+) -> Result<(), FrameError> {
+	// Zero padding bit
+	if reader.read_bit()? {
+		return Err(FrameError::Malformed);
+	}
+
+	let subframe_type = reader.read_bits(6)?;
+
+	let mut wasted_bits = 0;
+	if reader.read_bit()? {
+		wasted_bits = reader.read_unary()? + 1;
+	}
+
+	if wasted_bits >= u32::from(bits_per_sample) {
+		return Err(FrameError::Malformed);
+	}
+
+	let bits_per_sample = bits_per_sample - wasted_bits as u8;
+
+	match subframe_type {
+		SUBFRAME_CONSTANT => {
+			let value = reader.read_signed(bits_per_sample)?;
+			samples.resize(block_size, value);
+		},
+		SUBFRAME_VERBATIM => {
+			for _ in 0..block_size {
+				samples.push(reader.read_signed(bits_per_sample)?);
+			}
+		},
+		0b00_1000..=0b00_1100 => {
+			let order = (subframe_type & 0b111) as usize;
+			if order > block_size {
+				return Err(FrameError::Malformed);
+			}
+
+			for _ in 0..order {
+				samples.push(reader.read_signed(bits_per_sample)?);
+			}
+
+			decode_residual(reader, order, block_size, samples)?;
+			predict(samples, order, FIXED_PREDICTION_COEFFICIENTS[order], 0)?;
+		},
+		0b10_0000..=0b11_1111 => {
+			let order = (subframe_type & 0b1_1111) as usize + 1;
+			if order > block_size {
+				return Err(FrameError::Malformed);
+			}
+
+			for _ in 0..order {
+				samples.push(reader.read_signed(bits_per_sample)?);
+			}
+
+			let precision = reader.read_bits(4)? as u8;
+			if precision == 0b1111 {
+				return Err(FrameError::Malformed);
+			}
+
+			let shift = reader.read_signed(5)?;
+			if shift < 0 {
+				return Err(FrameError::Malformed);
+			}
+
+			let mut coefficients = [0; 32];
+			for coefficient in coefficients.iter_mut().take(order) {
+				*coefficient = reader.read_signed(precision + 1)?;
+			}
+
+			decode_residual(reader, order, block_size, samples)?;
+			predict(samples, order, &coefficients[..order], shift as u32)?;
+		},
+		_ => return Err(FrameError::Malformed),
+	}
+
+	if wasted_bits > 0 {
+		for sample in samples.iter_mut() {
+			*sample = sample
+				.checked_mul(1 << wasted_bits)
+				.ok_or(FrameError::Malformed)?;
+		}
+	}
+
+	Ok(())
+}
+
+// The residual is appended to `samples`, after the warm-up samples
+fn decode_residual(
+	reader: &mut BitReader<'_>,
+	order: usize,
+	block_size: usize,
+	samples: &mut Vec<i64>,
+) -> Result<(), FrameError> {
+	let (parameter_bits, escape_code) = match reader.read_bits(2)? {
+		0 => (4, 0b1111),
+		1 => (5, 0b1_1111),
+		_ => return Err(FrameError::Malformed),
+	};
+
+	let partition_order = reader.read_bits(4)?;
+	let partitions = 1usize << partition_order;
+
+	let partition_size = block_size >> partition_order;
+	if partition_size << partition_order != block_size || partition_size < order {
+		return Err(FrameError::Malformed);
+	}
+
+	for partition in 0..partitions {
+		let mut count = partition_size;
+		if partition == 0 {
+			count -= order;
+		}
+
+		let parameter = reader.read_bits(parameter_bits)?;
+		if parameter == escape_code {
+			let bits = reader.read_bits(5)? as u8;
+			for _ in 0..count {
+				samples.push(reader.read_signed(bits)?);
+			}
+
+			continue;
+		}
+
+		for _ in 0..count {
+			let quotient = u64::from(reader.read_unary()?);
+			let remainder = reader.read_bits_u64(parameter as u8)?;
+
+			let value = (quotient << parameter) | remainder;
+			samples.push(((value >> 1) as i64) ^ -((value & 1) as i64));
+		}
+	}
+
+	Ok(())
+}
+
+// Turns the residual into samples, in place
+//
+// Corrupt input can produce values that don't fit in an `i64`, which is reported as a malformed frame.
+fn predict(
+	samples: &mut [i64],
+	order: usize,
+	coefficients: &[i64],
+	shift: u32,
+) -> Result<(), FrameError> {
+	for i in order..samples.len() {
+		let mut prediction = 0i64;
+		for (j, coefficient) in coefficients.iter().enumerate() {
+			prediction = coefficient
+				.checked_mul(samples[i - j - 1])
+				.and_then(|term| prediction.checked_add(term))
+				.ok_or(FrameError::Malformed)?;
+		}
+
+		samples[i] = samples[i]
+			.checked_add(prediction >> shift)
+			.ok_or(FrameError::Malformed)?;
+	}
+
+	Ok(())
+}
@@ -48,6 +48,14 @@ pub(super) struct FlacEncodingError {
 	source: Box<dyn core::error::Error + Send + Sync + 'static>,
 }
 
+impl FlacEncodingError {
+	pub(super) fn message(message: &'static str) -> Self {
+		Self {
+			source: message.into(),
+		}
+	}
+}
+
 impl From<FlacEncodingError> for FileEncodingError {
 	fn from(input: FlacEncodingError) -> FileEncodingError {
 		Self::new(FileType::Flac, input.source)
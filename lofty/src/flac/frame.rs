@@ -0,0 +1,501 @@
+//! FLAC frame headers
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc9639.html#name-frame-header>
+
+use super::error::FlacParseError;
+use super::properties::FlacProperties;
+use crate::macros::try_vec;
+
+use std::io::{Read, Seek, SeekFrom};
+
+// 14 bit sync code, followed by a reserved 0 bit
+const FRAME_SYNC: u16 = 0b1111_1111_1111_1000;
+const FRAME_SYNC_MASK: u16 = 0b1111_1111_1111_1110;
+
+/// The largest possible frame header, including the CRC-8
+pub(super) const MAX_FRAME_HEADER_SIZE: usize = 16;
+
+static CRC8_TABLE: [u8; 256] = {
+	let mut table = [0; 256];
+
+	let mut n = 0;
+	while n < 256 {
+		let mut crc = n as u8;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 0x80 == 0 {
+				crc << 1
+			} else {
+				(crc << 1) ^ 0x07
+			};
+			bit += 1;
+		}
+
+		table[n] = crc;
+		n += 1;
+	}
+
+	table
+};
+
+#[cfg(feature = "flac_verification")]
+static CRC16_TABLE: [u16; 256] = {
+	let mut table = [0; 256];
+
+	let mut n = 0;
+	while n < 256 {
+		let mut crc = (n as u16) << 8;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 0x8000 == 0 {
+				crc << 1
+			} else {
+				(crc << 1) ^ 0x8005
+			};
+			bit += 1;
+		}
+
+		table[n] = crc;
+		n += 1;
+	}
+
+	table
+};
+
+pub(super) fn crc8(data: &[u8]) -> u8 {
+	data.iter()
+		.fold(0, |crc, byte| CRC8_TABLE[usize::from(crc ^ byte)])
+}
+
+#[cfg(feature = "flac_verification")]
+pub(super) fn crc16(data: &[u8]) -> u16 {
+	data.iter().fold(0, |crc, byte| {
+		(crc << 8) ^ CRC16_TABLE[usize::from((crc >> 8) as u8 ^ byte)]
+	})
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum ChannelAssignment {
+	Independent(u8),
+	LeftSide,
+	SideRight,
+	MidSide,
+}
+
+impl ChannelAssignment {
+	pub(super) fn channels(self) -> u8 {
+		match self {
+			ChannelAssignment::Independent(channels) => channels,
+			_ => 2,
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) struct FrameHeader {
+	pub(super) variable_block_size: bool,
+	pub(super) block_size: u32,
+	/// `None` if the sample rate should be taken from STREAMINFO
+	pub(super) sample_rate: Option<u32>,
+	pub(super) channel_assignment: ChannelAssignment,
+	/// `None` if the bit depth should be taken from STREAMINFO
+	pub(super) bits_per_sample: Option<u8>,
+	/// The sample number with a variable block size, otherwise the frame number
+	pub(super) coded_number: u64,
+	/// The size of the header, including the CRC-8
+	pub(super) len: usize,
+}
+
+impl FrameHeader {
+	/// Whether `data` starts with a frame sync code
+	pub(super) fn is_sync(data: &[u8]) -> bool {
+		match data {
+			[a, b, ..] => u16::from_be_bytes([*a, *b]) & FRAME_SYNC_MASK == FRAME_SYNC,
+			_ => false,
+		}
+	}
+
+	/// Parse a frame header from the start of `data`
+	///
+	/// This returns `None` if the header is invalid, truncated, or fails its CRC check.
+	pub(super) fn parse(data: &[u8]) -> Option<Self> {
+		if !Self::is_sync(data) || data.len() < 5 {
+			return None;
+		}
+
+		let variable_block_size = data[1] & 1 == 1;
+
+		let block_size_bits = data[2] >> 4;
+		let sample_rate_bits = data[2] & 0xF;
+
+		let channel_assignment = match data[3] >> 4 {
+			n @ 0..=7 => ChannelAssignment::Independent(n + 1),
+			8 => ChannelAssignment::LeftSide,
+			9 => ChannelAssignment::SideRight,
+			10 => ChannelAssignment::MidSide,
+			_ => return None,
+		};
+
+		let bits_per_sample = match (data[3] >> 1) & 0b111 {
+			0 => None,
+			1 => Some(8),
+			2 => Some(12),
+			4 => Some(16),
+			5 => Some(20),
+			6 => Some(24),
+			7 => Some(32),
+			_ => return None,
+		};
+
+		// Reserved bit
+		if data[3] & 1 != 0 {
+			return None;
+		}
+
+		let mut pos = 4;
+		let coded_number = read_coded_number(data, &mut pos)?;
+
+		let block_size = match block_size_bits {
+			0 => return None,
+			1 => 192,
+			n @ 2..=5 => 576 << (n - 2),
+			6 => {
+				let size = *data.get(pos)?;
+				pos += 1;
+				u32::from(size) + 1
+			},
+			7 => {
+				let size = u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]);
+				pos += 2;
+				u32::from(size) + 1
+			},
+			n => 256 << (n - 8),
+		};
+
+		let sample_rate = match sample_rate_bits {
+			0 => None,
+			1 => Some(88_200),
+			2 => Some(176_400),
+			3 => Some(192_000),
+			4 => Some(8000),
+			5 => Some(16000),
+			6 => Some(22050),
+			7 => Some(24000),
+			8 => Some(32000),
+			9 => Some(44100),
+			10 => Some(48000),
+			11 => Some(96000),
+			12 => {
+				let rate = *data.get(pos)?;
+				pos += 1;
+				Some(u32::from(rate) * 1000)
+			},
+			13 | 14 => {
+				let rate = u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]);
+				pos += 2;
+
+				if sample_rate_bits == 13 {
+					Some(u32::from(rate))
+				} else {
+					Some(u32::from(rate) * 10)
+				}
+			},
+			_ => return None,
+		};
+
+		let crc = *data.get(pos)?;
+		if crc8(&data[..pos]) != crc {
+			return None;
+		}
+
+		Some(Self {
+			variable_block_size,
+			block_size,
+			sample_rate,
+			channel_assignment,
+			bits_per_sample,
+			coded_number,
+			len: pos + 1,
+		})
+	}
+}
+
+// The initial amount of data to search for the last frame, doubled until a frame is found
+const SCAN_WINDOW_SIZE: u64 = 64 * 1024;
+
+// The smallest possible frame: a 6 byte header, a constant mono subframe, and the CRC-16
+const MIN_FRAME_SIZE: u64 = 6 + 2 + 2;
+
+/// Find the total number of samples by locating the last frame in the stream
+///
+/// `stream_start` is the offset of the first frame, and `stream_end` is the end of the audio data.
+/// If no plausible frame is found near the end, the entire stream is scanned with [`scan_frames`].
+/// This returns `None` if no frames could be found.
+pub(super) fn scan_total_samples<R>(
+	reader: &mut R,
+	stream_start: u64,
+	stream_end: u64,
+	properties: &FlacProperties,
+) -> Result<Option<u64>, FlacParseError>
+where
+	R: Read + Seek,
+{
+	let stream_length = stream_end.saturating_sub(stream_start);
+
+	let mut first_header = [0; MAX_FRAME_HEADER_SIZE];
+	let first_header_len = core::cmp::min(stream_length, MAX_FRAME_HEADER_SIZE as u64) as usize;
+
+	reader.seek(SeekFrom::Start(stream_start))?;
+	reader.read_exact(&mut first_header[..first_header_len])?;
+
+	let Some(first_frame) = FrameHeader::parse(&first_header[..first_header_len]) else {
+		log::warn!("Unable to find the first FLAC frame, cannot scan stream");
+		return Ok(None);
+	};
+
+	// Any frame header in the stream will describe the same stream as the first
+	let is_candidate = |header: &FrameHeader| {
+		header.variable_block_size == first_frame.variable_block_size
+			&& header.sample_rate == first_frame.sample_rate
+			&& header.bits_per_sample == first_frame.bits_per_sample
+			&& header.channel_assignment.channels() == properties.channels
+	};
+
+	// Audio data can easily look like a frame header, so make sure the frame could actually be at
+	// this offset, given the sizes in STREAMINFO
+	let is_plausible = |header: &FrameHeader, offset: u64| {
+		let max_block_size = u64::from(properties.max_block_size);
+		if max_block_size > 0 && u64::from(header.block_size) > max_block_size {
+			return false;
+		}
+
+		let (min_frames_before, max_frames_before) = if header.variable_block_size {
+			let min_block_size = u64::from(properties.min_block_size).max(1);
+			let min_frames_before = match max_block_size {
+				0 => 0,
+				_ => header.coded_number.div_ceil(max_block_size),
+			};
+
+			(min_frames_before, header.coded_number / min_block_size)
+		} else {
+			(header.coded_number, header.coded_number)
+		};
+
+		let min_frame_size = u64::from(properties.min_frame_size).max(MIN_FRAME_SIZE);
+		let max_frame_size = u64::from(properties.max_frame_size);
+
+		offset >= min_frames_before.saturating_mul(min_frame_size)
+			&& (max_frame_size == 0 || offset <= max_frames_before.saturating_mul(max_frame_size))
+	};
+
+	let mut window_size = SCAN_WINDOW_SIZE;
+	loop {
+		let window_size_clamped = core::cmp::min(window_size, stream_length);
+		let window_start = stream_end - window_size_clamped;
+
+		let mut window = try_vec![0; window_size_clamped as usize]?;
+		reader.seek(SeekFrom::Start(window_start))?;
+		reader.read_exact(&mut window)?;
+
+		let window_offset = window_start - stream_start;
+		let last_frame = (0..window.len()).rev().find_map(|pos| {
+			FrameHeader::parse(&window[pos..]).filter(|header| {
+				is_candidate(header) && is_plausible(header, window_offset + pos as u64)
+			})
+		});
+
+		if let Some(last_frame) = last_frame {
+			let first_sample = if last_frame.variable_block_size {
+				last_frame.coded_number
+			} else {
+				// All frames but the last have the same block size
+				last_frame.coded_number * u64::from(first_frame.block_size)
+			};
+
+			log::debug!("Found the last FLAC frame, starting at sample {first_sample}");
+			return Ok(Some(first_sample + u64::from(last_frame.block_size)));
+		}
+
+		if window_size_clamped == stream_length {
+			log::warn!("Unable to find the last FLAC frame, scanning the entire stream");
+
+			let frames = scan_frames(reader, stream_start, stream_end, properties.min_frame_size)?;
+			let Some(last_frame) = frames.last() else {
+				log::warn!("Unable to find any FLAC frames");
+				return Ok(None);
+			};
+
+			return Ok(Some(
+				last_frame.first_sample + u64::from(last_frame.block_size),
+			));
+		}
+
+		window_size *= 2;
+	}
+}
+
+/// The position of a frame in the stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) struct FrameInfo {
+	pub(super) first_sample: u64,
+	/// The offset from the start of the first frame
+	pub(super) offset: u64,
+	pub(super) block_size: u32,
+}
+
+/// Locate every frame in the stream
+///
+/// Without decoding, the frame boundaries can only be found by their sync codes. To avoid
+/// mistaking audio data for a frame header, a header is only accepted if it continues the
+/// numbering of the previous frame.
+pub(super) fn scan_frames<R>(
+	reader: &mut R,
+	stream_start: u64,
+	stream_end: u64,
+	min_frame_size: u32,
+) -> Result<Vec<FrameInfo>, FlacParseError>
+where
+	R: Read + Seek,
+{
+	reader.seek(SeekFrom::Start(stream_start))?;
+	let mut reader = reader.take(stream_end.saturating_sub(stream_start));
+
+	let mut frames: Vec<FrameInfo> = Vec::new();
+
+	// `buffer[0]` is at `buffer_offset` from the start of the stream
+	let mut buffer = Vec::new();
+	let mut buffer_offset = 0;
+	let mut pos = 0;
+	let mut eof = false;
+
+	let mut first_frame: Option<FrameHeader> = None;
+	loop {
+		if buffer.len().saturating_sub(pos) < MAX_FRAME_HEADER_SIZE && !eof {
+			let consumed = core::cmp::min(pos, buffer.len());
+			drop(buffer.drain(..consumed));
+			buffer_offset += consumed as u64;
+			pos -= consumed;
+
+			let read = (&mut reader)
+				.take(SCAN_WINDOW_SIZE)
+				.read_to_end(&mut buffer)?;
+			eof = read == 0;
+		}
+
+		if pos >= buffer.len() {
+			if eof {
+				break;
+			}
+
+			continue;
+		}
+
+		let Some(header) = FrameHeader::parse(&buffer[pos..]) else {
+			pos += 1;
+			continue;
+		};
+
+		let first_frame = *first_frame.get_or_insert(header);
+		let first_sample = if header.variable_block_size {
+			header.coded_number
+		} else {
+			header.coded_number * u64::from(first_frame.block_size)
+		};
+
+		if let Some(previous) = frames.last()
+			&& (header.variable_block_size != first_frame.variable_block_size
+				|| header.channel_assignment.channels()
+					!= first_frame.channel_assignment.channels()
+				|| first_sample != previous.first_sample + u64::from(previous.block_size))
+		{
+			pos += 1;
+			continue;
+		}
+
+		frames.push(FrameInfo {
+			first_sample,
+			offset: buffer_offset + pos as u64,
+			block_size: header.block_size,
+		});
+
+		// No frame can be smaller than the minimum, so we can skip ahead
+		pos += core::cmp::max(min_frame_size as usize, header.len + 1);
+	}
+
+	Ok(frames)
+}
+
+// The frame/sample number is coded similarly to UTF-8, allowing for up to 36 bits
+fn read_coded_number(data: &[u8], pos: &mut usize) -> Option<u64> {
+	let first = *data.get(*pos)?;
+	*pos += 1;
+
+	let leading_ones = first.leading_ones();
+	if leading_ones == 0 {
+		return Some(u64::from(first));
+	}
+
+	if leading_ones == 1 || leading_ones > 7 {
+		return None;
+	}
+
+	let mut value = u64::from(first & (0x7F >> leading_ones));
+	for _ in 1..leading_ones {
+		let byte = *data.get(*pos)?;
+		if byte & 0b1100_0000 != 0b1000_0000 {
+			return None;
+		}
+
+		value = (value << 6) | u64::from(byte & 0b0011_1111);
+		*pos += 1;
+	}
+
+	Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ChannelAssignment, FrameHeader, crc8};
+
+	#[test_log::test]
+	fn crc() {
+		assert_eq!(crc8(b"123456789"), 0xF4);
+		#[cfg(feature = "flac_verification")]
+		assert_eq!(super::crc16(b"123456789"), 0xFEE8);
+	}
+
+	#[test_log::test]
+	fn parse_frame_header() {
+		// Fixed block size of 4096, 44.1 kHz, mid/side stereo, 16 bits, frame #1
+		let mut header = vec![0xFF, 0xF8, 0xC9, 0xA8, 0x01];
+		header.push(crc8(&header));
+
+		let parsed = FrameHeader::parse(&header).unwrap();
+		assert!(!parsed.variable_block_size);
+		assert_eq!(parsed.block_size, 4096);
+		assert_eq!(parsed.sample_rate, Some(44100));
+		assert_eq!(parsed.channel_assignment, ChannelAssignment::MidSide);
+		assert_eq!(parsed.bits_per_sample, Some(16));
+		assert_eq!(parsed.coded_number, 1);
+		assert_eq!(parsed.len, 6);
+
+		// Variable block size, 8 bit block size, 16 bit sample rate in Hz, sample #0x1_2345_6789
+		let mut header = vec![
+			0xFF, 0xF9, 0x6D, 0x02, 0xFE, 0x84, 0xA3, 0x91, 0x96, 0x9E, 0x89, 0x7F, 0x1F, 0x40,
+		];
+		header.push(crc8(&header));
+
+		let parsed = FrameHeader::parse(&header).unwrap();
+		assert!(parsed.variable_block_size);
+		assert_eq!(parsed.block_size, 128);
+		assert_eq!(parsed.sample_rate, Some(8000));
+		assert_eq!(parsed.channel_assignment, ChannelAssignment::Independent(1));
+		assert_eq!(parsed.bits_per_sample, Some(8));
+		assert_eq!(parsed.coded_number, 0x1_2345_6789);
+
+		// Bad CRC
+		let last = header.len() - 1;
+		header[last] ^= 0xFF;
+		assert!(FrameHeader::parse(&header).is_none());
+	}
+}
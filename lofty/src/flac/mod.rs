@@ -4,10 +4,19 @@
 //!
 //! * See [`FlacFile`]
 
+mod application;
 pub(crate) mod block;
+mod block_order;
+mod cue_sheet;
+#[cfg(feature = "flac_verification")]
+mod decoder;
 pub mod error;
+mod frame;
 pub(crate) mod properties;
 mod read;
+mod seek_table;
+#[cfg(feature = "flac_verification")]
+mod verify;
 pub(crate) mod write;
 
 use crate::config::WriteOptions;
@@ -20,13 +29,21 @@ use crate::ogg::tag::{VorbisComments, VorbisCommentsRef};
 use crate::picture::{Picture, PictureInformation};
 use crate::tag::TagExt;
 use crate::util::io::FileLike;
+use block::{BLOCK_ID_CUESHEET, BLOCK_ID_SEEKTABLE, Block};
+use write::StreamBlocks;
 
 use std::borrow::Cow;
 
 use lofty_attr::LoftyFile;
 
 // Exports
+pub use application::ApplicationBlock;
+pub use block_order::{FlacBlockOrder, FlacBlockType};
+pub use cue_sheet::{CueSheet, CueSheetTrack, CueSheetTrackIndex};
 pub use properties::FlacProperties;
+pub use seek_table::{SeekPoint, SeekTable};
+#[cfg(feature = "flac_verification")]
+pub use verify::{InvalidFrame, InvalidFrameKind, Md5Verification, verify_md5};
 
 /// A FLAC file
 ///
@@ -39,6 +56,8 @@ pub use properties::FlacProperties;
 ///   methods on `FlacFile` ([`FlacFile::pictures`], [`FlacFile::remove_picture_type`], etc.)
 /// * When converting to [`TaggedFile`], all pictures will be put inside of a [`VorbisComments`] tag, even if the
 ///   file did not originally contain one.
+/// * The `SEEKTABLE`, `CUESHEET`, and `APPLICATION` blocks are only written when saving the `FlacFile` itself.
+///   Writing a [`Tag`](crate::tag::Tag) to the file will leave them untouched.
 #[derive(LoftyFile)]
 #[lofty(read_fn = "read::read_from")]
 #[lofty(write_fn = "Self::write_to")]
@@ -51,6 +70,15 @@ pub struct FlacFile {
 	#[lofty(tag_type = "VorbisComments")]
 	pub(crate) vorbis_comments_tag: Option<VorbisComments>,
 	pub(crate) pictures: Vec<(Picture, PictureInformation)>,
+	pub(crate) seek_table: Option<SeekTable>,
+	pub(crate) cue_sheet: Option<CueSheet>,
+	/// The `CUESHEET` as it was read, along with its content
+	///
+	/// An unchanged cue sheet is written back as-is, as reading it may have been lossy.
+	pub(crate) original_cue_sheet: Option<(CueSheet, Vec<u8>)>,
+	pub(crate) application_blocks: Vec<ApplicationBlock>,
+	/// `SEEKTABLE`, `CUESHEET`, and `APPLICATION` blocks that failed to parse, written back as-is
+	pub(crate) unparsed_blocks: Vec<Block>,
 	/// The file's audio properties
 	pub(crate) properties: FlacProperties,
 }
@@ -73,9 +101,17 @@ impl FlacFile {
 
 		let file = VerifiedFile::new(file)?;
 
+		let stream_blocks = StreamBlocks {
+			seek_table: self.seek_table.as_ref(),
+			cue_sheet: self.cue_sheet.as_ref(),
+			original_cue_sheet: self.original_cue_sheet.as_ref(),
+			application_blocks: &self.application_blocks,
+			unparsed_blocks: &self.unparsed_blocks,
+		};
+
 		// We have an existing vorbis comments tag, we can just append our pictures to it
 		if let Some(ref vorbis_comments) = self.vorbis_comments_tag {
-			return VorbisCommentsRef {
+			let mut comments_ref = VorbisCommentsRef {
 				vendor: Cow::from(vorbis_comments.vendor.as_str()),
 				items: vorbis_comments
 					.items
@@ -86,21 +122,106 @@ impl FlacFile {
 					.iter()
 					.map(|(p, i)| (p, *i))
 					.chain(self.pictures.iter().map(|(p, i)| (p, *i))),
-			}
-			.write_to(file, write_options);
+			};
+
+			return write::write_metadata(
+				file,
+				Some(&mut comments_ref),
+				Some(stream_blocks),
+				write_options,
+			);
 		}
 
 		// We have pictures, but no vorbis comments tag, we'll need to create a dummy one
 		if !self.pictures.is_empty() {
-			return VorbisCommentsRef {
+			let mut comments_ref = VorbisCommentsRef {
 				vendor: Cow::from(""),
 				items: std::iter::empty(),
 				pictures: self.pictures.iter().map(|(p, i)| (p, *i)),
-			}
-			.write_to(file, write_options);
+			};
+
+			return write::write_metadata(
+				file,
+				Some(&mut comments_ref),
+				Some(stream_blocks),
+				write_options,
+			);
 		}
 
-		Ok(())
+		// No tag to write, only the stream blocks need to be updated
+		write::write_metadata::<_, std::iter::Empty<_>, std::iter::Empty<_>>(
+			file,
+			None,
+			Some(stream_blocks),
+			write_options,
+		)
+	}
+
+	/// Returns a reference to the file's `SEEKTABLE` block, if it exists
+	pub fn seek_table(&self) -> Option<&SeekTable> {
+		self.seek_table.as_ref()
+	}
+
+	/// Returns a mutable reference to the file's `SEEKTABLE` block, if it exists
+	pub fn seek_table_mut(&mut self) -> Option<&mut SeekTable> {
+		self.seek_table.as_mut()
+	}
+
+	/// Sets the file's `SEEKTABLE` block, returning the old one
+	pub fn set_seek_table(&mut self, seek_table: SeekTable) -> Option<SeekTable> {
+		self.remove_unparsed_blocks(BLOCK_ID_SEEKTABLE);
+		self.seek_table.replace(seek_table)
+	}
+
+	/// Removes the file's `SEEKTABLE` block
+	pub fn remove_seek_table(&mut self) -> Option<SeekTable> {
+		self.remove_unparsed_blocks(BLOCK_ID_SEEKTABLE);
+		self.seek_table.take()
+	}
+
+	/// Returns a reference to the file's `CUESHEET` block, if it exists
+	pub fn cue_sheet(&self) -> Option<&CueSheet> {
+		self.cue_sheet.as_ref()
+	}
+
+	/// Returns a mutable reference to the file's `CUESHEET` block, if it exists
+	pub fn cue_sheet_mut(&mut self) -> Option<&mut CueSheet> {
+		self.cue_sheet.as_mut()
+	}
+
+	/// Sets the file's `CUESHEET` block, returning the old one
+	pub fn set_cue_sheet(&mut self, cue_sheet: CueSheet) -> Option<CueSheet> {
+		self.remove_unparsed_blocks(BLOCK_ID_CUESHEET);
+		self.cue_sheet.replace(cue_sheet)
+	}
+
+	/// Removes the file's `CUESHEET` block
+	pub fn remove_cue_sheet(&mut self) -> Option<CueSheet> {
+		self.remove_unparsed_blocks(BLOCK_ID_CUESHEET);
+		self.cue_sheet.take()
+	}
+
+	/// Returns the file's `APPLICATION` blocks, in the order they appear in the file
+	pub fn application_blocks(&self) -> &[ApplicationBlock] {
+		&self.application_blocks
+	}
+
+	/// Appends an `APPLICATION` block
+	pub fn push_application_block(&mut self, application: ApplicationBlock) {
+		self.application_blocks.push(application);
+	}
+
+	/// Removes all `APPLICATION` blocks with the given ID
+	pub fn remove_application_blocks(&mut self, id: [u8; 4]) {
+		self.application_blocks
+			.retain(|application| application.id != id);
+	}
+}
+
+impl FlacFile {
+	// A block that failed to parse is replaced along with the typed one
+	fn remove_unparsed_blocks(&mut self, ty: u8) {
+		self.unparsed_blocks.retain(|block| block.ty != ty);
 	}
 }
 
@@ -16,6 +16,11 @@ pub struct FlacProperties {
 	pub(crate) bit_depth: u8,
 	pub(crate) channels: u8,
 	pub(crate) signature: u128,
+	pub(crate) min_block_size: u16,
+	pub(crate) max_block_size: u16,
+	pub(crate) min_frame_size: u32,
+	pub(crate) max_frame_size: u32,
+	pub(crate) total_samples: u64,
 }
 
 impl From<FlacProperties> for FileProperties {
@@ -67,6 +72,40 @@ impl FlacProperties {
 	pub fn signature(&self) -> u128 {
 		self.signature
 	}
+
+	/// Minimum block size (in samples) used in the stream
+	pub fn min_block_size(&self) -> u16 {
+		self.min_block_size
+	}
+
+	/// Maximum block size (in samples) used in the stream
+	pub fn max_block_size(&self) -> u16 {
+		self.max_block_size
+	}
+
+	/// Minimum frame size (in bytes) used in the stream
+	///
+	/// This will be `0` if unknown.
+	pub fn min_frame_size(&self) -> u32 {
+		self.min_frame_size
+	}
+
+	/// Maximum frame size (in bytes) used in the stream
+	///
+	/// This will be `0` if unknown.
+	pub fn max_frame_size(&self) -> u32 {
+		self.max_frame_size
+	}
+
+	/// Total number of samples (per channel) in the stream
+	///
+	/// This will be `0` if unknown, which is common for streamed encodes. The sample count can be
+	/// recovered by scanning the audio frames, see [`ParseOptions::scan_frames`].
+	///
+	/// [`ParseOptions::scan_frames`]: crate::config::ParseOptions::scan_frames
+	pub fn total_samples(&self) -> u64 {
+		self.total_samples
+	}
 }
 
 pub(crate) fn read_properties<R>(
@@ -77,15 +116,11 @@ pub(crate) fn read_properties<R>(
 where
 	R: Read,
 {
-	// Skip 4 bytes
-	// Minimum block size (2)
-	// Maximum block size (2)
-	stream_info.read_u32::<BigEndian>()?;
+	let min_block_size = stream_info.read_u16::<BigEndian>()?;
+	let max_block_size = stream_info.read_u16::<BigEndian>()?;
 
-	// Skip 6 bytes
-	// Minimum frame size (3)
-	// Maximum frame size (3)
-	stream_info.read_uint::<BigEndian>(6)?;
+	let min_frame_size = stream_info.read_u24::<BigEndian>()?;
+	let max_frame_size = stream_info.read_u24::<BigEndian>()?;
 
 	// Read 4 bytes
 	// Sample rate (20 bits)
@@ -109,18 +144,32 @@ where
 		bit_depth: bits_per_sample as u8,
 		channels: channels as u8,
 		signature,
+		min_block_size,
+		max_block_size,
+		min_frame_size,
+		max_frame_size,
+		total_samples,
 		..FlacProperties::default()
 	};
 
-	if sample_rate > 0 && total_samples > 0 {
-		let length = (total_samples * 1000) / u64::from(sample_rate);
-		properties.duration = Duration::from_millis(length);
+	properties.calculate_duration(stream_length, file_length);
+
+	Ok(properties)
+}
+
+impl FlacProperties {
+	/// Calculate the duration and bitrates from the total sample count
+	pub(super) fn calculate_duration(&mut self, stream_length: u64, file_length: u64) {
+		if self.sample_rate == 0 || self.total_samples == 0 {
+			return;
+		}
+
+		let length = (self.total_samples * 1000) / u64::from(self.sample_rate);
+		self.duration = Duration::from_millis(length);
 
 		if length > 0 && file_length > 0 && stream_length > 0 {
-			properties.overall_bitrate = ((file_length * 8) / length) as u32;
-			properties.audio_bitrate = ((stream_length * 8) / length) as u32;
+			self.overall_bitrate = ((file_length * 8) / length) as u32;
+			self.audio_bitrate = ((stream_length * 8) / length) as u32;
 		}
 	}
-
-	Ok(properties)
 }
@@ -1,9 +1,15 @@
 use super::FlacFile;
+use super::application::ApplicationBlock;
 use super::block::Block;
+use super::cue_sheet::CueSheet;
 use super::properties::FlacProperties;
+use super::seek_table::SeekTable;
 use crate::config::{ParseOptions, ParsingMode};
 use crate::error::{SizeMismatchError, TagParseError};
-use crate::flac::block::{BLOCK_ID_PICTURE, BLOCK_ID_STREAMINFO, BLOCK_ID_VORBIS_COMMENTS};
+use crate::flac::block::{
+	BLOCK_ID_APPLICATION, BLOCK_ID_CUESHEET, BLOCK_ID_PICTURE, BLOCK_ID_SEEKTABLE,
+	BLOCK_ID_STREAMINFO, BLOCK_ID_VORBIS_COMMENTS,
+};
 use crate::flac::error::FlacParseError;
 use crate::id3::v2::read::parse_id3v2;
 use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v2};
@@ -48,6 +54,11 @@ where
 		id3v2_tag: None,
 		vorbis_comments_tag: None,
 		pictures: Vec::new(),
+		seek_table: None,
+		cue_sheet: None,
+		original_cue_sheet: None,
+		application_blocks: Vec::new(),
+		unparsed_blocks: Vec::new(),
 		properties: FlacProperties::default(),
 	};
 
@@ -79,17 +90,79 @@ where
 	let mut last_block = stream_info.last;
 
 	while !last_block {
-		let block = Block::read(data, |block_type| {
-			(block_type == BLOCK_ID_VORBIS_COMMENTS && parse_options.read_tags)
-				|| (block_type == BLOCK_ID_PICTURE && parse_options.read_cover_art)
+		let block = Block::read(data, |block_type| match block_type {
+			BLOCK_ID_VORBIS_COMMENTS => parse_options.read_tags,
+			BLOCK_ID_PICTURE => parse_options.read_cover_art,
+			BLOCK_ID_SEEKTABLE | BLOCK_ID_CUESHEET | BLOCK_ID_APPLICATION => true,
+			_ => false,
 		})?;
 
 		last_block = block.last;
 
+		if block.ty == BLOCK_ID_SEEKTABLE {
+			log::debug!("Encountered a SEEKTABLE block, parsing");
+
+			// An empty seek table is still valid
+			match SeekTable::parse(&block.content, parse_options.parsing_mode) {
+				Ok(seek_table) => flac_file.seek_table = Some(seek_table),
+				Err(e) => handle_block_error(
+					e,
+					block,
+					"SEEKTABLE",
+					parse_options.parsing_mode,
+					&mut flac_file.unparsed_blocks,
+				)?,
+			}
+
+			continue;
+		}
+
+		if matches!(block.ty, BLOCK_ID_CUESHEET | BLOCK_ID_APPLICATION) && block.content.is_empty()
+		{
+			// Nothing to parse, but the block is still written back
+			flac_file.unparsed_blocks.push(block);
+			continue;
+		}
+
 		if block.content.is_empty() {
 			continue;
 		}
 
+		if block.ty == BLOCK_ID_CUESHEET {
+			log::debug!("Encountered a CUESHEET block, parsing");
+
+			match CueSheet::parse(&block.content, parse_options.parsing_mode) {
+				Ok(cue_sheet) => {
+					flac_file.original_cue_sheet = Some((cue_sheet.clone(), block.content));
+					flac_file.cue_sheet = Some(cue_sheet);
+				},
+				Err(e) => handle_block_error(
+					e,
+					block,
+					"CUESHEET",
+					parse_options.parsing_mode,
+					&mut flac_file.unparsed_blocks,
+				)?,
+			}
+
+			continue;
+		}
+
+		if block.ty == BLOCK_ID_APPLICATION {
+			match ApplicationBlock::parse(&block.content) {
+				Ok(application) => flac_file.application_blocks.push(application),
+				Err(e) => handle_block_error(
+					e,
+					block,
+					"APPLICATION",
+					parse_options.parsing_mode,
+					&mut flac_file.unparsed_blocks,
+				)?,
+			}
+
+			continue;
+		}
+
 		if block.ty == BLOCK_ID_VORBIS_COMMENTS && parse_options.read_tags {
 			log::debug!("Encountered a Vorbis Comments block, parsing");
 
@@ -142,7 +215,7 @@ where
 		return Ok(flac_file);
 	}
 
-	let (stream_length, file_length) = {
+	let (stream_start, stream_length, file_length) = {
 		let current = data.stream_position()?;
 		let end = data.seek(SeekFrom::End(0))?;
 
@@ -152,11 +225,41 @@ where
 			return Err(SizeMismatchError.into());
 		}
 
-		(end - current, end)
+		(current, end - current, end)
 	};
 
-	flac_file.properties =
+	let mut properties =
 		super::properties::read_properties(&mut &*stream_info.content, stream_length, file_length)?;
 
+	if properties.total_samples == 0 && parse_options.scan_frames && stream_length > 0 {
+		log::debug!("STREAMINFO is missing the total sample count, scanning frames");
+
+		if let Some(total_samples) =
+			super::frame::scan_total_samples(data, stream_start, file_length, &properties)?
+		{
+			properties.total_samples = total_samples;
+			properties.calculate_duration(stream_length, file_length);
+		}
+	}
+
+	flac_file.properties = properties;
+
 	Ok(flac_file)
 }
+
+// Blocks that fail to parse are kept around, so they can be written back untouched
+fn handle_block_error(
+	err: FlacParseError,
+	block: Block,
+	block_name: &str,
+	parsing_mode: ParsingMode,
+	unparsed_blocks: &mut Vec<Block>,
+) -> Result<(), FlacParseError> {
+	if parsing_mode == ParsingMode::Strict {
+		return Err(err);
+	}
+
+	log::warn!("Unable to read FLAC {block_name} block, retaining it as-is");
+	unparsed_blocks.push(block);
+	Ok(())
+}
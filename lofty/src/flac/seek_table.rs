@@ -0,0 +1,153 @@
+use crate::config::ParsingMode;
+use crate::error::TooMuchDataError;
+use crate::flac::block::Block;
+use crate::flac::error::{FlacEncodingError, FlacParseError};
+use crate::flac::frame::FrameInfo;
+use crate::util::alloc::VecFallibleCapacity;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+/// A single point in a [`SeekTable`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SeekPoint {
+	/// The sample number of the first sample in the target frame
+	///
+	/// This is [`SeekPoint::PLACEHOLDER`] for placeholder points.
+	pub sample_number: u64,
+	/// The offset (in bytes) from the first byte of the first frame header to the first byte of
+	/// the target frame's header
+	pub stream_offset: u64,
+	/// The number of samples in the target frame
+	pub frame_samples: u16,
+}
+
+impl SeekPoint {
+	/// The sample number used for placeholder points
+	pub const PLACEHOLDER: u64 = u64::MAX;
+
+	const SIZE: usize = 18;
+
+	/// Create a placeholder point
+	///
+	/// Placeholder points are ignored by decoders, and exist to reserve space for seek points to
+	/// be filled in later.
+	pub fn placeholder() -> Self {
+		Self {
+			sample_number: Self::PLACEHOLDER,
+			stream_offset: 0,
+			frame_samples: 0,
+		}
+	}
+
+	/// Whether this is a placeholder point
+	pub fn is_placeholder(&self) -> bool {
+		self.sample_number == Self::PLACEHOLDER
+	}
+}
+
+/// A FLAC `SEEKTABLE` block
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9639.html#name-seektable>
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SeekTable {
+	/// The seek points, sorted by sample number with any placeholders at the end
+	pub points: Vec<SeekPoint>,
+}
+
+impl SeekTable {
+	/// Create a new `SeekTable`
+	///
+	/// NOTE: The points will be sorted by sample number, and duplicate non-placeholder points will
+	///       be removed.
+	pub fn new(points: Vec<SeekPoint>) -> Self {
+		let mut ret = Self { points };
+		ret.sort();
+		ret
+	}
+
+	/// Sort the seek points and remove duplicates, as required by the spec
+	pub fn sort(&mut self) {
+		self.points.sort_by_key(|point| point.sample_number);
+		self.points
+			.dedup_by(|a, b| !a.is_placeholder() && a.sample_number == b.sample_number);
+	}
+
+	/// Create a seek table with a point at every `interval` samples
+	///
+	/// Each point refers to the last frame starting at or before its target sample.
+	pub(super) fn from_frames(frames: &[FrameInfo], interval: u64) -> Self {
+		let mut points: Vec<SeekPoint> = Vec::new();
+
+		let Some(last_frame) = frames.last() else {
+			return Self { points };
+		};
+
+		let total_samples = last_frame.first_sample + u64::from(last_frame.block_size);
+
+		let mut frame_idx = 0;
+		let mut target = 0;
+		while target < total_samples {
+			while frames
+				.get(frame_idx + 1)
+				.is_some_and(|frame| frame.first_sample <= target)
+			{
+				frame_idx += 1;
+			}
+
+			let frame = frames[frame_idx];
+			if points.last().map(|point| point.sample_number) != Some(frame.first_sample) {
+				points.push(SeekPoint {
+					sample_number: frame.first_sample,
+					stream_offset: frame.offset,
+					frame_samples: frame.block_size as u16,
+				});
+			}
+
+			target += interval;
+		}
+
+		Self { points }
+	}
+
+	pub(super) fn parse(content: &[u8], parse_mode: ParsingMode) -> Result<Self, FlacParseError> {
+		if !content.len().is_multiple_of(SeekPoint::SIZE) {
+			if parse_mode == ParsingMode::Strict {
+				return Err(FlacParseError::message(
+					"SEEKTABLE size is not a multiple of the seek point size",
+				));
+			}
+
+			log::warn!("SEEKTABLE has trailing data, discarding");
+		}
+
+		let point_count = content.len() / SeekPoint::SIZE;
+		let mut points = Vec::try_with_capacity_stable(point_count)?;
+
+		let reader = &mut &content[..point_count * SeekPoint::SIZE];
+		for _ in 0..point_count {
+			points.push(SeekPoint {
+				sample_number: reader.read_u64::<BigEndian>()?,
+				stream_offset: reader.read_u64::<BigEndian>()?,
+				frame_samples: reader.read_u16::<BigEndian>()?,
+			});
+		}
+
+		Ok(Self { points })
+	}
+
+	pub(super) fn as_bytes(&self) -> Result<Vec<u8>, FlacEncodingError> {
+		let size = self.points.len() * SeekPoint::SIZE;
+		if size > Block::MAX_CONTENT_SIZE as usize {
+			return Err(TooMuchDataError.into());
+		}
+
+		let mut content = Vec::try_with_capacity_stable(size)?;
+		for point in &self.points {
+			content.write_u64::<BigEndian>(point.sample_number)?;
+			content.write_u64::<BigEndian>(point.stream_offset)?;
+			content.write_u16::<BigEndian>(point.frame_samples)?;
+		}
+
+		Ok(content)
+	}
+}
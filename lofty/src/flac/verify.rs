@@ -0,0 +1,240 @@
+use super::block::Block;
+use super::decoder::{Decoder, FrameError};
+use super::frame::{FrameHeader, MAX_FRAME_HEADER_SIZE};
+use super::read::verify_flac;
+use crate::error::{FileParseError, TagParseError};
+use crate::flac::error::FlacParseError;
+use crate::id3::{FindId3v2Config, find_id3v2};
+use crate::util::md5::Md5;
+
+use std::io::{Read, Seek};
+
+// The amount of data to read at a time
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+// The largest possible frame is a verbatim frame with 8 channels of 65536 33-bit samples,
+// this gives a little room for the headers.
+const MAX_FRAME_SIZE: usize = 2_200_000;
+
+/// The reason a frame failed verification
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InvalidFrameKind {
+	/// The frame could not be decoded
+	Malformed,
+	/// The frame decoded, but its CRC-16 did not match
+	CrcMismatch,
+	/// The stream ended in the middle of the frame
+	Truncated,
+}
+
+/// A frame that failed verification
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InvalidFrame {
+	/// The offset of the frame from the start of the file
+	pub offset: u64,
+	/// Why the frame is invalid
+	pub kind: InvalidFrameKind,
+}
+
+/// The result of [`verify_md5`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Md5Verification {
+	expected_signature: u128,
+	computed_signature: u128,
+	decoded_samples: u64,
+	invalid_frames: Vec<InvalidFrame>,
+}
+
+impl Md5Verification {
+	/// The MD5 signature stored in STREAMINFO
+	///
+	/// This will be `0` if the encoder did not compute one.
+	pub fn expected_signature(&self) -> u128 {
+		self.expected_signature
+	}
+
+	/// The MD5 signature of the decoded audio
+	pub fn computed_signature(&self) -> u128 {
+		self.computed_signature
+	}
+
+	/// Whether the stream has an MD5 signature to compare against
+	pub fn has_signature(&self) -> bool {
+		self.expected_signature != 0
+	}
+
+	/// Whether the decoded audio matches the stored signature
+	///
+	/// This will always be `false` if the stream has no signature. See [`Md5Verification::has_signature`].
+	pub fn is_valid(&self) -> bool {
+		self.has_signature()
+			&& self.expected_signature == self.computed_signature
+			&& self.invalid_frames.is_empty()
+	}
+
+	/// The number of samples (per channel) that were successfully decoded
+	pub fn decoded_samples(&self) -> u64 {
+		self.decoded_samples
+	}
+
+	/// The frames that failed to decode, in stream order
+	///
+	/// A run of consecutive damaged frames is reported once, at the offset of the first frame.
+	/// The samples of these frames are missing from the computed signature, so any entry here
+	/// will also cause a signature mismatch.
+	pub fn invalid_frames(&self) -> &[InvalidFrame] {
+		&self.invalid_frames
+	}
+}
+
+/// Decode a FLAC stream and verify it against the MD5 signature in STREAMINFO
+///
+/// Each frame is also checked against its own CRC-16, allowing the damaged parts of the
+/// stream to be located. See [`Md5Verification::invalid_frames`].
+///
+/// NOTE: This requires the `flac_verification` feature.
+///
+/// # Errors
+///
+/// * `reader` is not a valid FLAC stream
+/// * The STREAMINFO block is invalid
+/// * Any I/O error occurs while reading
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use lofty::flac::verify_md5;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut file = std::fs::File::open("tests/files/assets/minimal/full_test.flac")?;
+///
+/// let verification = verify_md5(&mut file)?;
+/// if !verification.is_valid() {
+/// 	for frame in verification.invalid_frames() {
+/// 		println!("Invalid frame at {}: {:?}", frame.offset, frame.kind);
+/// 	}
+/// }
+/// # Ok(()) }
+/// ```
+pub fn verify_md5<R>(reader: &mut R) -> Result<Md5Verification, FileParseError>
+where
+	R: Read + Seek,
+{
+	find_id3v2(reader, FindId3v2Config::NO_READ_TAG).map_err(TagParseError::from)?;
+
+	let stream_info = verify_flac(reader)?;
+	let properties = super::properties::read_properties(&mut &*stream_info.content, 0, 0)?;
+
+	if properties.channels == 0 || properties.bit_depth < 4 {
+		return Err(FlacParseError::message("STREAMINFO block is invalid").into());
+	}
+
+	let mut last_block = stream_info.last;
+	while !last_block {
+		let block = Block::read(reader, |_| false)?;
+		last_block = block.last;
+	}
+
+	let mut offset = reader.stream_position()?;
+	let mut decoder = Decoder::new(properties.channels, properties.bit_depth);
+	let mut md5 = Md5::default();
+	let mut sample_buffer = Vec::new();
+
+	let mut verification = Md5Verification {
+		expected_signature: properties.signature,
+		computed_signature: 0,
+		decoded_samples: 0,
+		invalid_frames: Vec::new(),
+	};
+
+	let bytes_per_sample = usize::from(properties.bit_depth.div_ceil(8));
+
+	let mut buffer = Vec::new();
+	let mut eof = false;
+
+	// The first frame of the region we're currently skipping over
+	let mut damaged_frame: Option<InvalidFrame> = None;
+	let mut damaged_frame_has_header = false;
+	loop {
+		if buffer.len() < READ_CHUNK_SIZE && !eof {
+			eof = fill_buffer(reader, &mut buffer)?;
+		}
+
+		if buffer.is_empty() {
+			break;
+		}
+
+		let (kind, has_header) = match decoder.decode_frame(&buffer) {
+			Ok(frame_len) => {
+				if let Some(frame) = damaged_frame.take() {
+					verification.invalid_frames.push(frame);
+				}
+
+				sample_buffer.clear();
+				for i in 0..decoder.block_size() {
+					for channel in 0..properties.channels {
+						let sample = decoder.sample(channel, i);
+						sample_buffer.extend_from_slice(&sample.to_le_bytes()[..bytes_per_sample]);
+					}
+				}
+
+				md5.update(&sample_buffer);
+				verification.decoded_samples += decoder.block_size() as u64;
+
+				drop(buffer.drain(..frame_len));
+				offset += frame_len as u64;
+				continue;
+			},
+			Err(FrameError::Truncated) if !eof && buffer.len() < MAX_FRAME_SIZE => {
+				eof = fill_buffer(reader, &mut buffer)?;
+				continue;
+			},
+			Err(FrameError::Truncated) => (InvalidFrameKind::Truncated, true),
+			Err(FrameError::InvalidHeader) => (InvalidFrameKind::Malformed, false),
+			Err(FrameError::Malformed) => (InvalidFrameKind::Malformed, true),
+			Err(FrameError::CrcMismatch) => (InvalidFrameKind::CrcMismatch, true),
+		};
+
+		if damaged_frame.is_none() {
+			log::warn!("Invalid FLAC frame at offset {offset}: {kind:?}");
+			damaged_frame = Some(InvalidFrame { offset, kind });
+			damaged_frame_has_header = has_header;
+		}
+
+		// Search for the next frame header, leaving any possibly incomplete header in the buffer
+		let skip = (1..buffer.len())
+			.find(|&pos| {
+				let data = &buffer[pos..];
+				FrameHeader::is_sync(data)
+					&& (FrameHeader::parse(data).is_some()
+						|| (!eof && data.len() < MAX_FRAME_HEADER_SIZE))
+			})
+			.unwrap_or(if eof { buffer.len() } else { buffer.len() - 1 });
+
+		drop(buffer.drain(..skip));
+		offset += skip as u64;
+	}
+
+	if let Some(frame) = damaged_frame {
+		// Some files have trailing data (such as an ID3v1 tag) after the final frame
+		if damaged_frame_has_header {
+			verification.invalid_frames.push(frame);
+		} else {
+			log::warn!("Ignoring trailing data at offset {}", frame.offset);
+		}
+	}
+
+	verification.computed_signature = md5.finalize();
+	Ok(verification)
+}
+
+// Returns `true` if the end of the stream has been reached
+fn fill_buffer<R>(reader: &mut R, buffer: &mut Vec<u8>) -> std::io::Result<bool>
+where
+	R: Read,
+{
+	let read = reader.take(READ_CHUNK_SIZE as u64).read_to_end(buffer)?;
+
+	Ok(read == 0)
+}
@@ -1,5 +1,12 @@
-use super::block::{BLOCK_ID_PADDING, BLOCK_ID_PICTURE, BLOCK_ID_VORBIS_COMMENTS, Block};
+use super::application::{ApplicationBlock, is_registered_id};
+use super::block::{
+	BLOCK_ID_APPLICATION, BLOCK_ID_CUESHEET, BLOCK_ID_PADDING, BLOCK_ID_PICTURE,
+	BLOCK_ID_SEEKTABLE, BLOCK_ID_VORBIS_COMMENTS, Block,
+};
+use super::cue_sheet::CueSheet;
+use super::frame::scan_frames;
 use super::read::verify_flac;
+use super::seek_table::SeekTable;
 use crate::config::WriteOptions;
 use crate::error::{FileEncodingError, FileParseError, SizeMismatchError, TagParseError};
 use crate::id3::{FindId3v2Config, find_id3v2};
@@ -8,12 +15,12 @@ use crate::macros::try_vec;
 use crate::ogg::tag::VorbisCommentsRef;
 use crate::picture::{Picture, PictureInformation};
 use crate::tag::{Tag, TagType};
-use crate::util::io::FileLike;
+use crate::util::io::{FileLike, replace_range};
 
 use std::borrow::Cow;
-use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::io::{Read, Seek};
 use std::iter::Peekable;
-use std::ops::Range;
+use std::time::Duration;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
@@ -45,11 +52,72 @@ where
 	}
 }
 
+/// Replacements for the `SEEKTABLE`, `CUESHEET`, and `APPLICATION` blocks
+///
+/// These are only provided when writing a [`FlacFile`](super::FlacFile), otherwise the
+/// existing blocks are left untouched.
+pub(super) struct StreamBlocks<'a> {
+	pub(super) seek_table: Option<&'a SeekTable>,
+	pub(super) cue_sheet: Option<&'a CueSheet>,
+	pub(super) original_cue_sheet: Option<&'a (CueSheet, Vec<u8>)>,
+	pub(super) application_blocks: &'a [ApplicationBlock],
+	/// Blocks that failed to parse, these are written back unchanged
+	pub(super) unparsed_blocks: &'a [Block],
+}
+
+impl StreamBlocks<'_> {
+	fn encode(&self) -> Result<Vec<Block>, FileEncodingError> {
+		let mut blocks = Vec::new();
+
+		if let Some(seek_table) = self.seek_table {
+			blocks.push(Block::new(BLOCK_ID_SEEKTABLE, seek_table.as_bytes()?));
+		}
+
+		if let Some(cue_sheet) = self.cue_sheet {
+			let content = match self.original_cue_sheet {
+				Some((original, content)) if original == cue_sheet => content.clone(),
+				_ => cue_sheet.as_bytes()?,
+			};
+
+			blocks.push(Block::new(BLOCK_ID_CUESHEET, content));
+		}
+
+		for application in self.application_blocks {
+			blocks.push(Block::new(BLOCK_ID_APPLICATION, application.as_bytes()?));
+		}
+
+		for block in self.unparsed_blocks {
+			blocks.push(Block::new(block.ty, block.content.clone()));
+		}
+
+		Ok(blocks)
+	}
+}
+
 pub(crate) fn write_to_inner<'a, F, II, IP>(
 	file: VerifiedFile<'_, F>,
 	tag: &mut VorbisCommentsRef<'a, II, IP>,
 	write_options: WriteOptions,
 ) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+	II: Iterator<Item = (&'a str, &'a str)>,
+	IP: Iterator<Item = (&'a Picture, PictureInformation)>,
+{
+	write_metadata(file, Some(tag), None, write_options)
+}
+
+/// Rewrite the metadata blocks of a FLAC file
+///
+/// * If `tag` is `None`, the existing `VORBIS_COMMENT` and `PICTURE` blocks are retained.
+/// * If `stream_blocks` is `None`, the existing `SEEKTABLE`, `CUESHEET`, and `APPLICATION` blocks
+///   are retained.
+pub(super) fn write_metadata<'a, F, II, IP>(
+	file: VerifiedFile<'_, F>,
+	mut tag: Option<&mut VorbisCommentsRef<'a, II, IP>>,
+	stream_blocks: Option<StreamBlocks<'_>>,
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
 where
 	F: FileLike,
 	II: Iterator<Item = (&'a str, &'a str)>,
@@ -64,6 +132,9 @@ where
 
 	let mut stream_info = verify_flac(&mut file).map_err(FileParseError::from)?;
 
+	let replace_tag = tag.is_some();
+	let replace_stream_blocks = stream_blocks.is_some();
+
 	let mut is_last_block = stream_info.last;
 	let mut has_blocks_to_remove = false;
 	let mut has_padding = false;
@@ -72,10 +143,11 @@ where
 
 	let mut metadata_range = stream_info.start..stream_info.end;
 	let mut blocks = vec![stream_info];
+	let mut original_stream_blocks = Vec::new();
 	while !is_last_block {
 		let mut skip = false;
 		let mut block = Block::read(&mut file, |ty| match ty {
-			BLOCK_ID_PICTURE => {
+			BLOCK_ID_PICTURE if replace_tag => {
 				has_blocks_to_remove = true;
 				skip = true;
 				false
@@ -89,7 +161,9 @@ where
 		.map_err(FileParseError::from)?;
 
 		// Retain the original vendor string
-		if block.ty == BLOCK_ID_VORBIS_COMMENTS {
+		if block.ty == BLOCK_ID_VORBIS_COMMENTS
+			&& let Some(tag) = tag.as_deref_mut()
+		{
 			let reader = &mut &block.content[..];
 
 			let vendor_len = reader.read_u32::<LittleEndian>()?;
@@ -116,6 +190,15 @@ where
 		is_last_block = block.last;
 		metadata_range.end = block.end;
 
+		if replace_stream_blocks
+			&& matches!(
+				block.ty,
+				BLOCK_ID_SEEKTABLE | BLOCK_ID_CUESHEET | BLOCK_ID_APPLICATION
+			) {
+			original_stream_blocks.push(block);
+			continue;
+		}
+
 		if !skip {
 			// Last block determined later
 			block.last = false;
@@ -123,24 +206,84 @@ where
 		}
 	}
 
-	let mut comments_peek = (&mut tag.items).peekable();
-	let mut pictures_peek = (&mut tag.pictures).peekable();
+	let new_stream_blocks = match stream_blocks {
+		Some(stream_blocks) => stream_blocks.encode()?,
+		None => Vec::new(),
+	};
+
+	// The `SEEKTABLE`, `CUESHEET`, and `APPLICATION` blocks are only rewritten if they changed
+	let stream_blocks_changed = new_stream_blocks.len() != original_stream_blocks.len()
+		|| new_stream_blocks
+			.iter()
+			.zip(&original_stream_blocks)
+			.any(|(new, original)| new.ty != original.ty || new.content != original.content);
+
+	// Typed blocks go directly after `STREAMINFO`
+	drop(blocks.splice(1..1, new_stream_blocks));
 
-	let has_comments = comments_peek.peek().is_some();
-	let has_pictures = pictures_peek.peek().is_some();
+	// Any of the FLAC specific options may require a rewrite, regardless of the tag
+	let options_require_rewrite = write_options.flac_block_order.is_some()
+		|| write_options.flac_seek_table_interval.is_some()
+		|| write_options.flac_strip_unknown_application_blocks;
+
+	let mut metadata_blocks = Vec::new();
+	if let Some(tag) = tag {
+		let mut comments_peek = (&mut tag.items).peekable();
+		let mut pictures_peek = (&mut tag.pictures).peekable();
+
+		let has_comments = comments_peek.peek().is_some();
+		let has_pictures = pictures_peek.peek().is_some();
+
+		// Attempting to strip an already empty file
+		if !has_blocks_to_remove
+			&& !has_comments
+			&& !has_pictures
+			&& !stream_blocks_changed
+			&& !options_require_rewrite
+		{
+			log::debug!("Nothing to do");
+			return Ok(());
+		}
 
-	// Attempting to strip an already empty file
-	if !has_blocks_to_remove && !has_comments && !has_pictures {
+		metadata_blocks = encode_tag(&tag.vendor, comments_peek, pictures_peek)?;
+	} else if !stream_blocks_changed && !options_require_rewrite {
 		log::debug!("Nothing to do");
 		return Ok(());
 	}
 
-	// TODO: We need to actually use padding (https://github.com/Serial-ATA/lofty-rs/issues/445)
-	let will_write_padding = !has_padding && write_options.preferred_padding.is_some();
+	blocks.extend(metadata_blocks);
 
-	let metadata_blocks = encode_tag(&tag.vendor, comments_peek, pictures_peek)?;
+	if let Some(interval) = write_options.flac_seek_table_interval {
+		// The file is positioned at the first frame
+		let stream_start = metadata_range.end;
+		if let Some(seek_table) =
+			generate_seek_table(&mut file, &blocks[0], stream_start, interval)?
+		{
+			blocks.retain(|block| block.ty != BLOCK_ID_SEEKTABLE);
+			blocks.insert(1, Block::new(BLOCK_ID_SEEKTABLE, seek_table.as_bytes()?));
+		}
+	}
 
-	blocks.extend(metadata_blocks);
+	if write_options.flac_strip_unknown_application_blocks {
+		blocks.retain(|block| {
+			if block.ty != BLOCK_ID_APPLICATION {
+				return true;
+			}
+
+			let keep = block
+				.content
+				.first_chunk::<4>()
+				.is_some_and(|id| is_registered_id(*id));
+			if !keep {
+				log::debug!("Removing unknown APPLICATION block");
+			}
+
+			keep
+		});
+	}
+
+	// TODO: We need to actually use padding (https://github.com/Serial-ATA/lofty-rs/issues/445)
+	let will_write_padding = !has_padding && write_options.preferred_padding.is_some();
 
 	if will_write_padding && let Some(preferred_padding) = write_options.preferred_padding {
 		log::warn!("File is missing a PADDING block. Adding one");
@@ -162,6 +305,11 @@ where
 		blocks.push(Block::new_padding(padding_len)?);
 	}
 
+	// `STREAMINFO` always stays first
+	if let Some(block_order) = write_options.flac_block_order {
+		blocks[1..].sort_by_key(|block| block_order.position(block.ty));
+	}
+
 	if let Some(block) = blocks.last_mut() {
 		block.last = true;
 	}
@@ -181,127 +329,38 @@ where
 	Ok(())
 }
 
-const MOVE_BUFFER_SIZE: usize = 64 * 1024;
-
-fn replace_range<F>(file: &mut F, range: Range<u64>, replacement: &[u8]) -> std::io::Result<()>
-where
-	F: FileLike,
-{
-	if range.start > range.end {
-		return Err(std::io::Error::new(
-			ErrorKind::InvalidInput,
-			"range start exceeds range end",
-		));
-	}
-
-	let file_len = Length::len(file)?;
-	if range.end > file_len {
-		return Err(std::io::Error::new(
-			ErrorKind::InvalidInput,
-			"range extends beyond file length",
-		));
-	}
-
-	let old_len = range.end - range.start;
-	let replacement_len = u64::try_from(replacement.len())
-		.map_err(|_| std::io::Error::new(ErrorKind::InvalidInput, "replacement is too large"))?;
-
-	let mut buffer = vec![0_u8; MOVE_BUFFER_SIZE];
-
-	match replacement_len.cmp(&old_len) {
-		std::cmp::Ordering::Greater => {
-			let difference = replacement_len - old_len;
-			// The ranges overlap, so move the tail backwards from EOF before writing metadata.
-			extend_storage(file, difference, &buffer)?;
-			shift_right(file, range.end, file_len, difference, &mut buffer)?;
-		},
-		std::cmp::Ordering::Less => {
-			let difference = old_len - replacement_len;
-			// Move forwards from the metadata boundary so writes cannot clobber unread tail data.
-			shift_left(file, range.end, file_len, difference, &mut buffer)?;
-			file.truncate(file_len - difference)?;
-		},
-		std::cmp::Ordering::Equal => {},
-	}
-
-	file.seek(SeekFrom::Start(range.start))?;
-	file.write_all(replacement)?;
-
-	Ok(())
-}
-
-fn extend_storage<F>(file: &mut F, amount: u64, zeros: &[u8]) -> std::io::Result<()>
-where
-	F: FileLike,
-{
-	file.seek(SeekFrom::End(0))?;
-
-	let mut remaining = amount;
-	while remaining != 0 {
-		let chunk_len = usize::try_from(remaining.min(zeros.len() as u64))
-			.expect("chunk length is bounded by the in-memory buffer");
-		file.write_all(&zeros[..chunk_len])?;
-		remaining -= chunk_len as u64;
-	}
-
-	Ok(())
-}
-
-fn shift_right<F>(
+// Scan the stream to create a new `SEEKTABLE`, returns `None` if no frames were found
+fn generate_seek_table<F>(
 	file: &mut F,
-	start: u64,
-	end: u64,
-	amount: u64,
-	buffer: &mut [u8],
-) -> std::io::Result<()>
+	stream_info: &Block,
+	stream_start: u64,
+	interval: Duration,
+) -> Result<Option<SeekTable>, FileEncodingError>
 where
 	F: FileLike,
 {
-	let mut cursor = end;
+	let properties = super::properties::read_properties(&mut &*stream_info.content, 0, 0)?;
 
-	while cursor > start {
-		let chunk_len = usize::try_from((cursor - start).min(buffer.len() as u64))
-			.expect("chunk length is bounded by the in-memory buffer");
-		let source = cursor - chunk_len as u64;
-
-		file.seek(SeekFrom::Start(source))?;
-		file.read_exact(&mut buffer[..chunk_len])?;
-
-		file.seek(SeekFrom::Start(source + amount))?;
-		file.write_all(&buffer[..chunk_len])?;
-
-		cursor = source;
+	let interval_samples =
+		(interval.as_secs_f64() * f64::from(properties.sample_rate)).round() as u64;
+	if interval_samples == 0 {
+		log::warn!("Seek point interval is too small, not generating a SEEKTABLE");
+		return Ok(None);
 	}
 
-	Ok(())
-}
-
-fn shift_left<F>(
-	file: &mut F,
-	start: u64,
-	end: u64,
-	amount: u64,
-	buffer: &mut [u8],
-) -> std::io::Result<()>
-where
-	F: FileLike,
-{
-	let mut cursor = start;
-
-	while cursor < end {
-		let chunk_len = usize::try_from((end - cursor).min(buffer.len() as u64))
-			.expect("chunk length is bounded by the in-memory buffer");
-
-		file.seek(SeekFrom::Start(cursor))?;
-		file.read_exact(&mut buffer[..chunk_len])?;
-
-		file.seek(SeekFrom::Start(cursor - amount))?;
-		file.write_all(&buffer[..chunk_len])?;
-
-		cursor += chunk_len as u64;
+	let stream_end = Length::len(file)?;
+	let frames = scan_frames(file, stream_start, stream_end, properties.min_frame_size)
+		.map_err(FileParseError::from)?;
+	if frames.is_empty() {
+		log::warn!("Unable to find any FLAC frames, not generating a SEEKTABLE");
+		return Ok(None);
 	}
 
-	Ok(())
+	log::debug!(
+		"Generating a SEEKTABLE from {} frames, with a point every {interval_samples} samples",
+		frames.len()
+	);
+	Ok(Some(SeekTable::from_frames(&frames, interval_samples)))
 }
 
 fn encode_tag<'a, II, IP>(
@@ -325,57 +384,3 @@ where
 
 	Ok(metadata_blocks)
 }
-
-#[cfg(test)]
-mod tests {
-	use super::*;
-
-	use std::io::Cursor;
-
-	fn apply_range(input: Vec<u8>, range: Range<usize>, replacement: &[u8]) {
-		let mut expected = input.clone();
-		drop(expected.splice(range.clone(), replacement.iter().copied()));
-
-		let mut cursor = Cursor::new(input);
-		replace_range(
-			&mut cursor,
-			(range.start as u64)..(range.end as u64),
-			replacement,
-		)
-		.expect("range replacement should succeed");
-
-		let actual = cursor.into_inner();
-		assert_eq!(actual, expected);
-	}
-
-	#[test]
-	fn replace_range_equal_size() {
-		apply_range(b"0123456789".to_vec(), 2..5, b"XYZ");
-	}
-
-	#[test]
-	fn replace_range_grows() {
-		apply_range(b"0123456789".to_vec(), 2..5, b"abcdef");
-	}
-
-	#[test]
-	fn replace_range_shrinks() {
-		apply_range(b"0123456789".to_vec(), 2..8, b"X");
-	}
-
-	#[test]
-	fn replace_range_grows_across_multiple_buffers() {
-		let mut input = b"prefix".to_vec();
-		input.extend((0..(MOVE_BUFFER_SIZE * 3 + 17)).map(|index| (index % 251) as u8));
-
-		apply_range(input, 1..4, b"a much longer metadata replacement");
-	}
-
-	#[test]
-	fn replace_range_shrinks_across_multiple_buffers() {
-		let mut input = b"prefix".to_vec();
-		input.extend((0..(MOVE_BUFFER_SIZE * 3 + 17)).map(|index| (index % 251) as u8));
-
-		apply_range(input, 1..4, b"x");
-	}
-}
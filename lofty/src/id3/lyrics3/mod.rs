@@ -0,0 +1,20 @@
+//! Lyrics3 items
+//!
+//! # Lyrics3 notes
+//!
+//! See also: [`Lyrics3Tag`]
+//!
+//! Lyrics3 is an extension to ID3v1, storing lyrics and longer versions of some ID3v1 fields.
+//! The tag is placed directly before an ID3v1 tag, or in the case of APE files, possibly
+//! before an APE tag.
+//!
+//! Both Lyrics3v1 and Lyrics3v2 tags can be read, but they can't be written, only removed.
+//! See [`TagType::remove_from`](crate::tag::TagType::remove_from).
+
+pub(crate) mod read;
+pub(crate) mod tag;
+pub(crate) mod write;
+
+// Exports
+
+pub use tag::{Lyrics3Indications, Lyrics3Tag, Lyrics3Version};
@@ -0,0 +1,344 @@
+use super::tag::{Lyrics3Indications, Lyrics3Tag, Lyrics3Version};
+use crate::config::ParsingMode;
+use crate::id3::{ID3FindResults, Lyrics3v2ParseError};
+use crate::macros::try_vec;
+use crate::util::text::{latin1_decode, utf8_decode_str};
+
+use std::io::{Read, Seek, SeekFrom};
+
+const LYRICS3_BEGIN: &[u8; 11] = b"LYRICSBEGIN";
+const LYRICS3V1_END: &[u8; 9] = b"LYRICSEND";
+const LYRICS3V2_END: &[u8; 9] = b"LYRICS200";
+
+/// The maximum size of the lyrics in a Lyrics3v1 tag
+const LYRICS3V1_MAX_LYRICS_SIZE: u64 = 5100;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Lyrics3Header {
+	/// The full size of the tag, including the begin and end markers
+	pub(crate) size: u32,
+}
+
+/// Attempt to find a Lyrics3 tag ending at the current position in the stream
+///
+/// If a tag is found, the position of `data` will be at the start of the tag. Otherwise,
+/// the position will be unchanged.
+///
+/// Outside of [`ParsingMode::Strict`], a tag with an invalid size is ignored, and invalid fields
+/// end the parsing of the tag early.
+pub(crate) fn find_lyrics3<R>(
+	data: &mut R,
+	read: bool,
+	parse_mode: ParsingMode,
+) -> Result<ID3FindResults<Lyrics3Header, Option<Lyrics3Tag>>, Lyrics3v2ParseError>
+where
+	R: Read + Seek,
+{
+	log::debug!("Searching for a Lyrics3 tag");
+
+	let end = data.stream_position()?;
+
+	// Too small to contain even an empty Lyrics3v1 tag
+	if end < (LYRICS3_BEGIN.len() + LYRICS3V1_END.len()) as u64 {
+		return Ok(ID3FindResults(None, None));
+	}
+
+	let Some((version, start)) = find_start(data, end, parse_mode)? else {
+		data.seek(SeekFrom::Start(end))?;
+		return Ok(ID3FindResults(None, None));
+	};
+
+	log::debug!("Found a Lyrics3 tag ({version:?}), offset: {start}");
+
+	let header = Lyrics3Header {
+		size: (end - start) as u32,
+	};
+
+	data.seek(SeekFrom::Start(start))?;
+
+	let mut tag = None;
+	if read {
+		let mut content = try_vec![0; header.size as usize]?;
+		data.read_exact(&mut content)?;
+
+		tag = Some(parse(&content, version, parse_mode)?);
+
+		data.seek(SeekFrom::Start(start))?;
+	}
+
+	Ok(ID3FindResults(Some(header), tag))
+}
+
+fn find_start<R>(
+	data: &mut R,
+	end: u64,
+	parse_mode: ParsingMode,
+) -> Result<Option<(Lyrics3Version, u64)>, Lyrics3v2ParseError>
+where
+	R: Read + Seek,
+{
+	// Lyrics3v2: "LYRICSBEGIN" + fields + 6 digit size + "LYRICS200"
+	// Lyrics3v1: "LYRICSBEGIN" + lyrics + "LYRICSEND"
+	let mut end_marker = [0; LYRICS3V1_END.len()];
+	data.seek(SeekFrom::Start(end - end_marker.len() as u64))?;
+	data.read_exact(&mut end_marker)?;
+
+	if &end_marker == LYRICS3V2_END {
+		let mut size = [0; 6];
+		data.seek(SeekFrom::Start(end - 15))?;
+		data.read_exact(&mut size)?;
+
+		// The size includes "LYRICSBEGIN", but not the size or end marker
+		let size = match parse_size(&size) {
+			Ok(size) => size,
+			Err(e) => {
+				if parse_mode == ParsingMode::Strict {
+					return Err(e);
+				}
+
+				log::warn!("Lyrics3v2 tag has an invalid size, ignoring");
+				return Ok(None);
+			},
+		};
+		let Some(start) = (end - 15).checked_sub(size) else {
+			log::warn!("Lyrics3v2 tag size is larger than the stream, ignoring");
+			return Ok(None);
+		};
+
+		let mut begin_marker = [0; LYRICS3_BEGIN.len()];
+		data.seek(SeekFrom::Start(start))?;
+		data.read_exact(&mut begin_marker)?;
+
+		if &begin_marker != LYRICS3_BEGIN {
+			log::warn!("Lyrics3v2 tag is missing \"LYRICSBEGIN\", ignoring");
+			return Ok(None);
+		}
+
+		return Ok(Some((Lyrics3Version::V2, start)));
+	}
+
+	if &end_marker == LYRICS3V1_END {
+		// There's no size for Lyrics3v1, we have to search for the start
+		let search_end = end - LYRICS3V1_END.len() as u64;
+		let search_len = std::cmp::min(
+			search_end,
+			LYRICS3V1_MAX_LYRICS_SIZE + LYRICS3_BEGIN.len() as u64,
+		);
+
+		let mut search_window = try_vec![0; search_len as usize]?;
+		data.seek(SeekFrom::Start(search_end - search_len))?;
+		data.read_exact(&mut search_window)?;
+
+		let Some(pos) = search_window
+			.windows(LYRICS3_BEGIN.len())
+			.rposition(|window| window == LYRICS3_BEGIN)
+		else {
+			log::warn!("Lyrics3v1 tag is missing \"LYRICSBEGIN\", ignoring");
+			return Ok(None);
+		};
+
+		return Ok(Some((
+			Lyrics3Version::V1,
+			search_end - search_len + pos as u64,
+		)));
+	}
+
+	Ok(None)
+}
+
+fn parse(
+	content: &[u8],
+	version: Lyrics3Version,
+	parse_mode: ParsingMode,
+) -> Result<Lyrics3Tag, Lyrics3v2ParseError> {
+	let mut tag = Lyrics3Tag {
+		version,
+		..Lyrics3Tag::default()
+	};
+
+	if version == Lyrics3Version::V1 {
+		let lyrics = &content[LYRICS3_BEGIN.len()..content.len() - LYRICS3V1_END.len()];
+		if !lyrics.is_empty() {
+			tag.lyrics = Some(latin1_decode(lyrics));
+		}
+
+		return Ok(tag);
+	}
+
+	// Skip "LYRICSBEGIN", and stop before the size and "LYRICS200"
+	let mut fields = &content[LYRICS3_BEGIN.len()..content.len() - 15];
+
+	// Each field has a 3 character ID, followed by a 5 digit size
+	while fields.len() >= 8 {
+		let (id, rest) = fields.split_at(3);
+		let (size, rest) = rest.split_at(5);
+
+		let size = match parse_size(size) {
+			Ok(size) => size as usize,
+			Err(e) => {
+				if parse_mode == ParsingMode::Strict {
+					return Err(e);
+				}
+
+				log::warn!("Lyrics3v2 field has an invalid size, discarding remaining fields");
+				break;
+			},
+		};
+
+		if size > rest.len() {
+			log::warn!("Lyrics3v2 field size is larger than the tag, discarding remaining fields");
+			break;
+		}
+
+		let (field_content, rest) = rest.split_at(size);
+		fields = rest;
+
+		let field = match id {
+			b"IND" => {
+				tag.indications = Some(Lyrics3Indications::parse(field_content));
+				continue;
+			},
+			b"LYR" => &mut tag.lyrics,
+			b"INF" => &mut tag.information,
+			b"AUT" => &mut tag.author,
+			b"EAL" => &mut tag.album,
+			b"EAR" => &mut tag.artist,
+			b"ETT" => &mut tag.title,
+			_ => {
+				log::debug!(
+					"Skipping unsupported Lyrics3v2 field: {}",
+					String::from_utf8_lossy(id)
+				);
+				continue;
+			},
+		};
+
+		if !field_content.is_empty() {
+			*field = Some(latin1_decode(field_content));
+		}
+	}
+
+	Ok(tag)
+}
+
+// Sizes in Lyrics3v2 tags are written as decimal strings
+fn parse_size(size: &[u8]) -> Result<u64, Lyrics3v2ParseError> {
+	Ok(utf8_decode_str(size)?.parse::<u64>()?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::find_lyrics3;
+	use crate::config::ParsingMode;
+	use crate::id3::lyrics3::{Lyrics3Indications, Lyrics3Tag, Lyrics3Version};
+
+	use std::io::{Cursor, Seek, SeekFrom};
+
+	fn read(bytes: &[u8]) -> (u32, Option<Lyrics3Tag>) {
+		let mut reader = Cursor::new(bytes);
+		reader.seek(SeekFrom::End(0)).unwrap();
+
+		let results = find_lyrics3(&mut reader, true, ParsingMode::BestAttempt).unwrap();
+		let header = results.0.unwrap();
+
+		assert_eq!(
+			reader.position(),
+			(bytes.len() - header.size as usize) as u64
+		);
+		(header.size, results.1)
+	}
+
+	#[test_log::test]
+	fn lyrics3v1() {
+		let bytes = b"audioLYRICSBEGINFoo lyrics\xE9LYRICSEND";
+
+		let (size, tag) = read(bytes);
+		assert_eq!(size, 31);
+
+		let tag = tag.unwrap();
+		assert_eq!(tag.version, Lyrics3Version::V1);
+		assert_eq!(tag.lyrics.as_deref(), Some("Foo lyrics\u{e9}"));
+	}
+
+	#[test_log::test]
+	fn lyrics3v2() {
+		let bytes = b"audioLYRICSBEGININD00003110LYR00014[00:01]Foo barINF00003BazAUT00003QuxEAL00005AlbumEAR00006ArtistETT00005Title000106LYRICS200";
+
+		let (size, tag) = read(bytes);
+		assert_eq!(size as usize, bytes.len() - 5);
+
+		let tag = tag.unwrap();
+		assert_eq!(
+			tag,
+			Lyrics3Tag {
+				version: Lyrics3Version::V2,
+				lyrics: Some(String::from("[00:01]Foo bar")),
+				indications: Some(Lyrics3Indications {
+					lyrics_present: true,
+					timestamps_present: true,
+					inhibit_random_selection: false,
+				}),
+				information: Some(String::from("Baz")),
+				author: Some(String::from("Qux")),
+				album: Some(String::from("Album")),
+				artist: Some(String::from("Artist")),
+				title: Some(String::from("Title")),
+			}
+		);
+	}
+
+	#[test_log::test]
+	fn no_lyrics3() {
+		let mut reader = Cursor::new(b"audio data, with no Lyrics3 tag".to_vec());
+		reader.seek(SeekFrom::End(0)).unwrap();
+
+		let results = find_lyrics3(&mut reader, true, ParsingMode::BestAttempt).unwrap();
+		assert!(results.0.is_none());
+		assert_eq!(reader.position(), 31);
+	}
+
+	// A Lyrics3v2 tag with the given fields
+	fn lyrics3v2_tag(fields: &[u8]) -> Vec<u8> {
+		let mut bytes = b"audioLYRICSBEGIN".to_vec();
+		bytes.extend(fields);
+		bytes.extend(format!("{:06}", fields.len() + 11).as_bytes());
+		bytes.extend(b"LYRICS200");
+		bytes
+	}
+
+	#[test_log::test]
+	fn lyrics3v2_invalid_field_size() {
+		let bytes = lyrics3v2_tag(b"INF00003BazLYR000x0Foo barETT00005Title");
+
+		let (_, tag) = read(&bytes);
+		assert_eq!(
+			tag.unwrap(),
+			Lyrics3Tag {
+				version: Lyrics3Version::V2,
+				information: Some(String::from("Baz")),
+				..Lyrics3Tag::default()
+			}
+		);
+
+		let mut reader = Cursor::new(&bytes);
+		reader.seek(SeekFrom::End(0)).unwrap();
+		assert!(find_lyrics3(&mut reader, true, ParsingMode::Strict).is_err());
+	}
+
+	#[test_log::test]
+	fn lyrics3v2_invalid_tag_size() {
+		let mut bytes = lyrics3v2_tag(b"INF00003Baz");
+		let size_pos = bytes.len() - 15;
+		bytes[size_pos..size_pos + 6].copy_from_slice(b"00x022");
+
+		let mut reader = Cursor::new(&bytes);
+		reader.seek(SeekFrom::End(0)).unwrap();
+
+		let results = find_lyrics3(&mut reader, true, ParsingMode::BestAttempt).unwrap();
+		assert!(results.0.is_none());
+		assert_eq!(reader.position(), bytes.len() as u64);
+
+		reader.seek(SeekFrom::End(0)).unwrap();
+		assert!(find_lyrics3(&mut reader, true, ParsingMode::Strict).is_err());
+	}
+}
@@ -0,0 +1,299 @@
+use crate::config::WriteOptions;
+use crate::error::{FileEncodingError, TagEncodingError, UnsupportedTagError};
+use crate::io::VerifiedFile;
+use crate::tag::{
+	Accessor, ItemKey, ItemValue, MergeTag, SplitTag, Tag, TagExt, TagItem, TagType, TagWriteExt,
+};
+use crate::util::io::FileLike;
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use lofty_attr::tag;
+
+/// The version of a [`Lyrics3Tag`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Lyrics3Version {
+	/// Lyrics3v1
+	///
+	/// This version only stores lyrics, up to 5100 bytes.
+	V1,
+	/// Lyrics3v2
+	#[default]
+	V2,
+}
+
+/// The contents of a Lyrics3v2 `IND` field
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lyrics3Indications {
+	/// Whether the tag contains a `LYR` field
+	pub lyrics_present: bool,
+	/// Whether the lyrics contain timestamps (e.g. `[00:12]`)
+	pub timestamps_present: bool,
+	/// Whether the track should be excluded from random selection
+	pub inhibit_random_selection: bool,
+}
+
+impl Lyrics3Indications {
+	pub(super) fn parse(content: &[u8]) -> Self {
+		let flag = |index: usize| content.get(index) == Some(&b'1');
+
+		Self {
+			lyrics_present: flag(0),
+			timestamps_present: flag(1),
+			inhibit_random_selection: flag(2),
+		}
+	}
+}
+
+/// ## Item storage
+///
+/// Lyrics3v2 tags are made up of fields, identified by a 3 character ID. Lyrics3v1 tags can only
+/// store lyrics.
+///
+/// The extended fields (`EAL`, `EAR`, `ETT`) are meant to replace their truncated
+/// [`Id3v1Tag`](crate::id3::v1::Id3v1Tag) counterparts, and are limited to 250 characters.
+///
+/// ## Conversions
+///
+/// ### To `Tag`
+///
+/// All text fields can be translated to a `TagItem`:
+///
+/// * `lyrics` -> [`ItemKey::Lyrics`]
+/// * `information` -> [`ItemKey::Comment`]
+/// * `author` -> [`ItemKey::Lyricist`]
+/// * `album` -> [`ItemKey::AlbumTitle`]
+/// * `artist` -> [`ItemKey::TrackArtist`]
+/// * `title` -> [`ItemKey::TrackTitle`]
+///
+/// The `indications` field will be discarded.
+///
+/// ### From `Tag`
+///
+/// All of the [`ItemKey`]s referenced in the conversion to [`Tag`] will be checked.
+///
+/// #### Pictures
+///
+/// Pictures will be discarded, as they aren't supported in this format.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+#[tag(description = "A Lyrics3 tag", supported_formats(read_only(Ape, Mpeg)))]
+pub struct Lyrics3Tag {
+	/// The version of the tag
+	pub version: Lyrics3Version,
+	/// The lyrics (`LYR`)
+	pub lyrics: Option<String>,
+	/// Information about the lyrics (`IND`)
+	pub indications: Option<Lyrics3Indications>,
+	/// Additional information (`INF`)
+	pub information: Option<String>,
+	/// The lyrics/music author (`AUT`)
+	pub author: Option<String>,
+	/// The extended album title (`EAL`)
+	pub album: Option<String>,
+	/// The extended artist (`EAR`)
+	pub artist: Option<String>,
+	/// The extended track title (`ETT`)
+	pub title: Option<String>,
+}
+
+impl Accessor for Lyrics3Tag {
+	fn artist(&self) -> Option<Cow<'_, str>> {
+		self.artist.as_deref().map(Cow::Borrowed)
+	}
+	fn set_artist(&mut self, value: String) {
+		self.artist = Some(value)
+	}
+	fn remove_artist(&mut self) {
+		self.artist = None
+	}
+
+	fn title(&self) -> Option<Cow<'_, str>> {
+		self.title.as_deref().map(Cow::Borrowed)
+	}
+	fn set_title(&mut self, value: String) {
+		self.title = Some(value)
+	}
+	fn remove_title(&mut self) {
+		self.title = None
+	}
+
+	fn album(&self) -> Option<Cow<'_, str>> {
+		self.album.as_deref().map(Cow::Borrowed)
+	}
+	fn set_album(&mut self, value: String) {
+		self.album = Some(value)
+	}
+	fn remove_album(&mut self) {
+		self.album = None
+	}
+
+	fn comment(&self) -> Option<Cow<'_, str>> {
+		self.information.as_deref().map(Cow::Borrowed)
+	}
+	fn set_comment(&mut self, value: String) {
+		self.information = Some(value)
+	}
+	fn remove_comment(&mut self) {
+		self.information = None
+	}
+}
+
+impl Lyrics3Tag {
+	/// Create a new empty `Lyrics3Tag`
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::id3::lyrics3::Lyrics3Tag;
+	/// use lofty::tag::TagExt;
+	///
+	/// let lyrics3_tag = Lyrics3Tag::new();
+	/// assert!(lyrics3_tag.is_empty());
+	/// ```
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl TagExt for Lyrics3Tag {
+	type RefKey<'a> = &'a ItemKey;
+
+	#[inline]
+	fn tag_type(&self) -> TagType {
+		TagType::Lyrics3
+	}
+
+	fn len(&self) -> usize {
+		usize::from(self.lyrics.is_some())
+			+ usize::from(self.indications.is_some())
+			+ usize::from(self.information.is_some())
+			+ usize::from(self.author.is_some())
+			+ usize::from(self.album.is_some())
+			+ usize::from(self.artist.is_some())
+			+ usize::from(self.title.is_some())
+	}
+
+	fn contains<'a>(&'a self, key: Self::RefKey<'a>) -> bool {
+		match key {
+			ItemKey::Lyrics => self.lyrics.is_some(),
+			ItemKey::Comment => self.information.is_some(),
+			ItemKey::Lyricist => self.author.is_some(),
+			ItemKey::AlbumTitle => self.album.is_some(),
+			ItemKey::TrackArtist => self.artist.is_some(),
+			ItemKey::TrackTitle => self.title.is_some(),
+			_ => false,
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Lyrics3 tags are read only, so this will not write anything
+	fn dump_to<W: Write>(
+		&self,
+		_writer: &mut W,
+		_write_options: WriteOptions,
+	) -> std::result::Result<(), TagEncodingError> {
+		Ok(())
+	}
+
+	fn clear(&mut self) {
+		*self = Self::default();
+	}
+}
+
+impl TagWriteExt for Lyrics3Tag {
+	fn save_to<F>(
+		&self,
+		file: VerifiedFile<'_, F>,
+		write_options: WriteOptions,
+	) -> std::result::Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		// Lyrics3 tags can only be removed
+		if !self.is_empty() {
+			return Err(UnsupportedTagError.into());
+		}
+
+		super::write::remove(file, write_options)
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SplitTagRemainder {
+	version: Lyrics3Version,
+	indications: Option<Lyrics3Indications>,
+}
+
+impl SplitTag for Lyrics3Tag {
+	type Remainder = SplitTagRemainder;
+
+	fn split_tag(mut self) -> (Self::Remainder, Tag) {
+		let remainder = SplitTagRemainder {
+			version: self.version,
+			indications: self.indications.take(),
+		};
+
+		(remainder, self.into())
+	}
+}
+
+impl MergeTag for SplitTagRemainder {
+	type Merged = Lyrics3Tag;
+
+	fn merge_tag(self, tag: Tag) -> Self::Merged {
+		let mut merged = Lyrics3Tag::from(tag);
+		merged.version = self.version;
+		merged.indications = self.indications;
+
+		merged
+	}
+}
+
+impl From<Lyrics3Tag> for Tag {
+	fn from(input: Lyrics3Tag) -> Self {
+		let mut tag = Self::new(TagType::Lyrics3);
+
+		let push_item = |field: Option<String>, item_key: ItemKey, tag: &mut Tag| {
+			if let Some(text) = field {
+				tag.items
+					.push(TagItem::new(item_key, ItemValue::Text(text)))
+			}
+		};
+
+		push_item(input.lyrics, ItemKey::Lyrics, &mut tag);
+		push_item(input.information, ItemKey::Comment, &mut tag);
+		push_item(input.author, ItemKey::Lyricist, &mut tag);
+		push_item(input.album, ItemKey::AlbumTitle, &mut tag);
+		push_item(input.artist, ItemKey::TrackArtist, &mut tag);
+		push_item(input.title, ItemKey::TrackTitle, &mut tag);
+
+		tag
+	}
+}
+
+impl From<Tag> for Lyrics3Tag {
+	fn from(mut input: Tag) -> Self {
+		let lyrics = input.take_strings(ItemKey::Lyrics).next();
+		let information = input.take_strings(ItemKey::Comment).next();
+		let author = input.take_strings(ItemKey::Lyricist).next();
+		let album = input.take_strings(ItemKey::AlbumTitle).next();
+		let artist = input.take_strings(ItemKey::TrackArtist).next();
+		let title = input.take_strings(ItemKey::TrackTitle).next();
+
+		Self {
+			version: Lyrics3Version::default(),
+			lyrics,
+			indications: None,
+			information,
+			author,
+			album,
+			artist,
+			title,
+		}
+	}
+}
@@ -0,0 +1,61 @@
+use super::read::find_lyrics3;
+use crate::ape::tag::read::read_ape_tag;
+use crate::config::WriteOptions;
+use crate::error::{FileEncodingError, FileParseError, TagParseError};
+use crate::id3::{ID3FindResults, find_id3v1};
+use crate::io::{Truncate, VerifiedFile};
+use crate::util::io::FileLike;
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Remove a Lyrics3 tag from the end of a file
+///
+/// The tag will be found either directly before an ID3v1 tag, or before an APE tag.
+pub(crate) fn remove<F>(
+	file: VerifiedFile<'_, F>,
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+{
+	let mut file = file.into_inner();
+
+	// This will seek us to the end of the Lyrics3 tag, if it exists
+	find_id3v1(&mut file, false, write_options.parse_options.parsing_mode)
+		.map_err(TagParseError::from)?;
+
+	let mut lyrics3 = find_lyrics3(&mut file, false, write_options.parse_options.parsing_mode)
+		.map_err(FileParseError::from)?;
+
+	// The Lyrics3 tag may be followed by an APE tag
+	let ape_end = file.stream_position()?;
+	if lyrics3.0.is_none() && ape_end >= 32 {
+		file.seek(SeekFrom::Current(-32))?;
+
+		if let (_, Some(header)) = read_ape_tag(&mut file, true, write_options.parse_options)
+			.map_err(TagParseError::from)?
+			&& let Some(ape_start) = ape_end.checked_sub(u64::from(header.size))
+		{
+			file.seek(SeekFrom::Start(ape_start))?;
+			lyrics3 = find_lyrics3(&mut file, false, write_options.parse_options.parsing_mode)
+				.map_err(FileParseError::from)?;
+		}
+	}
+
+	let ID3FindResults(Some(header), _) = lyrics3 else {
+		log::debug!("No Lyrics3 tag found, nothing to remove");
+		return Ok(());
+	};
+
+	let start = file.stream_position()?;
+	file.seek(SeekFrom::Start(start + u64::from(header.size)))?;
+
+	let mut remaining = Vec::new();
+	file.read_to_end(&mut remaining)?;
+
+	file.seek(SeekFrom::Start(start))?;
+	file.write_all(&remaining)?;
+	file.truncate(start + remaining.len() as u64)?;
+
+	Ok(())
+}
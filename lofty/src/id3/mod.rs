@@ -1,29 +1,29 @@
 //! ID3 specific items
 //!
-//! This covers both [`v1`] (ID3v1) and [`v2`] (ID3v2) tag formats.
+//! This covers both [`v1`] (ID3v1) and [`v2`] (ID3v2) tag formats, as well as the
+//! [`lyrics3`] (Lyrics3) extension to ID3v1.
 //!
 //! ID3 does things differently than other tags, making them a little more cumbersome to work with.
 //! Check the other modules for important notes and warnings.
 
+pub mod lyrics3;
 pub mod v1;
 pub mod v2;
 
 use crate::config::ParsingMode;
-use crate::error::TextDecodingError;
+use crate::error::{AllocationError, TextDecodingError};
 use crate::id3::v1::error::Id3v1ParseError;
 use crate::id3::v2::error::Id3v2ParseError;
 use crate::macros::try_vec;
-use crate::util::text::utf8_decode_str;
 use v1::constants::ID3V1_TAG_MARKER;
 use v2::header::Id3v2Header;
 
 use std::io::{Read, Seek, SeekFrom};
 use std::num::ParseIntError;
-use std::ops::Neg;
 
 pub(crate) struct ID3FindResults<Header, Content>(pub Option<Header>, pub Content);
 
-/// Errors that can occur while parsing Lyrics3v2 tags
+/// Errors that can occur while parsing Lyrics3 tags
 pub struct Lyrics3v2ParseError {
 	source: Box<dyn core::error::Error + Send + Sync + 'static>,
 }
@@ -64,44 +64,20 @@ impl From<ParseIntError> for Lyrics3v2ParseError {
 	}
 }
 
-impl From<TextDecodingError> for Lyrics3v2ParseError {
-	fn from(input: TextDecodingError) -> Self {
+impl From<AllocationError> for Lyrics3v2ParseError {
+	fn from(input: AllocationError) -> Self {
 		Self {
 			source: Box::new(input),
 		}
 	}
 }
 
-pub(crate) fn find_lyrics3v2<R>(
-	data: &mut R,
-) -> Result<ID3FindResults<(), u32>, Lyrics3v2ParseError>
-where
-	R: Read + Seek,
-{
-	log::debug!("Searching for a Lyrics3v2 tag");
-
-	let mut header = None;
-	let mut size = 0_u32;
-
-	data.seek(SeekFrom::Current(-15))?;
-
-	let mut lyrics3v2 = [0; 15];
-	data.read_exact(&mut lyrics3v2)?;
-
-	if &lyrics3v2[7..] == b"LYRICS200" {
-		log::warn!("Encountered a Lyrics3v2 tag. This is an outdated format, and will be skipped.");
-
-		header = Some(());
-
-		let lyrics_size = utf8_decode_str(&lyrics3v2[..7])?;
-		let lyrics_size = lyrics_size.parse::<u32>()?;
-
-		size += lyrics_size;
-
-		data.seek(SeekFrom::Current(i64::from(lyrics_size + 15).neg()))?;
+impl From<TextDecodingError> for Lyrics3v2ParseError {
+	fn from(input: TextDecodingError) -> Self {
+		Self {
+			source: Box::new(input),
+		}
 	}
-
-	Ok(ID3FindResults(header, size))
 }
 
 #[allow(unused_variables)]
@@ -5,7 +5,10 @@ use crate::error::{
 };
 use crate::file::FileType;
 use crate::iff::chunk::{Chunks, IFF_CHUNK_HEADER_SIZE};
+use crate::iff::chunks::{self, ChunkId, ChunkUpdate};
 use crate::iff::error::ChunkParseError;
+use crate::iff::wav::container::WavContainer;
+use crate::iff::wav::read::verify_wav;
 use crate::io::{Length, Truncate, VerifiedFile};
 use crate::tag::TagType;
 use crate::util::io::FileLike;
@@ -51,7 +54,7 @@ where
 }
 
 pub(in crate::id3::v2) fn write_to_chunk_file<F, B>(
-	file: VerifiedFile<'_, F>,
+	mut file: VerifiedFile<'_, F>,
 	tag: &[u8],
 	write_options: WriteOptions,
 ) -> Result<(), FileEncodingError>
@@ -59,6 +62,16 @@ where
 	F: FileLike,
 	B: ByteOrder,
 {
+	// Containers with 64-bit sizes need to go through the generic chunk writer
+	if file.format() == FileType::Wav {
+		let container = verify_wav(&mut *file).map_err(FileParseError::from)?;
+		file.rewind()?;
+
+		if container != WavContainer::Riff {
+			return write_to_large_wav(file, tag, write_options);
+		}
+	}
+
 	let mut tag_chunk_size;
 	if tag.is_empty() {
 		tag_chunk_size = 0u64;
@@ -185,6 +198,35 @@ where
 	Ok(())
 }
 
+fn write_to_large_wav<F>(
+	file: VerifiedFile<'_, F>,
+	tag: &[u8],
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+{
+	let (chunk_name, other_chunk_name) = if write_options.uppercase_id3v2_chunk {
+		(CHUNK_NAME_UPPER, CHUNK_NAME_LOWER)
+	} else {
+		(CHUNK_NAME_LOWER, CHUNK_NAME_UPPER)
+	};
+
+	let updates = [
+		ChunkUpdate {
+			id: ChunkId::Chunk(chunk_name),
+			content: (!tag.is_empty()).then(|| tag.to_vec()),
+		},
+		// Only keep a single tag around
+		ChunkUpdate {
+			id: ChunkId::Chunk(other_chunk_name),
+			content: None,
+		},
+	];
+
+	chunks::write_chunks(file, &updates, write_options)
+}
+
 struct IffFileContext {
 	stream_length: u64,
 	existing_id3_tag: Option<Range<u64>>,
@@ -211,7 +253,7 @@ where
 		return Err(UnknownFormatError.into());
 	};
 
-	let actual_stream_size = u64::from(first_chunk.size()) + u64::from(IFF_CHUNK_HEADER_SIZE);
+	let actual_stream_size = first_chunk.size() + u64::from(IFF_CHUNK_HEADER_SIZE);
 
 	if file_len < actual_stream_size {
 		return Err(SizeMismatchError.into());
@@ -1,6 +1,6 @@
 //! AIFF file/tag error types
 
-use crate::error::FileParseError;
+use crate::error::{FileEncodingError, FileParseError};
 use crate::file::FileType;
 
 use lofty_attr::LoftyError;
@@ -41,3 +41,29 @@ impl From<AiffParseError> for FileParseError {
 		Self::new(FileType::Aiff, input.source)
 	}
 }
+
+/// Internal concrete variant of [`FileEncodingError`] for conversions
+#[derive(LoftyError)]
+#[error(message = "failed to write to AIFF file")]
+pub(super) struct AiffEncodingError {
+	#[error(from(
+		std::io::Error,
+		crate::error::TooMuchDataError,
+		crate::util::text::TextEncodingError,
+	))]
+	source: Box<dyn core::error::Error + Send + Sync + 'static>,
+}
+
+impl AiffEncodingError {
+	pub(super) fn message(message: &'static str) -> Self {
+		Self {
+			source: message.into(),
+		}
+	}
+}
+
+impl From<AiffEncodingError> for FileEncodingError {
+	fn from(input: AiffEncodingError) -> FileEncodingError {
+		Self::new(FileType::Aiff, input.source)
+	}
+}
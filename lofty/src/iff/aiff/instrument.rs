@@ -0,0 +1,355 @@
+use super::error::{AiffEncodingError, AiffParseError};
+use crate::config::ParsingMode;
+use crate::error::TooMuchDataError;
+use crate::util::text::latin1_encode;
+
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+const INSTRUMENT_SIZE: usize = 20;
+
+/// How an [`InstrumentLoop`] is played
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PlayMode {
+	/// The loop is not played
+	NoLooping,
+	/// Play forward
+	Forward,
+	/// Alternate between playing forward and backward
+	ForwardBackward,
+	/// An unknown play mode
+	Other(i16),
+}
+
+impl From<i16> for PlayMode {
+	fn from(input: i16) -> Self {
+		match input {
+			0 => Self::NoLooping,
+			1 => Self::Forward,
+			2 => Self::ForwardBackward,
+			_ => Self::Other(input),
+		}
+	}
+}
+
+impl From<PlayMode> for i16 {
+	fn from(input: PlayMode) -> Self {
+		match input {
+			PlayMode::NoLooping => 0,
+			PlayMode::Forward => 1,
+			PlayMode::ForwardBackward => 2,
+			PlayMode::Other(value) => value,
+		}
+	}
+}
+
+/// A loop in an [`InstrumentChunk`]
+///
+/// The start and end of the loop are [`Marker`]s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstrumentLoop {
+	/// How the loop is played
+	pub play_mode: PlayMode,
+	/// The ID of the [`Marker`] at the start of the loop
+	pub begin_marker: i16,
+	/// The ID of the [`Marker`] at the end of the loop
+	pub end_marker: i16,
+}
+
+impl Default for InstrumentLoop {
+	fn default() -> Self {
+		Self {
+			play_mode: PlayMode::NoLooping,
+			begin_marker: 0,
+			end_marker: 0,
+		}
+	}
+}
+
+/// An instrument (`INST`) chunk
+///
+/// This describes the pitch, volume, key/velocity range, and loops a sampler should use for the
+/// audio.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstrumentChunk {
+	/// The MIDI note that plays the audio at its original pitch (0-127, 60 = middle C)
+	pub base_note: i8,
+	/// The pitch shift to apply, in cents (-50-+50)
+	pub detune: i8,
+	/// The lowest MIDI note to play the audio for
+	pub low_note: i8,
+	/// The highest MIDI note to play the audio for
+	pub high_note: i8,
+	/// The lowest MIDI velocity to play the audio for
+	pub low_velocity: i8,
+	/// The highest MIDI velocity to play the audio for
+	pub high_velocity: i8,
+	/// The volume adjustment to apply, in decibels
+	pub gain: i16,
+	/// The loop played while the note is held
+	pub sustain_loop: InstrumentLoop,
+	/// The loop played after the note is released
+	pub release_loop: InstrumentLoop,
+}
+
+impl Default for InstrumentChunk {
+	fn default() -> Self {
+		Self {
+			base_note: 60,
+			detune: 0,
+			low_note: 0,
+			high_note: 127,
+			low_velocity: 1,
+			high_velocity: 127,
+			gain: 0,
+			sustain_loop: InstrumentLoop::default(),
+			release_loop: InstrumentLoop::default(),
+		}
+	}
+}
+
+impl InstrumentChunk {
+	/// Create a new `InstrumentChunk`, covering the entire key and velocity range, with no loops
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub(super) fn parse(content: &[u8]) -> Result<Self, AiffParseError> {
+		if content.len() < INSTRUMENT_SIZE {
+			return Err(AiffParseError::message("INST chunk is too small"));
+		}
+
+		let reader = &mut &content[..];
+
+		let base_note = reader.read_i8()?;
+		let detune = reader.read_i8()?;
+		let low_note = reader.read_i8()?;
+		let high_note = reader.read_i8()?;
+		let low_velocity = reader.read_i8()?;
+		let high_velocity = reader.read_i8()?;
+		let gain = reader.read_i16::<BigEndian>()?;
+		let sustain_loop = read_loop(reader)?;
+		let release_loop = read_loop(reader)?;
+
+		Ok(Self {
+			base_note,
+			detune,
+			low_note,
+			high_note,
+			low_velocity,
+			high_velocity,
+			gain,
+			sustain_loop,
+			release_loop,
+		})
+	}
+
+	pub(super) fn as_bytes(self) -> Result<Vec<u8>, AiffEncodingError> {
+		let mut content = Vec::with_capacity(INSTRUMENT_SIZE);
+		content.write_i8(self.base_note)?;
+		content.write_i8(self.detune)?;
+		content.write_i8(self.low_note)?;
+		content.write_i8(self.high_note)?;
+		content.write_i8(self.low_velocity)?;
+		content.write_i8(self.high_velocity)?;
+		content.write_i16::<BigEndian>(self.gain)?;
+
+		for instrument_loop in [self.sustain_loop, self.release_loop] {
+			content.write_i16::<BigEndian>(i16::from(instrument_loop.play_mode))?;
+			content.write_i16::<BigEndian>(instrument_loop.begin_marker)?;
+			content.write_i16::<BigEndian>(instrument_loop.end_marker)?;
+		}
+
+		Ok(content)
+	}
+}
+
+fn read_loop(reader: &mut &[u8]) -> Result<InstrumentLoop, AiffParseError> {
+	Ok(InstrumentLoop {
+		play_mode: PlayMode::from(reader.read_i16::<BigEndian>()?),
+		begin_marker: reader.read_i16::<BigEndian>()?,
+		end_marker: reader.read_i16::<BigEndian>()?,
+	})
+}
+
+/// A marker (from the `MARK` chunk)
+///
+/// Markers point to a position in the audio, and are referenced by [`InstrumentLoop`]s and
+/// [`Comment`](super::Comment)s.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Marker {
+	/// The unique identifier of the marker, which must be positive
+	pub id: i16,
+	/// The position of the marker in the audio, in sample frames
+	pub position: u32,
+	/// The name of the marker
+	///
+	/// This is stored as Latin-1, and can be at most 255 characters.
+	pub name: String,
+}
+
+impl Marker {
+	/// Create a new `Marker` at `position`
+	pub fn new(id: i16, position: u32, name: String) -> Self {
+		Self { id, position, name }
+	}
+}
+
+pub(super) fn parse_markers(
+	content: &[u8],
+	parse_mode: ParsingMode,
+) -> Result<Vec<Marker>, AiffParseError> {
+	let reader = &mut &content[..];
+
+	let count = reader.read_u16::<BigEndian>()?;
+
+	let mut markers = Vec::with_capacity(usize::from(count).min(reader.len() / 8));
+	for _ in 0..count {
+		let marker = read_marker(reader);
+		match marker {
+			Ok(marker) => markers.push(marker),
+			Err(e) => {
+				if parse_mode == ParsingMode::Strict {
+					return Err(e);
+				}
+
+				log::warn!("MARK chunk is too small for {count} markers, reading what's available");
+				break;
+			},
+		}
+	}
+
+	Ok(markers)
+}
+
+fn read_marker(reader: &mut &[u8]) -> Result<Marker, AiffParseError> {
+	let id = reader.read_i16::<BigEndian>()?;
+	let position = reader.read_u32::<BigEndian>()?;
+
+	// The name is a Pascal string, padded so the count byte and text have an even length
+	let len = reader.read_u8()?;
+	let mut name = vec![0; usize::from(len)];
+	reader.read_exact(&mut name)?;
+
+	if len.is_multiple_of(2) {
+		*reader = reader.get(1..).unwrap_or_default();
+	}
+
+	// There's no specified encoding, Latin-1 at least keeps the original bytes intact
+	Ok(Marker {
+		id,
+		position,
+		name: name.iter().map(|c| char::from(*c)).collect(),
+	})
+}
+
+pub(super) fn create_markers(
+	markers: &[Marker],
+	lossy_text_encoding: bool,
+) -> Result<Vec<u8>, AiffEncodingError> {
+	let Ok(count) = u16::try_from(markers.len()) else {
+		return Err(TooMuchDataError.into());
+	};
+
+	let mut content = Vec::new();
+	content.write_u16::<BigEndian>(count)?;
+
+	for (idx, marker) in markers.iter().enumerate() {
+		if markers[..idx].iter().any(|other| other.id == marker.id) {
+			return Err(AiffEncodingError::message("marker IDs must be unique"));
+		}
+
+		let name =
+			latin1_encode(&marker.name, lossy_text_encoding).collect::<Result<Vec<u8>, _>>()?;
+		let Ok(len) = u8::try_from(name.len()) else {
+			return Err(TooMuchDataError.into());
+		};
+
+		content.write_i16::<BigEndian>(marker.id)?;
+		content.write_u32::<BigEndian>(marker.position)?;
+		content.write_u8(len)?;
+		content.write_all(&name)?;
+		if len.is_multiple_of(2) {
+			content.push(0);
+		}
+	}
+
+	Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{InstrumentChunk, InstrumentLoop, Marker, PlayMode, create_markers, parse_markers};
+	use crate::config::ParsingMode;
+
+	#[test_log::test]
+	fn instrument_round_trip() {
+		let instrument = InstrumentChunk {
+			detune: -10,
+			gain: -3,
+			sustain_loop: InstrumentLoop {
+				play_mode: PlayMode::Forward,
+				begin_marker: 1,
+				end_marker: 2,
+			},
+			release_loop: InstrumentLoop {
+				play_mode: PlayMode::Other(7),
+				begin_marker: 3,
+				end_marker: 4,
+			},
+			..InstrumentChunk::new()
+		};
+
+		let bytes = instrument.as_bytes().unwrap();
+		assert_eq!(bytes.len(), 20);
+		assert_eq!(InstrumentChunk::parse(&bytes).unwrap(), instrument);
+	}
+
+	#[test_log::test]
+	fn markers_round_trip() {
+		let markers = vec![
+			Marker::new(1, 0, String::from("Start")),
+			// Even length names need padding
+			Marker::new(2, 1000, String::from("Loop")),
+			Marker::new(3, 2000, String::new()),
+		];
+
+		let bytes = create_markers(&markers, false).unwrap();
+		assert_eq!(parse_markers(&bytes, ParsingMode::Strict).unwrap(), markers);
+
+		// Truncated
+		let truncated = &bytes[..bytes.len() - 2];
+		assert!(parse_markers(truncated, ParsingMode::Strict).is_err());
+		assert_eq!(
+			parse_markers(truncated, ParsingMode::BestAttempt).unwrap(),
+			&markers[..2]
+		);
+	}
+
+	#[test_log::test]
+	fn invalid_markers() {
+		assert!(
+			create_markers(
+				&[
+					Marker::new(1, 0, String::new()),
+					Marker::new(1, 10, String::new())
+				],
+				false
+			)
+			.is_err()
+		);
+		assert!(create_markers(&[Marker::new(1, 0, "a".repeat(256))], false).is_err());
+		assert!(create_markers(&[Marker::new(1, 0, String::from("\u{1F3B5}"))], false).is_err());
+	}
+
+	#[test_log::test]
+	fn latin1_marker_names() {
+		// "Caf\xE9", padded to an even length
+		let bytes = b"\0\x01\0\x01\0\0\0\0\x04Caf\xE9\0";
+
+		let markers = parse_markers(bytes, ParsingMode::Strict).unwrap();
+		assert_eq!(markers, [Marker::new(1, 0, String::from("Caf\u{e9}"))]);
+		assert_eq!(create_markers(&markers, false).unwrap(), bytes);
+	}
+}
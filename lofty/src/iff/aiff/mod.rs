@@ -1,22 +1,42 @@
 //! AIFF specific items
 
 pub mod error;
+mod instrument;
 mod properties;
-mod read;
+pub(crate) mod read;
 pub(crate) mod tag;
 
+use crate::config::WriteOptions;
+use crate::error::FileEncodingError;
 use crate::id3::v2::tag::Id3v2Tag;
+use crate::iff::chunks::{self, ChunkId, ChunkUpdate};
+use crate::io::VerifiedFile;
+use crate::tag::TagExt;
+use crate::util::io::FileLike;
 
 use lofty_attr::LoftyFile;
 
 // Exports
 
+pub use instrument::{InstrumentChunk, InstrumentLoop, Marker, PlayMode};
 pub use properties::{AiffCompressionType, AiffProperties};
 pub use tag::{AiffTextChunks, Comment};
 
 /// An AIFF file
+///
+/// ## Notes
+///
+/// * The `INST` and `MARK` chunks are only written when saving the `AiffFile` itself. Writing a
+///   [`Tag`](crate::tag::Tag) to the file will leave them untouched.
+/// * Chunks removed in memory (for example, with [`AiffFile::remove_instrument`]) will be removed from the
+///   file on the next call to [`AudioFile::save_to`](crate::file::AudioFile::save_to).
+/// * Chunks that failed to parse (outside of [`ParsingMode::Strict`](crate::config::ParsingMode::Strict))
+///   are left untouched, unless they are replaced.
+/// * The `MARK` chunk is left untouched unless the markers were changed, as a partially read chunk
+///   can't be rewritten as it was.
 #[derive(LoftyFile)]
 #[lofty(read_fn = "read::read_from")]
+#[lofty(write_fn = "Self::write_to")]
 #[lofty(internal_write_module_do_not_use_anywhere_else)]
 pub struct AiffFile {
 	/// Any text chunks included in the file
@@ -25,6 +45,142 @@ pub struct AiffFile {
 	/// An ID3v2 tag
 	#[lofty(tag_type = "Id3v2")]
 	pub(crate) id3v2_tag: Option<Id3v2Tag>,
+	pub(crate) instrument: Option<InstrumentChunk>,
+	pub(crate) markers: Vec<Marker>,
+	// The markers as they were read, the `MARK` chunk is left as-is if they're unchanged
+	pub(crate) original_markers: Vec<Marker>,
+	// Chunks that failed to parse, kept as-is when writing
+	pub(crate) unparsed_chunks: Vec<ChunkId>,
 	/// The file's audio properties
 	pub(crate) properties: AiffProperties,
 }
+
+impl AiffFile {
+	fn write_to<F>(
+		&self,
+		file: &mut F,
+		write_options: WriteOptions,
+	) -> Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		if let Some(ref text_chunks) = self.text_chunks_tag {
+			file.rewind()?;
+			text_chunks.save_to(file, write_options)?;
+		}
+
+		if let Some(ref id3v2) = self.id3v2_tag {
+			file.rewind()?;
+			id3v2.save_to(file, write_options)?;
+		}
+
+		let instrument = match self.instrument {
+			Some(instrument) => Some(instrument.as_bytes()?),
+			None => None,
+		};
+
+		let markers = if self.markers.is_empty() {
+			None
+		} else {
+			Some(instrument::create_markers(
+				&self.markers,
+				write_options.lossy_text_encoding,
+			)?)
+		};
+
+		let mut updates = vec![
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"MARK"),
+				content: markers,
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"INST"),
+				content: instrument,
+			},
+		];
+		chunks::retain_unparsed(&mut updates, &self.unparsed_chunks);
+
+		if self.markers == self.original_markers {
+			chunks::retain_changed(&mut updates, &[ChunkId::Chunk(*b"MARK")]);
+		}
+
+		file.rewind()?;
+		chunks::write_chunks(VerifiedFile::new(file)?, &updates, write_options)
+	}
+
+	/// Returns the instrument `INST` chunk, if it exists
+	pub fn instrument(&self) -> Option<InstrumentChunk> {
+		self.instrument
+	}
+
+	/// Returns a mutable reference to the `INST` chunk, if it exists
+	pub fn instrument_mut(&mut self) -> Option<&mut InstrumentChunk> {
+		self.instrument.as_mut()
+	}
+
+	/// Sets the `INST` chunk, returning the old one
+	///
+	/// The loops of the instrument refer to [`Marker`]s, which should be set with
+	/// [`AiffFile::set_markers`].
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::{ParseOptions, WriteOptions};
+	/// use lofty::iff::aiff::{AiffFile, InstrumentChunk, InstrumentLoop, Marker, PlayMode};
+	/// use lofty::prelude::*;
+	/// use std::io::Seek;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/full_test.aiff";
+	/// let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+	/// let mut aiff = AiffFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// aiff.set_markers(vec![
+	/// 	Marker::new(1, 0, String::from("Loop start")),
+	/// 	Marker::new(2, 44100, String::from("Loop end")),
+	/// ]);
+	///
+	/// let mut instrument = InstrumentChunk::new();
+	/// instrument.sustain_loop = InstrumentLoop {
+	/// 	play_mode: PlayMode::Forward,
+	/// 	begin_marker: 1,
+	/// 	end_marker: 2,
+	/// };
+	/// aiff.set_instrument(instrument);
+	///
+	/// file.rewind()?;
+	/// aiff.save_to(&mut file, WriteOptions::default())?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_instrument(&mut self, instrument: InstrumentChunk) -> Option<InstrumentChunk> {
+		self.instrument.replace(instrument)
+	}
+
+	/// Removes the `INST` chunk, in memory
+	pub fn remove_instrument(&mut self) -> Option<InstrumentChunk> {
+		self.instrument.take()
+	}
+
+	/// Returns the markers from the `MARK` chunk
+	pub fn markers(&self) -> &[Marker] {
+		&self.markers
+	}
+
+	/// Returns a mutable reference to the markers
+	pub fn markers_mut(&mut self) -> &mut Vec<Marker> {
+		&mut self.markers
+	}
+
+	/// Sets the markers, returning the old ones
+	///
+	/// Marker IDs must be unique, and names can be at most 255 bytes, otherwise writing will fail.
+	pub fn set_markers(&mut self, markers: Vec<Marker>) -> Vec<Marker> {
+		std::mem::replace(&mut self.markers, markers)
+	}
+
+	/// Removes all markers, in memory
+	pub fn remove_markers(&mut self) -> Vec<Marker> {
+		std::mem::take(&mut self.markers)
+	}
+}
@@ -2,7 +2,7 @@ use super::read::CompressionPresent;
 use crate::io::ReadExt;
 use crate::macros::try_vec;
 use crate::properties::FileProperties;
-use crate::util::text::utf8_decode;
+use crate::util::text::{latin1_decode, utf8_decode_str};
 
 use std::borrow::Cow;
 use std::io::Read;
@@ -16,6 +16,7 @@ use byteorder::{BigEndian, ReadBytesExt};
 /// This contains a non-exhaustive list of compression types
 #[allow(non_camel_case_types)]
 #[derive(Clone, Eq, PartialEq, Default, Debug)]
+#[non_exhaustive]
 pub enum AiffCompressionType {
 	#[default]
 	/// PCM
@@ -44,6 +45,10 @@ pub enum AiffCompressionType {
 	ALAW,
 	/// IEEE 32-bit float (From SoundHack & Csound)
 	FL32,
+	/// IMA 4:1 ADPCM
+	ima4,
+	/// QUALCOMM PureVoice (QCELP)
+	Qclp,
 	/// Catch-all for unknown compression algorithms
 	Other {
 		/// Identifier from the compression algorithm
@@ -81,11 +86,37 @@ impl AiffCompressionType {
 			AiffCompressionType::ULAW => Cow::Borrowed("CCITT G.711 u-law"),
 			AiffCompressionType::ALAW => Cow::Borrowed("CCITT G.711 A-law"),
 			AiffCompressionType::FL32 => Cow::Borrowed("Float 32"),
+			AiffCompressionType::ima4 => Cow::Borrowed("IMA 4:1"),
+			AiffCompressionType::Qclp => Cow::Borrowed("Qualcomm PureVoice"),
 			AiffCompressionType::Other {
 				compression_name, ..
 			} => Cow::from(compression_name),
 		}
 	}
+
+	// The number of bytes and sample frames in each packet of audio, if known
+	fn packet_layout(&self, channels: u16, sample_size: u16) -> Option<(u64, u64)> {
+		let channels = u64::from(channels);
+		match self {
+			AiffCompressionType::None | AiffCompressionType::sowt => {
+				Some((channels * u64::from(sample_size).div_ceil(8), 1))
+			},
+			AiffCompressionType::fl32 | AiffCompressionType::FL32 => Some((channels * 4, 1)),
+			AiffCompressionType::fl64 => Some((channels * 8, 1)),
+			AiffCompressionType::alaw
+			| AiffCompressionType::ulaw
+			| AiffCompressionType::ALAW
+			| AiffCompressionType::ULAW => Some((channels, 1)),
+			AiffCompressionType::ima4 => Some((channels * 34, 64)),
+			AiffCompressionType::Qclp => Some((35, 160)),
+			_ => None,
+		}
+	}
+
+	// Whether the `COMM` chunk stores the number of packets rather than sample frames
+	fn counts_packets(&self) -> bool {
+		matches!(self, AiffCompressionType::ima4 | AiffCompressionType::Qclp)
+	}
 }
 
 /// A AIFF file's audio properties
@@ -99,6 +130,7 @@ pub struct AiffProperties {
 	pub(crate) sample_size: u16,
 	pub(crate) channels: u16,
 	pub(crate) compression_type: Option<AiffCompressionType>,
+	pub(crate) compression_name: Option<String>,
 }
 
 impl From<AiffProperties> for FileProperties {
@@ -150,12 +182,20 @@ impl AiffProperties {
 	pub fn compression_type(&self) -> Option<&AiffCompressionType> {
 		self.compression_type.as_ref()
 	}
+
+	/// The human-readable compression name, as stored in the file
+	///
+	/// This may differ from [`AiffCompressionType::compression_name`], which uses standardized
+	/// names. This will be `None` for non-AIFC files, or if the name is empty.
+	pub fn compression_name(&self) -> Option<&str> {
+		self.compression_name.as_deref()
+	}
 }
 
 pub(super) fn read_properties(
 	comm: &mut &[u8],
 	compression_present: CompressionPresent,
-	stream_len: u32,
+	stream_len: u64,
 	file_length: u64,
 ) -> Result<AiffProperties, AiffParseError> {
 	let channels = comm.read_u16::<BigEndian>()?;
@@ -175,72 +215,48 @@ pub(super) fn read_properties(
 
 	let sample_rate = sample_rate_64.round() as u32;
 
-	let (duration, overall_bitrate, audio_bitrate) = if sample_rate > 0 && sample_frames > 0 {
-		let length = (f64::from(sample_frames) * 1000.0) / f64::from(sample_rate);
+	let mut compression_type = None;
+	let mut compression_name = None;
+
+	let is_compressed = comm.len() >= 5 && compression_present == CompressionPresent::Yes;
+	if is_compressed {
+		let (compression, name) = read_compression(comm)?;
+		compression_type = Some(compression);
+		compression_name = name;
+	}
+
+	let compression = compression_type.clone().unwrap_or_default();
+
+	let mut total_frames = u64::from(sample_frames);
+	if compression.counts_packets()
+		&& let Some((_, frames_per_packet)) = compression.packet_layout(channels, sample_size)
+	{
+		total_frames *= frames_per_packet;
+	}
+
+	// Streamed files may not have the sample frame count filled in, estimate it from the
+	// `SSND` size (minus the offset and block size fields)
+	if total_frames == 0
+		&& let Some((bytes_per_packet, frames_per_packet)) =
+			compression.packet_layout(channels, sample_size)
+		&& bytes_per_packet > 0
+	{
+		log::debug!("No sample frame count, estimating from stream length");
+		total_frames = (stream_len.saturating_sub(8) / bytes_per_packet) * frames_per_packet;
+	}
+
+	let (duration, overall_bitrate, audio_bitrate) = if sample_rate > 0 && total_frames > 0 {
+		let length = (total_frames as f64 * 1000.0) / f64::from(sample_rate);
 
 		(
 			Duration::from_millis(length as u64),
 			((file_length as f64) * 8.0 / length + 0.5) as u32,
-			(f64::from(stream_len) * 8.0 / length + 0.5) as u32,
+			(stream_len as f64 * 8.0 / length + 0.5) as u32,
 		)
 	} else {
 		(Duration::ZERO, 0, 0)
 	};
 
-	let is_compressed = comm.len() >= 5 && compression_present == CompressionPresent::Yes;
-	if !is_compressed {
-		return Ok(AiffProperties {
-			duration,
-			overall_bitrate,
-			audio_bitrate,
-			sample_rate,
-			sample_size,
-			channels,
-			compression_type: None,
-		});
-	}
-
-	let mut compression_type = [0u8; 4];
-	comm.read_exact(&mut compression_type)?;
-
-	let compression = Some(match &compression_type {
-		b"NONE" => AiffCompressionType::None,
-		b"ACE2" => AiffCompressionType::ACE2,
-		b"ACE8" => AiffCompressionType::ACE8,
-		b"MAC3" => AiffCompressionType::MAC3,
-		b"MAC6" => AiffCompressionType::MAC6,
-		b"sowt" => AiffCompressionType::sowt,
-		b"fl32" => AiffCompressionType::fl32,
-		b"fl64" => AiffCompressionType::fl64,
-		b"alaw" => AiffCompressionType::alaw,
-		b"ulaw" => AiffCompressionType::ulaw,
-		b"ULAW" => AiffCompressionType::ULAW,
-		b"ALAW" => AiffCompressionType::ALAW,
-		b"FL32" => AiffCompressionType::FL32,
-		_ => {
-			log::debug!(
-				"Encountered unknown compression type: {:?}",
-				compression_type
-			);
-
-			// We have to read the compression name string
-			let mut compression_name = String::new();
-
-			let compression_name_size = comm.read_u8()?;
-			if compression_name_size > 0 {
-				let mut compression_name_bytes = try_vec![0u8; compression_name_size as usize]?;
-				comm.read_exact(&mut compression_name_bytes)?;
-
-				compression_name = utf8_decode(compression_name_bytes)?;
-			}
-
-			AiffCompressionType::Other {
-				compression_type,
-				compression_name,
-			}
-		},
-	});
-
 	Ok(AiffProperties {
 		duration,
 		overall_bitrate,
@@ -248,6 +264,78 @@ pub(super) fn read_properties(
 		sample_rate,
 		sample_size,
 		channels,
-		compression_type: compression,
+		compression_type,
+		compression_name,
 	})
 }
+
+fn read_compression(
+	comm: &mut &[u8],
+) -> Result<(AiffCompressionType, Option<String>), AiffParseError> {
+	let mut compression_type = [0u8; 4];
+	comm.read_exact(&mut compression_type)?;
+
+	let known_compression = match &compression_type {
+		b"NONE" => Some(AiffCompressionType::None),
+		b"ACE2" => Some(AiffCompressionType::ACE2),
+		b"ACE8" => Some(AiffCompressionType::ACE8),
+		b"MAC3" => Some(AiffCompressionType::MAC3),
+		b"MAC6" => Some(AiffCompressionType::MAC6),
+		b"sowt" => Some(AiffCompressionType::sowt),
+		b"fl32" => Some(AiffCompressionType::fl32),
+		b"fl64" => Some(AiffCompressionType::fl64),
+		b"alaw" => Some(AiffCompressionType::alaw),
+		b"ulaw" => Some(AiffCompressionType::ulaw),
+		b"ULAW" => Some(AiffCompressionType::ULAW),
+		b"ALAW" => Some(AiffCompressionType::ALAW),
+		b"FL32" => Some(AiffCompressionType::FL32),
+		b"ima4" => Some(AiffCompressionType::ima4),
+		b"Qclp" => Some(AiffCompressionType::Qclp),
+		_ => None,
+	};
+
+	let compression_name = match read_compression_name(comm) {
+		Ok(compression_name) => compression_name,
+		// The name is purely informational for the compression types we know about
+		Err(e) if known_compression.is_some() => {
+			log::warn!("Failed to read the AIFF-C compression name, ignoring it: {e}");
+			String::new()
+		},
+		Err(e) => return Err(e),
+	};
+
+	let compression = known_compression.unwrap_or_else(|| {
+		log::debug!(
+			"Encountered unknown compression type: {:?}",
+			compression_type
+		);
+
+		AiffCompressionType::Other {
+			compression_type,
+			compression_name: compression_name.clone(),
+		}
+	});
+
+	Ok((
+		compression,
+		(!compression_name.is_empty()).then_some(compression_name),
+	))
+}
+
+// The compression name is a Pascal string, some encoders don't bother writing it
+fn read_compression_name(comm: &mut &[u8]) -> Result<String, AiffParseError> {
+	let Ok(compression_name_size) = comm.read_u8() else {
+		return Ok(String::new());
+	};
+
+	let mut compression_name_bytes = try_vec![0u8; compression_name_size as usize]?;
+	comm.read_exact(&mut compression_name_bytes)?;
+
+	// Names are often in Mac OS Roman, fall back to Latin-1 rather than failing
+	let compression_name = match utf8_decode_str(&compression_name_bytes) {
+		Ok(name) => name.to_owned(),
+		Err(_) => latin1_decode(&compression_name_bytes),
+	};
+
+	Ok(compression_name)
+}
@@ -1,4 +1,5 @@
 use super::AiffFile;
+use super::instrument::{self, InstrumentChunk};
 use super::properties::AiffProperties;
 use super::tag::{AiffTextChunks, Comment};
 use crate::config::ParseOptions;
@@ -7,6 +8,7 @@ use crate::id3::v2::tag::Id3v2Tag;
 use crate::iff::aiff::error::AiffParseError;
 use crate::iff::aiff::tag::error::AiffTextChunksParseError;
 use crate::iff::chunk::{Chunk, Chunks};
+use crate::iff::chunks::{ChunkId, discard_on_error};
 
 use std::io::{Read, Seek, SeekFrom};
 
@@ -70,6 +72,9 @@ where
 	let mut comments = Vec::new();
 
 	let mut id3v2_tag: Option<Id3v2Tag> = None;
+	let mut instrument = None;
+	let mut markers = None;
+	let mut unparsed_chunks = Vec::new();
 
 	let mut chunks = Chunks::<_, BigEndian>::new(data, file_len);
 	while let Some(mut chunk) = chunks.next(parse_options.parsing_mode)? {
@@ -103,6 +108,24 @@ where
 			b"SSND" if parse_options.read_properties => {
 				stream_len = chunk.size();
 			},
+			b"INST" if instrument.is_none() => {
+				let content = chunk.content()?;
+				instrument = discard_on_error(
+					ChunkId::Chunk(*b"INST"),
+					InstrumentChunk::parse(&content),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
+			b"MARK" if markers.is_none() => {
+				let content = chunk.content()?;
+				markers = discard_on_error(
+					ChunkId::Chunk(*b"MARK"),
+					instrument::parse_markers(&content, parse_options.parsing_mode),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
 			b"ANNO" if parse_options.read_tags => {
 				annotations.push(chunk.read_string(None)?);
 			},
@@ -217,5 +240,9 @@ where
 			_ => Some(text_chunks),
 		},
 		id3v2_tag,
+		instrument,
+		original_markers: markers.clone().unwrap_or_default(),
+		markers: markers.unwrap_or_default(),
+		unparsed_chunks,
 	})
 }
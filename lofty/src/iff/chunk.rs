@@ -3,6 +3,7 @@ use crate::error::{FakeTagError, SizeMismatchError};
 use crate::id3::v2::error::Id3v2ParseError;
 use crate::id3::v2::tag::Id3v2Tag;
 use crate::iff::error::ChunkParseError;
+use crate::iff::wav::container::{WAVE64_CHUNK_HEADER_SIZE, wave64_fourcc};
 use crate::macros::try_vec;
 use crate::util::text::utf8_decode;
 
@@ -35,9 +36,12 @@ pub(crate) fn valid_fourcc(fourcc: [u8; 4]) -> bool {
 pub(crate) struct Chunks<R, B> {
 	total_size: u64,
 	remaining_size: u64,
-	current_chunk_size: u32,
-	current_chunk_remaining_size: u32,
-	lock_state: Option<(u64, u32)>,
+	current_chunk_size: u64,
+	current_chunk_remaining_size: u64,
+	lock_state: Option<(u64, u64)>,
+	wave64: bool,
+	// The real sizes of chunks with a placeholder size (see `Chunks::set_large_chunk_sizes`)
+	large_chunk_sizes: Vec<([u8; 4], u64)>,
 	reader: R,
 	_phantom: PhantomData<B>,
 }
@@ -51,11 +55,28 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 			current_chunk_size: 0,
 			current_chunk_remaining_size: 0,
 			lock_state: None,
+			wave64: false,
+			large_chunk_sizes: Vec::new(),
 			reader,
 			_phantom: PhantomData,
 		}
 	}
 
+	/// Create a reader for Sony Wave64 chunks, which use GUIDs and 64-bit sizes
+	#[must_use]
+	pub const fn new_wave64(reader: R, file_size: u64) -> Self {
+		let mut chunks = Self::new(reader, file_size);
+		chunks.wave64 = true;
+		chunks
+	}
+
+	/// Set the real sizes of chunks that have a placeholder size of `0xFFFFFFFF`
+	///
+	/// This is used for RF64/BW64 files, where the sizes are stored in the `ds64` chunk.
+	pub fn set_large_chunk_sizes(&mut self, sizes: Vec<([u8; 4], u64)>) {
+		self.large_chunk_sizes = sizes;
+	}
+
 	pub fn stream_position(&self) -> u64 {
 		self.total_size - self.remaining_size
 	}
@@ -79,29 +100,74 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 
 		let start_pos = self.total_size - self.remaining_size;
 
-		let mut fourcc = [0; 4];
-		self.reader.read_exact(&mut fourcc)?;
+		let header_size;
+		let fourcc;
+		let size;
+		if self.wave64 {
+			if self.remaining_size < WAVE64_CHUNK_HEADER_SIZE {
+				return Ok(None);
+			}
+
+			let mut guid = [0; 16];
+			self.reader.read_exact(&mut guid)?;
 
-		// Maybe we're eating into some junk? just assume the rest of the stream is useless
-		if !valid_fourcc(fourcc) {
-			log::warn!("Encountered invalid FourCC, stopping");
+			// Wave64 sizes include the header
+			let Some(content_size) = self
+				.reader
+				.read_u64::<B>()?
+				.checked_sub(WAVE64_CHUNK_HEADER_SIZE)
+			else {
+				log::warn!("Encountered invalid Wave64 chunk size, stopping");
 
-			self.remaining_size = 0;
+				self.remaining_size = 0;
 
-			if parse_mode == ParsingMode::Strict {
-				return Err(SizeMismatchError.into());
+				if parse_mode == ParsingMode::Strict {
+					return Err(SizeMismatchError.into());
+				}
+
+				return Ok(None);
+			};
+
+			header_size = WAVE64_CHUNK_HEADER_SIZE;
+			fourcc = wave64_fourcc(guid);
+			size = content_size;
+		} else {
+			let mut id = [0; 4];
+			self.reader.read_exact(&mut id)?;
+
+			// Maybe we're eating into some junk? just assume the rest of the stream is useless
+			if !valid_fourcc(id) {
+				log::warn!("Encountered invalid FourCC, stopping");
+
+				self.remaining_size = 0;
+
+				if parse_mode == ParsingMode::Strict {
+					return Err(SizeMismatchError.into());
+				}
+
+				return Ok(None);
 			}
 
-			return Ok(None);
-		}
+			let mut id_size = u64::from(self.reader.read_u32::<B>()?);
+			if id_size == u64::from(u32::MAX)
+				&& let Some((_, large_size)) = self
+					.large_chunk_sizes
+					.iter()
+					.find(|(large_fourcc, _)| *large_fourcc == id)
+			{
+				id_size = *large_size;
+			}
 
-		let size = self.reader.read_u32::<B>()?;
+			header_size = u64::from(IFF_CHUNK_HEADER_SIZE);
+			fourcc = id;
+			size = id_size;
+		}
 
 		log::trace!(
 			"Parsed chunk header: (FourCC={}, size={size})",
 			fourcc.escape_ascii()
 		);
-		if u64::from(size) > self.remaining_size {
+		if size > self.remaining_size {
 			log::warn!(
 				"Chunk exceeds reader size, stopping (size: {size}, remaining: {})",
 				self.remaining_size
@@ -116,7 +182,7 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 			return Ok(None);
 		}
 
-		self.remaining_size -= u64::from(IFF_CHUNK_HEADER_SIZE);
+		self.remaining_size -= header_size;
 		self.current_chunk_size = size;
 		self.current_chunk_remaining_size = size;
 
@@ -126,20 +192,19 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 			start_pos,
 			fourcc,
 			size,
-			reader: self.reader.by_ref().take(u64::from(size)),
+			reader: self.reader.by_ref().take(size),
 		}))
 	}
 
 	/// Skip the rest of the current chunk's content
 	pub fn skip(&mut self) -> Result<(), ChunkParseError> {
 		if self.current_chunk_remaining_size > 0 {
-			self.reader.seek(SeekFrom::Current(i64::from(
-				self.current_chunk_remaining_size,
-			)))?;
+			self.reader
+				.seek(SeekFrom::Current(self.current_chunk_remaining_size as i64))?;
 
 			self.remaining_size = self
 				.remaining_size
-				.saturating_sub(u64::from(self.current_chunk_remaining_size));
+				.saturating_sub(self.current_chunk_remaining_size);
 
 			self.current_chunk_remaining_size = 0;
 		}
@@ -160,11 +225,11 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 
 		let outer_remaining = self
 			.remaining_size
-			.saturating_sub(u64::from(self.current_chunk_remaining_size));
+			.saturating_sub(self.current_chunk_remaining_size);
 
 		self.lock_state = Some((outer_remaining, self.current_chunk_size));
 
-		self.remaining_size = u64::from(self.current_chunk_remaining_size);
+		self.remaining_size = self.current_chunk_remaining_size;
 		self.current_chunk_size = 0;
 		self.current_chunk_remaining_size = 0;
 	}
@@ -194,7 +259,19 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 		Ok(())
 	}
 
-	fn correct_position(&mut self, current_chunk_size: u32) -> Result<(), ChunkParseError> {
+	fn correct_position(&mut self, current_chunk_size: u64) -> Result<(), ChunkParseError> {
+		// Wave64 chunks are aligned to 8 bytes, and there's no need to worry about broken padding
+		if self.wave64 {
+			let padding_size = (8 - current_chunk_size % 8) % 8;
+			let padding_size = padding_size.min(self.remaining_size);
+			if padding_size > 0 {
+				self.reader.seek(SeekFrom::Current(padding_size as i64))?;
+				self.remaining_size -= padding_size;
+			}
+
+			return Ok(());
+		}
+
 		// Chunks are expected to start on even boundaries, and are padded
 		// with a 0 if necessary. This is NOT the null terminator of the value,
 		// and it is NOT included in the chunk's size
@@ -235,10 +312,10 @@ impl<R: Read + Seek, B: ByteOrder> Chunks<R, B> {
 
 pub(crate) struct Chunk<'a, R> {
 	file_remaining_size: &'a mut u64,
-	chunk_remaining_size: &'a mut u32,
+	chunk_remaining_size: &'a mut u64,
 	start_pos: u64,
 	pub fourcc: [u8; 4],
-	size: u32,
+	size: u64,
 	reader: Take<&'a mut R>,
 }
 
@@ -246,7 +323,7 @@ impl<R: Read + Seek> Chunk<'_, R> {
 	/// Get the size of the chunk
 	///
 	/// This does **not** include the size of the chunk header.
-	pub fn size(&self) -> u32 {
+	pub fn size(&self) -> u64 {
 		self.size
 	}
 
@@ -259,7 +336,7 @@ impl<R: Read + Seek> Chunk<'_, R> {
 	///
 	/// If `size` isn't provided, the string is assumed to take up the entire chunk's content.
 	pub fn read_string(&mut self, size: Option<u32>) -> Result<String, ChunkParseError> {
-		let content = self.read_size(size.unwrap_or(self.size()))?;
+		let content = self.read_size(size.map_or(self.size(), u64::from))?;
 		utf8_decode(content).map_err(|e| ChunkParseError::from(e).with_fourcc(self.fourcc))
 	}
 
@@ -268,7 +345,7 @@ impl<R: Read + Seek> Chunk<'_, R> {
 		self.read_size(self.size())
 	}
 
-	fn read_size(&mut self, size: u32) -> Result<Vec<u8>, ChunkParseError> {
+	fn read_size(&mut self, size: u64) -> Result<Vec<u8>, ChunkParseError> {
 		let mut content = try_vec![0; size as usize]
 			.map_err(|e| ChunkParseError::from(e).with_fourcc(self.fourcc))?;
 		self.read_exact(&mut content)
@@ -307,7 +384,7 @@ impl<R: Read> Read for Chunk<'_, R> {
 	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
 		let bytes_read = self.reader.read(buf)?;
 		*self.file_remaining_size = self.file_remaining_size.saturating_sub(bytes_read as u64);
-		*self.chunk_remaining_size = self.chunk_remaining_size.saturating_sub(bytes_read as u32);
+		*self.chunk_remaining_size = self.chunk_remaining_size.saturating_sub(bytes_read as u64);
 		Ok(bytes_read)
 	}
 }
@@ -321,7 +398,7 @@ impl<R: Seek> Seek for Chunk<'_, R> {
 		let delta = (old_limit as i64) - (new_limit as i64);
 
 		*self.file_remaining_size = (*self.file_remaining_size as i64 - delta) as u64;
-		*self.chunk_remaining_size = (i64::from(*self.chunk_remaining_size) - delta) as u32;
+		*self.chunk_remaining_size = (*self.chunk_remaining_size as i64 - delta) as u64;
 
 		Ok(ret)
 	}
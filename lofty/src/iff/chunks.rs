@@ -0,0 +1,500 @@
+use crate::config::{ParsingMode, WriteOptions};
+use crate::error::{FileEncodingError, FileParseError, SizeMismatchError, TooMuchDataError};
+use crate::file::FileType;
+use crate::iff::aiff::read::verify_aiff;
+use crate::iff::chunk::{IFF_CHUNK_HEADER_SIZE, valid_fourcc};
+use crate::iff::wav::container::{
+	DS64_SIZE, Ds64, WAVE64_CHUNK_HEADER_SIZE, WavContainer, wave64_fourcc, wave64_guid,
+};
+use crate::iff::wav::read::verify_wav;
+use crate::io::VerifiedFile;
+use crate::util::io::{FileLike, replace_range};
+
+use std::fmt::Display;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+/// The identity of a top-level chunk, with `LIST` chunks being identified by their type
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum ChunkId {
+	Chunk([u8; 4]),
+	List([u8; 4]),
+}
+
+impl ChunkId {
+	fn new(fourcc: [u8; 4], content: &[u8]) -> Self {
+		match (&fourcc, content.first_chunk::<4>()) {
+			(b"LIST", Some(list_type)) => Self::List(*list_type),
+			_ => Self::Chunk(fourcc),
+		}
+	}
+}
+
+impl Display for ChunkId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Chunk(fourcc) => write!(f, "{}", fourcc.escape_ascii()),
+			Self::List(list_type) => write!(f, "LIST/{}", list_type.escape_ascii()),
+		}
+	}
+}
+
+/// Parse a chunk that isn't essential to reading the file
+///
+/// Outside of [`ParsingMode::Strict`], a chunk that fails to parse is skipped, and its ID is added to
+/// `unparsed`. Those chunks are left untouched in the file by [`retain_unparsed`].
+pub(crate) fn discard_on_error<T, E>(
+	id: ChunkId,
+	result: Result<T, E>,
+	parse_mode: ParsingMode,
+	unparsed: &mut Vec<ChunkId>,
+) -> Result<Option<T>, E>
+where
+	E: Display,
+{
+	match result {
+		Ok(chunk) => Ok(Some(chunk)),
+		Err(e) => {
+			if parse_mode == ParsingMode::Strict {
+				return Err(e);
+			}
+
+			log::warn!("Unable to parse '{id}' chunk, preserving it as-is: {e}");
+			unparsed.push(id);
+			Ok(None)
+		},
+	}
+}
+
+/// Drop the removal of any chunks that failed to parse
+///
+/// There is nothing to write in their place, so they are kept as they are. They can still be
+/// replaced, as new content is always written.
+pub(crate) fn retain_unparsed(updates: &mut Vec<ChunkUpdate>, unparsed: &[ChunkId]) {
+	updates.retain(|update| update.content.is_some() || !unparsed.contains(&update.id));
+}
+
+/// Drop the updates of any chunks that are unchanged since they were read
+///
+/// Re-encoding a chunk isn't always lossless, so chunks that weren't changed are left as they are.
+pub(crate) fn retain_changed(updates: &mut Vec<ChunkUpdate>, unchanged: &[ChunkId]) {
+	updates.retain(|update| !unchanged.contains(&update.id));
+}
+
+/// A top-level chunk to update
+pub(crate) struct ChunkUpdate {
+	pub(crate) id: ChunkId,
+	/// The new content of the chunk, or `None` to remove it
+	///
+	/// For `LIST` chunks, this does **not** include the list type.
+	pub(crate) content: Option<Vec<u8>>,
+}
+
+/// The layout of the container being written
+#[derive(Copy, Clone, PartialEq)]
+enum Layout {
+	/// RIFF....WAVE
+	Riff,
+	/// RF64....WAVE or BW64....WAVE, followed by a `ds64` chunk
+	Rf64,
+	/// FORM....AIFF
+	Form,
+	/// Sony Wave64
+	Wave64,
+}
+
+impl Layout {
+	fn header_size(self) -> usize {
+		match self {
+			Self::Wave64 => WavContainer::Wave64.header_size() as usize,
+			_ => IFF_CHUNK_HEADER_SIZE as usize + 4,
+		}
+	}
+
+	fn chunk_header_size(self) -> usize {
+		match self {
+			Self::Wave64 => WAVE64_CHUNK_HEADER_SIZE as usize,
+			_ => IFF_CHUNK_HEADER_SIZE as usize,
+		}
+	}
+
+	fn read_u32(self, bytes: &[u8]) -> u32 {
+		let bytes = bytes.try_into().unwrap();
+		match self {
+			Self::Form => u32::from_be_bytes(bytes),
+			_ => u32::from_le_bytes(bytes),
+		}
+	}
+
+	fn encode_u32(self, value: u32) -> [u8; 4] {
+		match self {
+			Self::Form => value.to_be_bytes(),
+			_ => value.to_le_bytes(),
+		}
+	}
+}
+
+// The position of the RIFF size in the `ds64` chunk, which directly follows the header
+const DS64_RIFF_SIZE_POS: u64 = 12 + IFF_CHUNK_HEADER_SIZE as u64;
+
+/// Replace, insert, or remove top-level chunks
+///
+/// Existing chunks are replaced in place, and any duplicates are removed. New chunks are
+/// inserted before the audio data chunk (`data` in WAV, `SSND` in AIFF).
+///
+/// Only the chunk headers are read, the audio data is moved in place when needed. If nothing
+/// changed, the file is left untouched.
+///
+/// For RF64/BW64 and Wave64 files, the 64-bit sizes are kept up to date.
+pub(crate) fn write_chunks<F>(
+	file: VerifiedFile<'_, F>,
+	updates: &[ChunkUpdate],
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+{
+	let parse_mode = write_options.parse_options.parsing_mode;
+
+	let format = file.format();
+	let mut file = file.into_inner();
+
+	let (layout, audio_fourcc) = match format {
+		FileType::Wav => match verify_wav(&mut file).map_err(FileParseError::from)? {
+			WavContainer::Riff => (Layout::Riff, *b"data"),
+			WavContainer::Rf64 | WavContainer::Bw64 => (Layout::Rf64, *b"data"),
+			WavContainer::Wave64 => (Layout::Wave64, *b"data"),
+		},
+		FileType::Aiff => {
+			verify_aiff(&mut file).map_err(FileParseError::from)?;
+			(Layout::Form, *b"SSND")
+		},
+		_ => unreachable!("only WAV and AIFF files can have their chunks written"),
+	};
+
+	let file_len = file.seek(SeekFrom::End(0))?;
+
+	let header_size = layout.header_size() as u64;
+	let chunk_header_size = layout.chunk_header_size();
+
+	file.rewind()?;
+	let mut header = [0; 40];
+	file.read_exact(&mut header[..layout.header_size()])?;
+
+	let mut large_chunk_sizes = Vec::new();
+	let riff_end = match layout {
+		Layout::Riff | Layout::Form => {
+			u64::from(layout.read_u32(&header[4..8])) + u64::from(IFF_CHUNK_HEADER_SIZE)
+		},
+		Layout::Rf64 => {
+			let mut ds64_header = [0; IFF_CHUNK_HEADER_SIZE as usize];
+			file.read_exact(&mut ds64_header)?;
+
+			let ds64_size = u64::from(u32::from_le_bytes(ds64_header[4..].try_into().unwrap()));
+			if &ds64_header[..4] != b"ds64"
+				|| ds64_size < DS64_SIZE as u64
+				|| header_size + u64::from(IFF_CHUNK_HEADER_SIZE) + ds64_size > file_len
+			{
+				return Err(FileParseError::new(
+					FileType::Wav,
+					"RF64 file does not start with a \"ds64\" chunk".into(),
+				)
+				.into());
+			}
+
+			let mut ds64_content = Vec::new();
+			Read::by_ref(&mut file)
+				.take(ds64_size)
+				.read_to_end(&mut ds64_content)?;
+
+			let ds64 = Ds64::parse(&ds64_content, parse_mode).map_err(FileParseError::from)?;
+			large_chunk_sizes = ds64.chunk_sizes();
+
+			ds64.riff_size + u64::from(IFF_CHUNK_HEADER_SIZE)
+		},
+		Layout::Wave64 => u64::from_le_bytes(header[16..24].try_into().unwrap()),
+	};
+
+	if file_len < riff_end || riff_end < header_size {
+		return Err(SizeMismatchError.into());
+	}
+
+	let mut chunks = Vec::new();
+	let mut audio_chunks = Vec::new();
+
+	let mut pos = header_size;
+	while pos + chunk_header_size as u64 <= riff_end {
+		let mut chunk_header = [0; WAVE64_CHUNK_HEADER_SIZE as usize];
+		file.seek(SeekFrom::Start(pos))?;
+		file.read_exact(&mut chunk_header[..chunk_header_size])?;
+
+		let fourcc;
+		let size;
+		if layout == Layout::Wave64 {
+			fourcc = wave64_fourcc(chunk_header[..16].try_into().unwrap());
+			size = u64::from_le_bytes(chunk_header[16..24].try_into().unwrap())
+				.saturating_sub(WAVE64_CHUNK_HEADER_SIZE);
+		} else {
+			fourcc = chunk_header[..4].try_into().unwrap();
+
+			let mut chunk_size = u64::from(layout.read_u32(&chunk_header[4..8]));
+			if layout == Layout::Rf64
+				&& chunk_size == u64::from(u32::MAX)
+				&& let Some((_, large_size)) = large_chunk_sizes
+					.iter()
+					.find(|(large_fourcc, _)| *large_fourcc == fourcc)
+			{
+				chunk_size = *large_size;
+			}
+
+			size = chunk_size;
+		}
+
+		let content_start = pos + chunk_header_size as u64;
+		let content_end = content_start.checked_add(size);
+		let Some(content_end) = content_end.filter(|end| *end <= riff_end) else {
+			if parse_mode == ParsingMode::Strict {
+				return Err(SizeMismatchError.into());
+			}
+
+			// Keep whatever this is around, we can't make sense of it
+			log::warn!(
+				"Encountered an invalid chunk while writing, preserving the rest of the file"
+			);
+			break;
+		};
+
+		if layout != Layout::Wave64 && !valid_fourcc(fourcc) {
+			if parse_mode == ParsingMode::Strict {
+				return Err(SizeMismatchError.into());
+			}
+
+			log::warn!(
+				"Encountered an invalid chunk while writing, preserving the rest of the file"
+			);
+			break;
+		}
+
+		let mut chunk_end = content_end;
+		if layout == Layout::Wave64 {
+			// Wave64 chunks are aligned to 8 bytes
+			chunk_end = content_end.next_multiple_of(8).min(riff_end);
+		} else if !size.is_multiple_of(2) && content_end < riff_end {
+			// Some encoders include the padding byte in the chunk size
+			let mut next = Vec::with_capacity(4);
+			file.seek(SeekFrom::Start(content_end))?;
+			Read::by_ref(&mut file).take(4).read_to_end(&mut next)?;
+
+			let next_is_chunk = next.len() == 4 && valid_fourcc(next[..].try_into().unwrap());
+			if next[0] == 0 || !next_is_chunk {
+				chunk_end += 1;
+			}
+		}
+
+		if fourcc == audio_fourcc {
+			audio_chunks.push(pos..chunk_end);
+		}
+
+		let mut list_type = Vec::new();
+		if &fourcc == b"LIST" {
+			file.seek(SeekFrom::Start(content_start))?;
+			Read::by_ref(&mut file)
+				.take(size.min(4))
+				.read_to_end(&mut list_type)?;
+		}
+
+		chunks.push((ChunkId::new(fourcc, &list_type), pos..chunk_end));
+		pos = chunk_end;
+	}
+
+	// Existing chunks are replaced in place, and any duplicates are removed
+	let mut edits = Vec::new();
+	let mut written = vec![false; updates.len()];
+	for (id, range) in chunks.iter().cloned() {
+		let Some(idx) = updates.iter().position(|update| update.id == id) else {
+			continue;
+		};
+
+		if written[idx] {
+			log::debug!("Removing duplicate '{id}' chunk");
+			edits.push((range, Vec::new()));
+			continue;
+		}
+
+		written[idx] = true;
+
+		let mut replacement = Vec::new();
+		if let Some(content) = &updates[idx].content {
+			write_chunk(&mut replacement, id, content, layout)?;
+		} else {
+			log::debug!("Removing '{id}' chunk");
+		}
+
+		if !chunk_unchanged(&mut file, range.clone(), &replacement)? {
+			edits.push((range, replacement));
+		}
+	}
+
+	// New chunks go before the audio data, or at the end if there isn't any. Anything we
+	// couldn't parse is kept as-is.
+	let mut pending = Vec::new();
+	write_pending(&mut pending, updates, &mut written, layout)?;
+	if !pending.is_empty() {
+		let insert_pos = audio_chunks.first().map_or(riff_end, |audio| audio.start);
+		let idx = edits.partition_point(|(range, _)| range.start < insert_pos);
+		edits.insert(idx, (insert_pos..insert_pos, pending));
+	}
+
+	if edits.is_empty() {
+		log::debug!("No chunks changed, nothing to write");
+		return Ok(());
+	}
+
+	let removed = edits
+		.iter()
+		.map(|(range, _)| range.end - range.start)
+		.sum::<u64>();
+	let added = edits
+		.iter()
+		.map(|(_, replacement)| replacement.len() as u64)
+		.sum::<u64>();
+	let new_riff_end = riff_end + added - removed;
+
+	let new_stream_length = new_riff_end - u64::from(IFF_CHUNK_HEADER_SIZE);
+	let size_field = match layout {
+		Layout::Riff | Layout::Form => {
+			let Ok(new_stream_length) = u32::try_from(new_stream_length) else {
+				return Err(TooMuchDataError.into());
+			};
+
+			(4, layout.encode_u32(new_stream_length).to_vec())
+		},
+		Layout::Rf64 => (DS64_RIFF_SIZE_POS, new_stream_length.to_le_bytes().to_vec()),
+		Layout::Wave64 => (16, new_riff_end.to_le_bytes().to_vec()),
+	};
+
+	// Edits between two audio chunks are merged, so the audio data only has to be moved once.
+	// They're applied back to front, so the earlier offsets stay valid.
+	for (range, replacement) in merge_edits(&mut file, edits, &audio_chunks)?
+		.into_iter()
+		.rev()
+	{
+		replace_range(&mut file, range, &replacement)?;
+	}
+
+	let (size_pos, size) = size_field;
+	file.seek(SeekFrom::Start(size_pos))?;
+	file.write_all(&size)?;
+
+	Ok(())
+}
+
+// Whether the chunk at `range` already has the contents of `replacement`
+fn chunk_unchanged<F>(file: &mut F, range: Range<u64>, replacement: &[u8]) -> std::io::Result<bool>
+where
+	F: Read + Seek,
+{
+	if range.end - range.start != replacement.len() as u64 {
+		return Ok(false);
+	}
+
+	let mut original = vec![0; replacement.len()];
+	file.seek(SeekFrom::Start(range.start))?;
+	file.read_exact(&mut original)?;
+
+	Ok(original == replacement)
+}
+
+// Combine all consecutive edits that don't have audio data between them into a single edit
+fn merge_edits<F>(
+	file: &mut F,
+	edits: Vec<(Range<u64>, Vec<u8>)>,
+	audio_chunks: &[Range<u64>],
+) -> std::io::Result<Vec<(Range<u64>, Vec<u8>)>>
+where
+	F: Read + Seek,
+{
+	let mut merged: Vec<(Range<u64>, Vec<u8>)> = Vec::with_capacity(edits.len());
+	for (range, replacement) in edits {
+		if let Some((last_range, last_replacement)) = merged.last_mut()
+			&& !audio_chunks
+				.iter()
+				.any(|audio| audio.start >= last_range.end && audio.end <= range.start)
+		{
+			let gap = range.start - last_range.end;
+			file.seek(SeekFrom::Start(last_range.end))?;
+			Read::by_ref(file).take(gap).read_to_end(last_replacement)?;
+
+			last_replacement.extend_from_slice(&replacement);
+			last_range.end = range.end;
+			continue;
+		}
+
+		merged.push((range, replacement));
+	}
+
+	Ok(merged)
+}
+
+fn write_pending(
+	bytes: &mut Vec<u8>,
+	updates: &[ChunkUpdate],
+	written: &mut [bool],
+	layout: Layout,
+) -> Result<(), TooMuchDataError> {
+	for (update, written) in updates.iter().zip(written.iter_mut()) {
+		if *written {
+			continue;
+		}
+
+		*written = true;
+		if let Some(content) = &update.content {
+			write_chunk(bytes, update.id, content, layout)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn write_chunk(
+	bytes: &mut Vec<u8>,
+	id: ChunkId,
+	content: &[u8],
+	layout: Layout,
+) -> Result<(), TooMuchDataError> {
+	let (fourcc, list_type) = match id {
+		ChunkId::Chunk(fourcc) => (fourcc, None),
+		ChunkId::List(list_type) => (*b"LIST", Some(list_type)),
+	};
+
+	let size = content.len() + list_type.map_or(0, |list_type| list_type.len());
+
+	log::debug!(
+		"Writing '{}' chunk, size: {size} bytes",
+		fourcc.escape_ascii()
+	);
+
+	if layout == Layout::Wave64 {
+		bytes.extend_from_slice(&wave64_guid(fourcc));
+		bytes.extend_from_slice(&((size as u64) + WAVE64_CHUNK_HEADER_SIZE).to_le_bytes());
+	} else {
+		let Ok(size) = u32::try_from(size) else {
+			return Err(TooMuchDataError);
+		};
+
+		bytes.extend_from_slice(&fourcc);
+		bytes.extend_from_slice(&layout.encode_u32(size));
+	}
+
+	if let Some(list_type) = list_type {
+		bytes.extend_from_slice(&list_type);
+	}
+	bytes.extend_from_slice(content);
+
+	// Chunks are expected to start on even boundaries, or 8 byte boundaries in Wave64
+	let alignment = if layout == Layout::Wave64 { 8 } else { 2 };
+	let padding = size.next_multiple_of(alignment) - size;
+	bytes.resize(bytes.len() + padding, 0);
+
+	Ok(())
+}
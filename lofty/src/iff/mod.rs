@@ -4,5 +4,6 @@
 
 pub mod aiff;
 pub(crate) mod chunk;
+pub(crate) mod chunks;
 pub mod error;
 pub mod wav;
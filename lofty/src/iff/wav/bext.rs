@@ -0,0 +1,524 @@
+use super::error::{WavEncodingError, WavParseError};
+use crate::config::ParsingMode;
+use crate::tag::items::Timestamp;
+use crate::tag::{ItemKey, Tag};
+use crate::util::text::latin1_decode;
+
+use std::io::Write;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+const DESCRIPTION_LEN: usize = 256;
+const ORIGINATOR_LEN: usize = 32;
+const ORIGINATOR_REFERENCE_LEN: usize = 32;
+const ORIGINATION_DATE_LEN: usize = 10;
+const ORIGINATION_TIME_LEN: usize = 8;
+const UMID_LEN: usize = 64;
+const RESERVED_LEN: usize = 180;
+
+// The size of the chunk, excluding the coding history
+const FIXED_SIZE: usize = DESCRIPTION_LEN
+	+ ORIGINATOR_LEN
+	+ ORIGINATOR_REFERENCE_LEN
+	+ ORIGINATION_DATE_LEN
+	+ ORIGINATION_TIME_LEN
+	+ 8 // Time reference
+	+ 2 // Version
+	+ UMID_LEN
+	+ 10 // Loudness values
+	+ RESERVED_LEN;
+
+// Loudness values are unset if they hold this value
+const LOUDNESS_UNSET: i16 = 0x7FFF;
+
+/// Loudness values of a [`BextChunk`], as defined in EBU R 128
+///
+/// All values are stored as hundredths of their unit (e.g. `-2300` is -23 LUFS).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct BextLoudness {
+	/// Integrated loudness, in LUFS
+	pub loudness_value: Option<i16>,
+	/// Loudness range, in LU
+	pub loudness_range: Option<i16>,
+	/// Maximum true peak level, in dBTP
+	pub max_true_peak_level: Option<i16>,
+	/// Highest value of the momentary loudness, in LUFS
+	pub max_momentary_loudness: Option<i16>,
+	/// Highest value of the short-term loudness, in LUFS
+	pub max_short_term_loudness: Option<i16>,
+}
+
+/// A Broadcast Wave Format `bext` chunk
+///
+/// See [EBU Tech 3285](https://tech.ebu.ch/docs/tech/tech3285.pdf)
+///
+/// The text fields must be ASCII. Outside of [`ParsingMode::Strict`], non-ASCII fields are read as
+/// Latin-1, and are written back as they were read as long as they aren't changed.
+///
+/// ## Conversions
+///
+/// The `bext` chunk is not a tag, and will not appear in the [`TaggedFile`](crate::file::TaggedFile)
+/// of a WAV file. Its text fields can be moved to and from a [`Tag`] with [`BextChunk::merge_into_tag`]
+/// and [`BextChunk::merge_from_tag`], using the following mappings:
+///
+/// | Field                                  | [`ItemKey`]                    |
+/// |----------------------------------------|--------------------------------|
+/// | `description`                          | [`ItemKey::Comment`]           |
+/// | `originator`                           | [`ItemKey::EncodedBy`]         |
+/// | `origination_date`, `origination_time` | [`ItemKey::RecordingDate`]     |
+/// | `coding_history`                       | [`ItemKey::EncoderSettings`]   |
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BextChunk {
+	/// A free description of the sound sequence (256 characters max)
+	pub description: String,
+	/// The name of the originator (32 characters max)
+	pub originator: String,
+	/// An unambiguous reference allocated by the originating organization (32 characters max)
+	pub originator_reference: String,
+	/// The date of creation, in the format `yyyy-mm-dd`
+	pub origination_date: String,
+	/// The time of creation, in the format `hh:mm:ss`
+	pub origination_time: String,
+	/// The number of samples since midnight of the first sample
+	pub time_reference: u64,
+	/// The version of the chunk
+	///
+	/// This will be raised to `1` when writing a UMID, and to `2` when writing loudness values.
+	pub version: u16,
+	/// A SMPTE 330M UMID, or all zeros if unused
+	pub umid: [u8; UMID_LEN],
+	/// Loudness values, only available in version 2 chunks
+	pub loudness: BextLoudness,
+	/// The coding history, made up of one line per coding process
+	pub coding_history: String,
+}
+
+impl Default for BextChunk {
+	fn default() -> Self {
+		Self {
+			description: String::new(),
+			originator: String::new(),
+			originator_reference: String::new(),
+			origination_date: String::new(),
+			origination_time: String::new(),
+			time_reference: 0,
+			version: 0,
+			umid: [0; UMID_LEN],
+			loudness: BextLoudness::default(),
+			coding_history: String::new(),
+		}
+	}
+}
+
+impl BextChunk {
+	/// Create a new empty `BextChunk`
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::iff::wav::BextChunk;
+	///
+	/// let bext = BextChunk::new();
+	/// assert!(bext.description.is_empty());
+	/// ```
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The origination date and time as a [`Timestamp`]
+	///
+	/// This will return `None` if the date is missing or invalid.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::iff::wav::BextChunk;
+	///
+	/// let mut bext = BextChunk::new();
+	/// bext.origination_date = String::from("2024-01-02");
+	/// bext.origination_time = String::from("13:14:15");
+	///
+	/// let timestamp = bext.origination_timestamp().unwrap();
+	/// assert_eq!(timestamp.to_string(), "2024-01-02T13:14:15");
+	/// ```
+	pub fn origination_timestamp(&self) -> Option<Timestamp> {
+		let [year, month, day] = parse_fields::<3>(&self.origination_date, &[4, 2, 2])?;
+
+		let mut timestamp = Timestamp {
+			year,
+			month: Some(month as u8),
+			day: Some(day as u8),
+			..Timestamp::default()
+		};
+
+		if let Some([hour, minute, second]) = parse_fields::<3>(&self.origination_time, &[2, 2, 2])
+		{
+			timestamp.hour = Some(hour as u8);
+			timestamp.minute = Some(minute as u8);
+			timestamp.second = Some(second as u8);
+		}
+
+		Some(timestamp)
+	}
+
+	/// Set the origination date and time from a [`Timestamp`]
+	///
+	/// Any missing components will be zeroed, as the chunk requires a full date.
+	pub fn set_origination_timestamp(&mut self, timestamp: Timestamp) {
+		self.origination_date = format!(
+			"{:04}-{:02}-{:02}",
+			timestamp.year,
+			timestamp.month.unwrap_or(0),
+			timestamp.day.unwrap_or(0)
+		);
+
+		self.origination_time = match timestamp.hour {
+			Some(hour) => format!(
+				"{hour:02}:{:02}:{:02}",
+				timestamp.minute.unwrap_or(0),
+				timestamp.second.unwrap_or(0)
+			),
+			None => String::new(),
+		};
+	}
+
+	/// Copy the mapped fields into `tag`
+	///
+	/// Empty fields are skipped. See the [conversion table](BextChunk#conversions).
+	///
+	/// NOTE: Items that can't be represented in the tag's format will be discarded. See [`Tag::insert`].
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::iff::wav::BextChunk;
+	/// use lofty::tag::{ItemKey, Tag, TagType};
+	///
+	/// let mut bext = BextChunk::new();
+	/// bext.originator = String::from("Studio A");
+	///
+	/// let mut tag = Tag::new(TagType::RiffInfo);
+	/// bext.merge_into_tag(&mut tag);
+	///
+	/// assert_eq!(tag.get_string(ItemKey::EncodedBy), Some("Studio A"));
+	/// ```
+	pub fn merge_into_tag(&self, tag: &mut Tag) {
+		for (key, value) in [
+			(ItemKey::Comment, &self.description),
+			(ItemKey::EncodedBy, &self.originator),
+			(ItemKey::EncoderSettings, &self.coding_history),
+		] {
+			if !value.is_empty() {
+				tag.insert_text(key, value.clone());
+			}
+		}
+
+		if let Some(timestamp) = self.origination_timestamp() {
+			tag.insert_text(ItemKey::RecordingDate, timestamp.to_string());
+		}
+	}
+
+	/// Set the mapped fields from the items in `tag`
+	///
+	/// Fields without a matching item are left untouched. See the [conversion table](BextChunk#conversions).
+	pub fn merge_from_tag(&mut self, tag: &Tag) {
+		for (key, value) in [
+			(ItemKey::Comment, &mut self.description),
+			(ItemKey::EncodedBy, &mut self.originator),
+			(ItemKey::EncoderSettings, &mut self.coding_history),
+		] {
+			if let Some(item) = tag.get_string(key) {
+				item.clone_into(value);
+			}
+		}
+
+		if let Some(timestamp) = tag
+			.get_string(ItemKey::RecordingDate)
+			.and_then(|date| date.parse::<Timestamp>().ok())
+		{
+			self.set_origination_timestamp(timestamp);
+		}
+	}
+
+	pub(super) fn parse(content: &[u8], parse_mode: ParsingMode) -> Result<Self, WavParseError> {
+		if content.len() < FIXED_SIZE {
+			return Err(WavParseError::message("bext chunk is too small"));
+		}
+
+		let reader = &mut &content[..];
+
+		let description = read_text(reader, DESCRIPTION_LEN, parse_mode)?;
+		let originator = read_text(reader, ORIGINATOR_LEN, parse_mode)?;
+		let originator_reference = read_text(reader, ORIGINATOR_REFERENCE_LEN, parse_mode)?;
+		let origination_date = read_text(reader, ORIGINATION_DATE_LEN, parse_mode)?;
+		let origination_time = read_text(reader, ORIGINATION_TIME_LEN, parse_mode)?;
+		let time_reference = reader.read_u64::<LittleEndian>()?;
+		let version = reader.read_u16::<LittleEndian>()?;
+
+		let (umid, rest) = reader.split_at(UMID_LEN);
+		*reader = rest;
+
+		let mut loudness = BextLoudness::default();
+		let loudness_values = [
+			&mut loudness.loudness_value,
+			&mut loudness.loudness_range,
+			&mut loudness.max_true_peak_level,
+			&mut loudness.max_momentary_loudness,
+			&mut loudness.max_short_term_loudness,
+		];
+		for value in loudness_values {
+			let raw = reader.read_i16::<LittleEndian>()?;
+
+			// Older versions have these bytes reserved, and they should be zeroed
+			if version >= 2 && raw != LOUDNESS_UNSET {
+				*value = Some(raw);
+			}
+		}
+
+		*reader = &reader[RESERVED_LEN..];
+
+		let coding_history = read_text(reader, reader.len(), parse_mode)?;
+
+		Ok(Self {
+			description,
+			originator,
+			originator_reference,
+			origination_date,
+			origination_time,
+			time_reference,
+			version,
+			umid: umid.try_into().expect("slice is exactly UMID_LEN"),
+			loudness,
+			coding_history,
+		})
+	}
+
+	/// Encode the chunk
+	///
+	/// Text fields that are unchanged from `original` (the chunk as read, along with its content)
+	/// are written back as they were read, as they may not have been ASCII.
+	pub(super) fn as_bytes(
+		&self,
+		original: Option<&(BextChunk, Vec<u8>)>,
+	) -> Result<Vec<u8>, WavEncodingError> {
+		let has_loudness = self.loudness != BextLoudness::default();
+		let has_umid = self.umid.iter().any(|b| *b != 0);
+
+		let mut version = self.version;
+		if has_loudness {
+			version = version.max(2);
+		} else if has_umid {
+			version = version.max(1);
+		}
+
+		let mut content = Vec::with_capacity(FIXED_SIZE + self.coding_history.len());
+
+		// These fields are stored back to back at the start of the chunk
+		let text_fields: [(fn(&BextChunk) -> &String, usize, &'static str); 5] = [
+			(|bext| &bext.description, DESCRIPTION_LEN, "description"),
+			(|bext| &bext.originator, ORIGINATOR_LEN, "originator"),
+			(
+				|bext| &bext.originator_reference,
+				ORIGINATOR_REFERENCE_LEN,
+				"originator reference",
+			),
+			(
+				|bext| &bext.origination_date,
+				ORIGINATION_DATE_LEN,
+				"origination date",
+			),
+			(
+				|bext| &bext.origination_time,
+				ORIGINATION_TIME_LEN,
+				"origination time",
+			),
+		];
+
+		for (field, len, name) in text_fields {
+			let value = field(self);
+
+			let start = content.len();
+			let original_raw = original
+				.filter(|(original, _)| field(original) == value)
+				.and_then(|(_, raw)| raw.get(start..start + len));
+
+			match original_raw {
+				Some(raw) => content.write_all(raw)?,
+				None => write_text(&mut content, value, len, name)?,
+			}
+		}
+
+		content.write_u64::<LittleEndian>(self.time_reference)?;
+		content.write_u16::<LittleEndian>(version)?;
+		content.write_all(&self.umid)?;
+
+		for value in [
+			self.loudness.loudness_value,
+			self.loudness.loudness_range,
+			self.loudness.max_true_peak_level,
+			self.loudness.max_momentary_loudness,
+			self.loudness.max_short_term_loudness,
+		] {
+			let raw = match value {
+				Some(value) => value,
+				None if version >= 2 => LOUDNESS_UNSET,
+				None => 0,
+			};
+			content.write_i16::<LittleEndian>(raw)?;
+		}
+
+		content.resize(content.len() + RESERVED_LEN, 0);
+
+		let original_coding_history = original
+			.filter(|(original, _)| original.coding_history == self.coding_history)
+			.and_then(|(_, raw)| raw.get(FIXED_SIZE..));
+
+		match original_coding_history {
+			Some(raw) => content.write_all(raw)?,
+			None => write_text(
+				&mut content,
+				&self.coding_history,
+				self.coding_history.len(),
+				"coding history",
+			)?,
+		}
+
+		Ok(content)
+	}
+}
+
+// Split `value` into numeric fields of the given widths, separated by a single character
+fn parse_fields<const N: usize>(value: &str, widths: &[usize; N]) -> Option<[u16; N]> {
+	let mut fields = [0; N];
+
+	let mut rest = value;
+	for (idx, (field, width)) in fields.iter_mut().zip(widths).enumerate() {
+		if idx > 0 {
+			let mut chars = rest.chars();
+			chars.next()?;
+			rest = chars.as_str();
+		}
+
+		let digits = rest.get(..*width)?;
+		if !digits.bytes().all(|b| b.is_ascii_digit()) {
+			return None;
+		}
+
+		*field = digits.parse().ok()?;
+		rest = &rest[*width..];
+	}
+
+	Some(fields)
+}
+
+fn read_text(
+	reader: &mut &[u8],
+	len: usize,
+	parse_mode: ParsingMode,
+) -> Result<String, WavParseError> {
+	let (raw, rest) = reader.split_at(len);
+	*reader = rest;
+
+	let end = raw.iter().position(|b| *b == 0).unwrap_or(len);
+	let raw = &raw[..end];
+
+	if !raw.is_ascii() {
+		if parse_mode == ParsingMode::Strict {
+			return Err(WavParseError::message(
+				"bext chunk contains a non-ASCII string",
+			));
+		}
+
+		log::warn!("bext chunk contains a non-ASCII string, decoding it as Latin-1");
+		return Ok(latin1_decode(raw));
+	}
+
+	Ok(String::from_utf8_lossy(raw).into_owned())
+}
+
+fn write_text(
+	content: &mut Vec<u8>,
+	value: &str,
+	len: usize,
+	field: &'static str,
+) -> Result<(), WavEncodingError> {
+	if !value.is_ascii() {
+		log::error!("bext {field} contains non-ASCII characters");
+		return Err(WavEncodingError::message("bext chunk fields must be ASCII"));
+	}
+
+	if value.len() > len {
+		log::error!("bext {field} is too long ({} > {len})", value.len());
+		return Err(WavEncodingError::message("bext chunk field is too long"));
+	}
+
+	content.write_all(value.as_bytes())?;
+	content.resize(content.len() + (len - value.len()), 0);
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{BextChunk, BextLoudness};
+	use crate::config::ParsingMode;
+	use crate::tag::items::Timestamp;
+
+	#[test_log::test]
+	fn round_trip() {
+		let bext = BextChunk {
+			description: String::from("Morning news"),
+			originator: String::from("Studio A"),
+			originator_reference: String::from("REF0001"),
+			origination_date: String::from("2024-01-02"),
+			origination_time: String::from("13:14:15"),
+			time_reference: 48000 * 3600,
+			version: 0,
+			umid: [0; 64],
+			loudness: BextLoudness {
+				loudness_value: Some(-2300),
+				max_true_peak_level: Some(-100),
+				..BextLoudness::default()
+			},
+			coding_history: String::from("A=PCM,F=48000,W=24,M=stereo\r\n"),
+		};
+
+		let bytes = bext.as_bytes(None).unwrap();
+		assert_eq!(bytes.len(), 602 + bext.coding_history.len());
+
+		let parsed = BextChunk::parse(&bytes, ParsingMode::Strict).unwrap();
+
+		// Version is raised for the loudness values
+		assert_eq!(parsed.version, 2);
+		assert_eq!(parsed, BextChunk { version: 2, ..bext });
+	}
+
+	#[test_log::test]
+	fn origination_timestamp() {
+		let mut bext = BextChunk::new();
+		assert!(bext.origination_timestamp().is_none());
+
+		// The spec allows any separator
+		bext.origination_date = String::from("2024:01:02");
+		assert_eq!(
+			bext.origination_timestamp(),
+			Some(Timestamp {
+				year: 2024,
+				month: Some(1),
+				day: Some(2),
+				..Timestamp::default()
+			})
+		);
+
+		bext.set_origination_timestamp(Timestamp {
+			year: 2020,
+			month: Some(5),
+			day: Some(6),
+			hour: Some(7),
+			minute: Some(8),
+			second: None,
+		});
+		assert_eq!(bext.origination_date, "2020-05-06");
+		assert_eq!(bext.origination_time, "07:08:00");
+	}
+}
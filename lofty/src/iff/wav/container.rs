@@ -0,0 +1,198 @@
+use super::error::WavParseError;
+use crate::config::ParsingMode;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+/// The container used by a WAV file
+///
+/// Standard WAV files are limited to 4 GiB, which the other containers exist to work around.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WavContainer {
+	/// A standard `RIFF` container
+	#[default]
+	Riff,
+	/// An `RF64` container (EBU Tech 3306), with 64-bit sizes stored in a `ds64` chunk
+	Rf64,
+	/// A `BW64` container (ITU-R BS.2088), with 64-bit sizes stored in a `ds64` chunk
+	Bw64,
+	/// A Sony Wave64 container, with GUID chunk identifiers and 64-bit sizes
+	Wave64,
+}
+
+impl WavContainer {
+	/// Whether the container stores its sizes in a `ds64` chunk
+	pub(crate) fn has_ds64(self) -> bool {
+		matches!(self, Self::Rf64 | Self::Bw64)
+	}
+
+	/// The size of the container header
+	///
+	/// This is `RIFF....WAVE` for all but Wave64, which uses GUIDs and a 64-bit size.
+	pub(crate) fn header_size(self) -> u64 {
+		match self {
+			Self::Wave64 => 40,
+			_ => 12,
+		}
+	}
+}
+
+// All Wave64 GUIDs other than `riff` and `list` are a FourCC followed by this suffix
+const WAVE64_GUID_SUFFIX: [u8; 12] = [
+	0xF3, 0xAC, 0xD3, 0x11, 0x8C, 0xD1, 0x00, 0xC0, 0x4F, 0x8E, 0xDB, 0x8A,
+];
+
+pub(crate) const WAVE64_RIFF_GUID: [u8; 16] = [
+	b'r', b'i', b'f', b'f', 0x2E, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00, 0x00,
+];
+pub(crate) const WAVE64_WAVE_GUID: [u8; 16] = fourcc_guid(*b"wave");
+const WAVE64_LIST_GUID: [u8; 16] = [
+	b'l', b'i', b's', b't', 0x2F, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00, 0x00,
+];
+
+/// The size of a Wave64 chunk header (a GUID and a 64-bit size)
+pub(crate) const WAVE64_CHUNK_HEADER_SIZE: u64 = 24;
+
+const fn fourcc_guid(fourcc: [u8; 4]) -> [u8; 16] {
+	let mut guid = [0; 16];
+	let mut i = 0;
+	while i < 4 {
+		guid[i] = fourcc[i];
+		i += 1;
+	}
+
+	while i < 16 {
+		guid[i] = WAVE64_GUID_SUFFIX[i - 4];
+		i += 1;
+	}
+
+	guid
+}
+
+/// Map a Wave64 chunk GUID to its equivalent RIFF FourCC
+///
+/// Unknown GUIDs are mapped to their first 4 bytes, which are unlikely to match anything.
+pub(crate) fn wave64_fourcc(guid: [u8; 16]) -> [u8; 4] {
+	if guid == WAVE64_LIST_GUID {
+		return *b"LIST";
+	}
+
+	[guid[0], guid[1], guid[2], guid[3]]
+}
+
+/// Map a RIFF FourCC to its equivalent Wave64 chunk GUID
+pub(crate) fn wave64_guid(fourcc: [u8; 4]) -> [u8; 16] {
+	if &fourcc == b"LIST" {
+		return WAVE64_LIST_GUID;
+	}
+
+	fourcc_guid(fourcc)
+}
+
+/// The 64-bit sizes from a `ds64` chunk
+#[derive(Debug, Default)]
+pub(crate) struct Ds64 {
+	pub(crate) riff_size: u64,
+	pub(crate) data_size: u64,
+	pub(crate) sample_count: u64,
+	/// The sizes of any other chunks larger than 4 GiB
+	pub(crate) table: Vec<([u8; 4], u64)>,
+}
+
+/// The size of the fixed portion of a `ds64` chunk
+pub(crate) const DS64_SIZE: usize = 28;
+
+impl Ds64 {
+	pub(crate) fn parse(content: &[u8], parse_mode: ParsingMode) -> Result<Self, WavParseError> {
+		if content.len() < DS64_SIZE {
+			return Err(WavParseError::message("ds64 chunk is too small"));
+		}
+
+		let reader = &mut &content[..];
+
+		let riff_size = reader.read_u64::<LittleEndian>()?;
+		let data_size = reader.read_u64::<LittleEndian>()?;
+		let sample_count = reader.read_u64::<LittleEndian>()?;
+		let table_length = reader.read_u32::<LittleEndian>()? as usize;
+
+		if table_length.saturating_mul(12) > reader.len() {
+			if parse_mode == ParsingMode::Strict {
+				return Err(WavParseError::message("ds64 chunk is too small"));
+			}
+
+			log::warn!("ds64 chunk is too small for its table, reading what's available");
+		}
+
+		let mut table = Vec::new();
+		for entry in reader.chunks_exact(12).take(table_length) {
+			let fourcc = [entry[0], entry[1], entry[2], entry[3]];
+			let size = u64::from_le_bytes(entry[4..].try_into().unwrap());
+			table.push((fourcc, size));
+		}
+
+		Ok(Self {
+			riff_size,
+			data_size,
+			sample_count,
+			table,
+		})
+	}
+
+	/// The 64-bit sizes of any chunks with a placeholder (`0xFFFFFFFF`) size
+	pub(crate) fn chunk_sizes(&self) -> Vec<([u8; 4], u64)> {
+		let mut sizes = vec![(*b"data", self.data_size)];
+		sizes.extend_from_slice(&self.table);
+		sizes
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Ds64, wave64_fourcc, wave64_guid};
+	use crate::config::ParsingMode;
+
+	#[test_log::test]
+	fn wave64_guids() {
+		for fourcc in [*b"fmt ", *b"data", *b"LIST", *b"bext"] {
+			assert_eq!(wave64_fourcc(wave64_guid(fourcc)), fourcc);
+		}
+
+		assert_eq!(
+			wave64_guid(*b"data"),
+			[
+				0x64, 0x61, 0x74, 0x61, 0xF3, 0xAC, 0xD3, 0x11, 0x8C, 0xD1, 0x00, 0xC0, 0x4F, 0x8E,
+				0xDB, 0x8A
+			]
+		);
+	}
+
+	#[test_log::test]
+	fn ds64() {
+		let mut content = Vec::new();
+		content.extend(5_000_000_000_u64.to_le_bytes());
+		content.extend(4_999_999_000_u64.to_le_bytes());
+		content.extend(1_249_999_750_u64.to_le_bytes());
+		content.extend(1_u32.to_le_bytes());
+		content.extend(b"JUNK");
+		content.extend(4_294_967_296_u64.to_le_bytes());
+
+		let ds64 = Ds64::parse(&content, ParsingMode::Strict).unwrap();
+		assert_eq!(ds64.riff_size, 5_000_000_000);
+		assert_eq!(ds64.sample_count, 1_249_999_750);
+		assert_eq!(
+			ds64.chunk_sizes(),
+			vec![(*b"data", 4_999_999_000), (*b"JUNK", 4_294_967_296)]
+		);
+
+		// Missing table entry
+		let truncated = &content[..content.len() - 1];
+		assert!(Ds64::parse(truncated, ParsingMode::Strict).is_err());
+		assert_eq!(
+			Ds64::parse(truncated, ParsingMode::BestAttempt)
+				.unwrap()
+				.table
+				.len(),
+			0
+		);
+	}
+}
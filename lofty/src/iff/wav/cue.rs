@@ -0,0 +1,526 @@
+use super::error::{WavEncodingError, WavParseError};
+use crate::config::ParsingMode;
+use crate::error::TooMuchDataError;
+use crate::iff::chunk::IFF_CHUNK_HEADER_SIZE;
+
+use std::io::{Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+const CUE_POINT_SIZE: usize = 24;
+const LABELED_TEXT_HEADER_SIZE: usize = 20;
+
+/// A labeled region of audio, from an `ltxt` entry in a `LIST/adtl` chunk
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CueRegion {
+	/// The length of the region, in samples
+	pub sample_length: u32,
+	/// The purpose of the region (e.g. `rgn `)
+	pub purpose: [u8; 4],
+	/// The country code
+	pub country: u16,
+	/// The language code
+	pub language: u16,
+	/// The dialect code
+	pub dialect: u16,
+	/// The code page of `text`
+	pub code_page: u16,
+	/// A description of the region
+	pub text: String,
+}
+
+impl CueRegion {
+	/// Create a new region of `sample_length` samples, with the `rgn ` purpose
+	pub fn new(sample_length: u32) -> Self {
+		Self {
+			sample_length,
+			purpose: *b"rgn ",
+			country: 0,
+			language: 0,
+			dialect: 0,
+			code_page: 0,
+			text: String::new(),
+		}
+	}
+}
+
+/// A marker in the audio, from the `cue ` chunk
+///
+/// The label, note, and regions are stored in the `LIST/adtl` chunk, and are written along with
+/// the cue point.
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::iff::wav::{CuePoint, CueRegion};
+///
+/// // Mark a loop from sample 48000 to 96000
+/// let mut cue_point = CuePoint::new(1, 48000);
+/// cue_point.label = Some(String::from("Loop"));
+/// cue_point.regions.push(CueRegion::new(48000));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CuePoint {
+	/// The unique identifier of the cue point
+	pub id: u32,
+	/// The position of the cue point in the play order
+	///
+	/// This is typically the same as `sample_offset`.
+	pub position: u32,
+	/// The ID of the chunk containing the cue point
+	///
+	/// This is `data` for the vast majority of files, the alternative being `slnt` for files
+	/// with a `wavl` list.
+	pub chunk_id: [u8; 4],
+	/// The offset of the chunk containing the cue point, within a `wavl` list
+	///
+	/// This is `0` when `chunk_id` is `data`.
+	pub chunk_start: u32,
+	/// The offset of the block containing the cue point, for compressed audio
+	///
+	/// This is `0` for uncompressed audio.
+	pub block_start: u32,
+	/// The position of the cue point, in samples, from the start of the block at `block_start`
+	pub sample_offset: u32,
+	/// The label of the cue point (`labl`)
+	pub label: Option<String>,
+	/// A comment on the cue point (`note`)
+	pub note: Option<String>,
+	/// Regions starting at the cue point (`ltxt`)
+	pub regions: Vec<CueRegion>,
+}
+
+impl CuePoint {
+	/// Create a new `CuePoint` at `sample_offset`
+	pub fn new(id: u32, sample_offset: u32) -> Self {
+		Self {
+			id,
+			position: sample_offset,
+			chunk_id: *b"data",
+			chunk_start: 0,
+			block_start: 0,
+			sample_offset,
+			label: None,
+			note: None,
+			regions: Vec::new(),
+		}
+	}
+}
+
+/// Parse a `cue ` chunk
+pub(super) fn parse_cue(
+	content: &[u8],
+	parse_mode: ParsingMode,
+) -> Result<Vec<CuePoint>, WavParseError> {
+	let reader = &mut &content[..];
+
+	let count = reader.read_u32::<LittleEndian>()? as usize;
+	if count.saturating_mul(CUE_POINT_SIZE) > reader.len() {
+		if parse_mode == ParsingMode::Strict {
+			return Err(WavParseError::message("cue chunk is too small"));
+		}
+
+		log::warn!("cue chunk is too small for {count} cue points, reading what's available");
+	}
+
+	let count = count.min(reader.len() / CUE_POINT_SIZE);
+
+	let mut cue_points = Vec::with_capacity(count);
+	for _ in 0..count {
+		let id = reader.read_u32::<LittleEndian>()?;
+		let position = reader.read_u32::<LittleEndian>()?;
+
+		let mut chunk_id = [0; 4];
+		reader.read_exact(&mut chunk_id)?;
+
+		let chunk_start = reader.read_u32::<LittleEndian>()?;
+		let block_start = reader.read_u32::<LittleEndian>()?;
+		let sample_offset = reader.read_u32::<LittleEndian>()?;
+
+		cue_points.push(CuePoint {
+			id,
+			position,
+			chunk_id,
+			chunk_start,
+			block_start,
+			sample_offset,
+			label: None,
+			note: None,
+			regions: Vec::new(),
+		});
+	}
+
+	Ok(cue_points)
+}
+
+/// Associated data from a `LIST/adtl` chunk
+#[derive(Default)]
+pub(super) struct AssociatedData {
+	labels: Vec<(u32, String)>,
+	notes: Vec<(u32, String)>,
+	regions: Vec<(u32, CueRegion)>,
+	/// All sub-chunks, in their original order
+	///
+	/// After [`apply_associated_data`], this only contains those that aren't attached to a cue point
+	/// (e.g. `file`, or labels of missing cue points), which are kept as-is.
+	pub(super) other: Vec<([u8; 4], Vec<u8>)>,
+}
+
+/// Parse the content of a `LIST/adtl` chunk, after the list type
+pub(super) fn parse_adtl(
+	mut content: &[u8],
+	parse_mode: ParsingMode,
+) -> Result<AssociatedData, WavParseError> {
+	let mut data = AssociatedData::default();
+
+	while content.len() >= IFF_CHUNK_HEADER_SIZE as usize {
+		let mut fourcc = [0; 4];
+		content.read_exact(&mut fourcc)?;
+		let size = content.read_u32::<LittleEndian>()? as usize;
+
+		let Some((mut sub_chunk, rest)) = content.split_at_checked(size) else {
+			if parse_mode == ParsingMode::Strict {
+				return Err(WavParseError::message("adtl sub-chunk is too large"));
+			}
+
+			log::warn!("adtl sub-chunk is too large, discarding the rest of the list");
+			break;
+		};
+
+		// Sub-chunks are padded to even lengths
+		content = rest.get(size % 2..).unwrap_or_default();
+
+		let raw = sub_chunk;
+		match &fourcc {
+			b"labl" | b"note" if sub_chunk.len() >= 4 => {
+				let id = sub_chunk.read_u32::<LittleEndian>()?;
+				let text = read_text(sub_chunk);
+
+				if &fourcc == b"labl" {
+					data.labels.push((id, text));
+				} else {
+					data.notes.push((id, text));
+				}
+
+				data.other.push((fourcc, raw.to_vec()));
+			},
+			b"ltxt" if sub_chunk.len() >= LABELED_TEXT_HEADER_SIZE => {
+				let id = sub_chunk.read_u32::<LittleEndian>()?;
+				let sample_length = sub_chunk.read_u32::<LittleEndian>()?;
+
+				let mut purpose = [0; 4];
+				sub_chunk.read_exact(&mut purpose)?;
+
+				let region = CueRegion {
+					sample_length,
+					purpose,
+					country: sub_chunk.read_u16::<LittleEndian>()?,
+					language: sub_chunk.read_u16::<LittleEndian>()?,
+					dialect: sub_chunk.read_u16::<LittleEndian>()?,
+					code_page: sub_chunk.read_u16::<LittleEndian>()?,
+					text: read_text(sub_chunk),
+				};
+
+				data.regions.push((id, region));
+				data.other.push((fourcc, raw.to_vec()));
+			},
+			b"labl" | b"note" | b"ltxt" => {
+				if parse_mode == ParsingMode::Strict {
+					return Err(WavParseError::message("adtl sub-chunk is too small"));
+				}
+
+				log::warn!(
+					"adtl '{}' sub-chunk is too small, keeping it as-is",
+					fourcc.escape_ascii()
+				);
+				data.other.push((fourcc, raw.to_vec()));
+			},
+			_ => data.other.push((fourcc, sub_chunk.to_vec())),
+		}
+	}
+
+	Ok(data)
+}
+
+/// Attach the associated data to the cue points
+///
+/// Any entries referring to cue points that don't exist are left in [`AssociatedData::other`],
+/// so they can be written back as-is.
+pub(super) fn apply_associated_data(cue_points: &mut [CuePoint], data: &mut AssociatedData) {
+	for (id, label) in data.labels.drain(..) {
+		if let Some(cue_point) = find_cue_point(cue_points, id) {
+			cue_point.label = Some(label);
+		}
+	}
+
+	for (id, note) in data.notes.drain(..) {
+		if let Some(cue_point) = find_cue_point(cue_points, id) {
+			cue_point.note = Some(note);
+		}
+	}
+
+	for (id, region) in data.regions.drain(..) {
+		if let Some(cue_point) = find_cue_point(cue_points, id) {
+			cue_point.regions.push(region);
+		}
+	}
+
+	data.other
+		.retain(|(fourcc, sub_chunk)| !is_attached(cue_points, *fourcc, sub_chunk));
+}
+
+fn find_cue_point(cue_points: &mut [CuePoint], id: u32) -> Option<&mut CuePoint> {
+	let cue_point = cue_points.iter_mut().find(|cue_point| cue_point.id == id);
+	if cue_point.is_none() {
+		log::debug!("Found associated data for missing cue point {id}, keeping it as-is");
+	}
+
+	cue_point
+}
+
+// Whether the sub-chunk is the label, note, or region of one of `cue_points`
+//
+// Sub-chunks that are too small to be parsed never are.
+fn is_attached(cue_points: &[CuePoint], fourcc: [u8; 4], sub_chunk: &[u8]) -> bool {
+	let min_size = match &fourcc {
+		b"labl" | b"note" => 4,
+		b"ltxt" => LABELED_TEXT_HEADER_SIZE,
+		_ => return false,
+	};
+
+	let Some(id) = sub_chunk.first_chunk::<4>() else {
+		return false;
+	};
+
+	if sub_chunk.len() < min_size {
+		return false;
+	}
+
+	let id = u32::from_le_bytes(*id);
+	cue_points.iter().any(|cue_point| cue_point.id == id)
+}
+
+/// Create the `cue ` chunk
+pub(super) fn create_cue(cue_points: &[CuePoint]) -> Result<Vec<u8>, WavEncodingError> {
+	for (idx, cue_point) in cue_points.iter().enumerate() {
+		if cue_points[..idx]
+			.iter()
+			.any(|other| other.id == cue_point.id)
+		{
+			return Err(WavEncodingError::message("cue point IDs must be unique"));
+		}
+	}
+
+	let Ok(count) = u32::try_from(cue_points.len()) else {
+		return Err(TooMuchDataError.into());
+	};
+
+	let mut content = Vec::with_capacity(4 + cue_points.len() * CUE_POINT_SIZE);
+	content.write_u32::<LittleEndian>(count)?;
+
+	for cue_point in cue_points {
+		content.write_u32::<LittleEndian>(cue_point.id)?;
+		content.write_u32::<LittleEndian>(cue_point.position)?;
+		content.write_all(&cue_point.chunk_id)?;
+		content.write_u32::<LittleEndian>(cue_point.chunk_start)?;
+		content.write_u32::<LittleEndian>(cue_point.block_start)?;
+		content.write_u32::<LittleEndian>(cue_point.sample_offset)?;
+	}
+
+	Ok(content)
+}
+
+/// Create the content of the `LIST/adtl` chunk, after the list type
+///
+/// Entries in `other` that belong to one of `cue_points` are replaced by the cue point's own.
+///
+/// This will return `None` if there is nothing to write.
+pub(super) fn create_adtl(
+	cue_points: &[CuePoint],
+	other: &[([u8; 4], Vec<u8>)],
+) -> Result<Option<Vec<u8>>, WavEncodingError> {
+	let mut content = Vec::new();
+
+	for cue_point in cue_points {
+		if let Some(label) = &cue_point.label {
+			write_sub_chunk(&mut content, *b"labl", &text_sub_chunk(cue_point.id, label))?;
+		}
+
+		if let Some(note) = &cue_point.note {
+			write_sub_chunk(&mut content, *b"note", &text_sub_chunk(cue_point.id, note))?;
+		}
+
+		for region in &cue_point.regions {
+			write_sub_chunk(
+				&mut content,
+				*b"ltxt",
+				&region_sub_chunk(cue_point.id, region),
+			)?;
+		}
+	}
+
+	for (fourcc, sub_chunk) in other {
+		if is_attached(cue_points, *fourcc, sub_chunk) {
+			continue;
+		}
+
+		write_sub_chunk(&mut content, *fourcc, sub_chunk)?;
+	}
+
+	if content.is_empty() {
+		return Ok(None);
+	}
+
+	Ok(Some(content))
+}
+
+// The content of a `labl` or `note` sub-chunk
+fn text_sub_chunk(id: u32, text: &str) -> Vec<u8> {
+	let mut sub_chunk = Vec::with_capacity(4 + text.len() + 1);
+	sub_chunk.extend_from_slice(&id.to_le_bytes());
+	sub_chunk.extend_from_slice(text.as_bytes());
+	sub_chunk.push(0);
+	sub_chunk
+}
+
+// The content of an `ltxt` sub-chunk
+fn region_sub_chunk(id: u32, region: &CueRegion) -> Vec<u8> {
+	let mut sub_chunk = Vec::with_capacity(LABELED_TEXT_HEADER_SIZE + region.text.len() + 1);
+	sub_chunk.extend_from_slice(&id.to_le_bytes());
+	sub_chunk.extend_from_slice(&region.sample_length.to_le_bytes());
+	sub_chunk.extend_from_slice(&region.purpose);
+	sub_chunk.extend_from_slice(&region.country.to_le_bytes());
+	sub_chunk.extend_from_slice(&region.language.to_le_bytes());
+	sub_chunk.extend_from_slice(&region.dialect.to_le_bytes());
+	sub_chunk.extend_from_slice(&region.code_page.to_le_bytes());
+	if !region.text.is_empty() {
+		sub_chunk.extend_from_slice(region.text.as_bytes());
+		sub_chunk.push(0);
+	}
+
+	sub_chunk
+}
+
+fn write_sub_chunk(
+	content: &mut Vec<u8>,
+	fourcc: [u8; 4],
+	sub_chunk: &[u8],
+) -> Result<(), WavEncodingError> {
+	let Ok(size) = u32::try_from(sub_chunk.len()) else {
+		return Err(TooMuchDataError.into());
+	};
+
+	content.write_all(&fourcc)?;
+	content.write_u32::<LittleEndian>(size)?;
+	content.write_all(sub_chunk)?;
+	if !size.is_multiple_of(2) {
+		content.push(0);
+	}
+
+	Ok(())
+}
+
+// Text in `adtl` chunks has no specified encoding, and is null terminated
+fn read_text(content: &[u8]) -> String {
+	let end = content
+		.iter()
+		.position(|b| *b == 0)
+		.unwrap_or(content.len());
+
+	String::from_utf8_lossy(&content[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		CuePoint, CueRegion, apply_associated_data, create_adtl, create_cue, parse_adtl, parse_cue,
+	};
+	use crate::config::ParsingMode;
+
+	#[test_log::test]
+	fn round_trip() {
+		let mut first = CuePoint::new(1, 0);
+		first.label = Some(String::from("Start"));
+		first.note = Some(String::from("Odd length"));
+
+		let mut second = CuePoint::new(2, 48000);
+		second.chunk_id = *b"slnt";
+		second.chunk_start = 12;
+		second.block_start = 34;
+		second.label = Some(String::from("Loop"));
+		second.regions.push(CueRegion {
+			text: String::from("Loop region"),
+			..CueRegion::new(24000)
+		});
+
+		let cue_points = vec![first, second];
+		let other = vec![(*b"file", vec![1, 2, 3])];
+
+		let cue = create_cue(&cue_points).unwrap();
+		let adtl = create_adtl(&cue_points, &other).unwrap().unwrap();
+
+		let mut parsed = parse_cue(&cue, ParsingMode::Strict).unwrap();
+		let mut data = parse_adtl(&adtl, ParsingMode::Strict).unwrap();
+		apply_associated_data(&mut parsed, &mut data);
+
+		assert_eq!(parsed, cue_points);
+		assert_eq!(data.other, other);
+	}
+
+	#[test_log::test]
+	fn missing_cue_point() {
+		let cue_points = vec![CuePoint {
+			label: Some(String::from("Orphan")),
+			..CuePoint::new(5, 100)
+		}];
+
+		let adtl = create_adtl(&cue_points, &[]).unwrap().unwrap();
+		let mut data = parse_adtl(&adtl, ParsingMode::Strict).unwrap();
+
+		let mut parsed = vec![CuePoint::new(1, 100)];
+		apply_associated_data(&mut parsed, &mut data);
+
+		// The label is kept around
+		assert_eq!(parsed, vec![CuePoint::new(1, 100)]);
+		assert_eq!(
+			create_adtl(&parsed, &data.other).unwrap(),
+			Some(adtl.clone())
+		);
+
+		// Until a cue point with the same ID is written
+		let mut replacement = CuePoint::new(5, 100);
+		replacement.label = Some(String::from("Replacement"));
+
+		let replacement_adtl = create_adtl(&[replacement.clone()], &data.other)
+			.unwrap()
+			.unwrap();
+		let mut data = parse_adtl(&replacement_adtl, ParsingMode::Strict).unwrap();
+		let mut parsed = vec![CuePoint::new(5, 100)];
+		apply_associated_data(&mut parsed, &mut data);
+
+		assert_eq!(parsed, vec![replacement]);
+		assert!(data.other.is_empty());
+	}
+
+	#[test_log::test]
+	fn undersized_sub_chunks_kept() {
+		// A label with no ID, and a region with nothing but an ID
+		let mut adtl = Vec::new();
+		adtl.extend(b"labl\x02\0\0\0ab");
+		adtl.extend(b"ltxt\x04\0\0\0\x01\0\0\0");
+
+		assert!(parse_adtl(&adtl, ParsingMode::Strict).is_err());
+
+		let mut data = parse_adtl(&adtl, ParsingMode::BestAttempt).unwrap();
+		let mut parsed = vec![CuePoint::new(1, 0)];
+		apply_associated_data(&mut parsed, &mut data);
+
+		assert_eq!(parsed, vec![CuePoint::new(1, 0)]);
+		assert_eq!(create_adtl(&parsed, &data.other).unwrap(), Some(adtl));
+	}
+
+	#[test_log::test]
+	fn duplicate_ids() {
+		assert!(create_cue(&[CuePoint::new(1, 0), CuePoint::new(1, 10)]).is_err());
+	}
+}
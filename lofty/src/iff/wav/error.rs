@@ -1,6 +1,6 @@
 //! WAV file/tag error types
 
-use crate::error::FileParseError;
+use crate::error::{FileEncodingError, FileParseError};
 use crate::file::FileType;
 
 use lofty_attr::LoftyError;
@@ -34,3 +34,29 @@ impl From<WavParseError> for FileParseError {
 		Self::new(FileType::Wav, input.source)
 	}
 }
+
+/// Internal concrete variant of [`FileEncodingError`] for conversions
+#[derive(LoftyError)]
+#[error(message = "failed to write to WAV file")]
+pub(super) struct WavEncodingError {
+	#[error(from(
+		std::io::Error,
+		crate::error::TooMuchDataError,
+		crate::error::SizeMismatchError,
+	))]
+	source: Box<dyn core::error::Error + Send + Sync + 'static>,
+}
+
+impl WavEncodingError {
+	pub(super) fn message(message: &'static str) -> Self {
+		Self {
+			source: message.into(),
+		}
+	}
+}
+
+impl From<WavEncodingError> for FileEncodingError {
+	fn from(input: WavEncodingError) -> FileEncodingError {
+		Self::new(FileType::Wav, input.source)
+	}
+}
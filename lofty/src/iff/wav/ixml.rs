@@ -0,0 +1,424 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+const ROOT_ELEMENT: &str = "BWFXML";
+
+const EMPTY_DOCUMENT_PREFIX: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<BWFXML>\n";
+
+/// An `iXML` chunk
+///
+/// This is commonly written by field recorders, and holds production information such as the
+/// scene, take, and tape.
+///
+/// The raw XML is preserved as-is, with [`Ixml::get`] and [`Ixml::set`] available to access the
+/// simple elements directly under the `BWFXML` root. Nested elements, such as `SPEED` or `TRACK_LIST`,
+/// are only available through [`Ixml::as_str`].
+///
+/// See <http://www.gallery.co.uk/ixml/>
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::iff::wav::Ixml;
+///
+/// let mut ixml = Ixml::new();
+/// ixml.set("SCENE", "12A");
+/// ixml.set("TAKE", "3");
+///
+/// assert_eq!(ixml.scene().as_deref(), Some("12A"));
+/// assert_eq!(ixml.take().as_deref(), Some("3"));
+/// assert!(ixml.as_str().contains("<SCENE>12A</SCENE>"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ixml {
+	xml: String,
+}
+
+impl Default for Ixml {
+	fn default() -> Self {
+		Self {
+			xml: format!("{EMPTY_DOCUMENT_PREFIX}</BWFXML>\n"),
+		}
+	}
+}
+
+impl Ixml {
+	/// Create a new `Ixml` with an empty `BWFXML` document
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create an `Ixml` from a raw XML document
+	///
+	/// The document is not validated.
+	pub fn from_xml(xml: String) -> Self {
+		Self { xml }
+	}
+
+	/// The raw XML document
+	pub fn as_str(&self) -> &str {
+		&self.xml
+	}
+
+	/// Consume the `Ixml`, returning the raw XML document
+	pub fn into_xml(self) -> String {
+		self.xml
+	}
+
+	/// Get the text of an element directly under the `BWFXML` root
+	///
+	/// This will return `None` if the element doesn't exist, or contains other elements.
+	pub fn get(&self, element: &str) -> Option<String> {
+		let found = find_element(&self.xml, element)?;
+		let content = &self.xml[found.content];
+		if content.contains('<') {
+			return None;
+		}
+
+		Some(unescape(content.trim()).into_owned())
+	}
+
+	/// Set the text of an element directly under the `BWFXML` root
+	///
+	/// This will replace the first element with the same name, or create a new one. If the document
+	/// has no `BWFXML` root, it will be replaced with an empty document first.
+	pub fn set(&mut self, element: &str, value: &str) {
+		let new_element = format!("<{element}>{}</{element}>", escape(value));
+
+		if let Some(found) = find_element(&self.xml, element) {
+			self.xml.replace_range(found.element, &new_element);
+			return;
+		}
+
+		let Some(root_end) = find_root_end(&self.xml) else {
+			log::warn!("iXML document has no BWFXML root, replacing it");
+			self.xml = format!("{EMPTY_DOCUMENT_PREFIX}\t{new_element}\n</BWFXML>\n");
+			return;
+		};
+
+		self.xml.insert_str(root_end, &format!("\t{new_element}\n"));
+	}
+
+	/// Remove an element directly under the `BWFXML` root, returning its text
+	pub fn remove(&mut self, element: &str) -> Option<String> {
+		let value = self.get(element);
+
+		let found = find_element(&self.xml, element)?;
+		self.xml.replace_range(found.element, "");
+
+		value
+	}
+
+	/// The project name (`PROJECT`)
+	pub fn project(&self) -> Option<String> {
+		self.get("PROJECT")
+	}
+
+	/// The scene name (`SCENE`)
+	pub fn scene(&self) -> Option<String> {
+		self.get("SCENE")
+	}
+
+	/// The take name (`TAKE`)
+	pub fn take(&self) -> Option<String> {
+		self.get("TAKE")
+	}
+
+	/// The tape or sound roll name (`TAPE`)
+	pub fn tape(&self) -> Option<String> {
+		self.get("TAPE")
+	}
+
+	/// Free text notes (`NOTE`)
+	pub fn note(&self) -> Option<String> {
+		self.get("NOTE")
+	}
+
+	/// Whether the take is circled (`CIRCLED`)
+	pub fn circled(&self) -> Option<bool> {
+		match self.get("CIRCLED")?.as_str() {
+			"TRUE" | "true" => Some(true),
+			"FALSE" | "false" => Some(false),
+			_ => None,
+		}
+	}
+
+	/// The unique identifier of the file (`FILE_UID`)
+	pub fn file_uid(&self) -> Option<String> {
+		self.get("FILE_UID")
+	}
+}
+
+struct FoundElement {
+	// The entire element, including its tags
+	element: Range<usize>,
+	content: Range<usize>,
+}
+
+// A tag in the document, with the position of its closing `>`
+struct XmlTag<'a> {
+	name: &'a str,
+	start: usize,
+	end: usize,
+	kind: TagKind,
+}
+
+#[derive(PartialEq)]
+enum TagKind {
+	Open,
+	Close,
+	Empty,
+}
+
+// Iterates over the element tags in the document, skipping declarations, comments, and CDATA
+fn tags(xml: &str) -> impl Iterator<Item = XmlTag<'_>> {
+	let mut pos = 0;
+	std::iter::from_fn(move || {
+		loop {
+			let start = pos + xml[pos..].find('<')?;
+			let rest = &xml[start..];
+
+			let skip_to = if rest.starts_with("<!--") {
+				Some("-->")
+			} else if rest.starts_with("<![CDATA[") {
+				Some("]]>")
+			} else if rest.starts_with("<?") {
+				Some("?>")
+			} else if rest.starts_with("<!") {
+				Some(">")
+			} else {
+				None
+			};
+
+			if let Some(terminator) = skip_to {
+				pos = start + rest.find(terminator)? + terminator.len();
+				continue;
+			}
+
+			let end = start + rest.find('>')?;
+			pos = end + 1;
+
+			let mut inner = &xml[start + 1..end];
+			let mut kind = TagKind::Open;
+			if let Some(name) = inner.strip_prefix('/') {
+				inner = name;
+				kind = TagKind::Close;
+			} else if let Some(name) = inner.strip_suffix('/') {
+				inner = name;
+				kind = TagKind::Empty;
+			}
+
+			let name = inner
+				.split(|c: char| c.is_ascii_whitespace())
+				.next()
+				.unwrap_or_default();
+
+			return Some(XmlTag {
+				name,
+				start,
+				end,
+				kind,
+			});
+		}
+	})
+}
+
+fn find_element(xml: &str, element: &str) -> Option<FoundElement> {
+	let mut depth = 0usize;
+	let mut in_root = false;
+	let mut open: Option<XmlTag<'_>> = None;
+
+	for tag in tags(xml) {
+		match tag.kind {
+			TagKind::Open => {
+				if depth == 0 && tag.name == ROOT_ELEMENT {
+					in_root = true;
+				} else if depth == 1 && in_root && tag.name == element && open.is_none() {
+					open = Some(tag);
+					depth += 1;
+					continue;
+				}
+
+				depth += 1;
+			},
+			TagKind::Close => {
+				depth = depth.saturating_sub(1);
+
+				if depth == 1
+					&& let Some(open) = open.take_if(|open| open.name == tag.name)
+				{
+					return Some(FoundElement {
+						element: open.start..tag.end + 1,
+						content: open.end + 1..tag.start,
+					});
+				}
+
+				if depth == 0 {
+					in_root = false;
+				}
+			},
+			TagKind::Empty => {
+				if depth == 1 && in_root && tag.name == element && open.is_none() {
+					return Some(FoundElement {
+						element: tag.start..tag.end + 1,
+						content: tag.end + 1..tag.end + 1,
+					});
+				}
+			},
+		}
+	}
+
+	None
+}
+
+// The position of the `BWFXML` closing tag
+fn find_root_end(xml: &str) -> Option<usize> {
+	let mut depth = 0usize;
+	for tag in tags(xml) {
+		match tag.kind {
+			TagKind::Open => depth += 1,
+			TagKind::Close => {
+				depth = depth.saturating_sub(1);
+				if depth == 0 && tag.name == ROOT_ELEMENT {
+					return Some(tag.start);
+				}
+			},
+			TagKind::Empty => {},
+		}
+	}
+
+	None
+}
+
+fn escape(value: &str) -> Cow<'_, str> {
+	if !value.contains(['&', '<', '>', '"', '\'']) {
+		return Cow::Borrowed(value);
+	}
+
+	let mut escaped = String::with_capacity(value.len());
+	for c in value.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			_ => escaped.push(c),
+		}
+	}
+
+	Cow::Owned(escaped)
+}
+
+fn unescape(value: &str) -> Cow<'_, str> {
+	if !value.contains('&') {
+		return Cow::Borrowed(value);
+	}
+
+	let mut unescaped = String::with_capacity(value.len());
+	let mut rest = value;
+	while let Some(pos) = rest.find('&') {
+		unescaped.push_str(&rest[..pos]);
+		rest = &rest[pos..];
+
+		let Some(end) = rest.find(';') else {
+			break;
+		};
+
+		let entity = &rest[1..end];
+		let c = match entity {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			_ => entity
+				.strip_prefix("#x")
+				.and_then(|hex| u32::from_str_radix(hex, 16).ok())
+				.or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+				.and_then(char::from_u32),
+		};
+
+		match c {
+			Some(c) => {
+				unescaped.push(c);
+				rest = &rest[end + 1..];
+			},
+			// Not an entity we know, leave it alone
+			None => {
+				unescaped.push('&');
+				rest = &rest[1..];
+			},
+		}
+	}
+
+	unescaped.push_str(rest);
+	Cow::Owned(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Ixml;
+
+	const DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Written by a field recorder -->
+<BWFXML>
+	<IXML_VERSION>2.10</IXML_VERSION>
+	<PROJECT>Feature &amp; Friends</PROJECT>
+	<SCENE>12A</SCENE>
+	<TAKE>3</TAKE>
+	<CIRCLED>TRUE</CIRCLED>
+	<NOTE/>
+	<SPEED>
+		<NOTE>nested</NOTE>
+		<MASTER_SPEED>24/1</MASTER_SPEED>
+	</SPEED>
+</BWFXML>
+"#;
+
+	#[test_log::test]
+	fn get() {
+		let ixml = Ixml::from_xml(String::from(DOCUMENT));
+
+		assert_eq!(ixml.project().as_deref(), Some("Feature & Friends"));
+		assert_eq!(ixml.scene().as_deref(), Some("12A"));
+		assert_eq!(ixml.take().as_deref(), Some("3"));
+		assert_eq!(ixml.circled(), Some(true));
+		assert_eq!(ixml.note().as_deref(), Some(""));
+		assert!(ixml.tape().is_none());
+
+		// Only direct children of the root are accessible
+		assert!(ixml.get("MASTER_SPEED").is_none());
+		assert!(ixml.get("SPEED").is_none());
+	}
+
+	#[test_log::test]
+	fn set_and_remove() {
+		let mut ixml = Ixml::from_xml(String::from(DOCUMENT));
+
+		ixml.set("SCENE", "14 <B>");
+		ixml.set("TAPE", "Day 2");
+		ixml.set("NOTE", "Plane overhead");
+		assert_eq!(ixml.scene().as_deref(), Some("14 <B>"));
+		assert_eq!(ixml.tape().as_deref(), Some("Day 2"));
+		assert_eq!(ixml.note().as_deref(), Some("Plane overhead"));
+		assert!(ixml.as_str().contains("<SCENE>14 &lt;B&gt;</SCENE>"));
+
+		// Nested elements are untouched
+		assert!(ixml.as_str().contains("<NOTE>nested</NOTE>"));
+
+		assert_eq!(ixml.remove("TAKE").as_deref(), Some("3"));
+		assert!(ixml.take().is_none());
+		assert!(ixml.remove("TAKE").is_none());
+
+		assert!(ixml.as_str().contains("<MASTER_SPEED>24/1</MASTER_SPEED>"));
+	}
+
+	#[test_log::test]
+	fn set_without_root() {
+		let mut ixml = Ixml::from_xml(String::from("not xml"));
+		ixml.set("SCENE", "1");
+
+		assert_eq!(ixml.scene().as_deref(), Some("1"));
+	}
+}
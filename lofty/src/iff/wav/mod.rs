@@ -1,21 +1,49 @@
 //! WAV specific items
 
+mod bext;
+pub(crate) mod container;
+mod cue;
 pub mod error;
+mod ixml;
 mod properties;
 pub(crate) mod read;
+mod sampler;
 pub(crate) mod tag;
 
+use crate::config::WriteOptions;
+use crate::error::FileEncodingError;
 use crate::id3::v2::tag::Id3v2Tag;
+use crate::iff::chunks::{self, ChunkId, ChunkUpdate};
+use crate::io::VerifiedFile;
+use crate::tag::TagExt;
+use crate::util::io::FileLike;
 
 use lofty_attr::LoftyFile;
 
 // Exports
 pub use crate::iff::wav::properties::{WavFormat, WavProperties};
+pub use bext::{BextChunk, BextLoudness};
+pub use container::WavContainer;
+pub use cue::{CuePoint, CueRegion};
+pub use ixml::Ixml;
+pub use sampler::{AcidChunk, InstrumentChunk, SampleLoop, SampleLoopType, SamplerChunk};
 pub use tag::RiffInfoList;
 
 /// A WAV file
+///
+/// ## Notes
+///
+/// * The `bext`, `iXML`, `axml`, `cue `, `LIST/adtl`, `smpl`, `inst`, and `acid` chunks are only written when saving the `WavFile` itself. Writing a
+///   [`Tag`](crate::tag::Tag) to the file will leave them untouched.
+/// * Chunks removed in memory (for example, with [`WavFile::remove_bext`]) will be removed from the file on the
+///   next call to [`AudioFile::save_to`](crate::file::AudioFile::save_to).
+/// * Chunks that failed to parse (outside of [`ParsingMode::Strict`](crate::config::ParsingMode::Strict))
+///   are left untouched, unless they are replaced.
+/// * The `iXML`, `axml`, `cue `, and `LIST/adtl` chunks are left untouched unless they were changed, as
+///   re-encoding them isn't always lossless.
 #[derive(LoftyFile)]
 #[lofty(read_fn = "read::read_from")]
+#[lofty(write_fn = "Self::write_to")]
 #[lofty(internal_write_module_do_not_use_anywhere_else)]
 pub struct WavFile {
 	/// A RIFF INFO LIST
@@ -24,6 +52,384 @@ pub struct WavFile {
 	/// An ID3v2 tag
 	#[lofty(tag_type = "Id3v2")]
 	pub(crate) id3v2_tag: Option<Id3v2Tag>,
+	pub(crate) bext: Option<BextChunk>,
+	// The `bext` chunk as it was read, along with its content, to keep the unchanged fields as-is
+	pub(crate) original_bext: Option<(BextChunk, Vec<u8>)>,
+	pub(crate) ixml: Option<Ixml>,
+	pub(crate) axml: Option<String>,
+	// The XML chunks as they were read, unchanged chunks are left as-is
+	pub(crate) original_ixml: Option<Ixml>,
+	pub(crate) original_axml: Option<String>,
+	pub(crate) cue_points: Vec<CuePoint>,
+	// The cue points as they were read, the `cue ` and `LIST/adtl` chunks are left as-is if unchanged
+	pub(crate) original_cue_points: Vec<CuePoint>,
+	// `LIST/adtl` sub-chunks that aren't attached to a cue point, rewritten as-is
+	pub(crate) other_adtl_chunks: Vec<([u8; 4], Vec<u8>)>,
+	pub(crate) sampler: Option<SamplerChunk>,
+	pub(crate) instrument: Option<InstrumentChunk>,
+	pub(crate) acid: Option<AcidChunk>,
+	// Chunks that failed to parse, kept as-is when writing
+	pub(crate) unparsed_chunks: Vec<ChunkId>,
+	pub(crate) container: WavContainer,
 	/// The file's audio properties
 	pub(crate) properties: WavProperties,
 }
+
+impl WavFile {
+	fn write_to<F>(
+		&self,
+		file: &mut F,
+		write_options: WriteOptions,
+	) -> Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		if let Some(ref riff_info) = self.riff_info_tag {
+			file.rewind()?;
+			riff_info.save_to(file, write_options)?;
+		}
+
+		if let Some(ref id3v2) = self.id3v2_tag {
+			file.rewind()?;
+			id3v2.save_to(file, write_options)?;
+		}
+
+		let bext = match &self.bext {
+			Some(bext) => Some(bext.as_bytes(self.original_bext.as_ref())?),
+			None => None,
+		};
+
+		let cue = if self.cue_points.is_empty() {
+			None
+		} else {
+			Some(cue::create_cue(&self.cue_points)?)
+		};
+
+		// Sub-chunks that don't belong to a cue point are kept, even with no cue points
+		let adtl = cue::create_adtl(&self.cue_points, &self.other_adtl_chunks)?;
+
+		let sampler = match &self.sampler {
+			Some(sampler) => Some(sampler.as_bytes()?),
+			None => None,
+		};
+
+		let acid = match &self.acid {
+			Some(acid) => Some(acid.as_bytes()?),
+			None => None,
+		};
+
+		let mut updates = vec![
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"bext"),
+				content: bext,
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"iXML"),
+				content: self
+					.ixml
+					.as_ref()
+					.map(|ixml| ixml.as_str().as_bytes().to_vec()),
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"axml"),
+				content: self.axml.as_ref().map(|axml| axml.as_bytes().to_vec()),
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"cue "),
+				content: cue,
+			},
+			ChunkUpdate {
+				id: ChunkId::List(*b"adtl"),
+				content: adtl,
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"smpl"),
+				content: sampler,
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"inst"),
+				content: self.instrument.map(InstrumentChunk::as_bytes),
+			},
+			ChunkUpdate {
+				id: ChunkId::Chunk(*b"acid"),
+				content: acid,
+			},
+		];
+		chunks::retain_unparsed(&mut updates, &self.unparsed_chunks);
+
+		let mut unchanged = Vec::new();
+		if self.ixml == self.original_ixml {
+			unchanged.push(ChunkId::Chunk(*b"iXML"));
+		}
+		if self.axml == self.original_axml {
+			unchanged.push(ChunkId::Chunk(*b"axml"));
+		}
+		if self.cue_points == self.original_cue_points {
+			unchanged.push(ChunkId::Chunk(*b"cue "));
+			unchanged.push(ChunkId::List(*b"adtl"));
+		}
+		chunks::retain_changed(&mut updates, &unchanged);
+
+		file.rewind()?;
+		chunks::write_chunks(VerifiedFile::new(file)?, &updates, write_options)
+	}
+
+	/// The container of the file
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::iff::wav::{WavContainer, WavFile};
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::File::open(path)?;
+	/// let wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// if wav.container() != WavContainer::Riff {
+	/// 	println!("File may be larger than 4 GiB");
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn container(&self) -> WavContainer {
+		self.container
+	}
+
+	/// Returns a reference to the Broadcast Wave Format `bext` chunk, if it exists
+	pub fn bext(&self) -> Option<&BextChunk> {
+		self.bext.as_ref()
+	}
+
+	/// Returns a mutable reference to the `bext` chunk, if it exists
+	pub fn bext_mut(&mut self) -> Option<&mut BextChunk> {
+		self.bext.as_mut()
+	}
+
+	/// Sets the `bext` chunk, returning the old one
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::{ParseOptions, WriteOptions};
+	/// use lofty::iff::wav::{BextChunk, WavFile};
+	/// use lofty::prelude::*;
+	/// use std::io::Seek;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+	/// let mut wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// let mut bext = BextChunk::new();
+	/// bext.description = String::from("Morning news");
+	/// bext.originator = String::from("Studio A");
+	/// wav.set_bext(bext);
+	///
+	/// file.rewind()?;
+	/// wav.save_to(&mut file, WriteOptions::default())?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_bext(&mut self, bext: BextChunk) -> Option<BextChunk> {
+		self.bext.replace(bext)
+	}
+
+	/// Removes the `bext` chunk, in memory
+	pub fn remove_bext(&mut self) -> Option<BextChunk> {
+		self.bext.take()
+	}
+
+	/// Returns a reference to the `iXML` chunk, if it exists
+	pub fn ixml(&self) -> Option<&Ixml> {
+		self.ixml.as_ref()
+	}
+
+	/// Returns a mutable reference to the `iXML` chunk, if it exists
+	pub fn ixml_mut(&mut self) -> Option<&mut Ixml> {
+		self.ixml.as_mut()
+	}
+
+	/// Sets the `iXML` chunk, returning the old one
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::{ParseOptions, WriteOptions};
+	/// use lofty::iff::wav::{Ixml, WavFile};
+	/// use lofty::prelude::*;
+	/// use std::io::Seek;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+	/// let mut wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// // Keep the existing document if there is one
+	/// let mut ixml = wav.remove_ixml().unwrap_or_default();
+	/// ixml.set("SCENE", "12A");
+	/// ixml.set("TAKE", "3");
+	/// wav.set_ixml(ixml);
+	///
+	/// file.rewind()?;
+	/// wav.save_to(&mut file, WriteOptions::default())?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_ixml(&mut self, ixml: Ixml) -> Option<Ixml> {
+		self.ixml.replace(ixml)
+	}
+
+	/// Removes the `iXML` chunk, in memory
+	pub fn remove_ixml(&mut self) -> Option<Ixml> {
+		self.ixml.take()
+	}
+
+	/// Returns the raw XML of the `axml` chunk, if it exists
+	///
+	/// This is typically an EBU Core (EBU Tech 3293) document.
+	pub fn axml(&self) -> Option<&str> {
+		self.axml.as_deref()
+	}
+
+	/// Sets the raw XML of the `axml` chunk, returning the old one
+	pub fn set_axml(&mut self, axml: String) -> Option<String> {
+		self.axml.replace(axml)
+	}
+
+	/// Removes the `axml` chunk, in memory
+	pub fn remove_axml(&mut self) -> Option<String> {
+		self.axml.take()
+	}
+
+	/// Returns the cue points, with their labels, notes, and regions
+	pub fn cue_points(&self) -> &[CuePoint] {
+		&self.cue_points
+	}
+
+	/// Returns a mutable reference to the cue points
+	pub fn cue_points_mut(&mut self) -> &mut Vec<CuePoint> {
+		&mut self.cue_points
+	}
+
+	/// Sets the cue points, returning the old ones
+	///
+	/// Cue point IDs must be unique, otherwise writing will fail.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::{ParseOptions, WriteOptions};
+	/// use lofty::iff::wav::{CuePoint, WavFile};
+	/// use lofty::prelude::*;
+	/// use std::io::Seek;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+	/// let mut wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// let mut intro = CuePoint::new(1, 0);
+	/// intro.label = Some(String::from("Intro"));
+	///
+	/// let mut chorus = CuePoint::new(2, 44100 * 30);
+	/// chorus.label = Some(String::from("Chorus"));
+	///
+	/// wav.set_cue_points(vec![intro, chorus]);
+	///
+	/// file.rewind()?;
+	/// wav.save_to(&mut file, WriteOptions::default())?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_cue_points(&mut self, cue_points: Vec<CuePoint>) -> Vec<CuePoint> {
+		std::mem::replace(&mut self.cue_points, cue_points)
+	}
+
+	/// Removes all cue points, in memory
+	///
+	/// Only the labels, notes, and regions of the cue points are removed from the `LIST/adtl` chunk.
+	/// Any other associated data is left as-is.
+	pub fn remove_cue_points(&mut self) -> Vec<CuePoint> {
+		std::mem::take(&mut self.cue_points)
+	}
+
+	/// Returns a reference to the sampler `smpl` chunk, if it exists
+	pub fn sampler(&self) -> Option<&SamplerChunk> {
+		self.sampler.as_ref()
+	}
+
+	/// Returns a mutable reference to the `smpl` chunk, if it exists
+	pub fn sampler_mut(&mut self) -> Option<&mut SamplerChunk> {
+		self.sampler.as_mut()
+	}
+
+	/// Sets the `smpl` chunk, returning the old one
+	pub fn set_sampler(&mut self, sampler: SamplerChunk) -> Option<SamplerChunk> {
+		self.sampler.replace(sampler)
+	}
+
+	/// Removes the `smpl` chunk, in memory
+	pub fn remove_sampler(&mut self) -> Option<SamplerChunk> {
+		self.sampler.take()
+	}
+
+	/// Returns the instrument `inst` chunk, if it exists
+	pub fn instrument(&self) -> Option<InstrumentChunk> {
+		self.instrument
+	}
+
+	/// Returns a mutable reference to the `inst` chunk, if it exists
+	pub fn instrument_mut(&mut self) -> Option<&mut InstrumentChunk> {
+		self.instrument.as_mut()
+	}
+
+	/// Sets the `inst` chunk, returning the old one
+	pub fn set_instrument(&mut self, instrument: InstrumentChunk) -> Option<InstrumentChunk> {
+		self.instrument.replace(instrument)
+	}
+
+	/// Removes the `inst` chunk, in memory
+	pub fn remove_instrument(&mut self) -> Option<InstrumentChunk> {
+		self.instrument.take()
+	}
+
+	/// Returns the ACID loop `acid` chunk, if it exists
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::iff::wav::WavFile;
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::File::open(path)?;
+	/// let wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// if let Some(acid) = wav.acid() {
+	/// 	println!("Tempo: {} BPM", acid.tempo);
+	/// 	if let Some(root_note) = acid.root_note {
+	/// 		println!("Root note: {root_note}");
+	/// 	}
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn acid(&self) -> Option<AcidChunk> {
+		self.acid
+	}
+
+	/// Returns a mutable reference to the `acid` chunk, if it exists
+	pub fn acid_mut(&mut self) -> Option<&mut AcidChunk> {
+		self.acid.as_mut()
+	}
+
+	/// Sets the `acid` chunk, returning the old one
+	pub fn set_acid(&mut self, acid: AcidChunk) -> Option<AcidChunk> {
+		self.acid.replace(acid)
+	}
+
+	/// Removes the `acid` chunk, in memory
+	pub fn remove_acid(&mut self) -> Option<AcidChunk> {
+		self.acid.take()
+	}
+}
@@ -7,21 +7,81 @@ use std::time::Duration;
 use byteorder::{LittleEndian, ReadBytesExt};
 
 const PCM: u16 = 0x0001;
+const ADPCM: u16 = 0x0002;
 const IEEE_FLOAT: u16 = 0x0003;
+const ALAW: u16 = 0x0006;
+const MULAW: u16 = 0x0007;
+const IMA_ADPCM: u16 = 0x0011;
+const GSM610: u16 = 0x0031;
+const MPEG: u16 = 0x0050;
+const MPEGLAYER3: u16 = 0x0055;
 const EXTENSIBLE: u16 = 0xFFFE;
 
+// The sub-format GUIDs of WAVE_FORMAT_EXTENSIBLE are made up of a format tag, followed by
+// one of these suffixes
+const KSDATAFORMAT_SUFFIX: [u8; 14] = [
+	0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+const AMBISONIC_B_FORMAT_SUFFIX: [u8; 14] = [
+	0x00, 0x00, 0x21, 0x07, 0xD3, 0x11, 0x86, 0x44, 0xC8, 0xC1, 0xCA, 0x00, 0x00, 0x00,
+];
+
 /// A WAV file's format
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum WavFormat {
 	/// Pulse-code modulation (uncompressed)
 	PCM,
+	/// Microsoft ADPCM
+	ADPCM,
 	/// IEEE 754 floating-point
 	IEEE_FLOAT,
+	/// ITU-T G.711 A-law
+	ALAW,
+	/// ITU-T G.711 µ-law
+	MULAW,
+	/// IMA/DVI ADPCM
+	IMA_ADPCM,
+	/// GSM 06.10
+	GSM610,
+	/// MPEG-1 Audio Layer I/II
+	MPEG,
+	/// MPEG-1 Audio Layer III
+	MPEGLAYER3,
 	/// Other format identified by its format tag
+	///
+	/// For `WAVE_FORMAT_EXTENSIBLE` files with an unknown sub-format, this will be `0xFFFE`. See
+	/// [`WavProperties::sub_format`].
 	Other(u16),
 }
 
+impl WavFormat {
+	fn from_tag(tag: u16) -> Self {
+		match tag {
+			PCM => Self::PCM,
+			ADPCM => Self::ADPCM,
+			IEEE_FLOAT => Self::IEEE_FLOAT,
+			ALAW => Self::ALAW,
+			MULAW => Self::MULAW,
+			IMA_ADPCM => Self::IMA_ADPCM,
+			GSM610 => Self::GSM610,
+			MPEG => Self::MPEG,
+			MPEGLAYER3 => Self::MPEGLAYER3,
+			other => Self::Other(other),
+		}
+	}
+
+	// Formats with a constant number of bytes per sample, allowing the sample count to be
+	// calculated from the stream length
+	fn is_fixed_rate(self) -> bool {
+		matches!(
+			self,
+			Self::PCM | Self::IEEE_FLOAT | Self::ALAW | Self::MULAW
+		)
+	}
+}
+
 impl Default for WavFormat {
 	fn default() -> Self {
 		Self::Other(0)
@@ -40,6 +100,8 @@ pub struct WavProperties {
 	pub(crate) bit_depth: u8,
 	pub(crate) channels: u8,
 	pub(crate) channel_mask: Option<ChannelMask>,
+	pub(crate) block_align: u16,
+	pub(crate) sub_format: Option<[u8; 16]>,
 }
 
 impl From<WavProperties> for FileProperties {
@@ -53,6 +115,8 @@ impl From<WavProperties> for FileProperties {
 			channels,
 			channel_mask,
 			format: _,
+			block_align: _,
+			sub_format: _,
 		} = input;
 		Self {
 			duration,
@@ -88,6 +152,11 @@ impl WavProperties {
 	}
 
 	/// Bits per sample
+	///
+	/// For `WAVE_FORMAT_EXTENSIBLE` files, this is the number of valid bits, which may be less
+	/// than the size of the sample container (e.g. 20-bit audio stored in 24-bit samples).
+	///
+	/// This will be `0` for compressed formats that don't specify a bit depth.
 	pub fn bit_depth(&self) -> u8 {
 		self.bit_depth
 	}
@@ -103,15 +172,54 @@ impl WavProperties {
 	}
 
 	/// WAV format
+	///
+	/// For `WAVE_FORMAT_EXTENSIBLE` files, this is the format identified by the sub-format GUID.
 	pub fn format(&self) -> &WavFormat {
 		&self.format
 	}
+
+	/// The size of a block of audio, in bytes
+	///
+	/// For uncompressed formats, this is the size of a single sample frame (all channels). For
+	/// block-based compressed formats, such as ADPCM, this is the size of a compressed block.
+	pub fn block_align(&self) -> u16 {
+		self.block_align
+	}
+
+	/// The sub-format GUID of a `WAVE_FORMAT_EXTENSIBLE` file
+	///
+	/// This is in the byte order it's stored in the file (the first three fields are little-endian).
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::iff::wav::{WavFile, WavFormat};
+	/// use lofty::prelude::*;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/wav_format_pcm.wav";
+	/// let mut file = std::fs::File::open(path)?;
+	/// let wav = WavFile::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// let properties = wav.properties();
+	/// if let Some(guid) = properties.sub_format() {
+	/// 	if *properties.format() == WavFormat::Other(0xFFFE) {
+	/// 		println!("Unknown sub-format: {guid:02X?}");
+	/// 	}
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn sub_format(&self) -> Option<[u8; 16]> {
+		self.sub_format
+	}
 }
 
 #[derive(Copy, Clone, Debug)]
 struct ExtensibleFmtChunk {
 	valid_bits_per_sample: u16,
 	channel_mask: ChannelMask,
+	sub_format: [u8; 16],
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -122,6 +230,7 @@ struct FmtChunk {
 	bytes_per_second: u32,
 	block_align: u16,
 	bits_per_sample: u16,
+	samples_per_block: Option<u16>,
 	extensible_info: Option<ExtensibleFmtChunk>,
 }
 
@@ -143,30 +252,53 @@ where
 		bytes_per_second,
 		block_align,
 		bits_per_sample,
+		samples_per_block: None,
 		extensible_info: None,
 	};
 
-	if format_tag == EXTENSIBLE {
-		if len < 40 {
-			return Err(WavParseError::message(
-				"extensible format identified, invalid \"fmt \" chunk size found (< 40)",
-			));
-		}
+	match format_tag {
+		EXTENSIBLE => {
+			if len < 40 {
+				return Err(WavParseError::message(
+					"extensible format identified, invalid \"fmt \" chunk size found (< 40)",
+				));
+			}
 
-		// cbSize (Size of extra format information) (2)
-		let _cb_size = reader.read_u16::<LittleEndian>()?;
+			// cbSize (Size of extra format information) (2)
+			let _cb_size = reader.read_u16::<LittleEndian>()?;
 
-		// Valid bits per sample (2)
-		let valid_bits_per_sample = reader.read_u16::<LittleEndian>()?;
+			// Valid bits per sample (2)
+			let valid_bits_per_sample = reader.read_u16::<LittleEndian>()?;
 
-		// Channel mask (4)
-		let channel_mask = ChannelMask(reader.read_u32::<LittleEndian>()?);
+			// Channel mask (4)
+			let channel_mask = ChannelMask(reader.read_u32::<LittleEndian>()?);
 
-		fmt_chunk.format_tag = reader.read_u16::<LittleEndian>()?;
-		fmt_chunk.extensible_info = Some(ExtensibleFmtChunk {
-			valid_bits_per_sample,
-			channel_mask,
-		});
+			// Sub-format GUID (16)
+			let mut sub_format = [0; 16];
+			reader.read_exact(&mut sub_format)?;
+
+			// Standard sub-formats are just the format tag with a fixed suffix, anything else
+			// is left for the caller to identify
+			if sub_format[2..] == KSDATAFORMAT_SUFFIX
+				|| sub_format[2..] == AMBISONIC_B_FORMAT_SUFFIX
+			{
+				fmt_chunk.format_tag = u16::from_le_bytes([sub_format[0], sub_format[1]]);
+			}
+
+			fmt_chunk.extensible_info = Some(ExtensibleFmtChunk {
+				valid_bits_per_sample,
+				channel_mask,
+				sub_format,
+			});
+		},
+		// These formats store the number of samples in each block
+		ADPCM | IMA_ADPCM | GSM610 if len >= 20 => {
+			let cb_size = reader.read_u16::<LittleEndian>()?;
+			if cb_size >= 2 {
+				fmt_chunk.samples_per_block = Some(reader.read_u16::<LittleEndian>()?);
+			}
+		},
+		_ => {},
 	}
 
 	Ok(fmt_chunk)
@@ -174,8 +306,8 @@ where
 
 pub(super) fn read_properties(
 	fmt: &mut &[u8],
-	mut total_samples: u32,
-	stream_len: u32,
+	mut total_samples: u64,
+	stream_len: u64,
 	file_length: u64,
 ) -> Result<WavProperties, WavParseError> {
 	if fmt.len() < 16 {
@@ -197,6 +329,7 @@ pub(super) fn read_properties(
 		bytes_per_second,
 		block_align,
 		bits_per_sample,
+		samples_per_block,
 		extensible_info,
 	} = read_fmt_chunk(fmt, fmt.len())?;
 
@@ -204,7 +337,10 @@ pub(super) fn read_properties(
 		return Err(WavParseError::message("file contains 0 channels"));
 	}
 
-	if bits_per_sample % 8 != 0 {
+	let format = WavFormat::from_tag(format_tag);
+	let fixed_rate = format.is_fixed_rate();
+
+	if fixed_rate && bits_per_sample % 8 != 0 {
 		return Err(WavParseError::message(
 			"bits per sample is not a multiple of 8",
 		));
@@ -219,20 +355,30 @@ pub(super) fn read_properties(
 			..
 		}) if valid_bits_per_sample > 0 => bit_depth = valid_bits_per_sample as u8,
 		_ if bits_per_sample > 0 => bit_depth = bits_per_sample as u8,
-		_ => bit_depth = bytes_per_sample.saturating_mul(8) as u8,
+		_ if fixed_rate => bit_depth = bytes_per_sample.saturating_mul(8) as u8,
+		_ => bit_depth = 0,
 	}
 
 	let channel_mask = extensible_info.map(|info| info.channel_mask);
+	let sub_format = extensible_info.map(|info| info.sub_format);
 
-	let pcm = format_tag == PCM || format_tag == IEEE_FLOAT;
-	if !pcm && total_samples == 0 {
-		return Err(WavParseError::message(
-			"non-PCM format identified, no \"fact\" chunk found",
-		));
-	}
-
-	if bits_per_sample > 0 && (total_samples == 0 || pcm) {
-		total_samples = stream_len / (u32::from(channels) * u32::from(bits_per_sample / 8));
+	if fixed_rate {
+		// The sample count can be calculated exactly, no need to trust the `fact` chunk
+		if bits_per_sample > 0 {
+			total_samples = stream_len / (u64::from(channels) * u64::from(bits_per_sample / 8));
+		}
+	} else if total_samples == 0 {
+		if let Some(samples_per_block) = samples_per_block
+			&& block_align > 0
+		{
+			log::debug!("No \"fact\" chunk found, calculating sample count from block size");
+			total_samples =
+				(stream_len / u64::from(block_align)).saturating_mul(u64::from(samples_per_block));
+		} else if bytes_per_second == 0 {
+			return Err(WavParseError::message(
+				"non-PCM format identified, no \"fact\" chunk found",
+			));
+		}
 	}
 
 	let mut duration = Duration::ZERO;
@@ -245,33 +391,30 @@ pub(super) fn read_properties(
 	if sample_rate > 0 && total_samples > 0 {
 		log::debug!("Calculating duration and bitrate from total samples");
 
-		let length = (u64::from(total_samples) * 1000).div_round(u64::from(sample_rate));
-		duration = Duration::from_millis(length);
+		// The sample count may come from the `ds64` chunk, which isn't bounded by anything
+		let length = (u128::from(total_samples) * 1000).div_round(u128::from(sample_rate));
+		duration = Duration::from_millis(u64::try_from(length).unwrap_or(u64::MAX));
 		if length > 0 {
-			overall_bitrate = (file_length * 8).div_round(length) as u32;
+			overall_bitrate = (u128::from(file_length) * 8).div_round(length) as u32;
 			if audio_bitrate == 0 {
 				log::warn!("Estimating audio bitrate from stream length");
-				audio_bitrate = (u64::from(stream_len) * 8).div_round(length) as u32;
+				audio_bitrate = (u128::from(stream_len) * 8).div_round(length) as u32;
 			}
 		}
 	} else if stream_len > 0 && bytes_per_second > 0 {
 		log::debug!("Calculating duration and bitrate from stream length/byte rate");
 
-		let length = (u64::from(stream_len) * 1000).div_round(u64::from(bytes_per_second));
-		duration = Duration::from_millis(length);
+		let length = (u128::from(stream_len) * 1000).div_round(u128::from(bytes_per_second));
+		duration = Duration::from_millis(u64::try_from(length).unwrap_or(u64::MAX));
 		if length > 0 {
-			overall_bitrate = (file_length * 8).div_round(length) as u32;
+			overall_bitrate = (u128::from(file_length) * 8).div_round(length) as u32;
 		}
 	} else {
 		log::warn!("Unable to calculate duration and bitrate");
 	}
 
 	Ok(WavProperties {
-		format: match format_tag {
-			PCM => WavFormat::PCM,
-			IEEE_FLOAT => WavFormat::IEEE_FLOAT,
-			other => WavFormat::Other(other),
-		},
+		format,
 		duration,
 		overall_bitrate,
 		audio_bitrate,
@@ -279,5 +422,7 @@ pub(super) fn read_properties(
 		bit_depth,
 		channels,
 		channel_mask,
+		block_align,
+		sub_format,
 	})
 }
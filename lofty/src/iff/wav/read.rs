@@ -1,31 +1,54 @@
 use super::WavFile;
+use super::bext::BextChunk;
+use super::container::{Ds64, WAVE64_RIFF_GUID, WAVE64_WAVE_GUID, WavContainer};
+use super::cue;
+use super::ixml::Ixml;
 use super::properties::WavProperties;
+use super::sampler::{AcidChunk, InstrumentChunk, SamplerChunk};
 use super::tag::RiffInfoList;
-use crate::config::ParseOptions;
+use crate::config::{ParseOptions, ParsingMode};
 use crate::error::{SizeMismatchError, TagParseError, UnknownFormatError};
 use crate::id3::v2::tag::Id3v2Tag;
 use crate::iff::chunk::Chunks;
+use crate::iff::chunks::{ChunkId, discard_on_error};
 use crate::iff::error::ChunkParseError;
 use crate::iff::wav::error::WavParseError;
+use crate::util::text::trim_end_nulls;
 
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
-// Verifies that the stream is a WAV file and returns the stream length
-pub(crate) fn verify_wav<T>(data: &mut T) -> Result<u32, WavParseError>
+// Verifies that the stream is a WAV file and returns its container
+pub(crate) fn verify_wav<T>(data: &mut T) -> Result<WavContainer, WavParseError>
 where
 	T: Read + Seek,
 {
 	let mut id = [0; 12];
 	data.read_exact(&mut id)?;
 
-	if &id[..4] != b"RIFF" || &id[8..] != b"WAVE" {
-		return Err(UnknownFormatError.into());
-	}
+	let container = match &id[..4] {
+		b"RIFF" if &id[8..] == b"WAVE" => WavContainer::Riff,
+		b"RF64" if &id[8..] == b"WAVE" => WavContainer::Rf64,
+		b"BW64" if &id[8..] == b"WAVE" => WavContainer::Bw64,
+		_ if id[..4] == WAVE64_RIFF_GUID[..4] => {
+			let mut rest = [0; 28];
+			data.read_exact(&mut rest)?;
+
+			if id[..] != WAVE64_RIFF_GUID[..12]
+				|| rest[..4] != WAVE64_RIFF_GUID[12..]
+				|| rest[12..] != WAVE64_WAVE_GUID
+			{
+				return Err(UnknownFormatError.into());
+			}
 
-	log::debug!("File verified to be WAV");
-	Ok(u32::from_le_bytes(id[4..8].try_into().unwrap()))
+			WavContainer::Wave64
+		},
+		_ => return Err(UnknownFormatError.into()),
+	};
+
+	log::debug!("File verified to be WAV, container: {container:?}");
+	Ok(container)
 }
 
 pub(super) fn read_from<R>(
@@ -35,36 +58,135 @@ pub(super) fn read_from<R>(
 where
 	R: Read + Seek,
 {
-	verify_wav(data)?;
+	let container = verify_wav(data)?;
 
 	let current_pos = data.stream_position()?;
 
-	// - 12 for the RIFF chunk we already read
-	let file_len = data.seek(SeekFrom::End(0))?.saturating_sub(12);
+	// Don't include the container header we already read
+	let file_len = data
+		.seek(SeekFrom::End(0))?
+		.saturating_sub(container.header_size());
 
 	data.seek(SeekFrom::Start(current_pos))?;
 
-	let mut stream_len = 0_u32;
-	let mut total_samples = 0_u32;
+	let mut stream_len = 0_u64;
+	let mut total_samples = 0_u64;
+	let mut ds64 = None;
 	let mut fmt = Vec::new();
 
 	let mut riff_info = RiffInfoList::default();
 	let mut id3v2_tag: Option<Id3v2Tag> = None;
+	let mut bext = None;
+	let mut original_bext = None;
+	let mut ixml = None;
+	let mut axml = None;
+	let mut cue_points = None;
+	let mut sampler = None;
+	let mut instrument = None;
+	let mut acid = None;
+	let mut associated_data = None;
+	let mut unparsed_chunks = Vec::new();
+
+	let mut chunks = match container {
+		WavContainer::Wave64 => Chunks::<_, LittleEndian>::new_wave64(data, file_len),
+		_ => Chunks::<_, LittleEndian>::new(data, file_len),
+	};
 
-	let mut chunks = Chunks::<_, LittleEndian>::new(data, file_len);
 	while let Some(mut chunk) = chunks.next(parse_options.parsing_mode)? {
 		match &chunk.fourcc {
+			// The `ds64` chunk is required to be the first chunk, so its sizes will be available
+			// for the rest
+			b"ds64" if container.has_ds64() && ds64.is_none() => {
+				let content = chunk.content()?;
+				let parsed = Ds64::parse(&content, parse_options.parsing_mode)?;
+
+				chunks.set_large_chunk_sizes(parsed.chunk_sizes());
+				ds64 = Some(parsed);
+			},
 			b"fmt " if parse_options.read_properties && fmt.is_empty() => {
 				fmt = chunk.content()?;
 			},
 			b"fact" if parse_options.read_properties && total_samples == 0 => {
 				total_samples = chunk
 					.read_u32::<LittleEndian>()
-					.map_err(|e| ChunkParseError::from(e).with_fourcc(chunk.fourcc))?;
+					.map_err(|e| ChunkParseError::from(e).with_fourcc(chunk.fourcc))?
+					.into();
+
+				// The real sample count is in the `ds64` chunk
+				if total_samples == u64::from(u32::MAX)
+					&& let Some(ds64) = &ds64
+				{
+					total_samples = ds64.sample_count;
+				}
 			},
 			b"data" if parse_options.read_properties && stream_len == 0 => {
 				stream_len += chunk.size()
 			},
+			b"bext" if bext.is_none() => {
+				let content = chunk.content()?;
+				bext = discard_on_error(
+					ChunkId::Chunk(*b"bext"),
+					BextChunk::parse(&content, parse_options.parsing_mode),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+				original_bext = bext.clone().map(|bext| (bext, content));
+			},
+			b"iXML" if ixml.is_none() => {
+				let content = chunk.content()?;
+				ixml = read_xml(
+					ChunkId::Chunk(*b"iXML"),
+					content,
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?
+				.map(Ixml::from_xml);
+			},
+			b"axml" if axml.is_none() => {
+				let content = chunk.content()?;
+				axml = read_xml(
+					ChunkId::Chunk(*b"axml"),
+					content,
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
+			b"cue " if cue_points.is_none() => {
+				let content = chunk.content()?;
+				cue_points = discard_on_error(
+					ChunkId::Chunk(*b"cue "),
+					cue::parse_cue(&content, parse_options.parsing_mode),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
+			b"smpl" if sampler.is_none() => {
+				let content = chunk.content()?;
+				sampler = discard_on_error(
+					ChunkId::Chunk(*b"smpl"),
+					SamplerChunk::parse(&content, parse_options.parsing_mode),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
+			b"inst" if instrument.is_none() => {
+				let content = chunk.content()?;
+				instrument = discard_on_error(
+					ChunkId::Chunk(*b"inst"),
+					InstrumentChunk::parse(&content),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
+			b"acid" if acid.is_none() => {
+				let content = chunk.content()?;
+				acid = discard_on_error(
+					ChunkId::Chunk(*b"acid"),
+					AcidChunk::parse(&content),
+					parse_options.parsing_mode,
+					&mut unparsed_chunks,
+				)?;
+			},
 			b"LIST" => {
 				let mut size = chunk.size();
 				if size < 4 {
@@ -76,11 +198,33 @@ where
 
 				size -= 4;
 
+				if &list_type == b"adtl" && associated_data.is_none() {
+					let mut content = Vec::new();
+					chunk.read_to_end(&mut content)?;
+					associated_data = Some(cue::parse_adtl(&content, parse_options.parsing_mode)?);
+					continue;
+				}
+
 				if &list_type != b"INFO" || !parse_options.read_tags {
 					continue;
 				}
 
-				let end = chunks.stream_position() + u64::from(size);
+				// The list itself is made up of standard RIFF chunks, even in Wave64
+				if container == WavContainer::Wave64 {
+					let mut content = Vec::new();
+					chunk.read_to_end(&mut content)?;
+
+					let len = content.len() as u64;
+					super::tag::read::parse_riff_info(
+						&mut Chunks::<_, LittleEndian>::new(&mut Cursor::new(content), len),
+						&mut riff_info,
+						parse_options.parsing_mode,
+					)
+					.map_err(TagParseError::from)?;
+					continue;
+				}
+
+				let end = chunks.stream_position() + size;
 				if end > file_len {
 					return Err(SizeMismatchError.into());
 				}
@@ -117,6 +261,10 @@ where
 		}
 	}
 
+	let mut cue_points = cue_points.unwrap_or_default();
+	let mut associated_data = associated_data.unwrap_or_default();
+	cue::apply_associated_data(&mut cue_points, &mut associated_data);
+
 	let data = chunks.into_inner();
 	let properties = if parse_options.read_properties {
 		let file_length = data.stream_position()?;
@@ -127,8 +275,47 @@ where
 	};
 
 	Ok(WavFile {
+		container,
 		properties,
 		riff_info_tag: (!riff_info.items.is_empty()).then_some(riff_info),
 		id3v2_tag,
+		bext,
+		original_bext,
+		original_ixml: ixml.clone(),
+		ixml,
+		original_axml: axml.clone(),
+		axml,
+		original_cue_points: cue_points.clone(),
+		cue_points,
+		other_adtl_chunks: associated_data.other,
+		sampler,
+		instrument,
+		acid,
+		unparsed_chunks,
 	})
 }
+
+// XML chunks are expected to be UTF-8, and are sometimes null padded
+//
+// Chunks that aren't UTF-8 or are empty are kept as-is, see `discard_on_error`
+fn read_xml(
+	id: ChunkId,
+	content: Vec<u8>,
+	parse_mode: ParsingMode,
+	unparsed_chunks: &mut Vec<ChunkId>,
+) -> Result<Option<String>, WavParseError> {
+	let xml = String::from_utf8(content)
+		.map_err(|_| WavParseError::message("XML chunk is not valid UTF-8"));
+	let Some(mut xml) = discard_on_error(id, xml, parse_mode, unparsed_chunks)? else {
+		return Ok(None);
+	};
+
+	trim_end_nulls(&mut xml);
+	if xml.trim().is_empty() {
+		log::debug!("'{id}' chunk is empty, preserving it as-is");
+		unparsed_chunks.push(id);
+		return Ok(None);
+	}
+
+	Ok(Some(xml))
+}
@@ -0,0 +1,505 @@
+use super::error::{WavEncodingError, WavParseError};
+use crate::config::ParsingMode;
+use crate::error::TooMuchDataError;
+
+use std::io::{Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+const SAMPLER_HEADER_SIZE: usize = 36;
+const SAMPLE_LOOP_SIZE: usize = 24;
+const INSTRUMENT_SIZE: usize = 7;
+const ACID_SIZE: usize = 24;
+
+/// The playback direction of a [`SampleLoop`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SampleLoopType {
+	/// Play forward
+	Forward,
+	/// Alternate between playing forward and backward
+	Alternating,
+	/// Play backward
+	Backward,
+	/// A manufacturer specific loop type
+	Other(u32),
+}
+
+impl From<u32> for SampleLoopType {
+	fn from(input: u32) -> Self {
+		match input {
+			0 => Self::Forward,
+			1 => Self::Alternating,
+			2 => Self::Backward,
+			_ => Self::Other(input),
+		}
+	}
+}
+
+impl From<SampleLoopType> for u32 {
+	fn from(input: SampleLoopType) -> Self {
+		match input {
+			SampleLoopType::Forward => 0,
+			SampleLoopType::Alternating => 1,
+			SampleLoopType::Backward => 2,
+			SampleLoopType::Other(value) => value,
+		}
+	}
+}
+
+/// A loop in a [`SamplerChunk`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SampleLoop {
+	/// The ID of the [`CuePoint`](super::CuePoint) associated with this loop, if any
+	pub cue_point_id: u32,
+	/// The playback direction
+	pub loop_type: SampleLoopType,
+	/// The first sample of the loop
+	pub start: u32,
+	/// The last sample of the loop
+	pub end: u32,
+	/// A fraction of a sample to fine tune the loop point (0 = 0%, `u32::MAX` ≈ 100%)
+	pub fraction: u32,
+	/// The number of times to play the loop, with 0 meaning infinitely
+	pub play_count: u32,
+}
+
+impl SampleLoop {
+	/// Create a new forward loop from `start` to `end`, played infinitely
+	pub fn new(start: u32, end: u32) -> Self {
+		Self {
+			cue_point_id: 0,
+			loop_type: SampleLoopType::Forward,
+			start,
+			end,
+			fraction: 0,
+			play_count: 0,
+		}
+	}
+}
+
+/// A sampler (`smpl`) chunk
+///
+/// This describes how a sampler should play the audio, including its pitch and loops.
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::iff::wav::{SampleLoop, SamplerChunk};
+///
+/// let mut sampler = SamplerChunk::new();
+/// // Middle C
+/// sampler.midi_unity_note = 60;
+/// sampler.loops.push(SampleLoop::new(0, 44099));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SamplerChunk {
+	/// The MIDI Manufacturers Association manufacturer code, or 0
+	pub manufacturer: u32,
+	/// A manufacturer specific product code, or 0
+	pub product: u32,
+	/// The duration of a sample, in nanoseconds
+	pub sample_period: u32,
+	/// The MIDI note that plays the audio at its original pitch (0-127, 60 = middle C)
+	pub midi_unity_note: u32,
+	/// A fraction of a semitone up from `midi_unity_note` (0x80000000 = 50 cents)
+	pub midi_pitch_fraction: u32,
+	/// The SMPTE format (0, 24, 25, 29, or 30)
+	pub smpte_format: u32,
+	/// The SMPTE time offset of the first sample, as `0xhhmmssff`
+	pub smpte_offset: u32,
+	/// The loops
+	pub loops: Vec<SampleLoop>,
+	/// Manufacturer specific data
+	pub sampler_data: Vec<u8>,
+}
+
+impl SamplerChunk {
+	/// Create a new empty `SamplerChunk`
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub(super) fn parse(content: &[u8], parse_mode: ParsingMode) -> Result<Self, WavParseError> {
+		if content.len() < SAMPLER_HEADER_SIZE {
+			return Err(WavParseError::message("smpl chunk is too small"));
+		}
+
+		let reader = &mut &content[..];
+
+		let manufacturer = reader.read_u32::<LittleEndian>()?;
+		let product = reader.read_u32::<LittleEndian>()?;
+		let sample_period = reader.read_u32::<LittleEndian>()?;
+		let midi_unity_note = reader.read_u32::<LittleEndian>()?;
+		let midi_pitch_fraction = reader.read_u32::<LittleEndian>()?;
+		let smpte_format = reader.read_u32::<LittleEndian>()?;
+		let smpte_offset = reader.read_u32::<LittleEndian>()?;
+		let loop_count = reader.read_u32::<LittleEndian>()? as usize;
+		let sampler_data_size = reader.read_u32::<LittleEndian>()? as usize;
+
+		if loop_count.saturating_mul(SAMPLE_LOOP_SIZE) > reader.len() {
+			if parse_mode == ParsingMode::Strict {
+				return Err(WavParseError::message("smpl chunk is too small"));
+			}
+
+			log::warn!("smpl chunk is too small for {loop_count} loops, reading what's available");
+		}
+
+		let loop_count = loop_count.min(reader.len() / SAMPLE_LOOP_SIZE);
+
+		let mut loops = Vec::with_capacity(loop_count);
+		for _ in 0..loop_count {
+			loops.push(SampleLoop {
+				cue_point_id: reader.read_u32::<LittleEndian>()?,
+				loop_type: SampleLoopType::from(reader.read_u32::<LittleEndian>()?),
+				start: reader.read_u32::<LittleEndian>()?,
+				end: reader.read_u32::<LittleEndian>()?,
+				fraction: reader.read_u32::<LittleEndian>()?,
+				play_count: reader.read_u32::<LittleEndian>()?,
+			});
+		}
+
+		if sampler_data_size > reader.len() {
+			if parse_mode == ParsingMode::Strict {
+				return Err(WavParseError::message("smpl chunk is too small"));
+			}
+
+			log::warn!("smpl chunk is too small for its sampler data, reading what's available");
+		}
+
+		let sampler_data = reader[..sampler_data_size.min(reader.len())].to_vec();
+
+		Ok(Self {
+			manufacturer,
+			product,
+			sample_period,
+			midi_unity_note,
+			midi_pitch_fraction,
+			smpte_format,
+			smpte_offset,
+			loops,
+			sampler_data,
+		})
+	}
+
+	pub(super) fn as_bytes(&self) -> Result<Vec<u8>, WavEncodingError> {
+		let (Ok(loop_count), Ok(sampler_data_size)) = (
+			u32::try_from(self.loops.len()),
+			u32::try_from(self.sampler_data.len()),
+		) else {
+			return Err(TooMuchDataError.into());
+		};
+
+		let mut content = Vec::with_capacity(
+			SAMPLER_HEADER_SIZE + self.loops.len() * SAMPLE_LOOP_SIZE + self.sampler_data.len(),
+		);
+
+		content.write_u32::<LittleEndian>(self.manufacturer)?;
+		content.write_u32::<LittleEndian>(self.product)?;
+		content.write_u32::<LittleEndian>(self.sample_period)?;
+		content.write_u32::<LittleEndian>(self.midi_unity_note)?;
+		content.write_u32::<LittleEndian>(self.midi_pitch_fraction)?;
+		content.write_u32::<LittleEndian>(self.smpte_format)?;
+		content.write_u32::<LittleEndian>(self.smpte_offset)?;
+		content.write_u32::<LittleEndian>(loop_count)?;
+		content.write_u32::<LittleEndian>(sampler_data_size)?;
+
+		for sample_loop in &self.loops {
+			content.write_u32::<LittleEndian>(sample_loop.cue_point_id)?;
+			content.write_u32::<LittleEndian>(u32::from(sample_loop.loop_type))?;
+			content.write_u32::<LittleEndian>(sample_loop.start)?;
+			content.write_u32::<LittleEndian>(sample_loop.end)?;
+			content.write_u32::<LittleEndian>(sample_loop.fraction)?;
+			content.write_u32::<LittleEndian>(sample_loop.play_count)?;
+		}
+
+		content.write_all(&self.sampler_data)?;
+
+		Ok(content)
+	}
+}
+
+/// An instrument (`inst`) chunk
+///
+/// This describes the pitch, volume, and key/velocity range a sampler should use for the audio.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstrumentChunk {
+	/// The MIDI note that plays the audio at its original pitch (0-127, 60 = middle C)
+	pub unshifted_note: u8,
+	/// The pitch shift to apply, in cents (-50-+50)
+	pub fine_tune: i8,
+	/// The volume adjustment to apply, in decibels
+	pub gain: i8,
+	/// The lowest MIDI note to play the audio for
+	pub low_note: u8,
+	/// The highest MIDI note to play the audio for
+	pub high_note: u8,
+	/// The lowest MIDI velocity to play the audio for
+	pub low_velocity: u8,
+	/// The highest MIDI velocity to play the audio for
+	pub high_velocity: u8,
+}
+
+impl Default for InstrumentChunk {
+	fn default() -> Self {
+		Self {
+			unshifted_note: 60,
+			fine_tune: 0,
+			gain: 0,
+			low_note: 0,
+			high_note: 127,
+			low_velocity: 1,
+			high_velocity: 127,
+		}
+	}
+}
+
+impl InstrumentChunk {
+	/// Create a new `InstrumentChunk`, covering the entire key and velocity range
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub(super) fn parse(content: &[u8]) -> Result<Self, WavParseError> {
+		let Some(content) = content.first_chunk::<INSTRUMENT_SIZE>() else {
+			return Err(WavParseError::message("inst chunk is too small"));
+		};
+
+		Ok(Self {
+			unshifted_note: content[0],
+			fine_tune: content[1] as i8,
+			gain: content[2] as i8,
+			low_note: content[3],
+			high_note: content[4],
+			low_velocity: content[5],
+			high_velocity: content[6],
+		})
+	}
+
+	pub(super) fn as_bytes(self) -> Vec<u8> {
+		vec![
+			self.unshifted_note,
+			self.fine_tune as u8,
+			self.gain as u8,
+			self.low_note,
+			self.high_note,
+			self.low_velocity,
+			self.high_velocity,
+		]
+	}
+}
+
+/// An ACID loop (`acid`) chunk
+///
+/// This is written by ACID and many other loop-based applications to describe the tempo and key
+/// of the audio.
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::iff::wav::AcidChunk;
+///
+/// let mut acid = AcidChunk::new();
+/// acid.tempo = 128.0;
+/// acid.beats = 16;
+/// // A
+/// acid.root_note = Some(57);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AcidChunk {
+	/// Whether the audio is a one-shot, rather than a loop
+	pub one_shot: bool,
+	/// The MIDI note of the key the audio is in (60 = middle C), if any
+	pub root_note: Option<u16>,
+	/// Whether the audio should be stretched to match the project tempo
+	pub stretch: bool,
+	/// Whether the audio should be streamed from disk, rather than loaded into memory
+	pub disk_based: bool,
+	/// The number of beats in the audio
+	pub beats: u32,
+	/// The lower number of the time signature
+	pub meter_denominator: u16,
+	/// The upper number of the time signature
+	pub meter_numerator: u16,
+	/// The tempo, in beats per minute
+	pub tempo: f32,
+	// Any flags and fields we don't know the meaning of
+	other_flags: u32,
+	reserved: [u8; 6],
+}
+
+impl Default for AcidChunk {
+	fn default() -> Self {
+		Self {
+			one_shot: false,
+			root_note: None,
+			stretch: true,
+			disk_based: false,
+			beats: 0,
+			meter_denominator: 4,
+			meter_numerator: 4,
+			tempo: 0.0,
+			other_flags: 0,
+			reserved: [0; 6],
+		}
+	}
+}
+
+impl AcidChunk {
+	const ONE_SHOT: u32 = 0x01;
+	const ROOT_NOTE_SET: u32 = 0x02;
+	const STRETCH: u32 = 0x04;
+	const DISK_BASED: u32 = 0x08;
+	const KNOWN_FLAGS: u32 =
+		Self::ONE_SHOT | Self::ROOT_NOTE_SET | Self::STRETCH | Self::DISK_BASED;
+
+	/// Create a new `AcidChunk`, with a 4/4 time signature
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub(super) fn parse(content: &[u8]) -> Result<Self, WavParseError> {
+		if content.len() < ACID_SIZE {
+			return Err(WavParseError::message("acid chunk is too small"));
+		}
+
+		let reader = &mut &content[..];
+
+		let flags = reader.read_u32::<LittleEndian>()?;
+		let root_note = reader.read_u16::<LittleEndian>()?;
+
+		let mut reserved = [0; 6];
+		reader.read_exact(&mut reserved)?;
+
+		let beats = reader.read_u32::<LittleEndian>()?;
+		let meter_denominator = reader.read_u16::<LittleEndian>()?;
+		let meter_numerator = reader.read_u16::<LittleEndian>()?;
+		let tempo = reader.read_f32::<LittleEndian>()?;
+
+		Ok(Self {
+			one_shot: flags & Self::ONE_SHOT != 0,
+			root_note: (flags & Self::ROOT_NOTE_SET != 0).then_some(root_note),
+			stretch: flags & Self::STRETCH != 0,
+			disk_based: flags & Self::DISK_BASED != 0,
+			beats,
+			meter_denominator,
+			meter_numerator,
+			tempo,
+			other_flags: flags & !Self::KNOWN_FLAGS,
+			reserved,
+		})
+	}
+
+	pub(super) fn as_bytes(&self) -> Result<Vec<u8>, WavEncodingError> {
+		let mut flags = self.other_flags;
+		for (set, flag) in [
+			(self.one_shot, Self::ONE_SHOT),
+			(self.root_note.is_some(), Self::ROOT_NOTE_SET),
+			(self.stretch, Self::STRETCH),
+			(self.disk_based, Self::DISK_BASED),
+		] {
+			if set {
+				flags |= flag;
+			}
+		}
+
+		let mut content = Vec::with_capacity(ACID_SIZE);
+		content.write_u32::<LittleEndian>(flags)?;
+		content.write_u16::<LittleEndian>(self.root_note.unwrap_or_default())?;
+		content.write_all(&self.reserved)?;
+		content.write_u32::<LittleEndian>(self.beats)?;
+		content.write_u16::<LittleEndian>(self.meter_denominator)?;
+		content.write_u16::<LittleEndian>(self.meter_numerator)?;
+		content.write_f32::<LittleEndian>(self.tempo)?;
+
+		Ok(content)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{AcidChunk, InstrumentChunk, SampleLoop, SampleLoopType, SamplerChunk};
+	use crate::config::ParsingMode;
+
+	#[test_log::test]
+	fn sampler_round_trip() {
+		let sampler = SamplerChunk {
+			sample_period: 22675,
+			midi_unity_note: 57,
+			midi_pitch_fraction: 0x8000_0000,
+			loops: vec![
+				SampleLoop::new(100, 200),
+				SampleLoop {
+					cue_point_id: 3,
+					loop_type: SampleLoopType::Other(32),
+					play_count: 2,
+					..SampleLoop::new(300, 400)
+				},
+			],
+			sampler_data: vec![1, 2, 3],
+			..SamplerChunk::new()
+		};
+
+		let bytes = sampler.as_bytes().unwrap();
+		assert_eq!(
+			SamplerChunk::parse(&bytes, ParsingMode::Strict).unwrap(),
+			sampler
+		);
+	}
+
+	#[test_log::test]
+	fn sampler_truncated_loops() {
+		let sampler = SamplerChunk {
+			loops: vec![SampleLoop::new(0, 1), SampleLoop::new(2, 3)],
+			..SamplerChunk::new()
+		};
+
+		let bytes = sampler.as_bytes().unwrap();
+		let truncated = &bytes[..bytes.len() - 1];
+
+		assert!(SamplerChunk::parse(truncated, ParsingMode::Strict).is_err());
+
+		let parsed = SamplerChunk::parse(truncated, ParsingMode::BestAttempt).unwrap();
+		assert_eq!(parsed.loops, vec![SampleLoop::new(0, 1)]);
+	}
+
+	#[test_log::test]
+	fn instrument_round_trip() {
+		let instrument = InstrumentChunk {
+			fine_tune: -12,
+			gain: -6,
+			low_note: 48,
+			high_note: 72,
+			..InstrumentChunk::new()
+		};
+
+		let bytes = instrument.as_bytes();
+		assert_eq!(InstrumentChunk::parse(&bytes).unwrap(), instrument);
+	}
+
+	#[test_log::test]
+	fn acid() {
+		// One-shot + root note set + an unknown flag, root note A, 8 beats of 3/4 at 92.5 BPM
+		#[rustfmt::skip]
+		let bytes = [
+			0x13, 0x00, 0x00, 0x00,
+			0x39, 0x00,
+			0x00, 0x80, 0x00, 0x00, 0x00, 0x00,
+			0x08, 0x00, 0x00, 0x00,
+			0x04, 0x00,
+			0x03, 0x00,
+			0x00, 0x00, 0xB9, 0x42,
+		];
+
+		let acid = AcidChunk::parse(&bytes).unwrap();
+		assert!(acid.one_shot);
+		assert!(!acid.stretch);
+		assert_eq!(acid.root_note, Some(57));
+		assert_eq!(acid.beats, 8);
+		assert_eq!(acid.meter_numerator, 3);
+		assert_eq!(acid.meter_denominator, 4);
+		assert!((acid.tempo - 92.5).abs() < f32::EPSILON);
+
+		// Unknown flags and fields are preserved
+		assert_eq!(acid.as_bytes().unwrap(), bytes);
+	}
+}
@@ -4,7 +4,9 @@ use crate::error::{
 	FileEncodingError, FileParseError, SizeMismatchError, TagEncodingError, TooMuchDataError,
 };
 use crate::iff::chunk::{Chunks, IFF_CHUNK_HEADER_SIZE};
+use crate::iff::chunks::{self, ChunkId, ChunkUpdate};
 use crate::iff::error::ChunkParseError;
+use crate::iff::wav::container::WavContainer;
 use crate::iff::wav::error::WavParseError;
 use crate::iff::wav::read::verify_wav;
 use crate::iff::wav::tag::error::RiffInfoListEncodingError;
@@ -18,7 +20,7 @@ use byteorder::{LittleEndian, WriteBytesExt};
 
 // TODO: Write JUNK chunk for padding
 pub(in crate::iff::wav) fn write_riff_info<'a, F, I>(
-	file: VerifiedFile<'_, F>,
+	mut file: VerifiedFile<'_, F>,
 	tag: &mut RIFFInfoListRef<'a, I>,
 	write_options: WriteOptions,
 ) -> Result<(), FileEncodingError>
@@ -29,17 +31,36 @@ where
 	// The first chunk format is RIFF....WAVE
 	const FIRST_CHUNK_LEN: u32 = IFF_CHUNK_HEADER_SIZE + 4;
 
-	let mut file = file.into_inner();
-	let original_stream_length = verify_wav(&mut file).map_err(FileParseError::from)?;
+	let container = verify_wav(&mut *file).map_err(FileParseError::from)?;
 
 	let mut riff_info_bytes = Vec::new();
 	create_riff_info(&mut tag.items, &mut riff_info_bytes).map_err(TagEncodingError::from)?;
 
 	file.rewind()?;
 
+	// Containers with 64-bit sizes need to go through the generic chunk writer
+	if container != WavContainer::Riff {
+		let update = ChunkUpdate {
+			id: ChunkId::List(*b"INFO"),
+			// Skip the `LIST....INFO` header
+			content: riff_info_bytes
+				.get(FIRST_CHUNK_LEN as usize..)
+				.map(<[u8]>::to_vec),
+		};
+
+		return chunks::write_chunks(file, &[update], write_options);
+	}
+
+	let mut file = file.into_inner();
+
 	let mut file_bytes = Cursor::new(Vec::new());
 	file.read_to_end(file_bytes.get_mut())?;
 
+	let Some(original_stream_length) = file_bytes.get_ref().get(4..8) else {
+		return Err(SizeMismatchError.into());
+	};
+	let original_stream_length = u32::from_le_bytes(original_stream_length.try_into().unwrap());
+
 	// File is lying about its length
 	if file_bytes.get_ref().len() < (original_stream_length + IFF_CHUNK_HEADER_SIZE) as usize {
 		return Err(SizeMismatchError.into());
@@ -86,7 +107,7 @@ where
 
 	// `original_info_list_size` doesn't include the b"LIST\0\0\0\0" chunk header
 	let info_list_end =
-		info_list_start + (IFF_CHUNK_HEADER_SIZE + original_info_list_size) as usize;
+		info_list_start + IFF_CHUNK_HEADER_SIZE as usize + original_info_list_size as usize;
 	let original_info_list = info_list_start..info_list_end;
 
 	let new_stream_length = riff_info_bytes.len() as u64
@@ -114,7 +135,7 @@ fn find_info_list<R>(
 	data: &mut R,
 	file_size: u64,
 	parse_mode: ParsingMode,
-) -> Result<Option<u32>, WavParseError>
+) -> Result<Option<u64>, WavParseError>
 where
 	R: Read + Seek,
 {
@@ -0,0 +1,405 @@
+//! Channel layouts from the QuickTime `chan` and ISO `chnl` atoms
+
+use crate::properties::ChannelMask;
+
+use std::io::Read;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+// QuickTime (CoreAudio) channel labels
+const LABEL_LEFT: u32 = 1;
+const LABEL_RIGHT: u32 = 2;
+const LABEL_CENTER: u32 = 3;
+const LABEL_LFE: u32 = 4;
+const LABEL_LEFT_SURROUND: u32 = 5;
+const LABEL_RIGHT_SURROUND: u32 = 6;
+const LABEL_LEFT_CENTER: u32 = 7;
+const LABEL_RIGHT_CENTER: u32 = 8;
+const LABEL_CENTER_SURROUND: u32 = 9;
+const LABEL_REAR_SURROUND_LEFT: u32 = 33;
+const LABEL_REAR_SURROUND_RIGHT: u32 = 34;
+
+// Special layout tags
+const LAYOUT_TAG_USE_CHANNEL_DESCRIPTIONS: u32 = 0;
+const LAYOUT_TAG_USE_CHANNEL_BITMAP: u32 = 1 << 16;
+
+/// Get the channel layout from the contents of a `chan` atom
+///
+/// See <https://developer.apple.com/library/archive/documentation/QuickTime/QTFF/QTFFChap3/qtff3.html#//apple_ref/doc/uid/TP40000939-CH205-BBCCBFEG>
+pub(super) fn parse_chan(mut content: &[u8]) -> std::io::Result<Option<ChannelMask>> {
+	let _version_and_flags = content.read_u32::<BigEndian>()?;
+
+	let layout_tag = content.read_u32::<BigEndian>()?;
+	let bitmap = content.read_u32::<BigEndian>()?;
+
+	let labels = match layout_tag {
+		// The bitmap uses the same bits as `ChannelMask`
+		LAYOUT_TAG_USE_CHANNEL_BITMAP => return Ok(Some(ChannelMask(bitmap))),
+		LAYOUT_TAG_USE_CHANNEL_DESCRIPTIONS => {
+			let description_count = content.read_u32::<BigEndian>()?;
+
+			let mut labels = Vec::new();
+			for _ in 0..description_count {
+				labels.push(content.read_u32::<BigEndian>()?);
+
+				// Flags (4)
+				// Coordinates (12)
+				let mut rest = [0; 16];
+				content.read_exact(&mut rest)?;
+			}
+
+			labels
+		},
+		_ => match layout_tag_labels(layout_tag) {
+			Some(labels) => labels.to_vec(),
+			None => {
+				log::debug!("Unsupported channel layout tag: {layout_tag:#X}");
+				return Ok(None);
+			},
+		},
+	};
+
+	// With rear surround channels present, the surround channels are moved to the sides
+	let has_rear_surround = labels
+		.iter()
+		.any(|label| matches!(*label, LABEL_REAR_SURROUND_LEFT | LABEL_REAR_SURROUND_RIGHT));
+
+	let mut mask = ChannelMask(0);
+	for label in labels {
+		let channel = match label {
+			LABEL_LEFT_SURROUND if has_rear_surround => ChannelMask::SIDE_LEFT,
+			LABEL_RIGHT_SURROUND if has_rear_surround => ChannelMask::SIDE_RIGHT,
+			LABEL_REAR_SURROUND_LEFT => ChannelMask::BACK_LEFT,
+			LABEL_REAR_SURROUND_RIGHT => ChannelMask::BACK_RIGHT,
+			// Labels 1-18 line up with the `ChannelMask` bits
+			1..=18 => ChannelMask(1 << (label - 1)),
+			_ => {
+				log::debug!("Unsupported channel label: {label}");
+				return Ok(None);
+			},
+		};
+
+		mask = mask | channel;
+	}
+
+	Ok(Some(mask))
+}
+
+/// The channel labels of the predefined QuickTime layouts
+fn layout_tag_labels(layout_tag: u32) -> Option<&'static [u32]> {
+	const L: u32 = LABEL_LEFT;
+	const R: u32 = LABEL_RIGHT;
+	const C: u32 = LABEL_CENTER;
+	const LFE: u32 = LABEL_LFE;
+	const LS: u32 = LABEL_LEFT_SURROUND;
+	const RS: u32 = LABEL_RIGHT_SURROUND;
+	const LC: u32 = LABEL_LEFT_CENTER;
+	const RC: u32 = LABEL_RIGHT_CENTER;
+	const CS: u32 = LABEL_CENTER_SURROUND;
+	const RLS: u32 = LABEL_REAR_SURROUND_LEFT;
+	const RRS: u32 = LABEL_REAR_SURROUND_RIGHT;
+
+	// The upper 16 bits identify the layout, the lower 16 bits are the channel count
+	let labels: &[u32] = match layout_tag >> 16 {
+		// Mono
+		100 => &[C],
+		// Stereo, StereoHeadphones, MatrixStereo, Binaural
+		101 | 102 | 103 | 106 => &[L, R],
+		// Quadraphonic, ITU_2_2
+		108 | 132 => &[L, R, LS, RS],
+		// Pentagonal
+		109 => &[L, R, RLS, RRS, C],
+		// Hexagonal
+		110 => &[L, R, RLS, RRS, C, CS],
+		// MPEG_3_0_A, MPEG_3_0_B
+		113 | 114 => &[L, R, C],
+		// MPEG_4_0_A, MPEG_4_0_B
+		115 | 116 => &[L, R, C, CS],
+		// MPEG_5_0_A-D
+		117..=120 => &[L, R, C, LS, RS],
+		// MPEG_5_1_A-D
+		121..=124 => &[L, R, C, LFE, LS, RS],
+		// MPEG_6_1_A
+		125 => &[L, R, C, LFE, LS, RS, CS],
+		// MPEG_7_1_A, MPEG_7_1_B, Emagic_Default_7_1
+		126 | 127 | 129 => &[L, R, C, LFE, LS, RS, LC, RC],
+		// MPEG_7_1_C
+		128 => &[L, R, C, LFE, LS, RS, RLS, RRS],
+		// ITU_2_1
+		131 => &[L, R, CS],
+		_ => return None,
+	};
+
+	Some(labels)
+}
+
+// ISO/IEC 23091-3 speaker positions
+const POSITION_LEFT_SURROUND: u8 = 4;
+const POSITION_RIGHT_SURROUND: u8 = 5;
+const POSITION_REAR_SURROUND_LEFT: u8 = 8;
+const POSITION_REAR_SURROUND_RIGHT: u8 = 9;
+const POSITION_EXPLICIT: u8 = 126;
+
+/// Get the channel layout from the contents of a `chnl` atom
+///
+/// `channels` is the channel count from the sample entry, which is needed to read explicit layouts.
+///
+/// See ISO/IEC 14496-12, 12.2.4
+pub(super) fn parse_chnl(mut content: &[u8], channels: u8) -> std::io::Result<Option<ChannelMask>> {
+	const CHANNEL_STRUCTURED: u8 = 1;
+
+	let version = content.read_u8()?;
+	let _flags = content.read_u24::<BigEndian>()?;
+
+	if version > 1 {
+		log::debug!("Unsupported `chnl` version: {version}");
+		return Ok(None);
+	}
+
+	let mut channel_count = channels;
+
+	let stream_structure;
+	if version == 0 {
+		stream_structure = content.read_u8()?;
+	} else {
+		// Stream structure (4 bits) and format ordering (4 bits)
+		stream_structure = content.read_u8()? >> 4;
+		let _base_channel_count = content.read_u8()?;
+	}
+
+	if stream_structure & CHANNEL_STRUCTURED == 0 {
+		return Ok(None);
+	}
+
+	let defined_layout = content.read_u8()?;
+	if defined_layout == 0 {
+		if version == 1 {
+			channel_count = content.read_u8()?;
+		}
+
+		let mut positions = Vec::with_capacity(usize::from(channel_count));
+		for _ in 0..channel_count {
+			let position = content.read_u8()?;
+			if position == POSITION_EXPLICIT {
+				// Azimuth (2)
+				// Elevation (1)
+				let mut direction = [0; 3];
+				content.read_exact(&mut direction)?;
+			}
+
+			positions.push(position);
+		}
+
+		return Ok(positions_mask(&positions));
+	}
+
+	let omitted_channels_map = if version == 0 {
+		content.read_u64::<BigEndian>()?
+	} else {
+		// Reserved (4 bits), channel order definition (3 bits), and omitted channels present (1 bit)
+		let omitted_channels_present = content.read_u8()? & 1 == 1;
+		if omitted_channels_present {
+			content.read_u64::<BigEndian>()?
+		} else {
+			0
+		}
+	};
+
+	let Some(layout) = defined_layout_positions(defined_layout) else {
+		log::debug!("Unsupported `chnl` defined layout: {defined_layout}");
+		return Ok(None);
+	};
+
+	// The least significant bit corresponds to the first channel in the layout
+	let positions = layout
+		.iter()
+		.enumerate()
+		.filter(|(i, _)| omitted_channels_map & (1 << i) == 0)
+		.map(|(_, position)| *position)
+		.collect::<Vec<_>>();
+
+	Ok(positions_mask(&positions))
+}
+
+/// The speaker positions of the ISO/IEC 23091-3 `ChannelConfiguration`s
+fn defined_layout_positions(defined_layout: u8) -> Option<&'static [u8]> {
+	const L: u8 = 0;
+	const R: u8 = 1;
+	const C: u8 = 2;
+	const LFE: u8 = 3;
+	const LS: u8 = POSITION_LEFT_SURROUND;
+	const RS: u8 = POSITION_RIGHT_SURROUND;
+	const LC: u8 = 6;
+	const RC: u8 = 7;
+	const LSR: u8 = POSITION_REAR_SURROUND_LEFT;
+	const RSR: u8 = POSITION_REAR_SURROUND_RIGHT;
+	const CS: u8 = 10;
+
+	let positions: &[u8] = match defined_layout {
+		1 => &[C],
+		2 => &[L, R],
+		3 => &[C, L, R],
+		4 => &[C, L, R, CS],
+		5 => &[C, L, R, LS, RS],
+		6 => &[C, L, R, LS, RS, LFE],
+		7 => &[C, LC, RC, L, R, LS, RS, LFE],
+		9 => &[L, R, CS],
+		10 => &[L, R, LS, RS],
+		11 => &[C, L, R, LS, RS, CS, LFE],
+		12 => &[C, L, R, LS, RS, LSR, RSR, LFE],
+		_ => return None,
+	};
+
+	Some(positions)
+}
+
+fn positions_mask(positions: &[u8]) -> Option<ChannelMask> {
+	// With rear surround channels present, the surround channels are moved to the sides
+	let has_rear_surround = positions.iter().any(|position| {
+		matches!(
+			*position,
+			POSITION_REAR_SURROUND_LEFT | POSITION_REAR_SURROUND_RIGHT
+		)
+	});
+
+	let mut mask = ChannelMask(0);
+	for position in positions {
+		let channel = match *position {
+			0 => ChannelMask::FRONT_LEFT,
+			1 => ChannelMask::FRONT_RIGHT,
+			2 => ChannelMask::FRONT_CENTER,
+			3 => ChannelMask::LOW_FREQUENCY,
+			POSITION_LEFT_SURROUND if has_rear_surround => ChannelMask::SIDE_LEFT,
+			POSITION_RIGHT_SURROUND if has_rear_surround => ChannelMask::SIDE_RIGHT,
+			POSITION_LEFT_SURROUND | POSITION_REAR_SURROUND_LEFT => ChannelMask::BACK_LEFT,
+			POSITION_RIGHT_SURROUND | POSITION_REAR_SURROUND_RIGHT => ChannelMask::BACK_RIGHT,
+			6 => ChannelMask::FRONT_LEFT_OF_CENTER,
+			7 => ChannelMask::FRONT_RIGHT_OF_CENTER,
+			10 => ChannelMask::BACK_CENTER,
+			13 => ChannelMask::SIDE_LEFT,
+			14 => ChannelMask::SIDE_RIGHT,
+			17 => ChannelMask::TOP_FRONT_LEFT,
+			18 => ChannelMask::TOP_FRONT_RIGHT,
+			19 => ChannelMask::TOP_FRONT_CENTER,
+			20 => ChannelMask::TOP_BACK_LEFT,
+			21 => ChannelMask::TOP_BACK_RIGHT,
+			22 => ChannelMask::TOP_BACK_CENTER,
+			25 => ChannelMask::TOP_CENTER,
+			_ => {
+				log::debug!("Unsupported speaker position: {position}");
+				return None;
+			},
+		};
+
+		mask = mask | channel;
+	}
+
+	Some(mask)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_chan, parse_chnl};
+	use crate::properties::ChannelMask;
+
+	fn surround_5_1() -> ChannelMask {
+		ChannelMask::linear_surround()
+			| ChannelMask::LOW_FREQUENCY
+			| ChannelMask::BACK_LEFT
+			| ChannelMask::BACK_RIGHT
+	}
+
+	#[test_log::test]
+	fn chan_layout_tag() {
+		#[rustfmt::skip]
+		let content = [
+			// Version, flags
+			0, 0, 0, 0,
+			// MPEG_5_1_A
+			0, 121, 0, 6,
+			// Bitmap, description count
+			0, 0, 0, 0, 0, 0, 0, 0,
+		];
+
+		assert_eq!(parse_chan(&content).unwrap(), Some(surround_5_1()));
+	}
+
+	#[test_log::test]
+	fn chan_bitmap() {
+		#[rustfmt::skip]
+		let content = [
+			0, 0, 0, 0,
+			// UseChannelBitmap
+			0, 1, 0, 0,
+			// Bitmap (L R C LFE Ls Rs)
+			0, 0, 0, 0x3F,
+			0, 0, 0, 0,
+		];
+
+		assert_eq!(parse_chan(&content).unwrap(), Some(surround_5_1()));
+	}
+
+	#[test_log::test]
+	fn chan_descriptions() {
+		let mut content = vec![0; 12];
+		content.extend(4u32.to_be_bytes());
+
+		// L, R, rear surround left, rear surround right
+		for label in [1u32, 2, 33, 34] {
+			content.extend(label.to_be_bytes());
+			content.extend([0; 16]);
+		}
+
+		assert_eq!(
+			parse_chan(&content).unwrap(),
+			Some(ChannelMask::stereo() | ChannelMask::BACK_LEFT | ChannelMask::BACK_RIGHT)
+		);
+	}
+
+	#[test_log::test]
+	fn chnl_defined_layout() {
+		#[rustfmt::skip]
+		let content = [
+			// Version, flags
+			0, 0, 0, 0,
+			// Channel structured, 7.1 (rear surround)
+			1, 12,
+			// Omitted channels (LFE)
+			0, 0, 0, 0, 0, 0, 0, 0x80,
+		];
+
+		assert_eq!(
+			parse_chnl(&content, 7).unwrap(),
+			Some(
+				ChannelMask::linear_surround()
+					| ChannelMask::SIDE_LEFT
+					| ChannelMask::SIDE_RIGHT
+					| ChannelMask::BACK_LEFT
+					| ChannelMask::BACK_RIGHT
+			)
+		);
+	}
+
+	#[test_log::test]
+	fn chnl_explicit_positions() {
+		#[rustfmt::skip]
+		let content = [
+			0, 0, 0, 0,
+			// Channel structured, explicit layout
+			1, 0,
+			// L, R, top front left, top front right
+			0, 1, 17, 18,
+		];
+
+		assert_eq!(
+			parse_chnl(&content, 4).unwrap(),
+			Some(
+				ChannelMask::stereo() | ChannelMask::TOP_FRONT_LEFT | ChannelMask::TOP_FRONT_RIGHT
+			)
+		);
+
+		// Explicit directions can't be represented
+		let content = [0, 0, 0, 0, 1, 0, 0, 126, 0, 30, 0];
+		assert_eq!(parse_chnl(&content, 2).unwrap(), None);
+	}
+}
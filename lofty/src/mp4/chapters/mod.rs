@@ -0,0 +1,31 @@
+pub(super) mod read;
+pub(super) mod write;
+
+use std::time::Duration;
+
+/// A chapter in an MP4 file
+///
+/// See [`Mp4File::chapters`](super::Mp4File::chapters) for the supported chapter formats.
+///
+/// # Examples
+///
+/// ```rust
+/// use lofty::mp4::Mp4Chapter;
+/// use std::time::Duration;
+///
+/// let chapter = Mp4Chapter::new(Duration::from_secs(90), String::from("Chapter 2"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Mp4Chapter {
+	/// The start of the chapter, relative to the start of the file
+	pub start: Duration,
+	/// The title of the chapter
+	pub title: String,
+}
+
+impl Mp4Chapter {
+	/// Create a new `Mp4Chapter`
+	pub fn new(start: Duration, title: String) -> Self {
+		Self { start, title }
+	}
+}
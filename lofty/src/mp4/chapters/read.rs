@@ -0,0 +1,389 @@
+use super::Mp4Chapter;
+use crate::config::ParsingMode;
+use crate::macros::try_vec;
+use crate::mp4::Mp4Track;
+use crate::mp4::atom_info::{AtomIdent, AtomInfo};
+use crate::mp4::error::AtomParseError;
+use crate::mp4::moov::Trak;
+use crate::mp4::read::{AtomReader, find_child_atom, skip_atom};
+use crate::util::text::{utf8_decode, utf16_decode_bytes};
+
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+/// Nero chapter start times are stored in 100 nanosecond units
+pub(in crate::mp4) const CHPL_TIMESCALE: u32 = 10_000_000;
+
+/// Parse a Nero `chpl` atom
+///
+/// NOTE: This expects the reader to be at the start of the atom's content, and will always read
+///       the entire atom.
+pub(in crate::mp4) fn parse_chpl<R>(
+	reader: &mut R,
+	atom: &AtomInfo,
+) -> Result<Vec<Mp4Chapter>, AtomParseError>
+where
+	R: Read,
+{
+	fn parse_inner(mut content: &[u8]) -> Result<Vec<Mp4Chapter>, AtomParseError> {
+		let version = content.read_u8()?;
+		let _flags = content.read_u24::<BigEndian>()?;
+
+		if version > 0 {
+			let _reserved = content.read_u32::<BigEndian>()?;
+		}
+
+		let chapter_count = content.read_u8()?;
+
+		let mut chapters = Vec::with_capacity(usize::from(chapter_count));
+		for _ in 0..chapter_count {
+			let start = content.read_u64::<BigEndian>()?;
+
+			let title_len = content.read_u8()?;
+			let mut title = vec![0; usize::from(title_len)];
+			content.read_exact(&mut title)?;
+
+			chapters.push(Mp4Chapter {
+				start: timescale_to_duration(start, CHPL_TIMESCALE),
+				title: utf8_decode(title)?,
+			});
+		}
+
+		Ok(chapters)
+	}
+
+	let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+	reader.read_exact(&mut content)?;
+
+	parse_inner(&content).map_err(|e| e.with_ident(atom.ident.clone()))
+}
+
+/// A QuickTime chapter track
+pub(in crate::mp4) struct ChapterTrack {
+	pub(in crate::mp4) id: u32,
+	// The offset and size of each sample
+	pub(in crate::mp4) samples: Vec<(u64, u32)>,
+	pub(in crate::mp4) chapters: Vec<Mp4Chapter>,
+}
+
+/// Read the first chapter track referenced by a `trak.tref.chap` atom
+///
+/// NOTE: The reader is expected to cover the entire file, as the samples are stored outside of `moov`.
+pub(in crate::mp4) fn read_chapter_track<R>(
+	reader: &mut AtomReader<R>,
+	traks: &[Trak],
+	parse_mode: ParsingMode,
+) -> Result<Option<ChapterTrack>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let chapter_trak = traks
+		.iter()
+		.flat_map(|trak| &trak.chapter_track_ids)
+		.find_map(|id| traks.iter().find(|trak| trak.track.id == *id));
+
+	let Some(Trak {
+		track: Mp4Track { id, .. },
+		mdia: Some(mdia),
+		..
+	}) = chapter_trak
+	else {
+		return Ok(None);
+	};
+
+	log::debug!("Found a chapter track, ID: {id}");
+
+	let Some((timescale, sample_table)) = read_text_media(reader, mdia, parse_mode)? else {
+		return Ok(None);
+	};
+
+	let samples = sample_table.samples();
+
+	let mut chapters = Vec::with_capacity(samples.len());
+	let mut sample_durations = sample_table
+		.time_to_sample
+		.iter()
+		.flat_map(|&(count, duration)| std::iter::repeat_n(duration, count as usize));
+
+	let mut time = 0;
+	for &(offset, size) in &samples {
+		let mut title = String::new();
+
+		// The sample is a 16-bit length, followed by the text and some optional atoms
+		if size >= 2 {
+			reader.seek(SeekFrom::Start(offset))?;
+
+			let text_len = std::cmp::min(reader.read_u16()?, (size - 2) as u16);
+			let mut text = try_vec![0; usize::from(text_len)]?;
+			reader.read_exact(&mut text)?;
+
+			title = match text.as_slice() {
+				[0xFE, 0xFF, ..] => utf16_decode_bytes(&text[2..], u16::from_be_bytes)?,
+				[0xFF, 0xFE, ..] => utf16_decode_bytes(&text[2..], u16::from_le_bytes)?,
+				_ => utf8_decode(text)?,
+			};
+		}
+
+		chapters.push(Mp4Chapter {
+			start: timescale_to_duration(time, timescale),
+			title,
+		});
+
+		time += u64::from(sample_durations.next().unwrap_or(0));
+	}
+
+	Ok(Some(ChapterTrack {
+		id: *id,
+		samples,
+		chapters,
+	}))
+}
+
+#[derive(Default)]
+struct SampleTable {
+	// (sample count, sample duration)
+	time_to_sample: Vec<(u32, u32)>,
+	// (first chunk, samples per chunk)
+	sample_to_chunk: Vec<(u32, u32)>,
+	sample_sizes: Vec<u32>,
+	chunk_offsets: Vec<u64>,
+}
+
+impl SampleTable {
+	/// Get the offset and size of each sample
+	fn samples(&self) -> Vec<(u64, u32)> {
+		let mut samples = Vec::with_capacity(self.sample_sizes.len());
+		let mut sizes = self.sample_sizes.iter().copied();
+
+		for (i, &(first_chunk, samples_per_chunk)) in self.sample_to_chunk.iter().enumerate() {
+			let last_chunk = match self.sample_to_chunk.get(i + 1) {
+				Some(&(next_first_chunk, _)) => next_first_chunk.saturating_sub(1),
+				None => self.chunk_offsets.len() as u32,
+			};
+
+			for chunk in first_chunk..=last_chunk {
+				// Chunk numbers start at 1
+				let Some(&chunk_offset) = self.chunk_offsets.get((chunk as usize).wrapping_sub(1))
+				else {
+					break;
+				};
+
+				let mut offset = chunk_offset;
+				for _ in 0..samples_per_chunk {
+					let Some(size) = sizes.next() else {
+						return samples;
+					};
+
+					samples.push((offset, size));
+					offset += u64::from(size);
+				}
+			}
+		}
+
+		samples
+	}
+}
+
+/// Read the timescale and sample table of a text track's `mdia` atom
+fn read_text_media<R>(
+	reader: &mut AtomReader<R>,
+	mdia: &AtomInfo,
+	parse_mode: ParsingMode,
+) -> Result<Option<(u32, SampleTable)>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut timescale = None;
+	let mut sample_table = None;
+
+	reader.seek(SeekFrom::Start(mdia.start + mdia.header_size()))?;
+
+	let mut read = mdia.header_size();
+	while read < mdia.len {
+		let Some(atom) = reader.next()? else { break };
+
+		read += atom.len;
+
+		let AtomIdent::Fourcc(fourcc) = atom.ident else {
+			skip_atom(reader, atom.extended, atom.len)?;
+			continue;
+		};
+
+		match &fourcc {
+			b"mdhd" if atom.len >= 24 => {
+				let version = reader.read_u8()?;
+				let _flags = reader.read_u24()?;
+
+				// Skip the creation and modification times
+				let times_len = if version == 1 { 16 } else { 8 };
+				reader.seek(SeekFrom::Current(times_len))?;
+
+				timescale = Some(reader.read_u32()?);
+
+				skip_atom(reader, atom.extended, atom.len - (times_len as u64 + 8))?;
+			},
+			b"hdlr" if atom.len >= 20 => {
+				// Version (1), flags (3), and pre-defined (4)
+				reader.seek(SeekFrom::Current(8))?;
+
+				let mut handler_type = [0; 4];
+				reader.read_exact(&mut handler_type)?;
+
+				if &handler_type != b"text" {
+					log::warn!(
+						"Chapter track has an unexpected handler type: {}",
+						handler_type.escape_ascii()
+					);
+					return Ok(None);
+				}
+
+				skip_atom(reader, atom.extended, atom.len - 12)?;
+			},
+			b"minf" => {
+				let Some(stbl) = find_child_atom(reader, atom.len, *b"stbl", parse_mode)? else {
+					return Ok(None);
+				};
+
+				sample_table = Some(read_stbl(reader, &stbl)?);
+				break;
+			},
+			_ => skip_atom(reader, atom.extended, atom.len)?,
+		}
+	}
+
+	match (timescale, sample_table) {
+		(Some(timescale), Some(sample_table)) if timescale > 0 => {
+			Ok(Some((timescale, sample_table)))
+		},
+		_ => {
+			log::warn!("Chapter track is missing its timescale or sample table");
+			Ok(None)
+		},
+	}
+}
+
+fn read_stbl<R>(reader: &mut AtomReader<R>, stbl: &AtomInfo) -> Result<SampleTable, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut sample_table = SampleTable::default();
+
+	let mut read = stbl.header_size();
+	while read < stbl.len {
+		let Some(atom) = reader.next()? else { break };
+
+		read += atom.len;
+
+		let AtomIdent::Fourcc(fourcc) = atom.ident else {
+			skip_atom(reader, atom.extended, atom.len)?;
+			continue;
+		};
+
+		// All of the tables are full atoms
+		match &fourcc {
+			b"stts" => {
+				let _version_and_flags = reader.read_u32()?;
+				let entry_count = reader.read_u32()?;
+				for _ in 0..entry_count {
+					let sample_count = reader.read_u32()?;
+					let sample_duration = reader.read_u32()?;
+					sample_table
+						.time_to_sample
+						.push((sample_count, sample_duration));
+				}
+			},
+			b"stsc" => {
+				let _version_and_flags = reader.read_u32()?;
+				let entry_count = reader.read_u32()?;
+				for _ in 0..entry_count {
+					let first_chunk = reader.read_u32()?;
+					let samples_per_chunk = reader.read_u32()?;
+					let _sample_description_index = reader.read_u32()?;
+					sample_table
+						.sample_to_chunk
+						.push((first_chunk, samples_per_chunk));
+				}
+			},
+			b"stsz" => {
+				let _version_and_flags = reader.read_u32()?;
+				let sample_size = reader.read_u32()?;
+				let sample_count = reader.read_u32()?;
+
+				// A non-zero size applies to every sample
+				if sample_size == 0 {
+					for _ in 0..sample_count {
+						sample_table.sample_sizes.push(reader.read_u32()?);
+					}
+				} else {
+					sample_table.sample_sizes = try_vec![sample_size; sample_count as usize]?;
+				}
+			},
+			b"stco" => {
+				let _version_and_flags = reader.read_u32()?;
+				let entry_count = reader.read_u32()?;
+				for _ in 0..entry_count {
+					sample_table
+						.chunk_offsets
+						.push(u64::from(reader.read_u32()?));
+				}
+			},
+			b"co64" => {
+				let _version_and_flags = reader.read_u32()?;
+				let entry_count = reader.read_u32()?;
+				for _ in 0..entry_count {
+					sample_table.chunk_offsets.push(reader.read_u64()?);
+				}
+			},
+			_ => skip_atom(reader, atom.extended, atom.len)?,
+		}
+	}
+
+	Ok(sample_table)
+}
+
+fn timescale_to_duration(time: u64, timescale: u32) -> Duration {
+	let nanos = u128::from(time) * 1_000_000_000 / u128::from(timescale);
+	Duration::from_nanos(nanos as u64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::parse_chpl;
+	use crate::mp4::Mp4Chapter;
+	use crate::mp4::atom_info::{AtomIdent, AtomInfo};
+
+	use std::time::Duration;
+
+	#[test_log::test]
+	fn chpl() {
+		#[rustfmt::skip]
+		let content = [
+			// Version 1, flags, reserved
+			1, 0, 0, 0, 0, 0, 0, 0,
+			// Chapter count
+			2,
+			// 0s, "Intro"
+			0, 0, 0, 0, 0, 0, 0, 0, 5, b'I', b'n', b't', b'r', b'o',
+			// 1.5s, "Outro"
+			0, 0, 0, 0, 0, 0xE4, 0xE1, 0xC0, 5, b'O', b'u', b't', b'r', b'o',
+		];
+
+		let atom = AtomInfo {
+			start: 0,
+			len: content.len() as u64 + 8,
+			extended: false,
+			ident: AtomIdent::Fourcc(*b"chpl"),
+		};
+
+		let chapters = parse_chpl(&mut &content[..], &atom).unwrap();
+		assert_eq!(
+			chapters,
+			[
+				Mp4Chapter::new(Duration::ZERO, String::from("Intro")),
+				Mp4Chapter::new(Duration::from_millis(1500), String::from("Outro")),
+			]
+		);
+	}
+}
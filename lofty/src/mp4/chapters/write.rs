@@ -0,0 +1,725 @@
+use super::Mp4Chapter;
+use super::read::{CHPL_TIMESCALE, ChapterTrack, read_chapter_track};
+use crate::config::WriteOptions;
+use crate::error::{FileEncodingError, FileParseError};
+use crate::mp4::atom_info::{ATOM_HEADER_LEN, AtomIdent, AtomInfo};
+use crate::mp4::error::Mp4ParseError;
+use crate::mp4::moov::Moov;
+use crate::mp4::read::{AtomReader, verify_mp4};
+use crate::mp4::write::{
+	AtomWriter, ContextualAtom, Mp4WriteStrategy, atom, atom_bytes, atom_content, fast_start,
+	read_atom, truncate_str,
+};
+use crate::util::io::FileLike;
+
+use std::io::{Seek, SeekFrom};
+
+/// Nero chapters are limited to 255 entries, with titles of up to 255 bytes
+const CHPL_MAX_LEN: usize = u8::MAX as usize;
+
+/// The timescale of the chapter tracks we create, in milliseconds
+const CHAPTER_TRACK_TIMESCALE: u32 = 1000;
+
+// The `text` sample description written by FFmpeg, which is understood by Apple's players
+#[rustfmt::skip]
+const TEXT_SAMPLE_DESCRIPTION: [u8; 43] = [
+	// Display flags
+	0x00, 0x00, 0x00, 0x01,
+	// Horizontal + vertical justification
+	0x00, 0x00,
+	// Background color (RGBA)
+	0x00, 0x00, 0x00, 0x00,
+	// Default text box (top, left, bottom, right)
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	// Start + end char
+	0x00, 0x00, 0x00, 0x00,
+	// Font ID
+	0x00, 0x01,
+	// Font style flags + font size
+	0x00, 0x00,
+	// Foreground color (RGBA)
+	0x00, 0x00, 0x00, 0x00,
+	// Font table (`ftab`) with a single, unnamed font
+	0x00, 0x00, 0x00, 0x0D, b'f', b't', b'a', b'b', 0x00, 0x01, 0x00, 0x01, 0x00,
+];
+
+// Marks a chapter title as UTF-8
+const ENCD_ATOM: [u8; 12] = [0, 0, 0, 12, b'e', b'n', b'c', b'd', 0, 0, 1, 0];
+
+#[rustfmt::skip]
+const UNITY_MATRIX: [u32; 9] = [
+	0x0001_0000, 0, 0,
+	0, 0x0001_0000, 0,
+	0, 0, 0x4000_0000,
+];
+
+/// Write chapters as both a Nero `chpl` atom and a QuickTime chapter track
+///
+/// Any existing chapters will be replaced. If `chapters` is empty, they will be removed.
+pub(in crate::mp4) fn write_to<F>(
+	file: &mut F,
+	chapters: &[Mp4Chapter],
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+{
+	log::debug!("Attempting to write chapters to file");
+
+	let parse_mode = write_options.parse_options.parsing_mode;
+
+	let mut reader = AtomReader::new(file, parse_mode)?;
+	verify_mp4(&mut reader).map_err(FileParseError::from)?;
+
+	let file = reader.into_inner();
+	file.rewind()?;
+
+	let atom_writer =
+		AtomWriter::new_from_file(file, parse_mode).map_err(Into::<FileParseError>::into)?;
+
+	let Some(moov) = atom_writer.find_contextual_atom(*b"moov") else {
+		return Err(FileParseError::from(Mp4ParseError::missing_moov()).into());
+	};
+
+	let ExistingChapters {
+		chpl,
+		chapter_track,
+	} = read_existing_chapters(&atom_writer, &moov.info, write_options)
+		.map_err(FileParseError::from)?;
+
+	let existing_chapters = match &chapter_track {
+		Some(chapter_track) => chapter_track.chapters.as_slice(),
+		None => chpl.as_deref().unwrap_or_default(),
+	};
+
+	if existing_chapters == chapters {
+		log::debug!("Chapters are unchanged, nothing to do");
+		fast_start(file, write_options)?;
+		return Ok(());
+	}
+
+	let mut sorted_chapters = chapters.to_vec();
+	sorted_chapters.sort_by_key(|chapter| chapter.start);
+
+	let moov_start = moov.info.start;
+	let moov_len = moov.info.len;
+
+	let moov_bytes = read_atom(&atom_writer, &moov.info)?;
+	let Some(movie_header) = MovieHeader::find(&moov_bytes, moov) else {
+		return Err(FileParseError::from(Mp4ParseError::message(
+			"file does not contain a \"mvhd\" atom",
+		))
+		.into());
+	};
+
+	let mut context = BuildContext {
+		moov,
+		moov_start,
+		chapters: &sorted_chapters,
+		movie_header,
+		old_chapter_track_id: chapter_track.as_ref().map(|chapter_track| chapter_track.id),
+		new_chapter_track: None,
+	};
+
+	if !sorted_chapters.is_empty() {
+		match find_audio_trak(&moov_bytes, &context) {
+			Some(audio_trak) => {
+				let max_id = moov
+					.find_all_children(*b"trak", false)
+					.filter_map(|trak| trak_id(&moov_bytes, moov_start, trak))
+					.max()
+					.unwrap_or(0);
+
+				context.new_chapter_track = Some(NewChapterTrack {
+					id: std::cmp::max(max_id + 1, movie_header.next_track_id),
+					audio_trak_start: audio_trak.start,
+					chunk_offset: 0,
+					large_offset: false,
+				});
+			},
+			None => log::warn!("File has no audio track, unable to create a chapter track"),
+		}
+	}
+
+	// A previous chapter track may have had its samples stored in an `mdat` at the end of the file,
+	// which we can remove along with it.
+	let mut reclaimed_mdat = None;
+	if let Some(chapter_track) = &chapter_track
+		&& let Some(last_atom) = atom_writer.atoms().last()
+		&& last_atom.info.ident == AtomIdent::Fourcc(*b"mdat")
+	{
+		let mdat = &last_atom.info;
+		let data_start = mdat.start + mdat.header_size();
+		let samples_len = chapter_track
+			.samples
+			.iter()
+			.map(|(_, size)| u64::from(*size))
+			.sum::<u64>();
+
+		if samples_len == mdat.len - mdat.header_size()
+			&& chapter_track
+				.samples
+				.iter()
+				.all(|(offset, _)| *offset >= data_start)
+		{
+			log::trace!("Removing the `mdat` atom of the previous chapter track");
+			reclaimed_mdat = Some(mdat.len);
+		}
+	}
+
+	// Without any new samples, everything following `moov` may just be padding for the old ones
+	let moov_becomes_last = reclaimed_mdat.is_some()
+		&& context.new_chapter_track.is_none()
+		&& atom_writer.atoms().split_last().is_some_and(|(_, rest)| {
+			rest.iter()
+				.filter(|atom| atom.info.start > moov_start)
+				.all(|atom| atom.info.ident == AtomIdent::Fourcc(*b"free"))
+		});
+
+	let file_len = atom_writer.start_write().len() as u64;
+
+	// Whether the new samples need a `co64` atom affects the size of `moov`, so it needs to be
+	// decided up front. `moov` can't grow by more than its new size.
+	let max_moov_len = build_moov(&moov_bytes, &context).len() as u64 + 4;
+	if let Some(new_chapter_track) = &mut context.new_chapter_track {
+		new_chapter_track.large_offset =
+			u32::try_from(file_len + max_moov_len + ATOM_HEADER_LEN).is_err();
+	}
+
+	let new_moov_len = build_moov(&moov_bytes, &context).len() as u64;
+	let difference = new_moov_len as i64 - moov_len as i64;
+
+	// Update the offsets before copying the `moov` atom, so our copy will have the new offsets
+	let strategy = atom_writer.resize_moov(moov, difference)?;
+
+	// The previous chapter samples are always at the very end, and the new ones take their place
+	let samples_start = atom_writer.start_write().len() as u64 - reclaimed_mdat.unwrap_or(0);
+	let new_samples_start = (samples_start as i64 + difference) as u64;
+	if let Some(new_chapter_track) = &mut context.new_chapter_track {
+		new_chapter_track.chunk_offset = new_samples_start + ATOM_HEADER_LEN;
+	}
+
+	let moov_bytes = read_atom(&atom_writer, &moov.info)?;
+	let new_moov = build_moov(&moov_bytes, &context);
+
+	let mut write_handle = atom_writer.start_write();
+
+	let len = write_handle.len();
+	write_handle.splice(samples_start as usize..len, []);
+
+	if context.new_chapter_track.is_some() {
+		let mut mdat = Vec::new();
+		for chapter in &sorted_chapters {
+			mdat.extend(chapter_sample(chapter));
+		}
+
+		write_handle.splice(
+			samples_start as usize..samples_start as usize,
+			atom(*b"mdat", &mdat),
+		);
+	}
+
+	write_handle.splice(
+		moov_start as usize..(moov_start + moov_len) as usize,
+		new_moov,
+	);
+
+	let mut strategy = strategy;
+	if moov_becomes_last {
+		log::trace!("Removing the padding after `moov`, it is now the last atom");
+
+		let len = write_handle.len();
+		write_handle.splice((moov_start + new_moov_len) as usize..len, []);
+		strategy = Mp4WriteStrategy::MoovResized;
+	}
+
+	drop(write_handle);
+
+	atom_writer.save_moov_to(file, moov, strategy)?;
+
+	// Nothing else moved, but the chapter samples at the end of the file may have changed
+	if strategy == Mp4WriteStrategy::InPlace
+		&& (reclaimed_mdat.is_some() || context.new_chapter_track.is_some())
+	{
+		atom_writer.save_tail_to(file, new_samples_start)?;
+	}
+
+	fast_start(file, write_options)?;
+
+	Ok(())
+}
+
+struct ExistingChapters {
+	chpl: Option<Vec<Mp4Chapter>>,
+	chapter_track: Option<ChapterTrack>,
+}
+
+fn read_existing_chapters(
+	atom_writer: &AtomWriter,
+	moov: &AtomInfo,
+	write_options: WriteOptions,
+) -> Result<ExistingChapters, Mp4ParseError> {
+	let mut parse_options = write_options.parse_options;
+	parse_options.read_properties(false);
+	parse_options.read_tags(true);
+	parse_options.read_cover_art(false);
+
+	let mut write_handle = atom_writer.start_write();
+	let mut reader = AtomReader::new(&mut write_handle, parse_options.parsing_mode)?;
+	let file_len = reader.len();
+
+	reader.seek(SeekFrom::Start(moov.start + moov.header_size()))?;
+	reader.reset_bounds(
+		moov.start + moov.header_size(),
+		moov.len - moov.header_size(),
+	);
+	let Moov { traks, chpl, .. } = Moov::parse(&mut reader, parse_options)?;
+
+	reader.reset_bounds(0, file_len);
+	let chapter_track = read_chapter_track(&mut reader, &traks, parse_options.parsing_mode)?;
+
+	Ok(ExistingChapters {
+		chpl,
+		chapter_track,
+	})
+}
+
+#[derive(Copy, Clone)]
+struct MovieHeader {
+	timescale: u32,
+	duration: u64,
+	next_track_id: u32,
+	// The offset of the next track ID, relative to the start of `moov`
+	next_track_id_offset: usize,
+}
+
+impl MovieHeader {
+	fn find(moov_bytes: &[u8], moov: &ContextualAtom) -> Option<Self> {
+		let mvhd = moov.find_all_children(*b"mvhd", false).next()?;
+		let content = atom_content(moov_bytes, moov.info.start, mvhd);
+
+		let version = *content.first()?;
+		let (timescale, duration, fields_len) = if version == 1 {
+			(
+				read_u32(content, 20)?,
+				u64::from_be_bytes(content.get(24..32)?.try_into().ok()?),
+				32,
+			)
+		} else {
+			(
+				read_u32(content, 12)?,
+				u64::from(read_u32(content, 16)?),
+				20,
+			)
+		};
+
+		// Rate (4), volume (2), reserved (10), matrix (36), and pre-defined (24)
+		let next_track_id_content_offset = fields_len + 76;
+
+		Some(Self {
+			timescale,
+			duration,
+			next_track_id: read_u32(content, next_track_id_content_offset)?,
+			next_track_id_offset: (mvhd.start - moov.info.start + mvhd.header_size()) as usize
+				+ next_track_id_content_offset,
+		})
+	}
+
+	fn duration_millis(self) -> u64 {
+		if self.timescale == 0 {
+			return 0;
+		}
+
+		(u128::from(self.duration) * u128::from(CHAPTER_TRACK_TIMESCALE)
+			/ u128::from(self.timescale)) as u64
+	}
+}
+
+struct NewChapterTrack {
+	id: u32,
+	// The `trak` that will reference the chapter track
+	audio_trak_start: u64,
+	chunk_offset: u64,
+	// Whether `chunk_offset` needs a `co64` atom rather than `stco`
+	large_offset: bool,
+}
+
+struct BuildContext<'a> {
+	moov: &'a ContextualAtom,
+	moov_start: u64,
+	chapters: &'a [Mp4Chapter],
+	movie_header: MovieHeader,
+	old_chapter_track_id: Option<u32>,
+	new_chapter_track: Option<NewChapterTrack>,
+}
+
+fn build_moov(moov_bytes: &[u8], context: &BuildContext<'_>) -> Vec<u8> {
+	let moov_start = context.moov_start;
+
+	let mut content = Vec::with_capacity(moov_bytes.len());
+	let mut found_udta = false;
+
+	for child in &context.moov.children {
+		let bytes = atom_bytes(moov_bytes, moov_start, &child.info);
+
+		match child.info.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"mvhd" => {
+				let start = content.len();
+				content.extend_from_slice(bytes);
+
+				if let Some(new_chapter_track) = &context.new_chapter_track {
+					let offset = start + context.movie_header.next_track_id_offset
+						- (child.info.start - moov_start) as usize;
+					content[offset..offset + 4]
+						.copy_from_slice(&(new_chapter_track.id + 1).to_be_bytes());
+				}
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"trak" => {
+				let id = trak_id(moov_bytes, moov_start, &child.info);
+				if id.is_some() && id == context.old_chapter_track_id {
+					log::trace!("Removing the previous chapter track");
+					continue;
+				}
+
+				let chapter_track_id = context
+					.new_chapter_track
+					.as_ref()
+					.filter(|new_chapter_track| {
+						new_chapter_track.audio_trak_start == child.info.start
+					})
+					.map(|new_chapter_track| new_chapter_track.id);
+
+				content.extend(build_trak(moov_bytes, moov_start, child, chapter_track_id));
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"udta" => {
+				found_udta = true;
+
+				let mut udta = build_chpl(context.chapters);
+				for udta_child in &child.children {
+					if udta_child.info.ident != AtomIdent::Fourcc(*b"chpl") {
+						udta.extend_from_slice(atom_bytes(
+							moov_bytes,
+							moov_start,
+							&udta_child.info,
+						));
+					}
+				}
+
+				if !udta.is_empty() {
+					content.extend(atom(*b"udta", &udta));
+				}
+			},
+			_ => content.extend_from_slice(bytes),
+		}
+	}
+
+	if !found_udta {
+		let chpl = build_chpl(context.chapters);
+		if !chpl.is_empty() {
+			content.extend(atom(*b"udta", &chpl));
+		}
+	}
+
+	if let Some(new_chapter_track) = &context.new_chapter_track {
+		content.extend(build_chapter_trak(
+			new_chapter_track,
+			context.chapters,
+			context.movie_header,
+		));
+	}
+
+	atom(*b"moov", &content)
+}
+
+/// Copy a `trak` atom, replacing any `tref.chap` atoms
+fn build_trak(
+	moov_bytes: &[u8],
+	moov_start: u64,
+	trak: &ContextualAtom,
+	chapter_track_id: Option<u32>,
+) -> Vec<u8> {
+	let chap = chapter_track_id.map(|id| atom(*b"chap", &id.to_be_bytes()));
+
+	let mut content = Vec::new();
+	let mut wrote_tref = false;
+	for child in &trak.children {
+		let is_tref = child.info.ident == AtomIdent::Fourcc(*b"tref");
+		let is_mdia = child.info.ident == AtomIdent::Fourcc(*b"mdia");
+
+		// Place a new `tref` atom before `mdia`
+		if is_mdia
+			&& !wrote_tref
+			&& let Some(chap) = &chap
+		{
+			content.extend(atom(*b"tref", chap));
+			wrote_tref = true;
+		}
+
+		if !is_tref {
+			content.extend_from_slice(atom_bytes(moov_bytes, moov_start, &child.info));
+			continue;
+		}
+
+		// Keep all references other than `chap`
+		let mut tref = Vec::new();
+		let mut references = atom_content(moov_bytes, moov_start, &child.info);
+		while let Some(len) = read_u32(references, 0) {
+			let len = (len as usize).clamp(ATOM_HEADER_LEN as usize, references.len());
+			let (reference, remaining) = references.split_at(len);
+			if reference.get(4..8) != Some(b"chap") {
+				tref.extend_from_slice(reference);
+			}
+
+			references = remaining;
+		}
+
+		if let Some(chap) = &chap
+			&& !wrote_tref
+		{
+			tref.extend_from_slice(chap);
+			wrote_tref = true;
+		}
+
+		if !tref.is_empty() {
+			content.extend(atom(*b"tref", &tref));
+		}
+	}
+
+	if let Some(chap) = &chap
+		&& !wrote_tref
+	{
+		content.extend(atom(*b"tref", chap));
+	}
+
+	atom(*b"trak", &content)
+}
+
+fn build_chpl(chapters: &[Mp4Chapter]) -> Vec<u8> {
+	if chapters.is_empty() {
+		return Vec::new();
+	}
+
+	if chapters.len() > CHPL_MAX_LEN {
+		log::warn!("Too many chapters for a Nero `chpl` atom, only writing a chapter track");
+		return Vec::new();
+	}
+
+	// Version 1, flags, reserved, and the chapter count
+	let mut content = vec![1, 0, 0, 0, 0, 0, 0, 0, chapters.len() as u8];
+	for chapter in chapters {
+		let start = chapter.start.as_nanos() / u128::from(1_000_000_000 / CHPL_TIMESCALE);
+		let title = truncate_str(&chapter.title, CHPL_MAX_LEN);
+
+		content.extend((start as u64).to_be_bytes());
+		content.push(title.len() as u8);
+		content.extend_from_slice(title.as_bytes());
+	}
+
+	atom(*b"chpl", &content)
+}
+
+fn build_chapter_trak(
+	new_chapter_track: &NewChapterTrack,
+	chapters: &[Mp4Chapter],
+	movie_header: MovieHeader,
+) -> Vec<u8> {
+	let total_duration = movie_header.duration_millis();
+
+	// Chapter tracks always start at zero, so the first chapter is extended to cover it
+	let starts = chapters
+		.iter()
+		.enumerate()
+		.map(|(i, chapter)| {
+			if i == 0 {
+				0
+			} else {
+				chapter.start.as_millis() as u64
+			}
+		})
+		.collect::<Vec<_>>();
+
+	let durations = starts
+		.iter()
+		.enumerate()
+		.map(|(i, start)| {
+			let end = starts.get(i + 1).copied().unwrap_or(total_duration);
+			end.saturating_sub(*start) as u32
+		})
+		.collect::<Vec<_>>();
+
+	// tkhd, flags = 0 (disabled), as the track shouldn't be played
+	let mut tkhd = full_atom_header(u32::try_from(movie_header.duration).is_err());
+	let version_1 = tkhd[0] == 1;
+	push_time_fields(&mut tkhd, version_1);
+	tkhd.extend(new_chapter_track.id.to_be_bytes());
+	tkhd.extend([0; 4]);
+	push_duration(&mut tkhd, movie_header.duration, version_1);
+	// Reserved (8), layer (2), alternate group (2), volume (2), reserved (2)
+	tkhd.extend([0; 16]);
+	for value in UNITY_MATRIX {
+		tkhd.extend(value.to_be_bytes());
+	}
+	// Width and height
+	tkhd.extend([0; 8]);
+
+	// mdhd
+	let media_duration = durations.iter().map(|d| u64::from(*d)).sum::<u64>();
+	let mut mdhd = full_atom_header(u32::try_from(media_duration).is_err());
+	let version_1 = mdhd[0] == 1;
+	push_time_fields(&mut mdhd, version_1);
+	mdhd.extend(CHAPTER_TRACK_TIMESCALE.to_be_bytes());
+	push_duration(&mut mdhd, media_duration, version_1);
+	// Language ("und"), quality
+	mdhd.extend([0x55, 0xC4, 0, 0]);
+
+	// hdlr: version + flags, pre-defined, handler type, reserved (12), and an empty name
+	let mut hdlr = vec![0; 8];
+	hdlr.extend(b"text");
+	hdlr.extend([0; 13]);
+
+	// gmhd.gmin: version + flags, graphics mode, opcolor, balance, reserved
+	let gmin = [
+		0, 0, 0, 0, 0x00, 0x40, 0x80, 0, 0x80, 0, 0x80, 0, 0, 0, 0, 0,
+	];
+	let mut gmhd_text = Vec::new();
+	gmhd_text.extend(1u16.to_be_bytes());
+	for value in [0u32, 0, 0, 1, 0, 0, 0, 0x4000] {
+		gmhd_text.extend(value.to_be_bytes());
+	}
+	gmhd_text.extend([0; 2]);
+
+	let mut gmhd = atom(*b"gmin", &gmin);
+	gmhd.extend(atom(*b"text", &gmhd_text));
+
+	// dinf.dref, with a single self-reference
+	let mut dref = vec![0, 0, 0, 0, 0, 0, 0, 1];
+	dref.extend(atom(*b"url ", &[0, 0, 0, 1]));
+	let dinf = atom(*b"dref", &dref);
+
+	// stsd, with a single `text` sample description
+	let mut text = vec![0; 6];
+	text.extend(1u16.to_be_bytes());
+	text.extend(TEXT_SAMPLE_DESCRIPTION);
+
+	let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+	stsd.extend(atom(*b"text", &text));
+
+	let sample_count = chapters.len() as u32;
+
+	let mut stts = vec![0; 4];
+	stts.extend(sample_count.to_be_bytes());
+	for duration in &durations {
+		stts.extend(1u32.to_be_bytes());
+		stts.extend(duration.to_be_bytes());
+	}
+
+	// All samples are stored in a single chunk
+	let mut stsc = vec![0, 0, 0, 0, 0, 0, 0, 1];
+	stsc.extend(1u32.to_be_bytes());
+	stsc.extend(sample_count.to_be_bytes());
+	stsc.extend(1u32.to_be_bytes());
+
+	let mut stsz = vec![0; 8];
+	stsz.extend(sample_count.to_be_bytes());
+	for chapter in chapters {
+		stsz.extend((chapter_sample(chapter).len() as u32).to_be_bytes());
+	}
+
+	let mut stbl = atom(*b"stsd", &stsd);
+	stbl.extend(atom(*b"stts", &stts));
+	stbl.extend(atom(*b"stsc", &stsc));
+	stbl.extend(atom(*b"stsz", &stsz));
+
+	let chunk_offset = new_chapter_track.chunk_offset;
+	let mut chunk_offsets = vec![0, 0, 0, 0, 0, 0, 0, 1];
+	if new_chapter_track.large_offset {
+		chunk_offsets.extend(chunk_offset.to_be_bytes());
+		stbl.extend(atom(*b"co64", &chunk_offsets));
+	} else {
+		chunk_offsets.extend((chunk_offset as u32).to_be_bytes());
+		stbl.extend(atom(*b"stco", &chunk_offsets));
+	}
+
+	let mut minf = atom(*b"gmhd", &gmhd);
+	minf.extend(atom(*b"dinf", &dinf));
+	minf.extend(atom(*b"stbl", &stbl));
+
+	let mut mdia = atom(*b"mdhd", &mdhd);
+	mdia.extend(atom(*b"hdlr", &hdlr));
+	mdia.extend(atom(*b"minf", &minf));
+
+	let mut trak = atom(*b"tkhd", &tkhd);
+	trak.extend(atom(*b"mdia", &mdia));
+
+	atom(*b"trak", &trak)
+}
+
+/// A chapter track sample: a 16-bit length, the title, and its encoding
+fn chapter_sample(chapter: &Mp4Chapter) -> Vec<u8> {
+	let title = truncate_str(&chapter.title, u16::MAX as usize);
+
+	let mut sample = Vec::with_capacity(2 + title.len() + ENCD_ATOM.len());
+	sample.extend((title.len() as u16).to_be_bytes());
+	sample.extend_from_slice(title.as_bytes());
+	sample.extend(ENCD_ATOM);
+
+	sample
+}
+
+/// Find the first audio `trak`, other than the existing chapter track
+fn find_audio_trak<'a>(moov_bytes: &[u8], context: &BuildContext<'a>) -> Option<&'a AtomInfo> {
+	context
+		.moov
+		.children
+		.iter()
+		.filter(|trak| trak.info.ident == AtomIdent::Fourcc(*b"trak"))
+		.filter(|trak| {
+			let id = trak_id(moov_bytes, context.moov_start, &trak.info);
+			id.is_none() || id != context.old_chapter_track_id
+		})
+		.find(|trak| {
+			trak.find_all_children(*b"hdlr", true).any(|hdlr| {
+				atom_content(moov_bytes, context.moov_start, hdlr).get(8..12) == Some(b"soun")
+			})
+		})
+		.map(|trak| &trak.info)
+}
+
+fn trak_id(moov_bytes: &[u8], moov_start: u64, trak: &AtomInfo) -> Option<u32> {
+	let content = atom_content(moov_bytes, moov_start, trak);
+
+	// The `tkhd` atom should always be first
+	let tkhd = content.get(ATOM_HEADER_LEN as usize..)?;
+	if content.get(4..8)? != b"tkhd" {
+		return None;
+	}
+
+	// Skip the version, flags, and creation and modification times
+	match tkhd.first()? {
+		1 => read_u32(tkhd, 20),
+		_ => read_u32(tkhd, 12),
+	}
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+	let bytes = bytes.get(offset..offset + 4)?;
+	Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn full_atom_header(version_1: bool) -> Vec<u8> {
+	vec![u8::from(version_1), 0, 0, 0]
+}
+
+// Creation and modification times
+fn push_time_fields(content: &mut Vec<u8>, version_1: bool) {
+	let len = if version_1 { 16 } else { 8 };
+	content.extend(std::iter::repeat_n(0, len));
+}
+
+fn push_duration(content: &mut Vec<u8>, duration: u64, version_1: bool) {
+	if version_1 {
+		content.extend(duration.to_be_bytes());
+	} else {
+		content.extend((duration as u32).to_be_bytes());
+	}
+}
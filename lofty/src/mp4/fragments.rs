@@ -0,0 +1,351 @@
+use super::atom_info::{AtomIdent, AtomInfo};
+use super::error::AtomParseError;
+use super::read::{AtomReader, seek_to_end, skip_atom};
+use crate::macros::try_vec;
+
+use std::io::{Read, Seek};
+use std::time::Duration;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+// tfhd flags
+const TFHD_BASE_DATA_OFFSET: u32 = 0x01;
+const TFHD_SAMPLE_DESCRIPTION_INDEX: u32 = 0x02;
+const TFHD_DEFAULT_SAMPLE_DURATION: u32 = 0x08;
+const TFHD_DEFAULT_SAMPLE_SIZE: u32 = 0x10;
+
+// trun flags
+const TRUN_DATA_OFFSET: u32 = 0x01;
+const TRUN_FIRST_SAMPLE_FLAGS: u32 = 0x04;
+const TRUN_SAMPLE_DURATION: u32 = 0x100;
+const TRUN_SAMPLE_SIZE: u32 = 0x200;
+const TRUN_SAMPLE_FLAGS: u32 = 0x400;
+const TRUN_SAMPLE_COMPOSITION_TIME_OFFSET: u32 = 0x800;
+
+/// The parts of a `moov.mvex` atom we care about
+///
+/// The presence of this atom means the file is fragmented, with (most of) its samples described
+/// by `moof` atoms rather than the `moov.trak` sample tables.
+#[derive(Default)]
+pub(crate) struct Mvex {
+	// Represents moov.mvex.mehd, converted from the movie timescale
+	pub(crate) fragment_duration: Option<Duration>,
+	fragment_duration_units: Option<u64>,
+	pub(crate) trex: Vec<Trex>,
+}
+
+/// The per-track sample defaults from a `moov.mvex.trex` atom
+#[derive(Copy, Clone, Default)]
+pub(crate) struct Trex {
+	pub(crate) track_id: u32,
+	pub(crate) default_sample_duration: u32,
+	pub(crate) default_sample_size: u32,
+}
+
+impl Mvex {
+	pub(super) fn parse<R>(reader: &mut AtomReader<R>, len: u64) -> Result<Self, AtomParseError>
+	where
+		R: Read + Seek,
+	{
+		let mut mvex = Mvex::default();
+
+		let mut read = 8;
+		while read < len {
+			let Some(atom) = reader.next()? else { break };
+
+			read += atom.len;
+
+			match atom.ident {
+				AtomIdent::Fourcc(ref fourcc) if fourcc == b"mehd" && atom.len >= 16 => {
+					let version = reader.read_u8()?;
+					let _flags = reader.read_u24()?;
+
+					let fragment_duration = if version == 1 {
+						reader.read_u64()?
+					} else {
+						u64::from(reader.read_u32()?)
+					};
+
+					mvex.fragment_duration_units = Some(fragment_duration);
+				},
+				AtomIdent::Fourcc(ref fourcc) if fourcc == b"trex" && atom.len >= 32 => {
+					let _version_and_flags = reader.read_u32()?;
+					let track_id = reader.read_u32()?;
+					let _default_sample_description_index = reader.read_u32()?;
+					let default_sample_duration = reader.read_u32()?;
+					let default_sample_size = reader.read_u32()?;
+
+					mvex.trex.push(Trex {
+						track_id,
+						default_sample_duration,
+						default_sample_size,
+					});
+				},
+				_ => {},
+			}
+
+			seek_to_end(reader, &atom)?;
+		}
+
+		Ok(mvex)
+	}
+
+	/// Convert the `mehd` duration, now that the movie timescale is known
+	pub(super) fn set_movie_timescale(&mut self, timescale: u32) {
+		if timescale == 0 {
+			return;
+		}
+
+		self.fragment_duration = self.fragment_duration_units.map(|duration| {
+			let nanos = u128::from(duration) * 1_000_000_000 / u128::from(timescale);
+			Duration::from_nanos(nanos as u64)
+		});
+	}
+
+	/// The sample defaults for `track_id`
+	pub(super) fn trex(&self, track_id: u32) -> Trex {
+		self.trex
+			.iter()
+			.find(|trex| trex.track_id == track_id)
+			.copied()
+			.unwrap_or_default()
+	}
+}
+
+/// The totals of a single track's samples across all `moof` atoms
+#[derive(Default, Debug, PartialEq, Eq)]
+pub(super) struct FragmentSamples {
+	/// The total duration, in the media timescale
+	pub(super) duration: u64,
+	/// The total size of the samples, in bytes
+	pub(super) size: u64,
+	pub(super) count: u64,
+}
+
+impl FragmentSamples {
+	// The totals can only overflow with bogus sample counts, durations, or sizes
+	fn add(&mut self, duration: u64, size: u64, count: u64) -> Result<(), AtomParseError> {
+		let (Some(duration), Some(size), Some(count)) = (
+			self.duration.checked_add(duration),
+			self.size.checked_add(size),
+			self.count.checked_add(count),
+		) else {
+			return Err(AtomParseError::message(
+				None,
+				"fragment sample totals overflow",
+			));
+		};
+
+		self.duration = duration;
+		self.size = size;
+		self.count = count;
+		Ok(())
+	}
+}
+
+/// Sum the durations and sizes of a track's samples in every `moof` atom in the file
+///
+/// NOTE: The reader is expected to cover the entire file.
+pub(super) fn read_fragments<R>(
+	reader: &mut AtomReader<R>,
+	trex: Trex,
+) -> Result<FragmentSamples, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut samples = FragmentSamples::default();
+
+	reader.rewind()?;
+	while let Some(atom) = reader.next()? {
+		if atom.ident != AtomIdent::Fourcc(*b"moof") {
+			skip_atom(reader, atom.extended, atom.len)?;
+			continue;
+		}
+
+		let mut read = atom.header_size();
+		while read < atom.len {
+			let Some(traf) = reader.next()? else { break };
+
+			read += traf.len;
+
+			if traf.ident == AtomIdent::Fourcc(*b"traf") {
+				read_traf(reader, &traf, trex, &mut samples)?;
+			}
+
+			seek_to_end(reader, &traf)?;
+		}
+
+		seek_to_end(reader, &atom)?;
+	}
+
+	log::debug!(
+		"Found {} samples in movie fragments for track {}",
+		samples.count,
+		trex.track_id
+	);
+
+	Ok(samples)
+}
+
+fn read_traf<R>(
+	reader: &mut AtomReader<R>,
+	traf: &AtomInfo,
+	trex: Trex,
+	samples: &mut FragmentSamples,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut default_sample_duration = trex.default_sample_duration;
+	let mut default_sample_size = trex.default_sample_size;
+	let mut found_tfhd = false;
+
+	let mut read = traf.header_size();
+	while read < traf.len {
+		let Some(atom) = reader.next()? else { break };
+
+		read += atom.len;
+
+		match atom.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"tfhd" && atom.len >= 16 => {
+				let _version = reader.read_u8()?;
+				let flags = reader.read_u24()?;
+
+				// The `tfhd` comes first, so we can skip fragments of other tracks entirely
+				if reader.read_u32()? != trex.track_id {
+					return Ok(());
+				}
+
+				if flags & TFHD_BASE_DATA_OFFSET != 0 {
+					let _base_data_offset = reader.read_u64()?;
+				}
+				if flags & TFHD_SAMPLE_DESCRIPTION_INDEX != 0 {
+					let _sample_description_index = reader.read_u32()?;
+				}
+				if flags & TFHD_DEFAULT_SAMPLE_DURATION != 0 {
+					default_sample_duration = reader.read_u32()?;
+				}
+				if flags & TFHD_DEFAULT_SAMPLE_SIZE != 0 {
+					default_sample_size = reader.read_u32()?;
+				}
+
+				found_tfhd = true;
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"trun" && found_tfhd => {
+				let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+				reader.read_exact(&mut content)?;
+
+				read_trun(
+					&mut &content[..],
+					default_sample_duration,
+					default_sample_size,
+					samples,
+				)
+				.map_err(|e| e.with_ident(atom.ident.clone()))?;
+			},
+			_ => {},
+		}
+
+		seek_to_end(reader, &atom)?;
+	}
+
+	Ok(())
+}
+
+fn read_trun(
+	content: &mut &[u8],
+	default_sample_duration: u32,
+	default_sample_size: u32,
+	samples: &mut FragmentSamples,
+) -> Result<(), AtomParseError> {
+	let _version = content.read_u8()?;
+	let flags = content.read_u24::<BigEndian>()?;
+	let sample_count = content.read_u32::<BigEndian>()?;
+
+	if flags & TRUN_DATA_OFFSET != 0 {
+		let _data_offset = content.read_i32::<BigEndian>()?;
+	}
+	if flags & TRUN_FIRST_SAMPLE_FLAGS != 0 {
+		let _first_sample_flags = content.read_u32::<BigEndian>()?;
+	}
+
+	// Every field is the same for all samples, no need to read them
+	if flags & (TRUN_SAMPLE_DURATION | TRUN_SAMPLE_SIZE) == 0 {
+		let sample_count = u64::from(sample_count);
+		return samples.add(
+			sample_count * u64::from(default_sample_duration),
+			sample_count * u64::from(default_sample_size),
+			sample_count,
+		);
+	}
+
+	for _ in 0..sample_count {
+		let mut sample_duration = default_sample_duration;
+		let mut sample_size = default_sample_size;
+
+		if flags & TRUN_SAMPLE_DURATION != 0 {
+			sample_duration = content.read_u32::<BigEndian>()?;
+		}
+		if flags & TRUN_SAMPLE_SIZE != 0 {
+			sample_size = content.read_u32::<BigEndian>()?;
+		}
+		if flags & TRUN_SAMPLE_FLAGS != 0 {
+			let _sample_flags = content.read_u32::<BigEndian>()?;
+		}
+		if flags & TRUN_SAMPLE_COMPOSITION_TIME_OFFSET != 0 {
+			let _sample_composition_time_offset = content.read_u32::<BigEndian>()?;
+		}
+
+		samples.add(u64::from(sample_duration), u64::from(sample_size), 1)?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{FragmentSamples, read_trun};
+
+	#[test_log::test]
+	fn trun_defaults() {
+		#[rustfmt::skip]
+		let content = [
+			// Version, flags (data offset + sample sizes)
+			0, 0, 0x02, 0x01,
+			// Sample count
+			0, 0, 0, 2,
+			// Data offset
+			0, 0, 0, 0x50,
+			// Sample sizes
+			0, 0, 0x01, 0x00,
+			0, 0, 0x02, 0x00,
+		];
+
+		let mut samples = FragmentSamples::default();
+		read_trun(&mut &content[..], 1024, 0, &mut samples).unwrap();
+
+		assert_eq!(
+			samples,
+			FragmentSamples {
+				duration: 2048,
+				size: 0x300,
+				count: 2,
+			}
+		);
+	}
+
+	#[test_log::test]
+	fn trun_totals_overflow() {
+		#[rustfmt::skip]
+		let content = [
+			// Version, flags (none)
+			0, 0, 0, 0,
+			// Sample count
+			0xFF, 0xFF, 0xFF, 0xFF,
+		];
+
+		let mut samples = FragmentSamples::default();
+		read_trun(&mut &content[..], u32::MAX, u32::MAX, &mut samples).unwrap();
+		assert!(read_trun(&mut &content[..], u32::MAX, u32::MAX, &mut samples).is_err());
+	}
+}
@@ -0,0 +1,43 @@
+/// The kind of account a file was purchased with, as stored in the `akID` atom
+///
+/// See also:
+/// * <https://exiftool.org/TagNames/QuickTime.html>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccountKind {
+	/// *iTunes* (0)
+	ITunes,
+	/// *AOL* (1)
+	Aol,
+}
+
+impl AccountKind {
+	/// Returns the account kind as it appears in the `akID` atom
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::AccountKind;
+	///
+	/// assert_eq!(AccountKind::ITunes.as_u8(), 0);
+	/// assert_eq!(AccountKind::Aol.as_u8(), 1);
+	/// ```
+	pub fn as_u8(&self) -> u8 {
+		match self {
+			AccountKind::ITunes => 0,
+			AccountKind::Aol => 1,
+		}
+	}
+}
+
+impl TryFrom<u8> for AccountKind {
+	type Error = u8;
+
+	fn try_from(input: u8) -> Result<Self, Self::Error> {
+		match input {
+			0 => Ok(Self::ITunes),
+			1 => Ok(Self::Aol),
+			value => Err(value),
+		}
+	}
+}
@@ -371,4 +371,34 @@ impl AtomData {
 			AtomData::Unknown { code, .. } => *code,
 		}
 	}
+
+	/// Get the value of an integer as an unsigned integer of `size` (1-4) bytes
+	///
+	/// Negative values are reinterpreted, so `-1` with a `size` of 1 is `255`. This returns `None`
+	/// if the data isn't an integer, or doesn't fit in `size` bytes.
+	pub(crate) fn sized_integer(&self, size: usize) -> Option<u32> {
+		debug_assert!((1..=4).contains(&size));
+
+		let max = (1_u64 << (size * 8)) - 1;
+		let value = match self {
+			AtomData::SignedInteger(int) => {
+				let (int, min) = (i64::from(*int), -((max as i64 + 1) / 2));
+				if int < min || int > max as i64 {
+					return None;
+				}
+
+				int as u32 & max as u32
+			},
+			AtomData::UnsignedInteger(uint) => *uint,
+			AtomData::Unknown {
+				code: DataType::BeSignedInteger | DataType::BeUnsignedInteger,
+				data,
+			} if !data.is_empty() && data.len() <= size => data
+				.iter()
+				.fold(0, |value, byte| (value << 8) | u32::from(*byte)),
+			_ => return None,
+		};
+
+		(u64::from(value) <= max).then_some(value)
+	}
 }
@@ -21,3 +21,30 @@ pub mod flags {
 }
 
 pub(crate) const WELL_KNOWN_TYPE_SET: u8 = 0;
+
+/// Integer atoms that are expected to be a specific size, in bytes
+///
+/// These are always written with this size, rather than the smallest size that fits the value.
+const FIXED_SIZE_INTEGERS: [([u8; 4], usize); 10] = [
+	(*b"stik", 1),
+	(*b"rtng", 1),
+	(*b"akID", 1),
+	(*b"tmpo", 2),
+	(*b"tvsn", 4),
+	(*b"tves", 4),
+	(*b"sfID", 4),
+	(*b"atID", 4),
+	(*b"cnID", 4),
+	(*b"geID", 4),
+];
+
+/// The size of the integer stored in the atom, if it's expected to be a specific size
+pub(crate) fn fixed_integer_size(ident: &crate::mp4::AtomIdent<'_>) -> Option<usize> {
+	let crate::mp4::AtomIdent::Fourcc(fourcc) = ident else {
+		return None;
+	};
+
+	FIXED_SIZE_INTEGERS
+		.iter()
+		.find_map(|(ident, size)| (ident == fourcc).then_some(*size))
+}
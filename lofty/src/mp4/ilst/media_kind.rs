@@ -0,0 +1,80 @@
+/// The kind of media, as stored in the `stik` atom
+///
+/// See also:
+/// * <https://exiftool.org/TagNames/QuickTime.html>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MediaKind {
+	/// *Normal*/*Music* (1)
+	Music,
+	/// *Audiobook* (2)
+	Audiobook,
+	/// *Whacked Bookmark* (5)
+	WhackedBookmark,
+	/// *Music Video* (6)
+	MusicVideo,
+	/// *Movie* (0 or 9)
+	///
+	/// Older versions of iTunes used the value `0` for movies, which has since been
+	/// replaced by `9`. Both values are considered as valid when reading but only the newer
+	/// value `9` is written.
+	Movie,
+	/// *TV Show* (10)
+	TvShow,
+	/// *Booklet* (11)
+	Booklet,
+	/// *Ringtone* (14)
+	Ringtone,
+	/// *Podcast* (21)
+	Podcast,
+	/// *iTunes U* (23)
+	ITunesU,
+}
+
+impl MediaKind {
+	/// Returns the media kind as it appears in the `stik` atom
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::MediaKind;
+	///
+	/// assert_eq!(MediaKind::Music.as_u8(), 1);
+	/// assert_eq!(MediaKind::Movie.as_u8(), 9);
+	/// assert_eq!(MediaKind::Podcast.as_u8(), 21);
+	/// ```
+	pub fn as_u8(&self) -> u8 {
+		match self {
+			MediaKind::Music => 1,
+			MediaKind::Audiobook => 2,
+			MediaKind::WhackedBookmark => 5,
+			MediaKind::MusicVideo => 6,
+			MediaKind::Movie => 9,
+			MediaKind::TvShow => 10,
+			MediaKind::Booklet => 11,
+			MediaKind::Ringtone => 14,
+			MediaKind::Podcast => 21,
+			MediaKind::ITunesU => 23,
+		}
+	}
+}
+
+impl TryFrom<u8> for MediaKind {
+	type Error = u8;
+
+	fn try_from(input: u8) -> Result<Self, Self::Error> {
+		match input {
+			1 => Ok(Self::Music),
+			2 => Ok(Self::Audiobook),
+			5 => Ok(Self::WhackedBookmark),
+			6 => Ok(Self::MusicVideo),
+			0 | 9 => Ok(Self::Movie),
+			10 => Ok(Self::TvShow),
+			11 => Ok(Self::Booklet),
+			14 => Ok(Self::Ringtone),
+			21 => Ok(Self::Podcast),
+			23 => Ok(Self::ITunesU),
+			value => Err(value),
+		}
+	}
+}
@@ -1,10 +1,12 @@
+pub(super) mod account_kind;
 pub(super) mod advisory_rating;
 pub(super) mod atom;
 pub(super) mod constants;
 pub(super) mod data_type;
 pub(super) mod error;
+pub(super) mod media_kind;
 pub(super) mod read;
-mod r#ref;
+pub(super) mod r#ref;
 pub(crate) mod write;
 
 #[cfg(test)]
@@ -12,8 +14,9 @@ mod tests;
 
 use super::AtomIdent;
 use crate::config::{WriteOptions, global_options};
-use crate::error::{FileEncodingError, TagEncodingError};
+use crate::error::{FileEncodingError, TagEncodingError, UnsupportedTagError};
 use crate::io::VerifiedFile;
+use crate::mp4::Mp4WriteStrategy;
 use crate::mp4::ilst::atom::AtomDataStorage;
 use crate::picture::{Picture, PictureType};
 use crate::tag::companion_tag::CompanionTag;
@@ -24,9 +27,11 @@ use crate::tag::{
 };
 use crate::util::flag_item;
 use crate::util::io::FileLike;
+use account_kind::AccountKind;
 use advisory_rating::AdvisoryRating;
 use atom::{Atom, AtomData};
 use data_type::DataType;
+use media_kind::MediaKind;
 
 use std::borrow::Cow;
 use std::io::Write;
@@ -40,6 +45,19 @@ const ALBUM: AtomIdent<'_> = AtomIdent::Fourcc(*b"\xa9alb");
 const GENRE: AtomIdent<'_> = AtomIdent::Fourcc(*b"\xa9gen");
 const COMMENT: AtomIdent<'_> = AtomIdent::Fourcc(*b"\xa9cmt");
 const ADVISORY_RATING: AtomIdent<'_> = AtomIdent::Fourcc(*b"rtng");
+const MEDIA_KIND: AtomIdent<'_> = AtomIdent::Fourcc(*b"stik");
+const ACCOUNT_KIND: AtomIdent<'_> = AtomIdent::Fourcc(*b"akID");
+const BPM: AtomIdent<'_> = AtomIdent::Fourcc(*b"tmpo");
+const TV_SHOW_NAME: AtomIdent<'_> = AtomIdent::Fourcc(*b"tvsh");
+const TV_SEASON: AtomIdent<'_> = AtomIdent::Fourcc(*b"tvsn");
+const TV_EPISODE: AtomIdent<'_> = AtomIdent::Fourcc(*b"tves");
+const STOREFRONT_ID: AtomIdent<'_> = AtomIdent::Fourcc(*b"sfID");
+const ARTIST_ID: AtomIdent<'_> = AtomIdent::Fourcc(*b"atID");
+const CATALOG_ID: AtomIdent<'_> = AtomIdent::Fourcc(*b"cnID");
+const GENRE_ID: AtomIdent<'_> = AtomIdent::Fourcc(*b"geID");
+const PURCHASE_DATE: AtomIdent<'_> = AtomIdent::Fourcc(*b"purd");
+const OWNER: AtomIdent<'_> = AtomIdent::Fourcc(*b"ownr");
+const APPLE_ID: AtomIdent<'_> = AtomIdent::Fourcc(*b"apID");
 const COVR: AtomIdent<'_> = AtomIdent::Fourcc(*b"covr");
 const TRACK_NUMBER: AtomIdent<'_> = AtomIdent::Fourcc(*b"trkn");
 const DISC_NUMBER: AtomIdent<'_> = AtomIdent::Fourcc(*b"disk");
@@ -99,6 +117,64 @@ macro_rules! impl_flag_accessors {
 	};
 }
 
+macro_rules! impl_integer_accessors {
+	($($name:ident: $ty:ty => $const:ident, $description:literal;)+) => {
+		$(
+			paste::paste! {
+				#[doc = "Returns the " $description]
+				///
+				/// This is only returned if the atom holds an integer that fits in its expected size.
+				pub fn $name(&self) -> Option<$ty> {
+					self.get_integer(&$const)
+						.and_then(|value| <$ty>::try_from(value).ok())
+				}
+
+				#[doc = "Sets the " $description]
+				pub fn [<set_ $name>](&mut self, value: $ty) {
+					self.set_integer($const, u32::from(value));
+				}
+
+				#[doc = "Removes the " $description]
+				pub fn [<remove_ $name>](&mut self) {
+					let _ = self.remove(&$const);
+				}
+			}
+		)+
+	};
+}
+
+macro_rules! impl_text_accessors {
+	($($name:ident => $const:ident, $description:literal;)+) => {
+		$(
+			paste::paste! {
+				#[doc = "Returns the " $description]
+				pub fn $name(&self) -> Option<&str> {
+					self.get(&$const)
+						.into_iter()
+						.flat_map(Atom::data)
+						.find_map(|data| match data {
+							AtomData::UTF8(text) | AtomData::UTF16(text) => Some(text.as_str()),
+							_ => None,
+						})
+				}
+
+				#[doc = "Sets the " $description]
+				pub fn [<set_ $name>](&mut self, value: String) {
+					self.replace_atom(Atom {
+						ident: $const,
+						data: AtomDataStorage::Single(AtomData::UTF8(value)),
+					})
+				}
+
+				#[doc = "Removes the " $description]
+				pub fn [<remove_ $name>](&mut self) {
+					let _ = self.remove(&$const);
+				}
+			}
+		)+
+	};
+}
+
 /// ## Pictures
 ///
 /// Unlike other formats, ilst does not store a [`PictureType`]. All pictures will have
@@ -461,6 +537,130 @@ impl Ilst {
 		})
 	}
 
+	/// Returns the media kind according to the `stik` atom
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::{Ilst, MediaKind};
+	///
+	/// let mut ilst = Ilst::new();
+	/// ilst.set_media_kind(MediaKind::TvShow);
+	///
+	/// assert_eq!(ilst.media_kind(), Some(MediaKind::TvShow));
+	/// ```
+	pub fn media_kind(&self) -> Option<MediaKind> {
+		self.get_integer(&MEDIA_KIND)
+			.and_then(|kind| MediaKind::try_from(kind as u8).ok())
+	}
+
+	/// Sets the media kind
+	pub fn set_media_kind(&mut self, media_kind: MediaKind) {
+		self.set_integer(MEDIA_KIND, u32::from(media_kind.as_u8()));
+	}
+
+	/// Returns the kind of account the file was purchased with, according to the `akID` atom
+	pub fn account_kind(&self) -> Option<AccountKind> {
+		self.get_integer(&ACCOUNT_KIND)
+			.and_then(|kind| AccountKind::try_from(kind as u8).ok())
+	}
+
+	/// Sets the kind of account the file was purchased with
+	pub fn set_account_kind(&mut self, account_kind: AccountKind) {
+		self.set_integer(ACCOUNT_KIND, u32::from(account_kind.as_u8()));
+	}
+
+	impl_integer_accessors!(
+		bpm: u16 => BPM, "BPM (`tmpo`)";
+		tv_season: u32 => TV_SEASON, "TV season number (`tvsn`)";
+		tv_episode: u32 => TV_EPISODE, "TV episode number (`tves`)";
+		storefront_id: u32 => STOREFRONT_ID, "iTunes Store country ID (`sfID`)";
+		artist_id: u32 => ARTIST_ID, "iTunes Store artist ID (`atID`)";
+		catalog_id: u32 => CATALOG_ID, "iTunes Store catalog ID (`cnID`)";
+		genre_id: u32 => GENRE_ID, "iTunes Store genre ID (`geID`)";
+	);
+
+	impl_text_accessors!(
+		tv_show_name => TV_SHOW_NAME, "TV show name (`tvsh`)";
+		purchase_date => PURCHASE_DATE, "purchase date (`purd`)";
+		owner => OWNER, "name of the owner (`ownr`)";
+		apple_id => APPLE_ID, "Apple ID of the purchaser (`apID`)";
+	);
+
+	/// The value of an integer atom with a fixed size, see [`constants::fixed_integer_size`]
+	fn get_integer(&self, ident: &AtomIdent<'_>) -> Option<u32> {
+		let size = constants::fixed_integer_size(ident)?;
+		self.get(ident)
+			.into_iter()
+			.flat_map(Atom::data)
+			.find_map(|data| data.sized_integer(size))
+	}
+
+	/// Set an integer atom with a fixed size
+	///
+	/// Since the atoms are written with their expected size, the value is stored as-is.
+	fn set_integer(&mut self, ident: AtomIdent<'static>, value: u32) {
+		self.replace_atom(Atom {
+			ident,
+			data: AtomDataStorage::Single(AtomData::SignedInteger(value as i32)),
+		})
+	}
+
+	/// Save the tag to a file, returning how the file was updated
+	///
+	/// This is the same as [`TagExt::save_to`], but reports the [`Mp4WriteStrategy`] that was
+	/// used. Small edits are made in place where possible, by making use of any `free` atoms around
+	/// the `ilst` atom and after the `moov` atom. If the rest of the file has to move, padding is
+	/// added according to [`WriteOptions::preferred_padding`], so that later edits can be made in
+	/// place.
+	///
+	/// # Errors
+	///
+	/// * See [`TagExt::save_to`]
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::WriteOptions;
+	/// use lofty::mp4::{Ilst, Mp4WriteStrategy};
+	/// use lofty::tag::Accessor;
+	/// use std::fs::OpenOptions;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// let mut file = OpenOptions::new()
+	/// 	.read(true)
+	/// 	.write(true)
+	/// 	.open("audiobook.m4b")?;
+	///
+	/// let mut ilst = Ilst::default();
+	/// ilst.set_title(String::from("Foo title"));
+	///
+	/// let strategy = ilst.save_to_with_report(&mut file, WriteOptions::default())?;
+	/// if strategy == Mp4WriteStrategy::DataMoved {
+	/// 	println!("The audio data had to be moved");
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn save_to_with_report<F>(
+		&self,
+		file: &mut F,
+		write_options: WriteOptions,
+	) -> Result<Mp4WriteStrategy, FileEncodingError>
+	where
+		F: FileLike,
+	{
+		let file = VerifiedFile::new(file)?;
+
+		let format = file.format();
+		if !format.tag_support(TagType::Mp4Ilst).is_writable() {
+			return Err(FileEncodingError::from(UnsupportedTagError).with_format(format));
+		}
+
+		self.as_ref()
+			.write_to(file, write_options)
+			.map_err(|e| e.with_format(format))
+	}
+
 	// Extracts a u16 from an integer pair
 	fn extract_number(&self, fourcc: [u8; 4], expected_size: usize) -> Option<u16> {
 		if let Some(atom) = self.get(&AtomIdent::Fourcc(fourcc)) {
@@ -655,7 +855,7 @@ impl TagWriteExt for Ilst {
 	where
 		F: FileLike,
 	{
-		self.as_ref().write_to(file, write_options)
+		self.as_ref().write_to(file, write_options).map(|_| ())
 	}
 }
 
@@ -766,6 +966,11 @@ impl SplitTag for Ilst {
 			let _ = self.remove(&ADVISORY_RATING);
 		}
 
+		if let Some(bpm) = self.bpm() {
+			tag.insert_text(ItemKey::IntegerBpm, bpm.to_string());
+			self.remove_bpm();
+		}
+
 		(SplitTagRemainder(self), tag)
 	}
 }
@@ -850,6 +1055,14 @@ impl MergeTag for SplitTagRemainder {
 							))),
 						})
 					},
+					ItemKey::IntegerBpm => {
+						let Ok(bpm) = text.parse::<u16>() else {
+							log::warn!("BPM is not a valid integer: {text}, discarding");
+							continue;
+						};
+
+						merged.set_bpm(bpm);
+					},
 					_ => merged.atoms.push(Atom {
 						ident: ident.into_owned(),
 						data: AtomDataStorage::Single(AtomData::UTF8(text)),
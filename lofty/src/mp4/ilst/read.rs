@@ -232,7 +232,7 @@ where
 
 const DATA_ATOM_IDENT: AtomIdent<'static> = AtomIdent::Fourcc(*b"data");
 
-fn parse_data_inner<R>(
+pub(in crate::mp4) fn parse_data_inner<R>(
 	reader: &mut AtomReader<R>,
 	parsing_mode: ParsingMode,
 	atom_info: &AtomInfo,
@@ -437,7 +437,10 @@ where
 	Ok(())
 }
 
-fn interpret_atom_content(flags: DataType, content: Vec<u8>) -> Result<AtomData, AtomParseError> {
+pub(in crate::mp4) fn interpret_atom_content(
+	flags: DataType,
+	content: Vec<u8>,
+) -> Result<AtomData, AtomParseError> {
 	// https://developer.apple.com/library/archive/documentation/QuickTime/QTFF/Metadata/Metadata.html#//apple_ref/doc/uid/TP40000939-CH1-SW35
 	Ok(match flags {
 		DataType::Utf8 => AtomData::UTF8(utf8_decode(content)?),
@@ -6,7 +6,7 @@ use crate::config::WriteOptions;
 use crate::error::FileEncodingError;
 use crate::io::VerifiedFile;
 use crate::mp4::ilst::error::IlstEncodingError;
-use crate::mp4::{Atom, AtomData, AtomIdent, Ilst};
+use crate::mp4::{Atom, AtomData, AtomIdent, Ilst, Mp4WriteStrategy};
 use crate::util::io::FileLike;
 
 use std::io::Write;
@@ -31,7 +31,7 @@ where
 		&mut self,
 		file: VerifiedFile<'_, F>,
 		write_options: WriteOptions,
-	) -> Result<(), FileEncodingError>
+	) -> Result<Mp4WriteStrategy, FileEncodingError>
 	where
 		F: FileLike,
 	{
@@ -2,7 +2,9 @@ use crate::config::{ParseOptions, ParsingMode, WriteOptions};
 use crate::mp4::ilst::TITLE;
 use crate::mp4::ilst::atom::AtomDataStorage;
 use crate::mp4::read::AtomReader;
-use crate::mp4::{AdvisoryRating, Atom, AtomData, AtomIdent, DataType, Ilst, Mp4File};
+use crate::mp4::{
+	AccountKind, AdvisoryRating, Atom, AtomData, AtomIdent, DataType, Ilst, MediaKind, Mp4File,
+};
 use crate::picture::{MimeType, Picture, PictureType};
 use crate::prelude::*;
 use crate::tag::utils::test_utils;
@@ -246,6 +248,153 @@ fn advisory_rating() {
 	assert_eq!(ilst.advisory_rating(), Some(AdvisoryRating::Explicit));
 }
 
+#[test_log::test]
+fn itunes_atoms() {
+	let mut ilst = Ilst::new();
+	ilst.set_media_kind(MediaKind::TvShow);
+	ilst.set_account_kind(AccountKind::ITunes);
+	ilst.set_advisory_rating(AdvisoryRating::Clean);
+	ilst.set_bpm(120);
+	ilst.set_tv_show_name(String::from("Foo show"));
+	ilst.set_tv_season(2);
+	ilst.set_tv_episode(13);
+	ilst.set_storefront_id(143_441);
+	ilst.set_artist_id(u32::MAX);
+	ilst.set_catalog_id(1_234_567_890);
+	ilst.set_genre_id(26);
+	ilst.set_purchase_date(String::from("2026-10-19 12:00:00"));
+	ilst.set_owner(String::from("Foo owner"));
+	ilst.set_apple_id(String::from("foo@example.com"));
+
+	let mut tag_bytes = Vec::new();
+	ilst.dump_to(&mut tag_bytes, WriteOptions::default())
+		.unwrap();
+
+	// The integers are written with their expected sizes, as a "BE signed integer" (21)
+	for (ident, size) in [
+		(b"stik", 1),
+		(b"akID", 1),
+		(b"rtng", 1),
+		(b"tmpo", 2),
+		(b"tvsn", 4),
+		(b"sfID", 4),
+		(b"atID", 4),
+	] {
+		let pos = tag_bytes
+			.windows(4)
+			.position(|window| window == ident)
+			.unwrap();
+
+		let atom_size = u32::from_be_bytes(tag_bytes[pos - 4..pos].try_into().unwrap());
+		assert_eq!(atom_size as usize, 24 + size);
+		assert_eq!(tag_bytes[pos + 15], 21);
+	}
+
+	tag_bytes.drain(..8); // Remove the ilst identifier and size for `read_ilst`
+
+	let ilst = read_ilst_raw(
+		&tag_bytes,
+		ParseOptions::new().parsing_mode(ParsingMode::Strict),
+	);
+
+	assert_eq!(ilst.media_kind(), Some(MediaKind::TvShow));
+	assert_eq!(ilst.account_kind(), Some(AccountKind::ITunes));
+	assert_eq!(ilst.advisory_rating(), Some(AdvisoryRating::Clean));
+	assert_eq!(ilst.bpm(), Some(120));
+	assert_eq!(ilst.tv_show_name(), Some("Foo show"));
+	assert_eq!(ilst.tv_season(), Some(2));
+	assert_eq!(ilst.tv_episode(), Some(13));
+	assert_eq!(ilst.storefront_id(), Some(143_441));
+	assert_eq!(ilst.artist_id(), Some(u32::MAX));
+	assert_eq!(ilst.catalog_id(), Some(1_234_567_890));
+	assert_eq!(ilst.genre_id(), Some(26));
+	assert_eq!(ilst.purchase_date(), Some("2026-10-19 12:00:00"));
+	assert_eq!(ilst.owner(), Some("Foo owner"));
+	assert_eq!(ilst.apple_id(), Some("foo@example.com"));
+}
+
+#[test_log::test]
+fn itunes_atoms_invalid_data() {
+	let mut ilst = Ilst::new();
+
+	// Not an integer
+	ilst.insert(Atom::new(
+		AtomIdent::Fourcc(*b"stik"),
+		AtomData::UTF8(String::from("10")),
+	));
+	assert_eq!(ilst.media_kind(), None);
+
+	// Too large for the 1 byte `stik` atom
+	ilst.replace_atom(Atom::new(
+		AtomIdent::Fourcc(*b"stik"),
+		AtomData::SignedInteger(266),
+	));
+	assert_eq!(ilst.media_kind(), None);
+
+	// Too large for the 2 byte `tmpo` atom
+	ilst.insert(Atom::new(
+		AtomIdent::Fourcc(*b"tmpo"),
+		AtomData::UnsignedInteger(70000),
+	));
+	assert_eq!(ilst.bpm(), None);
+
+	// Unknown media kind
+	ilst.replace_atom(Atom::new(
+		AtomIdent::Fourcc(*b"stik"),
+		AtomData::SignedInteger(3),
+	));
+	assert_eq!(ilst.media_kind(), None);
+
+	// Integers smaller than expected are still accepted
+	ilst.replace_atom(Atom::new(
+		AtomIdent::Fourcc(*b"tmpo"),
+		AtomData::Unknown {
+			code: DataType::BeSignedInteger,
+			data: vec![120],
+		},
+	));
+	assert_eq!(ilst.bpm(), Some(120));
+
+	// And integers created without the typed accessors are written with the expected size
+	ilst.replace_atom(Atom::new(
+		AtomIdent::Fourcc(*b"stik"),
+		AtomData::SignedInteger(10),
+	));
+
+	let mut tag_bytes = Vec::new();
+	ilst.dump_to(&mut tag_bytes, WriteOptions::default())
+		.unwrap();
+
+	let stik = tag_bytes
+		.windows(4)
+		.position(|window| window == b"stik")
+		.unwrap();
+	assert_eq!(&tag_bytes[stik - 4..stik], &25_u32.to_be_bytes());
+	assert_eq!(tag_bytes[stik + 20], 10);
+
+	tag_bytes.drain(..8); // Remove the ilst identifier and size for `read_ilst`
+
+	let ilst = read_ilst_raw(
+		&tag_bytes,
+		ParseOptions::new().parsing_mode(ParsingMode::Strict),
+	);
+	assert_eq!(ilst.media_kind(), Some(MediaKind::TvShow));
+	assert_eq!(ilst.bpm(), Some(120));
+}
+
+#[test_log::test]
+fn bpm_conversion() {
+	let mut ilst = Ilst::new();
+	ilst.set_bpm(120);
+
+	let tag: Tag = ilst.into();
+	assert_eq!(tag.get_string(ItemKey::IntegerBpm), Some("120"));
+
+	let ilst: Ilst = tag.into();
+	assert_eq!(ilst.bpm(), Some(120));
+	verify_atom(&ilst, *b"tmpo", &AtomData::SignedInteger(120));
+}
+
 #[test_log::test]
 fn trailing_padding() {
 	const ILST_START: usize = 97;
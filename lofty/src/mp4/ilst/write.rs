@@ -8,10 +8,14 @@ use crate::macros::try_vec;
 use crate::mp4::AtomData;
 use crate::mp4::atom_info::{ATOM_HEADER_LEN, AtomIdent, AtomInfo, FOURCC_LEN};
 use crate::mp4::error::{AtomParseError, Mp4ParseError};
+use crate::mp4::ilst::constants::fixed_integer_size;
 use crate::mp4::ilst::error::IlstEncodingError;
 use crate::mp4::ilst::r#ref::AtomRef;
 use crate::mp4::read::{AtomReader, atom_tree, find_child_atom, meta_is_full, verify_mp4};
-use crate::mp4::write::{AtomWriter, AtomWriterCompanion, ContextualAtom};
+use crate::mp4::write::{
+	AtomWriter, AtomWriterCompanion, ContextualAtom, Mp4WriteStrategy, fast_start,
+	preferred_padding,
+};
 use crate::picture::{MimeType, Picture};
 use crate::util::alloc::VecFallibleCapacity;
 use crate::util::io::FileLike;
@@ -32,7 +36,7 @@ pub(crate) fn write_to<'a, F, I>(
 	file: VerifiedFile<'_, F>,
 	tag: &mut IlstRef<'a, I>,
 	write_options: WriteOptions,
-) -> Result<(), FileEncodingError>
+) -> Result<Mp4WriteStrategy, FileEncodingError>
 where
 	F: FileLike,
 	I: IntoIterator<Item = &'a AtomData> + 'a,
@@ -48,7 +52,7 @@ where
 	let mut file = reader.into_inner();
 	file.rewind()?;
 
-	let mut atom_writer =
+	let atom_writer =
 		AtomWriter::new_from_file(&mut file, write_options.parse_options.parsing_mode)
 			.map_err(Into::<FileParseError>::into)?;
 
@@ -74,7 +78,7 @@ where
 	let mut write_handle = atom_writer.start_write();
 	write_handle.seek(SeekFrom::Start(moov_data_start))?;
 
-	let ilst = build_ilst(&mut tag.atoms, write_options).map_err(TagEncodingError::from)?;
+	let mut ilst = build_ilst(&mut tag.atoms, write_options).map_err(TagEncodingError::from)?;
 	let remove_tag = ilst.is_empty();
 
 	let udta = find_child_atom(
@@ -87,7 +91,12 @@ where
 
 	// Nothing to do
 	if remove_tag && udta.is_none() {
-		return Ok(());
+		drop(write_handle);
+		if fast_start(&mut file, write_options)? {
+			return Ok(Mp4WriteStrategy::DataMoved);
+		}
+
+		return Ok(Mp4WriteStrategy::InPlace);
 	}
 
 	// Total size of new atoms
@@ -95,6 +104,8 @@ where
 	// Size of the existing udta atom
 	let mut existing_udta_size = 0;
 
+	let strategy;
+
 	// ilst is nested in udta.meta, so we need to check what atoms actually exist
 	if let Some(udta) = udta {
 		log::trace!(
@@ -116,7 +127,12 @@ where
 
 		// Nothing to do
 		if remove_tag && meta.is_none() {
-			return Ok(());
+			drop(write_handle);
+			if fast_start(&mut file, write_options)? {
+				return Ok(Mp4WriteStrategy::DataMoved);
+			}
+
+			return Ok(Mp4WriteStrategy::InPlace);
 		}
 
 		match meta {
@@ -132,7 +148,7 @@ where
 				drop(write_handle);
 
 				// We can use the existing `udta` and `meta` atoms
-				save_to_existing(
+				strategy = save_to_existing(
 					&atom_writer,
 					moov,
 					(meta, udta),
@@ -150,19 +166,25 @@ where
 
 				existing_udta_size = udta.len;
 
-				// `meta` + `ilst`
-				let capacity = FULL_ATOM_SIZE as usize + ilst.len();
-				let buf = Vec::with_capacity(capacity);
+				let build_meta = |ilst: &[u8]| -> Result<Vec<u8>, FileEncodingError> {
+					// `meta` + `ilst`
+					let capacity = FULL_ATOM_SIZE as usize + ilst.len();
+					let buf = Vec::with_capacity(capacity);
 
-				let bytes;
-				{
 					let meta_writer =
 						AtomWriter::new(buf, write_options.parse_options.parsing_mode);
-					create_meta(&meta_writer, &ilst)?;
+					create_meta(&meta_writer, ilst)?;
+
+					Ok(meta_writer.into_contents())
+				};
 
-					bytes = meta_writer.into_contents();
+				let mut bytes = build_meta(&ilst)?;
+				if pad_new_ilst(&atom_writer, moov, &mut ilst, bytes.len(), write_options)? {
+					bytes = build_meta(&ilst)?;
 				}
 
+				strategy = atom_writer.resize_moov(moov, bytes.len() as i64)?;
+
 				write_handle = atom_writer.start_write();
 
 				new_udta_size = udta.len + bytes.len() as u64;
@@ -182,12 +204,20 @@ where
 	} else {
 		log::trace!("No `udta` atom found, creating one");
 
+		drop(write_handle);
+
 		// We have to create the `udta` atom
-		let bytes = create_udta(&ilst, write_options)?;
+		let mut bytes = create_udta(&ilst, write_options)?;
+		if pad_new_ilst(&atom_writer, moov, &mut ilst, bytes.len(), write_options)? {
+			bytes = create_udta(&ilst, write_options)?;
+		}
+
 		new_udta_size = bytes.len() as u64;
+		strategy = atom_writer.resize_moov(moov, bytes.len() as i64)?;
 
 		// We'll put the new `udta` atom right at the start of `moov`
-		let udta_pos = (moov_start + ATOM_HEADER_LEN) as usize;
+		let udta_pos = moov_data_start as usize;
+		let mut write_handle = atom_writer.start_write();
 		write_handle.splice(udta_pos..udta_pos, bytes);
 
 		drop(write_handle);
@@ -209,9 +239,39 @@ where
 
 	drop(write_handle);
 
-	atom_writer.save_to(&mut file)?;
+	log::debug!("Writing `ilst` tag, strategy: {strategy:?}");
+	atom_writer.save_moov_to(&mut file, moov, strategy)?;
 
-	Ok(())
+	if fast_start(&mut file, write_options)? {
+		return Ok(Mp4WriteStrategy::DataMoved);
+	}
+
+	Ok(strategy)
+}
+
+/// Pad a new `ilst` atom, if adding it means the file will have to be rewritten anyway
+///
+/// This makes it possible for future writes to happen in place. Returns `true` if padding was
+/// added.
+fn pad_new_ilst(
+	writer: &AtomWriter,
+	moov: &ContextualAtom,
+	ilst: &mut Vec<u8>,
+	growth: usize,
+	write_options: WriteOptions,
+) -> Result<bool, FileEncodingError> {
+	if !writer.growth_moves_data(moov, growth as u64) {
+		return Ok(false);
+	}
+
+	let Some(padding) = preferred_padding(write_options) else {
+		return Ok(false);
+	};
+
+	log::trace!("Padding new `ilst` atom with {padding} bytes");
+
+	write_free_atom(ilst, padding)?;
+	Ok(true)
 }
 
 fn save_to_existing(
@@ -222,7 +282,7 @@ fn save_to_existing(
 	ilst: Vec<u8>,
 	remove_tag: bool,
 	write_options: WriteOptions,
-) -> Result<(), FileEncodingError> {
+) -> Result<Mp4WriteStrategy, FileEncodingError> {
 	let mut replacement;
 	let range;
 
@@ -239,7 +299,7 @@ fn save_to_existing(
 	if tree.is_empty() {
 		// Nothing to do
 		if remove_tag {
-			return Ok(());
+			return Ok(Mp4WriteStrategy::InPlace);
 		}
 
 		let meta_end = (meta.start + meta.len) as usize;
@@ -315,7 +375,7 @@ fn save_to_existing(
 				// Write the remaining padding
 				write_free_atom(&mut write_handle, remaining_space)?;
 
-				return Ok(());
+				return Ok(Mp4WriteStrategy::InPlace);
 			}
 
 			replacement = ilst;
@@ -332,9 +392,16 @@ fn save_to_existing(
 	if !replacement.is_empty() && difference != 0 {
 		log::trace!("Tag size changed, attempting to avoid offset update");
 
+		let growth_moves_data =
+			difference.is_positive() && writer.growth_moves_data(moov, difference as u64);
+
 		let mut ilst_writer = Cursor::new(replacement);
-		let (atom_size_difference, padding_size) =
-			pad_atom(&mut ilst_writer, difference, write_options)?;
+		let (atom_size_difference, padding_size) = pad_atom(
+			&mut ilst_writer,
+			difference,
+			growth_moves_data,
+			write_options,
+		)?;
 
 		replacement = ilst_writer.into_inner();
 		new_meta_size += padding_size;
@@ -356,31 +423,48 @@ fn save_to_existing(
 		drop(write_handle);
 	}
 
-	// Update offset atoms
-	if difference != 0 {
-		let offset = range.start as u64;
-		update_offsets(writer, moov, difference, offset)?;
-	}
+	// Make room for the new `ilst`, before `moov` is modified
+	let strategy = writer.resize_moov(moov, difference)?;
 
 	// Replace the `ilst` atom
 	let mut write_handle = writer.start_write();
 	write_handle.splice(range, replacement);
 	drop(write_handle);
 
-	Ok(())
+	Ok(strategy)
 }
 
 fn pad_atom<W>(
 	writer: &mut W,
 	mut atom_size_difference: i64,
+	growth_moves_data: bool,
 	write_options: WriteOptions,
 ) -> Result<(i64, u64), FileEncodingError>
 where
 	W: Write + Seek,
 {
 	if atom_size_difference.is_positive() {
-		log::trace!("Atom has grown, cannot avoid offset update");
-		return Ok((atom_size_difference, 0));
+		if !growth_moves_data {
+			log::trace!("Atom has grown, but the rest of the file does not need to move");
+			return Ok((atom_size_difference, 0));
+		}
+
+		// The file has to be rewritten anyway, so make room for future writes
+		let Some(preferred_padding) = preferred_padding(write_options) else {
+			log::trace!("Atom has grown, cannot avoid offset update");
+			return Ok((atom_size_difference, 0));
+		};
+
+		log::trace!(
+			"Atom has grown, cannot avoid offset update, padding atom with {} bytes",
+			preferred_padding
+		);
+
+		writer.seek(SeekFrom::End(0))?;
+		write_free_atom(writer, preferred_padding)?;
+
+		let padding_size = u64::from(preferred_padding);
+		return Ok((atom_size_difference + padding_size as i64, padding_size));
 	}
 
 	// When the tag shrinks, we need to try and pad it out to avoid updating
@@ -404,7 +488,7 @@ where
 		return Ok((atom_size_difference, padding_size));
 	}
 
-	let Some(preferred_padding) = write_options.preferred_padding else {
+	let Some(preferred_padding) = preferred_padding(write_options) else {
 		log::trace!("Cannot avoid offset update, not padding atom");
 		return Ok((atom_size_difference, 0));
 	};
@@ -416,9 +500,9 @@ where
 
 	// Otherwise, we'll have to just pad the default amount,
 	// and update the offsets.
-	write_free_atom(writer, preferred_padding.get())?;
-	atom_size_difference += i64::from(preferred_padding.get());
-	padding_size = u64::from(preferred_padding.get());
+	write_free_atom(writer, preferred_padding)?;
+	atom_size_difference += i64::from(preferred_padding);
+	padding_size = u64::from(preferred_padding);
 
 	Ok((atom_size_difference, padding_size))
 }
@@ -433,7 +517,7 @@ where
 	Ok(())
 }
 
-fn update_offsets(
+pub(in crate::mp4) fn update_offsets(
 	writer: &AtomWriter,
 	moov: &ContextualAtom,
 	difference: i64,
@@ -464,13 +548,13 @@ fn update_offsets(
 			if u64::from(read_offset) < ilst_offset {
 				continue;
 			}
+
+			let new_offset = shift_offset(stco, read_offset.into(), difference, u32::MAX.into())?;
+
 			write_handle.seek(SeekFrom::Current(-4))?;
-			write_handle.write_u32::<BigEndian>((i64::from(read_offset) + difference) as u32)?;
+			write_handle.write_u32::<BigEndian>(new_offset as u32)?;
 
-			log::trace!(
-				"Updated offset from {read_offset} to {}",
-				(i64::from(read_offset) + difference) as u32
-			);
+			log::trace!("Updated offset from {read_offset} to {new_offset}");
 		}
 	}
 
@@ -487,20 +571,40 @@ fn update_offsets(
 				continue;
 			}
 
+			let new_offset = shift_offset(co64, read_offset, difference, u64::MAX)?;
+
 			write_handle.seek(SeekFrom::Current(-8))?;
-			write_handle.write_u64::<BigEndian>((read_offset as i64 + difference) as u64)?;
+			write_handle.write_u64::<BigEndian>(new_offset)?;
 
-			log::trace!(
-				"Updated offset from {read_offset} to {}",
-				((read_offset as i64) + difference) as u64
-			);
+			log::trace!("Updated offset from {read_offset} to {new_offset}");
 		}
 	}
 
-	let Some(moof) = writer.find_contextual_atom(*b"moof") else {
-		return Ok(());
-	};
+	// Fragmented files have a `tfhd` in every `moof.traf`, and may have a `mfra` index of
+	// the `moof` atoms at the end of the file
+	for top_level_atom in writer.atoms() {
+		match top_level_atom.info.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"moof" => {
+				update_tfhd_offsets(&mut write_handle, top_level_atom, difference, ilst_offset)?;
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"mfra" => {
+				update_tfra_offsets(&mut write_handle, top_level_atom, difference, ilst_offset)?;
+			},
+			_ => {},
+		}
+	}
 
+	drop(write_handle);
+
+	Ok(())
+}
+
+fn update_tfhd_offsets(
+	write_handle: &mut AtomWriterCompanion<'_>,
+	moof: &ContextualAtom,
+	difference: i64,
+	ilst_offset: u64,
+) -> Result<(), FileEncodingError> {
 	log::trace!("Found `moof` atom, checking for `tfhd` atoms to update");
 
 	// 64-bit offsets
@@ -523,26 +627,92 @@ fn update_offsets(
 		let base_data_offset = (flags & 0b1) != 0;
 
 		if base_data_offset {
+			// Skip the track ID
+			write_handle.seek(SeekFrom::Current(4))?;
+
 			let read_offset = write_handle.read_u64::<BigEndian>()?;
 			if read_offset < ilst_offset {
 				continue;
 			}
 
+			let new_offset = shift_offset(tfhd, read_offset, difference, u64::MAX)?;
+
 			write_handle.seek(SeekFrom::Current(-8))?;
-			write_handle.write_u64::<BigEndian>((read_offset as i64 + difference) as u64)?;
+			write_handle.write_u64::<BigEndian>(new_offset)?;
 
-			log::trace!(
-				"Updated offset from {read_offset} to {}",
-				((read_offset as i64) + difference) as u64
-			);
+			log::trace!("Updated offset from {read_offset} to {new_offset}");
 		}
 	}
 
-	drop(write_handle);
+	Ok(())
+}
+
+fn update_tfra_offsets(
+	write_handle: &mut AtomWriterCompanion<'_>,
+	mfra: &ContextualAtom,
+	difference: i64,
+	ilst_offset: u64,
+) -> Result<(), FileEncodingError> {
+	log::trace!("Found `mfra` atom, checking for `tfra` atoms to update");
+
+	for tfra in mfra.find_all_children(*b"tfra", false) {
+		log::trace!("Found `tfra` atom");
+
+		write_handle.seek(SeekFrom::Start(tfra.start + tfra.header_size()))?;
+
+		let version = write_handle.read_u8()?;
+		let _flags = write_handle.read_u24::<BigEndian>()?;
+		let _track_id = write_handle.read_u32::<BigEndian>()?;
+
+		// The sizes of the `traf`, `trun`, and sample numbers are stored as (size - 1) in the
+		// lowest 6 bits
+		let sizes = write_handle.read_u32::<BigEndian>()?;
+		let numbers_len = ((sizes >> 4) & 0b11) + ((sizes >> 2) & 0b11) + (sizes & 0b11) + 3;
+
+		let entry_count = write_handle.read_u32::<BigEndian>()?;
+		for _ in 0..entry_count {
+			// The entry's time and `moof` offset, followed by the numbers
+			let offset_len = if version == 1 { 8 } else { 4 };
+			write_handle.seek(SeekFrom::Current(offset_len))?;
+
+			let read_offset = write_handle.read_uint::<BigEndian>(offset_len as usize)?;
+			if read_offset >= ilst_offset {
+				let max = if version == 1 {
+					u64::MAX
+				} else {
+					u32::MAX.into()
+				};
+				let new_offset = shift_offset(tfra, read_offset, difference, max)?;
+
+				write_handle.seek(SeekFrom::Current(-offset_len))?;
+				write_handle.write_uint::<BigEndian>(new_offset, offset_len as usize)?;
+
+				log::trace!("Updated offset from {read_offset} to {new_offset}");
+			}
+
+			write_handle.seek(SeekFrom::Current(i64::from(numbers_len)))?;
+		}
+	}
 
 	Ok(())
 }
 
+// Shift an offset stored in `atom`, making sure it still fits in the field
+fn shift_offset(
+	atom: &AtomInfo,
+	offset: u64,
+	difference: i64,
+	max: u64,
+) -> Result<u64, FileEncodingError> {
+	match offset.checked_add_signed(difference) {
+		Some(new_offset) if new_offset <= max => Ok(new_offset),
+		_ => Err(handle_atom_parse_error(AtomParseError::message(
+			Some(atom.ident.clone()),
+			"offset no longer fits in its field after moving the data",
+		))),
+	}
+}
+
 fn create_udta(ilst: &[u8], write_options: WriteOptions) -> Result<Vec<u8>, FileEncodingError> {
 	const UDTA_HEADER: [u8; 8] = [0, 0, 0, 0, b'u', b'd', b't', b'a'];
 
@@ -599,7 +769,7 @@ fn create_meta(writer: &AtomWriter, ilst: &[u8]) -> Result<(), FileEncodingError
 	Ok(())
 }
 
-pub(super) fn build_ilst<'a, I>(
+pub(in crate::mp4) fn build_ilst<'a, I>(
 	atoms: &mut dyn Iterator<Item = AtomRef<'a, I>>,
 	write_options: WriteOptions,
 ) -> Result<Vec<u8>, IlstEncodingError>
@@ -626,12 +796,14 @@ where
 		// Empty size, we get it later
 		write_handle.write_all(&[0; FOURCC_LEN as usize])?;
 
+		let integer_size = fixed_integer_size(&atom.ident);
+
 		match atom.ident {
 			AtomIdent::Fourcc(ref fourcc) => write_handle.write_all(fourcc)?,
 			AtomIdent::Freeform { mean, name } => write_freeform(&mean, &name, &mut write_handle)?,
 		}
 
-		write_atom_data(atom.data, &mut write_handle)?;
+		write_atom_data(atom.data, integer_size, &mut write_handle)?;
 
 		let end = write_handle.stream_position()?;
 
@@ -679,8 +851,13 @@ where
 	Ok(())
 }
 
+/// Write the data of an atom
+///
+/// If `integer_size` is set, any integers that fit will be written with that many bytes, see
+/// [`fixed_integer_size`]. Integers that don't fit are written as usual.
 fn write_atom_data<'a, I>(
 	data: I,
+	integer_size: Option<usize>,
 	writer: &mut AtomWriterCompanion<'_>,
 ) -> Result<(), IlstEncodingError>
 where
@@ -691,8 +868,16 @@ where
 			AtomData::UTF8(text) => write_data(DataType::Utf8, text.as_bytes(), writer)?,
 			AtomData::UTF16(text) => write_data(DataType::Utf16, text.as_bytes(), writer)?,
 			AtomData::Picture(pic) => write_picture(pic, writer)?,
-			AtomData::SignedInteger(int) => write_signed_int(*int, writer)?,
-			AtomData::UnsignedInteger(uint) => write_unsigned_int(*uint, writer)?,
+			AtomData::SignedInteger(int) => {
+				if !write_sized_int(value, integer_size, writer)? {
+					write_signed_int(*int, writer)?
+				}
+			},
+			AtomData::UnsignedInteger(uint) => {
+				if !write_sized_int(value, integer_size, writer)? {
+					write_unsigned_int(*uint, writer)?
+				}
+			},
 			AtomData::Bool(b) => write_bool(*b, writer)?,
 			AtomData::Unknown { code, data } => write_data(*code, data, writer)?,
 		}
@@ -701,6 +886,24 @@ where
 	Ok(())
 }
 
+// Write an integer with `integer_size` bytes, returning `false` if it doesn't fit
+fn write_sized_int(
+	value: &AtomData,
+	integer_size: Option<usize>,
+	writer: &mut AtomWriterCompanion<'_>,
+) -> Result<bool, IlstEncodingError> {
+	let Some(size) = integer_size else {
+		return Ok(false);
+	};
+
+	let Some(int) = value.sized_integer(size) else {
+		return Ok(false);
+	};
+
+	write_int(value.data_type(), int.to_be_bytes(), size, writer)?;
+	Ok(true)
+}
+
 fn write_signed_int(
 	int: i32,
 	writer: &mut AtomWriterCompanion<'_>,
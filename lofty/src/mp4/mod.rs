@@ -2,15 +2,26 @@
 //!
 //! ## File notes
 //!
-//! The only supported tag format is [`Ilst`].
+//! The supported tag formats are [`Ilst`] and [`QuickTimeMetadata`].
 mod atom_info;
+mod channel_layout;
+mod chapters;
 pub mod error;
+mod fragments;
 pub(crate) mod ilst;
 mod moov;
 mod properties;
+mod protection;
+pub(crate) mod quicktime;
 mod read;
+mod track;
 mod write;
 
+use crate::config::WriteOptions;
+use crate::error::FileEncodingError;
+use crate::tag::TagExt;
+use crate::util::io::FileLike;
+
 use lofty_attr::LoftyFile;
 
 // Exports
@@ -22,27 +33,68 @@ pub mod constants {
 	pub use super::ilst::constants::*;
 }
 
-pub use crate::mp4::properties::{AudioObjectType, Mp4Codec, Mp4Properties};
+pub use crate::mp4::properties::{AudioObjectType, Mp4Codec, Mp4Properties, Mp4Timing};
+pub use crate::mp4::protection::Mp4Protection;
 pub use atom_info::AtomIdent;
+pub use chapters::Mp4Chapter;
 pub use ilst::Ilst;
+pub use ilst::account_kind::AccountKind;
 pub use ilst::advisory_rating::AdvisoryRating;
 pub use ilst::atom::{Atom, AtomData};
 pub use ilst::data_type::DataType;
+pub use ilst::media_kind::MediaKind;
+pub use quicktime::{QuickTimeItem, QuickTimeKey, QuickTimeMetadata};
+pub use track::{Mp4AudioTrack, Mp4Track};
+pub use write::Mp4WriteStrategy;
 
 pub(crate) use properties::SAMPLE_RATES;
 
 /// An MP4 file
+///
+/// ## Notes
+///
+/// * Chapters are only written when saving the `Mp4File` itself. Writing a
+///   [`Tag`](crate::tag::Tag) to the file will leave them untouched.
 #[derive(LoftyFile)]
 #[lofty(read_fn = "read::read_from")]
+#[lofty(write_fn = "Self::write_to")]
 pub struct Mp4File {
 	#[lofty(tag_type = "Mp4Ilst")]
 	/// The parsed `ilst` (metadata) atom, if it exists
 	pub(crate) ilst_tag: Option<Ilst>,
+	#[lofty(tag_type = "QuickTime")]
+	/// The QuickTime metadata (`moov.meta` with an `mdta` handler, and `moov.udta` text atoms), if
+	/// it exists
+	pub(crate) quicktime_tag: Option<QuickTimeMetadata>,
+	pub(crate) chapters: Vec<Mp4Chapter>,
+	pub(crate) tracks: Vec<Mp4Track>,
 	/// The file's audio properties
 	pub(crate) properties: Mp4Properties,
 }
 
 impl Mp4File {
+	fn write_to<F>(
+		&self,
+		file: &mut F,
+		write_options: WriteOptions,
+	) -> Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		if let Some(ref ilst) = self.ilst_tag {
+			file.rewind()?;
+			ilst.save_to(file, write_options)?;
+		}
+
+		if let Some(ref quicktime) = self.quicktime_tag {
+			file.rewind()?;
+			quicktime.save_to(file, write_options)?;
+		}
+
+		file.rewind()?;
+		chapters::write::write_to(file, &self.chapters, write_options)
+	}
+
 	/// Returns the file format from ftyp's "major brand" (Ex. "M4A ")
 	///
 	/// # Examples
@@ -62,4 +114,92 @@ impl Mp4File {
 	pub fn ftyp(&self) -> &str {
 		self.properties.ftyp()
 	}
+
+	/// Returns all of the tracks in the file, in the order they appear
+	///
+	/// NOTE: The tracks are only read when [`ParseOptions::read_properties`] is enabled.
+	///       See [`ParseOptions::mp4_audio_track`] to select the track used for the [`Mp4Properties`].
+	///
+	/// [`ParseOptions::read_properties`]: crate::config::ParseOptions::read_properties
+	/// [`ParseOptions::mp4_audio_track`]: crate::config::ParseOptions::mp4_audio_track
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::file::AudioFile;
+	/// use lofty::mp4::Mp4File;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileParseError> {
+	/// # let mut mp4_reader = std::io::Cursor::new(&[]);
+	/// let mp4_file = Mp4File::read_from(&mut mp4_reader, ParseOptions::new())?;
+	///
+	/// for track in mp4_file.tracks() {
+	/// 	if &track.handler_type() == b"soun" {
+	/// 		println!("Audio track {}: {:?}", track.id(), track.language());
+	/// 	}
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn tracks(&self) -> &[Mp4Track] {
+		&self.tracks
+	}
+
+	/// Returns the chapters of the file
+	///
+	/// Chapters can be stored in two ways:
+	///
+	/// * A QuickTime chapter track, a text track referenced by the audio track's `tref.chap` atom
+	/// * A Nero `udta.chpl` atom, which is limited to 255 chapters
+	///
+	/// When both exist, the chapter track is used.
+	pub fn chapters(&self) -> &[Mp4Chapter] {
+		&self.chapters
+	}
+
+	/// Returns a mutable reference to the chapters
+	pub fn chapters_mut(&mut self) -> &mut Vec<Mp4Chapter> {
+		&mut self.chapters
+	}
+
+	/// Sets the chapters, returning the old ones
+	///
+	/// When saving, the chapters are written as both a QuickTime chapter track and a Nero `chpl`
+	/// atom. Note that a chapter track always starts at zero, so the first chapter will be
+	/// extended to cover the start of the file.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::{ParseOptions, WriteOptions};
+	/// use lofty::mp4::{Mp4Chapter, Mp4File};
+	/// use lofty::prelude::*;
+	/// use std::io::Seek;
+	/// use std::time::Duration;
+	///
+	/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// # let path = "tests/files/assets/minimal/m4a_codec_aac.m4a";
+	/// let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+	/// let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new())?;
+	///
+	/// mp4.set_chapters(vec![
+	/// 	Mp4Chapter::new(Duration::ZERO, String::from("Introduction")),
+	/// 	Mp4Chapter::new(Duration::from_secs(300), String::from("Chapter 1")),
+	/// ]);
+	///
+	/// file.rewind()?;
+	/// mp4.save_to(&mut file, WriteOptions::default())?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_chapters(&mut self, chapters: Vec<Mp4Chapter>) -> Vec<Mp4Chapter> {
+		std::mem::replace(&mut self.chapters, chapters)
+	}
+
+	/// Removes all chapters, in memory
+	///
+	/// The chapter track and `chpl` atom will be removed from the file on the next call to
+	/// [`AudioFile::save_to`](crate::file::AudioFile::save_to).
+	pub fn remove_chapters(&mut self) -> Vec<Mp4Chapter> {
+		std::mem::take(&mut self.chapters)
+	}
 }
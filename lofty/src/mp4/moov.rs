@@ -1,19 +1,189 @@
+use super::Mp4Chapter;
 use super::atom_info::{AtomIdent, AtomInfo};
+use super::chapters::read::parse_chpl;
+use super::fragments::Mvex;
 use super::ilst::Ilst;
 use super::ilst::read::parse_ilst;
-use super::read::{AtomReader, find_child_atom, meta_is_full, skip_atom};
-use crate::config::ParseOptions;
+use super::properties::Mp4Timing;
+use super::quicktime::read::{parse_mdta_meta, parse_user_data_atom};
+use super::quicktime::{QuickTimeItem, QuickTimeMetadata};
+use super::read::{AtomReader, find_child_atom, meta_is_full, seek_to_end, skip_atom};
+use super::track::Mp4Track;
+use crate::config::{ParseOptions, ParsingMode};
 use crate::error::TagParseError;
-use crate::mp4::error::Mp4ParseError;
+use crate::mp4::error::{AtomParseError, Mp4ParseError};
 use crate::mp4::ilst::error::IlstParseError;
+use crate::tag::TagType;
+use crate::util::math::RoundedDivision;
 
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
 
 pub(crate) struct Moov {
-	// Represents the trak.mdia atom
-	pub(crate) traks: Vec<AtomInfo>,
+	pub(crate) traks: Vec<Trak>,
 	// Represents a parsed moov.udta.meta.ilst
 	pub(crate) ilst: Option<Ilst>,
+	// Represents a parsed moov.udta.chpl
+	pub(crate) chpl: Option<Vec<Mp4Chapter>>,
+	// Represents moov.meta (with an `mdta` handler) and the moov.udta text atoms
+	pub(crate) quicktime: Option<QuickTimeMetadata>,
+	// Represents moov.mvhd
+	pub(crate) mvhd: Option<Mp4Timing>,
+	// Represents moov.mvex, only present in fragmented files
+	pub(crate) mvex: Option<Mvex>,
+}
+
+/// The parts of a `trak` atom we care about
+pub(crate) struct Trak {
+	pub(crate) track: Mp4Track,
+	// The chapter tracks referenced by trak.tref.chap
+	pub(crate) chapter_track_ids: Vec<u32>,
+	// Represents the trak.mdia atom
+	pub(crate) mdia: Option<AtomInfo>,
+}
+
+impl Trak {
+	fn parse<R>(
+		reader: &mut AtomReader<R>,
+		len: u64,
+		parse_mode: ParsingMode,
+	) -> Result<Self, AtomParseError>
+	where
+		R: Read + Seek,
+	{
+		let mut track = Mp4Track::default();
+		let mut chapter_track_ids = Vec::new();
+		let mut mdia = None;
+
+		let mut read = 8;
+		while read < len {
+			let Some(atom) = reader.next()? else { break };
+
+			read += atom.len;
+
+			match atom.ident {
+				AtomIdent::Fourcc(ref fourcc) if fourcc == b"tkhd" && atom.len >= 24 => {
+					let version = reader.read_u8()?;
+					let flags = reader.read_u24()?;
+
+					// Skip the creation and modification times
+					let times_len = if version == 1 { 16 } else { 8 };
+					reader.seek(SeekFrom::Current(times_len))?;
+
+					track.id = reader.read_u32()?;
+					track.enabled = flags & TKHD_FLAG_ENABLED != 0;
+
+					skip_atom(reader, atom.extended, atom.len - (times_len as u64 + 8))?;
+				},
+				AtomIdent::Fourcc(ref fourcc) if fourcc == b"tref" => {
+					let mut tref_read = 8;
+					while tref_read < atom.len {
+						let Some(reference) = reader.next()? else {
+							break;
+						};
+
+						tref_read += reference.len;
+
+						if reference.ident != AtomIdent::Fourcc(*b"chap") {
+							skip_atom(reader, reference.extended, reference.len)?;
+							continue;
+						}
+
+						for _ in 0..(reference.len - 8) / 4 {
+							chapter_track_ids.push(reader.read_u32()?);
+						}
+					}
+				},
+				AtomIdent::Fourcc(ref fourcc) if fourcc == b"mdia" => {
+					parse_mdia(reader, &atom, &mut track, parse_mode)?;
+					mdia = Some(atom);
+				},
+				_ => skip_atom(reader, atom.extended, atom.len)?,
+			}
+		}
+
+		Ok(Self {
+			track,
+			chapter_track_ids,
+			mdia,
+		})
+	}
+}
+
+const TKHD_FLAG_ENABLED: u32 = 0x01;
+
+fn parse_mdia<R>(
+	reader: &mut AtomReader<R>,
+	mdia: &AtomInfo,
+	track: &mut Mp4Track,
+	parse_mode: ParsingMode,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut read = mdia.header_size();
+	while read < mdia.len {
+		let Some(atom) = reader.next()? else { break };
+
+		read += atom.len;
+
+		match atom.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"mdhd" && atom.len >= 32 => {
+				let version = reader.read_u8()?;
+				let _flags = reader.read_u24()?;
+
+				let (timescale, duration) = if version == 1 {
+					// Skip the creation and modification times
+					reader.seek(SeekFrom::Current(16))?;
+					(reader.read_u32()?, reader.read_u64()?)
+				} else {
+					reader.seek(SeekFrom::Current(8))?;
+					(reader.read_u32()?, u64::from(reader.read_u32()?))
+				};
+
+				if timescale > 0 {
+					let duration_millis = duration
+						.saturating_mul(1000)
+						.div_round(u64::from(timescale));
+					track.duration = Duration::from_millis(duration_millis);
+				}
+
+				// The language is packed into 3 5-bit characters, offset from 0x60. Values below
+				// 0x400 are Macintosh language codes, which we don't handle.
+				let language = reader.read_u16()?;
+				if language >= 0x400 {
+					track.language = Some([
+						((language >> 10) & 0x1F) as u8 + 0x60,
+						((language >> 5) & 0x1F) as u8 + 0x60,
+						(language & 0x1F) as u8 + 0x60,
+					]);
+				}
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"hdlr" && atom.len >= 20 => {
+				// Version (1), flags (3), and pre-defined (4)
+				reader.seek(SeekFrom::Current(8))?;
+				reader.read_exact(&mut track.handler_type)?;
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"minf" => {
+				if let Some(stbl) = find_child_atom(reader, atom.len, *b"stbl", parse_mode)?
+					&& let Some(stsd) = find_child_atom(reader, stbl.len, *b"stsd", parse_mode)?
+					&& stsd.len >= 24
+				{
+					// Version (1), flags (3), entry count (4), and the size of the first entry (4)
+					reader.seek(SeekFrom::Current(12))?;
+
+					let mut codec = [0; 4];
+					reader.read_exact(&mut codec)?;
+					track.codec = Some(codec);
+				}
+			},
+			_ => {},
+		}
+
+		seek_to_end(reader, &atom)?;
+	}
+
+	Ok(())
 }
 
 impl Moov {
@@ -44,23 +214,47 @@ impl Moov {
 	{
 		let mut traks = Vec::new();
 		let mut ilst = None;
+		let mut chpl = None;
+		let mut mdta_items = Vec::new();
+		let mut user_data_items = Vec::new();
+		let mut mvhd = None;
+		let mut mvex = None;
 
 		while let Ok(Some(atom)) = reader.next() {
 			if let AtomIdent::Fourcc(fourcc) = atom.ident {
 				match &fourcc {
-					b"trak" if parse_options.read_properties => {
-						// All we need from here is trak.mdia
-						if let Some(mdia) =
-							find_child_atom(reader, atom.len, *b"mdia", parse_options.parsing_mode)?
-						{
-							skip_atom(reader, mdia.extended, mdia.len)?;
-							traks.push(mdia);
-						}
+					// The tracks are needed for both the properties and the chapters
+					b"trak" if parse_options.read_properties || parse_options.read_tags => {
+						traks.push(Trak::parse(reader, atom.len, parse_options.parsing_mode)?);
+					},
+					b"mvhd" if parse_options.read_properties && atom.len >= 28 => {
+						mvhd = Some(
+							Mp4Timing::parse(reader)
+								.map_err(|e| e.with_ident(atom.ident.clone()))?,
+						);
+						seek_to_end(reader, &atom)?;
+					},
+					b"mvex" if parse_options.read_properties => {
+						mvex = Some(Mvex::parse(reader, atom.len)?);
+					},
+					b"meta" if parse_options.read_tags => {
+						let items = parse_mdta_meta(reader, &atom, parse_options.parsing_mode)
+							.map_err(|e| TagParseError::new(TagType::QuickTime, Box::new(e)))?;
+						mdta_items.extend(items);
+
+						seek_to_end(reader, &atom)?;
 					},
 					b"udta" if parse_options.read_tags => {
-						let ilst_parsed = ilst_from_udta(reader, parse_options, atom.len - 8)
+						let udta = parse_udta(reader, parse_options, atom.len)
 							.map_err(TagParseError::from)?;
-						if let Some(ilst_parsed) = ilst_parsed {
+
+						if chpl.is_none() {
+							chpl = udta.chpl;
+						}
+
+						user_data_items.extend(udta.user_data);
+
+						if let Some(ilst_parsed) = udta.ilst {
 							let Some(mut existing_ilst) = ilst else {
 								ilst = Some(ilst_parsed);
 								continue;
@@ -83,50 +277,96 @@ impl Moov {
 			skip_atom(reader, atom.extended, atom.len)?
 		}
 
-		Ok(Self { traks, ilst })
+		// The `mehd` duration is in the movie timescale
+		if let (Some(mvex), Some(mvhd)) = (&mut mvex, mvhd) {
+			mvex.set_movie_timescale(mvhd.timescale);
+		}
+
+		let mut quicktime = None;
+		if !mdta_items.is_empty() || !user_data_items.is_empty() {
+			mdta_items.extend(user_data_items);
+			quicktime = Some(QuickTimeMetadata { items: mdta_items });
+		}
+
+		Ok(Self {
+			traks,
+			ilst,
+			chpl,
+			quicktime,
+			mvhd,
+			mvex,
+		})
 	}
 }
 
-fn ilst_from_udta<R>(
+struct Udta {
+	ilst: Option<Ilst>,
+	chpl: Option<Vec<Mp4Chapter>>,
+	user_data: Vec<QuickTimeItem>,
+}
+
+fn parse_udta<R>(
 	reader: &mut AtomReader<R>,
 	parse_options: ParseOptions,
 	len: u64,
-) -> Result<Option<Ilst>, IlstParseError>
+) -> Result<Udta, IlstParseError>
 where
 	R: Read + Seek,
 {
-	let mut read = 8;
-	let mut found_meta = false;
-	let mut meta_atom_size = 0;
+	let mut ilst = None;
+	let mut chpl = None;
+	let mut user_data = Vec::new();
 
+	let mut read = 8;
 	while read < len {
 		let Some(atom) = reader.next()? else {
 			break;
 		};
 
-		if atom.ident == AtomIdent::Fourcc(*b"meta") {
-			found_meta = true;
-			meta_atom_size = atom.len;
-			break;
-		}
-
 		read += atom.len;
-		skip_atom(reader, atom.extended, atom.len)?;
-	}
 
-	if !found_meta {
-		return Ok(None);
+		match atom.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"meta" && ilst.is_none() => {
+				ilst = ilst_from_meta(reader, parse_options, atom.len)?;
+
+				// The `ilst` may not be the last atom in `meta`
+				let pos = reader.stream_position()?;
+				reader.seek(SeekFrom::Current(
+					(atom.start + atom.len) as i64 - pos as i64,
+				))?;
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"chpl" => match parse_chpl(reader, &atom) {
+				Ok(chapters) => chpl = Some(chapters),
+				Err(e) if parse_options.parsing_mode == ParsingMode::Strict => return Err(e.into()),
+				Err(_) => log::warn!("Unable to parse Nero chapters, discarding"),
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc[0] == b'\xa9' => {
+				user_data.extend(parse_user_data_atom(reader, &atom)?);
+			},
+			_ => skip_atom(reader, atom.extended, atom.len)?,
+		}
 	}
 
+	Ok(Udta {
+		ilst,
+		chpl,
+		user_data,
+	})
+}
+
+fn ilst_from_meta<R>(
+	reader: &mut AtomReader<R>,
+	parse_options: ParseOptions,
+	meta_atom_size: u64,
+) -> Result<Option<Ilst>, IlstParseError>
+where
+	R: Read + Seek,
+{
 	// It's possible for the `meta` atom to be non-full,
 	// so we have to check for that case
 	let full_meta_atom = meta_is_full(reader)?;
 
-	if full_meta_atom {
-		read = 12;
-	} else {
-		read = 8;
-	}
+	let mut read = if full_meta_atom { 12 } else { 8 };
 
 	let mut found_ilst = false;
 	let mut ilst_atom_size = 0;
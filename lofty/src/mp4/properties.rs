@@ -1,10 +1,16 @@
 use super::atom_info::{AtomIdent, AtomInfo};
+use super::channel_layout::{parse_chan, parse_chnl};
+use super::fragments::{Mvex, read_fragments};
+use super::moov::Trak;
+use super::protection::{Mp4Protection, read_sinf};
 use super::read::{AtomReader, find_child_atom, skip_atom};
+use super::track::Mp4AudioTrack;
 use crate::config::ParsingMode;
 use crate::macros::try_vec;
 use crate::mp4::error::{AtomParseError, Mp4ParseError};
-use crate::properties::FileProperties;
+use crate::properties::{ChannelMask, FileProperties};
 use crate::util::alloc::VecFallibleCapacity;
+use crate::util::bits::BitReader;
 use crate::util::math::RoundedDivision;
 
 use std::io::{Cursor, Read, Seek, SeekFrom};
@@ -24,6 +30,12 @@ pub enum Mp4Codec {
 	MP3,
 	/// Free Lossless Audio Codec
 	FLAC,
+	/// Opus
+	Opus,
+	/// Dolby Digital (AC-3)
+	AC3,
+	/// Dolby Digital Plus (E-AC-3)
+	EAC3,
 }
 
 /// MPEG-4 Audio Object Type as defined in ISO 14496-3
@@ -184,14 +196,19 @@ impl TryFrom<u8> for AudioObjectType {
 pub struct Mp4Properties {
 	pub(crate) codec: Option<Mp4Codec>,
 	pub(crate) extended_audio_object_type: Option<AudioObjectType>,
+	pub(crate) extension_audio_object_type: Option<AudioObjectType>,
 	pub(crate) duration: Duration,
 	pub(crate) overall_bitrate: Option<u32>,
 	pub(crate) audio_bitrate: Option<u32>,
 	pub(crate) sample_rate: Option<u32>,
 	pub(crate) bit_depth: Option<u8>,
 	pub(crate) channels: Option<u8>,
+	pub(crate) channel_mask: Option<ChannelMask>,
 	pub(crate) drm_protected: bool,
+	pub(crate) protection: Option<Mp4Protection>,
 	pub(crate) ftyp: String,
+	pub(crate) movie_timing: Option<Mp4Timing>,
+	pub(crate) media_timing: Option<Mp4Timing>,
 }
 
 impl From<Mp4Properties> for FileProperties {
@@ -203,7 +220,7 @@ impl From<Mp4Properties> for FileProperties {
 			sample_rate: input.sample_rate,
 			bit_depth: input.bit_depth,
 			channels: input.channels,
-			channel_mask: None,
+			channel_mask: input.channel_mask,
 		}
 	}
 }
@@ -239,6 +256,15 @@ impl Mp4Properties {
 		self.channels
 	}
 
+	/// Channel mask
+	///
+	/// This comes from the sample entry's `chan` (QuickTime) or `chnl` (ISO) atom if present,
+	/// otherwise it is only available for codecs that describe their channel layout, such as Opus,
+	/// FLAC, AC-3, and E-AC-3.
+	pub fn channel_mask(&self) -> Option<ChannelMask> {
+		self.channel_mask
+	}
+
 	/// Audio codec
 	pub fn codec(&self) -> Option<Mp4Codec> {
 		self.codec
@@ -249,15 +275,88 @@ impl Mp4Properties {
 	/// This is only applicable to MP4 files with an Elementary Stream Descriptor.
 	/// See [here](https://wiki.multimedia.cx/index.php?title=MPEG-4_Audio#Audio_Specific_Config) for
 	/// more information.
+	///
+	/// For HE-AAC streams, this is the object type of the core stream (usually
+	/// [`AudioObjectType::AacLowComplexity`]). See [`Mp4Properties::extension_audio_object_type`].
 	pub fn audio_object_type(&self) -> Option<AudioObjectType> {
 		self.extended_audio_object_type
 	}
 
+	/// Extension audio object type
+	///
+	/// This will be one of:
+	///
+	/// * [`AudioObjectType::SpectralBandReplication`] for HE-AAC v1
+	/// * [`AudioObjectType::ParametricStereo`] for HE-AAC v2
+	///
+	/// Explicit signalling (both hierarchical and backward compatible) is always detected. Implicit
+	/// SBR signalling is only detected when the sample entry specifies double the core sample rate.
+	///
+	/// When present, [`Mp4Properties::sample_rate`] and [`Mp4Properties::channels`] describe the decoded
+	/// output rather than the core AAC stream.
+	pub fn extension_audio_object_type(&self) -> Option<AudioObjectType> {
+		self.extension_audio_object_type
+	}
+
+	/// The timing information of the entire movie, from `mvhd`
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::file::AudioFile;
+	/// use lofty::mp4::Mp4File;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileParseError> {
+	/// # let mut m4a_reader = std::io::Cursor::new(&[]);
+	/// let m4a_file = Mp4File::read_from(&mut m4a_reader, ParseOptions::new())?;
+	///
+	/// if let Some(movie_timing) = m4a_file.properties().movie_timing() {
+	/// 	println!("Created: {}", movie_timing.creation_time());
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn movie_timing(&self) -> Option<Mp4Timing> {
+		self.movie_timing
+	}
+
+	/// The timing information of the audio track, from `mdhd`
+	pub fn media_timing(&self) -> Option<Mp4Timing> {
+		self.media_timing
+	}
+
 	/// Whether or not the file is DRM protected
+	///
+	/// This covers both FairPlay (`drms`) and Common Encryption (`enca`) protected audio. For
+	/// details on the protection, see [`Mp4Properties::protection`].
 	pub fn is_drm_protected(&self) -> bool {
 		self.drm_protected
 	}
 
+	/// The protection details of a DRM protected file, if available
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use lofty::config::ParseOptions;
+	/// use lofty::file::AudioFile;
+	/// use lofty::mp4::Mp4File;
+	///
+	/// # fn main() -> Result<(), lofty::error::FileParseError> {
+	/// # let mut m4a_reader = std::io::Cursor::new(&[]);
+	/// let m4a_file = Mp4File::read_from(&mut m4a_reader, ParseOptions::new())?;
+	///
+	/// if let Some(protection) = m4a_file.properties().protection() {
+	/// 	if let Some(user_name) = protection.user_name() {
+	/// 		println!("Purchased by: {user_name}");
+	/// 	}
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub fn protection(&self) -> Option<&Mp4Protection> {
+		self.protection.as_ref()
+	}
+
 	/// The file format from ftyp's "major brand" (Ex. "M4A ")
 	///
 	/// # Examples
@@ -280,81 +379,77 @@ impl Mp4Properties {
 }
 
 struct AudioTrak {
+	id: u32,
 	mdhd: AtomInfo,
 	minf: Option<AtomInfo>,
 }
 
-/// Search through all the traks to find the first one with audio
+/// Search through all the traks to find the selected audio track
 fn find_audio_trak<R>(
 	reader: &mut AtomReader<R>,
-	traks: &[AtomInfo],
+	traks: &[Trak],
+	audio_track: Mp4AudioTrack,
 ) -> Result<AudioTrak, Mp4ParseError>
 where
 	R: Read + Seek,
 {
 	const IDENT: AtomIdent<'static> = AtomIdent::Fourcc(*b"mdia");
 
-	let mut audio_track = false;
-	let mut mdhd = None;
-	let mut minf = None;
-
-	// We have to search through the traks with a mdia atom to find the audio track
-	for mdia in traks {
-		if audio_track {
-			break;
-		}
+	let mut audio_traks = traks
+		.iter()
+		.filter(|trak| &trak.track.handler_type == b"soun" && trak.mdia.is_some());
 
-		mdhd = None;
-		minf = None;
-
-		reader.seek(SeekFrom::Start(mdia.start + 8))?;
-
-		let mut read = 8;
-		while read < mdia.len {
-			let Some(atom) = reader.next()? else { break };
+	let selected = match audio_track {
+		Mp4AudioTrack::First => None,
+		Mp4AudioTrack::Id(id) => audio_traks.clone().find(|trak| trak.track.id == id),
+		Mp4AudioTrack::Language(language) => audio_traks
+			.clone()
+			.find(|trak| trak.track.language == Some(language)),
+	};
 
-			read += atom.len;
+	if selected.is_none() && audio_track != Mp4AudioTrack::First {
+		log::warn!("No audio track matches {audio_track:?}, using the first audio track");
+	}
 
-			if let AtomIdent::Fourcc(fourcc) = atom.ident {
-				match &fourcc {
-					b"mdhd" => {
-						skip_atom(reader, atom.extended, atom.len)?;
-						mdhd = Some(atom)
-					},
-					b"hdlr" => {
-						if atom.len < 20 {
-							log::warn!("Incomplete 'hdlr' atom, skipping");
-							skip_atom(reader, atom.extended, atom.len)?;
-							continue;
-						}
+	let Some(Trak {
+		track,
+		mdia: Some(mdia),
+		..
+	}) = selected.or_else(|| audio_traks.next())
+	else {
+		return Err(Mp4ParseError::message("file contains no audio tracks"));
+	};
 
-						// The hdlr atom is followed by 8 zeros
-						reader.seek(SeekFrom::Current(8))?;
+	let mut mdhd = None;
+	let mut minf = None;
 
-						let mut handler_type = [0; 4];
-						reader.read_exact(&mut handler_type)?;
+	reader.seek(SeekFrom::Start(mdia.start + mdia.header_size()))?;
 
-						if &handler_type == b"soun" {
-							audio_track = true
-						}
+	let mut read = mdia.header_size();
+	while read < mdia.len {
+		let Some(atom) = reader.next()? else { break };
 
-						skip_atom(reader, atom.extended, atom.len - 12)?;
-					},
-					b"minf" => minf = Some(atom),
-					_ => {
-						skip_atom(reader, atom.extended, atom.len)?;
-					},
-				}
+		read += atom.len;
 
-				continue;
+		if let AtomIdent::Fourcc(fourcc) = atom.ident {
+			match &fourcc {
+				b"mdhd" => {
+					skip_atom(reader, atom.extended, atom.len)?;
+					mdhd = Some(atom)
+				},
+				b"minf" => {
+					skip_atom(reader, atom.extended, atom.len)?;
+					minf = Some(atom)
+				},
+				_ => {
+					skip_atom(reader, atom.extended, atom.len)?;
+				},
 			}
 
-			skip_atom(reader, atom.extended, atom.len)?;
+			continue;
 		}
-	}
 
-	if !audio_track {
-		return Err(Mp4ParseError::message("file contains no audio tracks"));
+		skip_atom(reader, atom.extended, atom.len)?;
 	}
 
 	let Some(mdhd) = mdhd else {
@@ -363,54 +458,72 @@ where
 		);
 	};
 
-	Ok(AudioTrak { mdhd, minf })
+	Ok(AudioTrak {
+		id: track.id,
+		mdhd,
+		minf,
+	})
 }
 
-struct Mdhd {
-	timescale: u32,
-	duration: u64,
+/// Timing information from an `mvhd` (movie header) or `mdhd` (media header) atom
+///
+/// See [`Mp4Properties::movie_timing`] and [`Mp4Properties::media_timing`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Mp4Timing {
+	pub(crate) creation_time: u64,
+	pub(crate) modification_time: u64,
+	pub(crate) timescale: u32,
+	pub(crate) duration: u64,
 }
 
-impl Mdhd {
-	const IDENT: AtomIdent<'static> = AtomIdent::Fourcc(*b"mdhd");
-
-	fn parse<R>(reader: &mut AtomReader<R>) -> Result<Self, AtomParseError>
-	where
-		R: Read + Seek,
-	{
-		fn parse_inner<R>(reader: &mut AtomReader<R>) -> Result<Mdhd, AtomParseError>
-		where
-			R: Read + Seek,
-		{
-			let version = reader.read_u8()?;
-			let _flags = reader.read_uint(3)?;
-
-			let (timescale, duration) = if version == 1 {
-				// We don't care about these two values
-				let _creation_time = reader.read_u64()?;
-				let _modification_time = reader.read_u64()?;
-
-				let timescale = reader.read_u32()?;
-				let duration = reader.read_u64()?;
+impl Mp4Timing {
+	/// The creation time
+	///
+	/// The unit is the number of seconds since midnight, January 1, 1904 (UTC).
+	pub fn creation_time(&self) -> u64 {
+		self.creation_time
+	}
 
-				(timescale, duration)
-			} else {
-				let _creation_time = reader.read_u32()?;
-				let _modification_time = reader.read_u32()?;
+	/// The last modification time
+	///
+	/// The unit is the number of seconds since midnight, January 1, 1904 (UTC).
+	pub fn modification_time(&self) -> u64 {
+		self.modification_time
+	}
 
-				let timescale = reader.read_u32()?;
-				let duration = reader.read_u32()?;
+	/// The number of time units per second
+	pub fn timescale(&self) -> u32 {
+		self.timescale
+	}
 
-				(timescale, u64::from(duration))
-			};
+	/// The duration, in units of [`Mp4Timing::timescale`]
+	pub fn duration(&self) -> u64 {
+		self.duration
+	}
 
-			Ok(Mdhd {
-				timescale,
-				duration,
-			})
+	/// Parse the start of an `mvhd` or `mdhd` atom, which share the same layout
+	pub(super) fn parse<R>(reader: &mut AtomReader<R>) -> Result<Self, AtomParseError>
+	where
+		R: Read + Seek,
+	{
+		let version = reader.read_u8()?;
+		let _flags = reader.read_uint(3)?;
+
+		if version == 1 {
+			return Ok(Self {
+				creation_time: reader.read_u64()?,
+				modification_time: reader.read_u64()?,
+				timescale: reader.read_u32()?,
+				duration: reader.read_u64()?,
+			});
 		}
 
-		parse_inner(reader).map_err(|e| e.with_ident(Self::IDENT))
+		Ok(Self {
+			creation_time: u64::from(reader.read_u32()?),
+			modification_time: u64::from(reader.read_u32()?),
+			timescale: reader.read_u32()?,
+			duration: u64::from(reader.read_u32()?),
+		})
 	}
 }
 
@@ -544,6 +657,7 @@ const STSD_IDENT: AtomIdent<'static> = AtomIdent::Fourcc(*b"stsd");
 fn read_stsd<R>(
 	reader: &mut AtomReader<R>,
 	properties: &mut Mp4Properties,
+	parse_mode: ParsingMode,
 ) -> Result<(), AtomParseError>
 where
 	R: Read + Seek,
@@ -554,6 +668,7 @@ where
 	fn read_inner<R>(
 		reader: &mut AtomReader<R>,
 		properties: &mut Mp4Properties,
+		parse_mode: ParsingMode,
 	) -> Result<(), AtomParseError>
 	where
 		R: Read + Seek,
@@ -631,24 +746,43 @@ where
 				offset = reader.seek(SeekFrom::Current(32 + i64::from(extension_struct_size)))?;
 			}
 
-			match descriptor_format {
+			// Encrypted entries are otherwise the same as the original entry, which we can find
+			// in the `sinf` atom
+			let mut descriptor_format = *descriptor_format;
+			if matches!(&descriptor_format, b"drms" | b"enca") {
+				properties.drm_protected = true;
+
+				let protection = read_sinf(reader, &atom, parse_mode).map_err(|e| {
+					e.with_ident_if_not_present(AtomIdent::Fourcc(descriptor_format))
+				})?;
+				reader.seek(SeekFrom::Start(offset))?;
+
+				if let Some(original_format) =
+					protection.as_ref().and_then(Mp4Protection::original_format)
+				{
+					descriptor_format = original_format;
+				}
+
+				properties.protection = protection;
+			}
+
+			match &descriptor_format {
 				b"mp4a" => mp4a_properties(reader, properties)
 					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"mp4a")))?,
 				b"alac" => alac_properties(reader, properties)
 					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"alac")))?,
 				b"fLaC" => flac_properties(reader, properties)
 					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"fLaC")))?,
+				b"Opus" => opus_properties(reader, &atom, properties)
+					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"Opus")))?,
+				b"ac-3" => ac3_properties(reader, &atom, properties)
+					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"ac-3")))?,
+				b"ec-3" => eac3_properties(reader, &atom, properties)
+					.map_err(|e| e.with_ident_if_not_present(AtomIdent::Fourcc(*b"ec-3")))?,
+				// MP3 has no configuration atom, the sample entry has everything we need
+				b".mp3" => properties.codec = Some(Mp4Codec::MP3),
 				// Maybe do these?
-				// TODO: dops (opus)
 				// TODO: wave (https://developer.apple.com/library/archive/documentation/QuickTime/QTFF/QTFFChap3/qtff3.html#//apple_ref/doc/uid/TP40000939-CH205-134202)
-
-				// Special case to detect encrypted files
-				b"drms" => {
-					properties.drm_protected = true;
-					skip_atom(reader, false, offset - atom.start)
-						.map_err(|e| e.with_ident(atom.ident))?;
-					continue;
-				},
 				_ => {
 					log::warn!(
 						"Found unsupported sample entry: {:?}",
@@ -659,6 +793,12 @@ where
 				},
 			}
 
+			// An explicit channel layout takes precedence over the one implied by the codec
+			reader.seek(SeekFrom::Start(offset))?;
+			if let Some(channel_mask) = read_channel_layout(reader, &atom, properties.channels)? {
+				properties.channel_mask = Some(channel_mask);
+			}
+
 			// We only want to read the properties of the first stream
 			// that we can actually recognize
 			break;
@@ -667,12 +807,15 @@ where
 		Ok(())
 	}
 
-	read_inner(reader, properties).map_err(|e| e.with_ident_if_not_present(STSD_IDENT))
+	read_inner(reader, properties, parse_mode).map_err(|e| e.with_ident_if_not_present(STSD_IDENT))
 }
 
 pub(super) fn read_properties<R>(
 	reader: &mut AtomReader<R>,
-	traks: &[AtomInfo],
+	traks: &[Trak],
+	mvhd: Option<Mp4Timing>,
+	mvex: Option<&Mvex>,
+	audio_track: Mp4AudioTrack,
 	file_length: u64,
 	parse_mode: ParsingMode,
 ) -> Result<Mp4Properties, Mp4ParseError>
@@ -680,20 +823,55 @@ where
 	R: Read + Seek,
 {
 	// We need the mdhd and minf atoms from the audio track
-	let AudioTrak { mdhd, minf } = find_audio_trak(reader, traks)?;
+	let AudioTrak { id, mdhd, minf } = find_audio_trak(reader, traks, audio_track)?;
 
 	reader.seek(SeekFrom::Start(mdhd.start + 8))?;
-	let Mdhd {
-		timescale,
-		duration,
-	} = Mdhd::parse(reader)?;
+	let media_timing =
+		Mp4Timing::parse(reader).map_err(|e| e.with_ident(AtomIdent::Fourcc(*b"mdhd")))?;
+
+	let timescale = media_timing.timescale;
+	let mut duration = media_timing.duration;
+
+	// Fragmented files describe (most of) their samples in `moof` atoms, so the `mdhd` duration
+	// only covers the samples in `moov`, if any.
+	let mut fragment_samples_size = None;
+	if let Some(mvex) = mvex {
+		// An unknown duration is all 1s
+		if duration == u64::from(u32::MAX) || duration == u64::MAX {
+			duration = 0;
+		}
+
+		match read_fragments(reader, mvex.trex(id)) {
+			Ok(fragment_samples) if fragment_samples.count > 0 => {
+				duration = duration.saturating_add(fragment_samples.duration);
+				fragment_samples_size = Some(fragment_samples.size);
+			},
+			Ok(_) => log::warn!("No movie fragments found for the audio track"),
+			Err(e) if parse_mode == ParsingMode::Strict => return Err(e.into()),
+			Err(_) => log::warn!("Unable to read movie fragments, discarding"),
+		}
+
+		// Fall back to the `mehd` duration
+		if duration == 0
+			&& let Some(fragment_duration) = mvex.fragment_duration
+		{
+			duration =
+				((fragment_duration.as_nanos() * u128::from(timescale)) / 1_000_000_000) as u64;
+		}
+	}
 
 	// We create the properties here, since it is possible the other information isn't available
-	let mut properties = Mp4Properties::default();
+	let mut properties = Mp4Properties {
+		movie_timing: mvhd,
+		media_timing: Some(media_timing),
+		..Mp4Properties::default()
+	};
 
 	if timescale > 0 {
-		let duration_millis = (duration * 1000).div_round(u64::from(timescale));
-		properties.duration = Duration::from_millis(duration_millis);
+		// The fragment durations are summed, so this can be well past anything sensible
+		let duration_millis = (u128::from(duration) * 1000).div_round(u128::from(timescale));
+		properties.duration =
+			Duration::from_millis(u64::try_from(duration_millis).unwrap_or(u64::MAX));
 	}
 
 	// We need an `mdhd` atom at the bare minimum, everything else can be optional.
@@ -709,14 +887,14 @@ where
 	// `stsd` contains the majority of the audio properties
 	let mut cursor = Cursor::new(&*stsd_data);
 	let mut stsd_reader = AtomReader::new(&mut cursor, parse_mode)?;
-	read_stsd(&mut stsd_reader, &mut properties)?;
+	read_stsd(&mut stsd_reader, &mut properties, parse_mode)?;
 
 	// We do the mdat check up here, so we have access to the entire file
 	if duration > 0 {
 		// TODO: We should keep track of the `mdat` length when first reading the file.
 		//       This extra read is unnecessary.
 		let mdat_len;
-		match mdat_length(reader) {
+		match fragment_samples_size.map_or_else(|| mdat_length(reader), Ok) {
 			Ok(len) => mdat_len = len,
 			Err(err) => {
 				if parse_mode == ParsingMode::Strict {
@@ -829,89 +1007,11 @@ where
 			// Yet another descriptor to check
 			let descriptor = Descriptor::read(stsd)?;
 			if descriptor.tag == DECODER_SPECIFIC_DESCRIPTOR_TAG {
-				// https://wiki.multimedia.cx/index.php?title=MPEG-4_Audio#Audio_Specific_Config
-				//
-				// 5 bits: object type
-				// if (object type == 31)
-				//     6 bits + 32: object type
-				// 4 bits: frequency index
-				// if (frequency index == 15)
-				//     24 bits: frequency
-				// 4 bits: channel configuration
-				let byte_a = stsd.read_u8()?;
-				let byte_b = stsd.read_u8()?;
-
-				let mut object_type = byte_a >> 3;
-				let mut frequency_index = ((byte_a & 0x07) << 1) | (byte_b >> 7);
-				let mut channel_conf = (byte_b >> 3) & 0x0F;
-
-				let mut extended_object_type = false;
-				if object_type == 31 {
-					extended_object_type = true;
-
-					object_type = 32 + ((byte_a & 7) | (byte_b >> 5));
-					frequency_index = (byte_b >> 1) & 0x0F;
-				}
-
-				properties.extended_audio_object_type = Some(
-					AudioObjectType::try_from(object_type)
-						.map_err(|_| AtomParseError::message(None, "invalid audio object type"))?,
-				);
-
-				match frequency_index {
-					// 15 means the sample rate is stored in the next 24 bits
-					0x0F => {
-						let sample_rate;
-						let explicit_sample_rate = stsd.read_u24()?;
-						if extended_object_type {
-							sample_rate = explicit_sample_rate >> 1;
-							channel_conf = ((explicit_sample_rate >> 4) & 0x0F) as u8;
-						} else {
-							sample_rate = explicit_sample_rate << 1;
-							let byte_c = stsd.read_u8()?;
-
-							channel_conf =
-								((explicit_sample_rate & 0x80) as u8 | (byte_c >> 1)) & 0x0F;
-						}
-
-						// Just use the sample rate we already read above if this is invalid
-						if sample_rate > 0 {
-							properties.sample_rate = Some(sample_rate);
-						}
-					},
-					i if i < SAMPLE_RATES.len() as u8 => {
-						properties.sample_rate = Some(SAMPLE_RATES[i as usize]);
-
-						if extended_object_type {
-							let byte_c = stsd.read_u8()?;
-							channel_conf = (byte_b & 1) | (byte_c & 0xE0);
-						} else {
-							channel_conf = (byte_b >> 3) & 0x0F;
-						}
-					},
-					// Keep the sample rate we read above
-					_ => {},
-				}
-
-				// The channel configuration isn't always set, at least when testing with
-				// the Audio Lossless Coding reference software
-				if channel_conf > 0 {
-					properties.channels = Some(channel_conf);
-				}
-
-				// We just check for ALS here, might extend it for more codes eventually
-				if object_type == 36 {
-					let mut ident = [0; 5];
-					stsd.read_exact(&mut ident)?;
+				let mut decoder_specific_info = try_vec![0; descriptor.size as usize]?;
+				stsd.read_exact(&mut decoder_specific_info)?;
 
-					if &ident == b"\0ALS\0" {
-						properties.sample_rate = Some(stsd.read_u32()?);
-
-						// Sample count
-						stsd.seek(SeekFrom::Current(4))?;
-						properties.channels = Some(stsd.read_u16()? as u8 + 1);
-					}
-				}
+				let config = AudioSpecificConfig::parse(&decoder_specific_info)?;
+				apply_audio_specific_config(&config, properties)?;
 			}
 
 			if average_bitrate > 0 || properties.duration.is_zero() {
@@ -1014,12 +1114,583 @@ where
 	properties.sample_rate = Some(flac_properties.sample_rate);
 	properties.bit_depth = Some(flac_properties.bit_depth);
 	properties.channels = Some(flac_properties.channels);
+	// FLAC's default channel assignments are the same as Opus'
+	properties.channel_mask = ChannelMask::from_opus_channels(flac_properties.channels);
 
 	// Bitrate values are calculated later...
 
 	Ok(())
 }
 
+/// Find a child of a sample entry, such as its decoder configuration atom
+fn find_sample_entry_child<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	expected: [u8; 4],
+) -> Result<Option<AtomInfo>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let entry_end = entry.start + entry.len;
+	while stsd.stream_position()? + 8 <= entry_end {
+		let Some(atom) = stsd.next()? else {
+			break;
+		};
+
+		if atom.ident == AtomIdent::Fourcc(expected) {
+			return Ok(Some(atom));
+		}
+
+		skip_atom(stsd, atom.extended, atom.len)?;
+	}
+
+	Ok(None)
+}
+
+/// Search a sample entry for a `chan` or `chnl` atom
+///
+/// NOTE: This expects the reader to be at the start of the sample entry's children.
+fn read_channel_layout<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	channels: Option<u8>,
+) -> Result<Option<ChannelMask>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let entry_end = entry.start + entry.len;
+	while stsd.stream_position()? + 8 <= entry_end {
+		let Some(atom) = stsd.next()? else {
+			break;
+		};
+
+		let AtomIdent::Fourcc(ref fourcc) = atom.ident else {
+			skip_atom(stsd, atom.extended, atom.len)?;
+			continue;
+		};
+
+		let parse: fn(&[u8], u8) -> std::io::Result<Option<ChannelMask>> = match fourcc {
+			b"chan" => |content, _| parse_chan(content),
+			b"chnl" => parse_chnl,
+			// QuickTime files may have the `chan` atom nested in a `wave` atom, so we can
+			// just continue on to its children
+			b"wave" => continue,
+			_ => {
+				skip_atom(stsd, atom.extended, atom.len)?;
+				continue;
+			},
+		};
+
+		let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+		stsd.read_exact(&mut content)?;
+
+		return match parse(&content, channels.unwrap_or(0)) {
+			Ok(channel_mask) => Ok(channel_mask),
+			Err(e) => {
+				log::warn!("Unable to read channel layout from `{}`: {e}", atom.ident);
+				Ok(None)
+			},
+		};
+	}
+
+	Ok(None)
+}
+
+fn opus_properties<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	properties: &mut Mp4Properties,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	// https://opus-codec.org/docs/opus_in_isobmff.html
+	const OPUS_SAMPLE_RATE: u32 = 48000;
+
+	properties.codec = Some(Mp4Codec::Opus);
+
+	// Opus is always decoded at 48 kHz, regardless of the input sample rate
+	properties.sample_rate = Some(OPUS_SAMPLE_RATE);
+
+	let Some(dops) = find_sample_entry_child(stsd, entry, *b"dOps")? else {
+		return Ok(());
+	};
+
+	// Version (1)
+	// Output channel count (1)
+	// Pre-skip (2)
+	// Input sample rate (4)
+	// Output gain (2)
+	// Channel mapping family (1)
+	if dops.len < 8 + 11 {
+		return Ok(());
+	}
+
+	let version = stsd.read_u8()?;
+	if version != 0 {
+		log::warn!("Unsupported `dOps` version: {version}");
+		return Ok(());
+	}
+
+	let channels = stsd.read_u8()?;
+	properties.channels = Some(channels);
+
+	// Skipping 8 bytes
+	// Pre-skip (2)
+	// Input sample rate (4)
+	// Output gain (2)
+	stsd.seek(SeekFrom::Current(8))?;
+
+	// Only the Vorbis channel mapping families have a defined layout
+	let channel_mapping_family = stsd.read_u8()?;
+	if channel_mapping_family <= 1 {
+		properties.channel_mask = ChannelMask::from_opus_channels(channels);
+	}
+
+	Ok(())
+}
+
+// https://www.etsi.org/deliver/etsi_ts/102300_102399/102366/01.04.01_60/ts_102366v010401p.pdf
+const AC3_SAMPLE_RATES: [u32; 3] = [48000, 44100, 32000];
+
+// Indexed by `bit_rate_code`, in kb/s
+const AC3_BITRATES: [u32; 19] = [
+	32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 448, 512, 576, 640,
+];
+
+/// The channel count and layout of an AC-3 (or E-AC-3 independent substream) audio coding mode
+fn ac3_channels(acmod: u32, lfeon: bool) -> (u8, ChannelMask) {
+	let (channels, mask) = match acmod {
+		1 => (1, ChannelMask::mono()),
+		// 0 is 1+1 (dual mono)
+		0 | 2 => (2, ChannelMask::stereo()),
+		3 => (3, ChannelMask::linear_surround()),
+		4 => (3, ChannelMask::stereo() | ChannelMask::BACK_CENTER),
+		5 => (4, ChannelMask::linear_surround() | ChannelMask::BACK_CENTER),
+		6 => (
+			4,
+			ChannelMask::stereo() | ChannelMask::SIDE_LEFT | ChannelMask::SIDE_RIGHT,
+		),
+		_ => (
+			5,
+			ChannelMask::linear_surround() | ChannelMask::SIDE_LEFT | ChannelMask::SIDE_RIGHT,
+		),
+	};
+
+	if lfeon {
+		return (channels + 1, mask | ChannelMask::LOW_FREQUENCY);
+	}
+
+	(channels, mask)
+}
+
+fn ac3_properties<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	properties: &mut Mp4Properties,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	properties.codec = Some(Mp4Codec::AC3);
+
+	let Some(dac3) = find_sample_entry_child(stsd, entry, *b"dac3")? else {
+		return Ok(());
+	};
+
+	if dac3.len < 8 + 3 {
+		return Ok(());
+	}
+
+	let mut content = [0; 3];
+	stsd.read_exact(&mut content)?;
+
+	let mut reader = BitReader::new(&content);
+
+	let fscod = reader.read_bits(2)?;
+
+	// Skipping 8 bits
+	// bsid (5)
+	// bsmod (3)
+	reader.skip_bits(8)?;
+
+	let acmod = reader.read_bits(3)?;
+	let lfeon = reader.read_bit()?;
+	let bit_rate_code = reader.read_bits(5)?;
+
+	if let Some(sample_rate) = AC3_SAMPLE_RATES.get(fscod as usize) {
+		properties.sample_rate = Some(*sample_rate);
+	}
+
+	let (channels, channel_mask) = ac3_channels(acmod, lfeon);
+	properties.channels = Some(channels);
+	properties.channel_mask = Some(channel_mask);
+
+	if let Some(bitrate) = AC3_BITRATES.get(bit_rate_code as usize) {
+		properties.audio_bitrate = Some(*bitrate);
+	}
+
+	Ok(())
+}
+
+fn eac3_properties<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	properties: &mut Mp4Properties,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	// The additional channel locations of dependent substreams, from the most significant bit
+	const CHANNEL_LOCATIONS: [(u8, Option<ChannelMask>); 9] = [
+		// Lc/Rc pair
+		(
+			2,
+			Some(ChannelMask(
+				ChannelMask::FRONT_LEFT_OF_CENTER.bits()
+					| ChannelMask::FRONT_RIGHT_OF_CENTER.bits(),
+			)),
+		),
+		// Lrs/Rrs pair
+		(
+			2,
+			Some(ChannelMask(
+				ChannelMask::BACK_LEFT.bits() | ChannelMask::BACK_RIGHT.bits(),
+			)),
+		),
+		// Cs
+		(1, Some(ChannelMask::BACK_CENTER)),
+		// Ts
+		(1, Some(ChannelMask::TOP_CENTER)),
+		// Lsd/Rsd pair
+		(2, None),
+		// Lw/Rw pair
+		(2, None),
+		// Lvh/Rvh pair
+		(
+			2,
+			Some(ChannelMask(
+				ChannelMask::TOP_FRONT_LEFT.bits() | ChannelMask::TOP_FRONT_RIGHT.bits(),
+			)),
+		),
+		// Cvh
+		(1, Some(ChannelMask::TOP_FRONT_CENTER)),
+		// LFE2
+		(1, None),
+	];
+
+	properties.codec = Some(Mp4Codec::EAC3);
+
+	let Some(dec3) = find_sample_entry_child(stsd, entry, *b"dec3")? else {
+		return Ok(());
+	};
+
+	// Data rate (13 bits)
+	// Independent substream count (3 bits)
+	// At least one independent substream (3 bytes)
+	if dec3.len < 8 + 5 {
+		return Ok(());
+	}
+
+	let mut content = try_vec![0; (dec3.len - 8) as usize]?;
+	stsd.read_exact(&mut content)?;
+
+	let mut reader = BitReader::new(&content);
+
+	let data_rate = reader.read_bits(13)?;
+	if data_rate > 0 {
+		properties.audio_bitrate = Some(data_rate);
+	}
+
+	// Skipping 3 bits
+	// num_ind_sub (3)
+	reader.skip_bits(3)?;
+
+	// We only care about the first independent substream, the others are for alternate programs
+	let fscod = reader.read_bits(2)?;
+
+	// Skipping 10 bits
+	// bsid (5)
+	// reserved (1)
+	// asvc (1)
+	// bsmod (3)
+	reader.skip_bits(10)?;
+
+	let acmod = reader.read_bits(3)?;
+	let lfeon = reader.read_bit()?;
+
+	// reserved (3)
+	reader.skip_bits(3)?;
+
+	let num_dep_sub = reader.read_bits(4)?;
+
+	if let Some(sample_rate) = AC3_SAMPLE_RATES.get(fscod as usize) {
+		properties.sample_rate = Some(*sample_rate);
+	}
+
+	let (mut channels, channel_mask) = ac3_channels(acmod, lfeon);
+	let mut channel_mask = Some(channel_mask);
+
+	if num_dep_sub > 0 {
+		let chan_loc = reader.read_bits(9)?;
+		for (i, (count, mask)) in CHANNEL_LOCATIONS.into_iter().enumerate() {
+			if chan_loc & (1 << (8 - i)) == 0 {
+				continue;
+			}
+
+			channels += count;
+
+			// Not every location can be represented in a `ChannelMask`
+			channel_mask = channel_mask.zip(mask).map(|(a, b)| a | b);
+		}
+	}
+
+	properties.channels = Some(channels);
+	properties.channel_mask = channel_mask;
+
+	Ok(())
+}
+
+/// The relevant parts of an MPEG-4 AudioSpecificConfig
+///
+/// See ISO/IEC 14496-3, 1.6.2.1
+#[derive(Default, Debug)]
+struct AudioSpecificConfig {
+	object_type: u8,
+	sample_rate: Option<u32>,
+	channel_configuration: u8,
+	sbr_present: bool,
+	ps_present: bool,
+	extension_sample_rate: Option<u32>,
+	// Sample rate and channel count from an `ALSSpecificConfig`
+	als: Option<(u32, u16)>,
+}
+
+impl AudioSpecificConfig {
+	const OBJECT_TYPE_SBR: u8 = 5;
+	const OBJECT_TYPE_PS: u8 = 29;
+	const OBJECT_TYPE_ALS: u8 = 36;
+
+	// `syncExtensionType`s for backward compatible HE-AAC signalling
+	const SYNC_EXTENSION_SBR: u32 = 0x2B7;
+	const SYNC_EXTENSION_PS: u32 = 0x548;
+
+	fn parse(data: &[u8]) -> std::io::Result<Self> {
+		let mut reader = BitReader::new(data);
+
+		let mut config = AudioSpecificConfig {
+			object_type: Self::read_object_type(&mut reader)?,
+			sample_rate: Self::read_sample_rate(&mut reader)?,
+			channel_configuration: reader.read_bits(4)? as u8,
+			..AudioSpecificConfig::default()
+		};
+
+		// Anything past this point is optional, a failure only means we can't
+		// gather any extra information.
+		if let Err(e) = config.parse_extensions(&mut reader) {
+			log::debug!("Unable to read AudioSpecificConfig extensions: {e}");
+		}
+
+		Ok(config)
+	}
+
+	fn parse_extensions(&mut self, reader: &mut BitReader<'_>) -> std::io::Result<()> {
+		// Explicit hierarchical signalling, the SBR/PS object type comes first, followed by the
+		// output sample rate and the core object type.
+		if self.object_type == Self::OBJECT_TYPE_SBR || self.object_type == Self::OBJECT_TYPE_PS {
+			self.sbr_present = true;
+			self.ps_present = self.object_type == Self::OBJECT_TYPE_PS;
+			self.extension_sample_rate = Self::read_sample_rate(reader)?;
+			self.object_type = Self::read_object_type(reader)?;
+
+			if self.object_type == 22 {
+				// extensionChannelConfiguration
+				reader.skip_bits(4)?;
+			}
+		}
+
+		match self.object_type {
+			1..=4 | 6 | 7 | 17 | 19..=23 => {
+				if !self.skip_ga_specific_config(reader)? {
+					return Ok(());
+				}
+			},
+			Self::OBJECT_TYPE_ALS => {
+				// fillBits
+				reader.skip_bits(5)?;
+
+				if reader.read_bits(32)?.to_be_bytes() != *b"ALS\0" {
+					return Ok(());
+				}
+
+				let sample_rate = reader.read_bits(32)?;
+
+				// Sample count
+				reader.skip_bits(32)?;
+
+				let channels = reader.read_bits(16)? as u16 + 1;
+				self.als = Some((sample_rate, channels));
+				return Ok(());
+			},
+			_ => return Ok(()),
+		}
+
+		if matches!(self.object_type, 17 | 19..=27 | 39) {
+			let ep_config = reader.read_bits(2)?;
+			if ep_config == 2 || ep_config == 3 {
+				// ErrorProtectionSpecificConfig, not worth parsing
+				return Ok(());
+			}
+		}
+
+		// Explicit backward compatible signalling, the core config is followed by sync extensions
+		if self.sbr_present || reader.bits_left() < 16 {
+			return Ok(());
+		}
+
+		if reader.read_bits(11)? != Self::SYNC_EXTENSION_SBR {
+			return Ok(());
+		}
+
+		if Self::read_object_type(reader)? != Self::OBJECT_TYPE_SBR {
+			return Ok(());
+		}
+
+		self.sbr_present = reader.read_bit()?;
+		if !self.sbr_present {
+			return Ok(());
+		}
+
+		self.extension_sample_rate = Self::read_sample_rate(reader)?;
+
+		if reader.bits_left() >= 12 && reader.read_bits(11)? == Self::SYNC_EXTENSION_PS {
+			self.ps_present = reader.read_bit()?;
+		}
+
+		Ok(())
+	}
+
+	/// Skips over a GASpecificConfig, returning `false` if it could not be skipped
+	fn skip_ga_specific_config(&self, reader: &mut BitReader<'_>) -> std::io::Result<bool> {
+		// frameLengthFlag
+		reader.skip_bits(1)?;
+
+		let depends_on_core_coder = reader.read_bit()?;
+		if depends_on_core_coder {
+			// coreCoderDelay
+			reader.skip_bits(14)?;
+		}
+
+		let extension_flag = reader.read_bit()?;
+
+		// There's an embedded `program_config_element`, no need to go any further
+		if self.channel_configuration == 0 {
+			return Ok(false);
+		}
+
+		if self.object_type == 6 || self.object_type == 20 {
+			// layerNr
+			reader.skip_bits(3)?;
+		}
+
+		if extension_flag {
+			match self.object_type {
+				// numOfSubFrame (5)
+				// layer_length (11)
+				22 => reader.skip_bits(16)?,
+				// aacSectionDataResilienceFlag (1)
+				// aacScalefactorDataResilienceFlag (1)
+				// aacSpectralDataResilienceFlag (1)
+				17 | 19 | 20 | 23 => reader.skip_bits(3)?,
+				_ => {},
+			}
+
+			// extensionFlag3
+			reader.skip_bits(1)?;
+		}
+
+		Ok(true)
+	}
+
+	fn read_object_type(reader: &mut BitReader<'_>) -> std::io::Result<u8> {
+		let object_type = reader.read_bits(5)? as u8;
+		if object_type == 31 {
+			return Ok(32 + reader.read_bits(6)? as u8);
+		}
+
+		Ok(object_type)
+	}
+
+	fn read_sample_rate(reader: &mut BitReader<'_>) -> std::io::Result<Option<u32>> {
+		let frequency_index = reader.read_bits(4)?;
+
+		// 15 means the sample rate is stored in the next 24 bits
+		let sample_rate = if frequency_index == 0x0F {
+			reader.read_bits(24)?
+		} else {
+			SAMPLE_RATES[frequency_index as usize]
+		};
+
+		Ok((sample_rate > 0).then_some(sample_rate))
+	}
+}
+
+fn apply_audio_specific_config(
+	config: &AudioSpecificConfig,
+	properties: &mut Mp4Properties,
+) -> Result<(), AtomParseError> {
+	properties.extended_audio_object_type = Some(
+		AudioObjectType::try_from(config.object_type)
+			.map_err(|_| AtomParseError::message(None, "invalid audio object type"))?,
+	);
+
+	// The sample rate in the sample entry is usually the output sample rate, but it is limited to 16 bits.
+	let sample_entry_sample_rate = properties.sample_rate;
+
+	// Just use the sample rate we already read from the sample entry if this is invalid
+	if let Some(sample_rate) = config.sample_rate {
+		properties.sample_rate = Some(sample_rate);
+	}
+
+	// The channel configuration isn't always set, at least when testing with
+	// the Audio Lossless Coding reference software
+	if config.channel_configuration > 0 {
+		properties.channels = Some(config.channel_configuration);
+	}
+
+	let mut sbr_present = config.sbr_present;
+
+	// Implicit signalling, the only hint we get is a sample entry with double the core sample rate
+	let is_aac_lc = config.object_type == AudioObjectType::AacLowComplexity as u8;
+	if !sbr_present
+		&& is_aac_lc
+		&& let (Some(core), Some(sample_entry)) = (config.sample_rate, sample_entry_sample_rate)
+	{
+		sbr_present = core * 2 == sample_entry;
+	}
+
+	if sbr_present {
+		properties.extension_audio_object_type = Some(AudioObjectType::SpectralBandReplication);
+		properties.sample_rate = config
+			.extension_sample_rate
+			.or_else(|| config.sample_rate.map(|core| core * 2))
+			.or(properties.sample_rate);
+	}
+
+	if config.ps_present {
+		properties.extension_audio_object_type = Some(AudioObjectType::ParametricStereo);
+		// Parametric stereo always produces two channels from a mono core
+		properties.channels = Some(2);
+	}
+
+	if let Some((sample_rate, channels)) = config.als {
+		properties.sample_rate = Some(sample_rate);
+		properties.channels = Some(channels as u8);
+	}
+
+	Ok(())
+}
+
 // Used to calculate the bitrate, when it isn't readily available to us
 fn mdat_length<R>(reader: &mut AtomReader<R>) -> Result<u64, Mp4ParseError>
 where
@@ -1042,7 +1713,7 @@ where
 
 struct Descriptor {
 	tag: u8,
-	_size: u32,
+	size: u32,
 }
 
 impl Descriptor {
@@ -1059,6 +1730,253 @@ impl Descriptor {
 			}
 		}
 
-		Ok(Descriptor { tag, _size: size })
+		Ok(Descriptor { tag, size })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{AudioSpecificConfig, Mp4Codec, Mp4Properties, read_stsd};
+	use crate::config::ParsingMode;
+	use crate::mp4::read::AtomReader;
+	use crate::properties::ChannelMask;
+
+	use std::io::Cursor;
+
+	/// Create the contents of an `stsd` atom with a single (version 0) audio sample entry
+	fn stsd(fourcc: [u8; 4], channels: u16, sample_rate: u16, children: &[u8]) -> Vec<u8> {
+		let mut entry = Vec::new();
+		entry.extend((36 + children.len() as u32).to_be_bytes());
+		entry.extend(fourcc);
+		// Reserved (6), data reference index (2), version (2), revision level (2), vendor (4)
+		entry.extend([0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+		entry.extend(channels.to_be_bytes());
+		// Sample size (2), compression ID (2), packet size (2)
+		entry.extend([0, 16, 0, 0, 0, 0]);
+		entry.extend(u32::from(sample_rate).wrapping_shl(16).to_be_bytes());
+		entry.extend(children);
+
+		// Version (1), flags (3), entry count (4)
+		let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+		stsd.extend(entry);
+		stsd
+	}
+
+	fn read_sample_entry(stsd: &[u8]) -> Mp4Properties {
+		let mut cursor = Cursor::new(stsd);
+		let mut reader = AtomReader::new(&mut cursor, ParsingMode::Strict).unwrap();
+
+		let mut properties = Mp4Properties::default();
+		read_stsd(&mut reader, &mut properties, ParsingMode::Strict).unwrap();
+		properties
+	}
+
+	#[test_log::test]
+	fn opus_sample_entry() {
+		#[rustfmt::skip]
+		let dops = [
+			0, 0, 0, 19, b'd', b'O', b'p', b's',
+			// Version, output channel count, pre-skip
+			0, 6, 0x01, 0x38,
+			// Input sample rate (44.1 kHz), output gain, channel mapping family
+			0, 0, 0xAC, 0x44, 0, 0, 1,
+		];
+
+		let properties = read_sample_entry(&stsd(*b"Opus", 2, 48000, &dops));
+		assert_eq!(properties.codec, Some(Mp4Codec::Opus));
+		assert_eq!(properties.sample_rate, Some(48000));
+		assert_eq!(properties.channels, Some(6));
+		assert_eq!(properties.channel_mask, ChannelMask::from_opus_channels(6));
+	}
+
+	#[test_log::test]
+	fn ac3_sample_entry() {
+		// 44.1 kHz, acmod 7 (3/2), LFE, 384 kb/s
+		let dac3 = [0, 0, 0, 11, b'd', b'a', b'c', b'3', 0x50, 0x3D, 0xC0];
+
+		let properties = read_sample_entry(&stsd(*b"ac-3", 2, 44100, &dac3));
+		assert_eq!(properties.codec, Some(Mp4Codec::AC3));
+		assert_eq!(properties.sample_rate, Some(44100));
+		assert_eq!(properties.channels, Some(6));
+		assert_eq!(
+			properties.channel_mask,
+			Some(
+				ChannelMask::linear_surround()
+					| ChannelMask::SIDE_LEFT
+					| ChannelMask::SIDE_RIGHT
+					| ChannelMask::LOW_FREQUENCY
+			)
+		);
+		assert_eq!(properties.audio_bitrate, Some(384));
+	}
+
+	#[test_log::test]
+	fn eac3_sample_entry() {
+		#[rustfmt::skip]
+		let dec3 = [
+			0, 0, 0, 15, b'd', b'e', b'c', b'3',
+			// 640 kb/s, 1 independent substream
+			0x14, 0x00,
+			// 48 kHz, bsid 16, acmod 7 (3/2), LFE, 1 dependent substream
+			0x20, 0x0F, 0x02,
+			// chan_loc: Lrs/Rrs pair
+			0x80, 0x00,
+		];
+
+		let properties = read_sample_entry(&stsd(*b"ec-3", 2, 48000, &dec3));
+		assert_eq!(properties.codec, Some(Mp4Codec::EAC3));
+		assert_eq!(properties.sample_rate, Some(48000));
+		assert_eq!(properties.channels, Some(8));
+		assert_eq!(
+			properties.channel_mask,
+			Some(
+				ChannelMask::linear_surround()
+					| ChannelMask::SIDE_LEFT
+					| ChannelMask::SIDE_RIGHT
+					| ChannelMask::BACK_LEFT
+					| ChannelMask::BACK_RIGHT
+					| ChannelMask::LOW_FREQUENCY
+			)
+		);
+		assert_eq!(properties.audio_bitrate, Some(640));
+	}
+
+	#[test_log::test]
+	fn sample_entry_channel_layout() {
+		#[rustfmt::skip]
+		let chan = [
+			0, 0, 0, 24, b'c', b'h', b'a', b'n',
+			// Version, flags
+			0, 0, 0, 0,
+			// MPEG_3_0_A (L R C)
+			0, 113, 0, 3,
+			// Bitmap, description count
+			0, 0, 0, 0, 0, 0, 0, 0,
+		];
+
+		let properties = read_sample_entry(&stsd(*b".mp3", 3, 44100, &chan));
+		assert_eq!(properties.channels, Some(3));
+		assert_eq!(
+			properties.channel_mask,
+			Some(ChannelMask::linear_surround())
+		);
+	}
+
+	#[test_log::test]
+	fn mp3_sample_entry() {
+		let properties = read_sample_entry(&stsd(*b".mp3", 2, 44100, &[]));
+		assert_eq!(properties.codec, Some(Mp4Codec::MP3));
+		assert_eq!(properties.sample_rate, Some(44100));
+		assert_eq!(properties.channels, Some(2));
+		assert_eq!(properties.channel_mask, None);
+	}
+
+	/// Create an atom with the given contents
+	fn atom(fourcc: [u8; 4], content: &[u8]) -> Vec<u8> {
+		let mut atom = (8 + content.len() as u32).to_be_bytes().to_vec();
+		atom.extend(fourcc);
+		atom.extend(content);
+		atom
+	}
+
+	#[test_log::test]
+	fn fairplay_sample_entry() {
+		let mut name = b"Foo Bar".to_vec();
+		name.resize(16, 0);
+
+		let mut schi = atom(*b"user", &1234_u32.to_be_bytes());
+		schi.extend(atom(*b"key ", &1_u32.to_be_bytes()));
+		schi.extend(atom(*b"name", &name));
+
+		let mut sinf = atom(*b"frma", b"mp4a");
+		sinf.extend(atom(
+			*b"schm",
+			&[0, 0, 0, 0, b'i', b't', b'u', b'n', 0, 0, 0, 0],
+		));
+		sinf.extend(atom(*b"schi", &schi));
+
+		let properties = read_sample_entry(&stsd(*b"drms", 2, 44100, &atom(*b"sinf", &sinf)));
+		assert!(properties.is_drm_protected());
+		assert_eq!(properties.codec, Some(Mp4Codec::AAC));
+		assert_eq!(properties.sample_rate, Some(44100));
+		assert_eq!(properties.channels, Some(2));
+
+		let protection = properties.protection().unwrap();
+		assert_eq!(protection.original_format(), Some(*b"mp4a"));
+		assert_eq!(protection.scheme_type(), Some(*b"itun"));
+		assert_eq!(protection.scheme_version(), Some(0));
+		assert_eq!(protection.user_id(), Some(1234));
+		assert_eq!(protection.user_name(), Some("Foo Bar"));
+	}
+
+	#[test_log::test]
+	fn common_encryption_sample_entry() {
+		let mut sinf = atom(*b"frma", b".mp3");
+		sinf.extend(atom(
+			*b"schm",
+			&[0, 0, 0, 0, b'c', b'e', b'n', b'c', 0, 1, 0, 0],
+		));
+		sinf.extend(atom(*b"schi", &atom(*b"tenc", &[0; 24])));
+
+		let properties = read_sample_entry(&stsd(*b"enca", 2, 48000, &atom(*b"sinf", &sinf)));
+		assert!(properties.is_drm_protected());
+		assert_eq!(properties.codec, Some(Mp4Codec::MP3));
+
+		let protection = properties.protection().unwrap();
+		assert_eq!(protection.original_format(), Some(*b".mp3"));
+		assert_eq!(protection.scheme_type(), Some(*b"cenc"));
+		assert_eq!(protection.scheme_version(), Some(0x0001_0000));
+		assert_eq!(protection.user_id(), None);
+		assert_eq!(protection.user_name(), None);
+	}
+
+	#[test_log::test]
+	fn malformed_sinf_skipped() {
+		let mut sinf = atom(*b"frma", b".mp3");
+		// `schm` claims to be larger than the rest of the entry
+		sinf.extend(100_u32.to_be_bytes());
+		sinf.extend(b"schm");
+		let stsd = stsd(*b"enca", 2, 48000, &atom(*b"sinf", &sinf));
+
+		let mut cursor = Cursor::new(&*stsd);
+		let mut reader = AtomReader::new(&mut cursor, ParsingMode::Strict).unwrap();
+		let mut properties = Mp4Properties::default();
+		assert!(read_stsd(&mut reader, &mut properties, ParsingMode::Strict).is_err());
+
+		let mut cursor = Cursor::new(&*stsd);
+		let mut reader = AtomReader::new(&mut cursor, ParsingMode::BestAttempt).unwrap();
+		let mut properties = Mp4Properties::default();
+		read_stsd(&mut reader, &mut properties, ParsingMode::BestAttempt).unwrap();
+
+		assert!(properties.is_drm_protected());
+		assert!(properties.protection().is_none());
+		assert_eq!(properties.sample_rate, Some(48000));
+		assert_eq!(properties.channels, Some(2));
+	}
+
+	#[test_log::test]
+	fn audio_specific_config_hierarchical_ps() {
+		// AOT 29 (PS), 24 kHz core, mono, 48 kHz output, AOT 2 (LC) core
+		let config = AudioSpecificConfig::parse(&[0xEB, 0x09, 0x88]).unwrap();
+
+		assert_eq!(config.object_type, 2);
+		assert_eq!(config.sample_rate, Some(24000));
+		assert_eq!(config.channel_configuration, 1);
+		assert!(config.sbr_present);
+		assert!(config.ps_present);
+		assert_eq!(config.extension_sample_rate, Some(48000));
+	}
+
+	#[test_log::test]
+	fn audio_specific_config_backward_compatible_sbr() {
+		// AOT 2 (LC), 22.05 kHz core, mono, followed by the SBR and PS sync extensions
+		let config =
+			AudioSpecificConfig::parse(&[0x13, 0x88, 0x56, 0xE5, 0xA5, 0x48, 0x00]).unwrap();
+
+		assert_eq!(config.object_type, 2);
+		assert_eq!(config.sample_rate, Some(22050));
+		assert!(config.sbr_present);
+		assert!(!config.ps_present);
+		assert_eq!(config.extension_sample_rate, Some(44100));
 	}
 }
@@ -0,0 +1,139 @@
+use super::atom_info::{AtomIdent, AtomInfo};
+use super::read::{AtomReader, seek_to_end};
+use crate::config::ParsingMode;
+use crate::macros::try_vec;
+use crate::mp4::error::AtomParseError;
+
+use std::io::{Read, Seek};
+
+/// Protection details of an encrypted audio sample entry (`drms` or `enca`)
+///
+/// This comes from the entry's `sinf` (protection scheme information) atom.
+///
+/// See [`Mp4Properties::protection`](crate::mp4::Mp4Properties::protection).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Mp4Protection {
+	pub(crate) original_format: Option<[u8; 4]>,
+	pub(crate) scheme_type: Option<[u8; 4]>,
+	pub(crate) scheme_version: Option<u32>,
+	pub(crate) user_id: Option<u32>,
+	pub(crate) user_name: Option<String>,
+}
+
+impl Mp4Protection {
+	/// The format of the sample entry before it was encrypted, from `frma` (Ex. `mp4a`)
+	pub fn original_format(&self) -> Option<[u8; 4]> {
+		self.original_format
+	}
+
+	/// The protection scheme, from `schm` (Ex. `itun` for FairPlay, or `cenc` for Common Encryption)
+	pub fn scheme_type(&self) -> Option<[u8; 4]> {
+		self.scheme_type
+	}
+
+	/// The version of the protection scheme, from `schm`
+	pub fn scheme_version(&self) -> Option<u32> {
+		self.scheme_version
+	}
+
+	/// The ID of the account that purchased the file, from the FairPlay `user` atom
+	pub fn user_id(&self) -> Option<u32> {
+		self.user_id
+	}
+
+	/// The name of the account that purchased the file, from the FairPlay `name` atom
+	pub fn user_name(&self) -> Option<&str> {
+		self.user_name.as_deref()
+	}
+}
+
+/// Find and read the `sinf` atom of a protected sample entry
+///
+/// This expects the reader to be at the start of the entry's children.
+///
+/// Outside of [`ParsingMode::Strict`], a malformed `sinf` atom is skipped, as the protection
+/// details aren't needed to read the rest of the properties.
+pub(super) fn read_sinf<R>(
+	stsd: &mut AtomReader<R>,
+	entry: &AtomInfo,
+	parse_mode: ParsingMode,
+) -> Result<Option<Mp4Protection>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let entry_end = entry.start + entry.len;
+	while stsd.stream_position()? + 8 <= entry_end {
+		let Some(atom) = stsd.next()? else {
+			break;
+		};
+
+		if atom.ident == AtomIdent::Fourcc(*b"sinf") {
+			let mut protection = Mp4Protection::default();
+			if let Err(e) = read_protection_atoms(stsd, &atom, &mut protection) {
+				if parse_mode == ParsingMode::Strict {
+					return Err(e.with_ident_if_not_present(AtomIdent::Fourcc(*b"sinf")));
+				}
+
+				log::warn!("Unable to read the `sinf` atom, discarding the protection details");
+				return Ok(None);
+			}
+
+			return Ok(Some(protection));
+		}
+
+		seek_to_end(stsd, &atom)?;
+	}
+
+	Ok(None)
+}
+
+fn read_protection_atoms<R>(
+	stsd: &mut AtomReader<R>,
+	parent: &AtomInfo,
+	protection: &mut Mp4Protection,
+) -> Result<(), AtomParseError>
+where
+	R: Read + Seek,
+{
+	let parent_end = parent.start + parent.len;
+	while stsd.stream_position()? + 8 <= parent_end {
+		let Some(atom) = stsd.next()? else {
+			break;
+		};
+
+		let content_len = atom.len - atom.header_size();
+		if let AtomIdent::Fourcc(ref fourcc) = atom.ident {
+			match fourcc {
+				b"frma" if content_len >= 4 => {
+					protection.original_format = Some(stsd.read_u32()?.to_be_bytes());
+				},
+				b"schm" if content_len >= 12 => {
+					// Version (1), flags (3)
+					let _ = stsd.read_u32()?;
+
+					protection.scheme_type = Some(stsd.read_u32()?.to_be_bytes());
+					protection.scheme_version = Some(stsd.read_u32()?);
+				},
+				// The scheme specific information, the FairPlay atoms are stored here
+				b"schi" => read_protection_atoms(stsd, &atom, protection)?,
+				b"user" if content_len >= 4 => protection.user_id = Some(stsd.read_u32()?),
+				b"name" => {
+					let mut name = try_vec![0; content_len as usize]?;
+					stsd.read_exact(&mut name)?;
+
+					// The name is null terminated, and may be padded
+					let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+					name.truncate(len);
+
+					protection.user_name = Some(String::from_utf8_lossy(&name).into_owned());
+				},
+				_ => {},
+			}
+		}
+
+		seek_to_end(stsd, &atom)?;
+	}
+
+	Ok(())
+}
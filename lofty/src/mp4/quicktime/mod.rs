@@ -0,0 +1,487 @@
+pub(super) mod read;
+pub(crate) mod write;
+
+#[cfg(test)]
+mod tests;
+
+use crate::config::WriteOptions;
+use crate::error::{FileEncodingError, TagEncodingError};
+use crate::io::VerifiedFile;
+use crate::mp4::AtomData;
+use crate::tag::{
+	Accessor, ItemKey, ItemValue, MergeTag, SplitTag, Tag, TagExt, TagItem, TagType, TagWriteExt,
+};
+use crate::util::io::FileLike;
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use lofty_attr::tag;
+
+/// The packed ISO 639-2/T language code for "und" (undetermined)
+const UNDETERMINED_LANGUAGE: u16 = 0x55C4;
+
+/// The key of a [`QuickTimeItem`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QuickTimeKey {
+	/// A key from the `moov.meta.keys` atom (Ex. `com.apple.quicktime.title`)
+	Mdta(String),
+	/// A `moov.udta` text atom (Ex. `©nam`)
+	UserData([u8; 4]),
+}
+
+impl QuickTimeKey {
+	/// The key as a string, as used in the [`ItemKey`] mapping
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::QuickTimeKey;
+	///
+	/// let title = QuickTimeKey::UserData(*b"\xa9nam");
+	/// assert_eq!(title.as_str(), "©nam");
+	/// ```
+	pub fn as_str(&self) -> Cow<'_, str> {
+		match self {
+			QuickTimeKey::Mdta(key) => Cow::Borrowed(key),
+			QuickTimeKey::UserData(fourcc) => {
+				Cow::Owned(fourcc.iter().map(|b| *b as char).collect())
+			},
+		}
+	}
+
+	/// The inverse of `as_str`, `udta` text atoms are identified by their leading `©`
+	fn from_str(key: &str) -> Self {
+		let mut fourcc = [0; 4];
+		let mut len = 0;
+		for c in key.chars() {
+			let Ok(b) = u8::try_from(c) else {
+				return QuickTimeKey::Mdta(key.to_owned());
+			};
+
+			if len == fourcc.len() {
+				return QuickTimeKey::Mdta(key.to_owned());
+			}
+
+			fourcc[len] = b;
+			len += 1;
+		}
+
+		if len == fourcc.len() && fourcc[0] == b'\xa9' {
+			return QuickTimeKey::UserData(fourcc);
+		}
+
+		QuickTimeKey::Mdta(key.to_owned())
+	}
+
+	fn item_key(&self) -> Option<ItemKey> {
+		ItemKey::from_key(TagType::QuickTime, &self.as_str())
+	}
+}
+
+/// A single QuickTime metadata item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuickTimeItem {
+	pub(crate) key: QuickTimeKey,
+	pub(crate) data: AtomData,
+	// The language code of a `udta` text atom
+	pub(crate) language: u16,
+}
+
+impl QuickTimeItem {
+	/// Create a new `QuickTimeItem`
+	///
+	/// NOTE: `udta` text atoms ([`QuickTimeKey::UserData`]) can only store text, any other
+	///       [`AtomData`] will be discarded when writing.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::{AtomData, QuickTimeItem, QuickTimeKey};
+	///
+	/// let key = QuickTimeKey::Mdta(String::from("com.apple.quicktime.title"));
+	/// let item = QuickTimeItem::new(key, AtomData::UTF8(String::from("Foo title")));
+	/// ```
+	pub fn new(key: QuickTimeKey, data: AtomData) -> Self {
+		Self {
+			key,
+			data,
+			language: UNDETERMINED_LANGUAGE,
+		}
+	}
+
+	/// Returns the item's key
+	pub fn key(&self) -> &QuickTimeKey {
+		&self.key
+	}
+
+	/// Returns the item's data
+	pub fn data(&self) -> &AtomData {
+		&self.data
+	}
+
+	/// Consumes the item, returning its data
+	pub fn into_data(self) -> AtomData {
+		self.data
+	}
+
+	fn text(&self) -> Option<&str> {
+		match &self.data {
+			AtomData::UTF8(text) | AtomData::UTF16(text) => Some(text),
+			_ => None,
+		}
+	}
+}
+
+macro_rules! impl_accessor {
+	($($name:ident => $key:ident;)+) => {
+		paste::paste! {
+			$(
+				fn $name(&self) -> Option<Cow<'_, str>> {
+					self.get_text(ItemKey::$key).map(Cow::Borrowed)
+				}
+
+				fn [<set_ $name>](&mut self, value: String) {
+					self.set_text(ItemKey::$key, value)
+				}
+
+				fn [<remove_ $name>](&mut self) {
+					self.remove_text(ItemKey::$key)
+				}
+			)+
+		}
+	}
+}
+
+/// ## Item storage
+///
+/// QuickTime files can store metadata in two places, separate from the iTunes-style
+/// [`Ilst`](crate::mp4::Ilst):
+///
+/// * `moov.meta`, with an `mdta` handler, where items are identified by reverse DNS keys
+///   (Ex. `com.apple.quicktime.title`). These are written by Apple devices, such as iPhone voice
+///   memos and screen recordings.
+/// * `moov.udta` text atoms, the legacy format, where items are identified by a `©` prefixed
+///   atom (Ex. `©nam`).
+///
+/// Both are represented by [`QuickTimeKey`]. When setting an item through [`Accessor`], existing
+/// items will be updated in place, otherwise an `mdta` item is created.
+///
+/// ## Conversions
+///
+/// ### To `Tag`
+///
+/// For a [`QuickTimeItem`] to be converted, it must have a key with an [`ItemKey`] mapping and a
+/// value of [`AtomData::UTF8`] or [`AtomData::UTF16`].
+///
+/// ### From `Tag`
+///
+/// For a [`TagItem`] to be converted, it must have a value of [`ItemValue::Text`]. Items split from
+/// a `QuickTimeMetadata` are written back to the keys they came from. Otherwise, they are created in
+/// `moov.meta`, unless the [`ItemKey`] only has a `udta` mapping.
+///
+/// #### Pictures
+///
+/// Pictures will be discarded.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+#[tag(description = "QuickTime metadata", supported_formats(Mp4))]
+pub struct QuickTimeMetadata {
+	pub(crate) items: Vec<QuickTimeItem>,
+}
+
+impl QuickTimeMetadata {
+	/// Create a new empty `QuickTimeMetadata`
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::QuickTimeMetadata;
+	/// use lofty::tag::TagExt;
+	///
+	/// let quicktime = QuickTimeMetadata::new();
+	/// assert!(quicktime.is_empty());
+	/// ```
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns all items
+	pub fn items(&self) -> impl ExactSizeIterator<Item = &QuickTimeItem> + Clone {
+		self.items.iter()
+	}
+
+	/// Get the first item with the given key
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use lofty::mp4::{AtomData, QuickTimeItem, QuickTimeKey, QuickTimeMetadata};
+	///
+	/// let key = QuickTimeKey::UserData(*b"\xa9day");
+	///
+	/// let mut quicktime = QuickTimeMetadata::new();
+	/// quicktime.insert(QuickTimeItem::new(key.clone(), AtomData::UTF8(String::from("2024"))));
+	///
+	/// assert!(quicktime.get(&key).is_some());
+	/// ```
+	pub fn get(&self, key: &QuickTimeKey) -> Option<&QuickTimeItem> {
+		self.items.iter().find(|item| &item.key == key)
+	}
+
+	/// Get all items with the given key
+	pub fn get_all<'a>(&'a self, key: &'a QuickTimeKey) -> impl Iterator<Item = &'a QuickTimeItem> {
+		self.items.iter().filter(move |item| &item.key == key)
+	}
+
+	/// Insert an item, replacing any existing items with the same key
+	pub fn insert(&mut self, item: QuickTimeItem) {
+		self.items.retain(|existing| existing.key != item.key);
+		self.items.push(item);
+	}
+
+	/// Append an item, keeping any existing items with the same key
+	pub fn push(&mut self, item: QuickTimeItem) {
+		self.items.push(item);
+	}
+
+	/// Remove all items with the given key, returning them
+	pub fn remove(&mut self, key: &QuickTimeKey) -> impl Iterator<Item = QuickTimeItem> + use<> {
+		let mut removed = Vec::new();
+
+		let mut i = 0;
+		while i < self.items.len() {
+			if &self.items[i].key == key {
+				removed.push(self.items.remove(i));
+				continue;
+			}
+
+			i += 1;
+		}
+
+		removed.into_iter()
+	}
+
+	/// Retain only the items that match the predicate
+	pub fn retain<F>(&mut self, f: F)
+	where
+		F: FnMut(&QuickTimeItem) -> bool,
+	{
+		self.items.retain(f)
+	}
+
+	fn get_text(&self, item_key: ItemKey) -> Option<&str> {
+		self.items
+			.iter()
+			.filter(|item| item.key.item_key() == Some(item_key))
+			.find_map(QuickTimeItem::text)
+	}
+
+	// Update every existing key that maps to `item_key`, so the `mdta` and `udta` items can't
+	// disagree. A new `mdta` item is only created if there are none.
+	fn set_text(&mut self, item_key: ItemKey, value: String) {
+		let mut updated_keys = Vec::new();
+		self.items.retain_mut(|item| {
+			if item.key.item_key() != Some(item_key) {
+				return true;
+			}
+
+			if updated_keys.contains(&item.key) {
+				return false;
+			}
+
+			updated_keys.push(item.key.clone());
+			item.data = AtomData::UTF8(value.clone());
+			true
+		});
+
+		if updated_keys.is_empty()
+			&& let Some(key) = item_key.map_key(TagType::QuickTime)
+		{
+			self.items.push(QuickTimeItem::new(
+				QuickTimeKey::from_str(key),
+				AtomData::UTF8(value),
+			));
+		}
+	}
+
+	fn remove_text(&mut self, item_key: ItemKey) {
+		self.items
+			.retain(|item| item.key.item_key() != Some(item_key));
+	}
+}
+
+impl Accessor for QuickTimeMetadata {
+	impl_accessor!(
+		artist  => TrackArtist;
+		title   => TrackTitle;
+		album   => AlbumTitle;
+		genre   => Genre;
+		comment => Comment;
+	);
+}
+
+impl TagExt for QuickTimeMetadata {
+	type RefKey<'a> = &'a QuickTimeKey;
+
+	#[inline]
+	fn tag_type(&self) -> TagType {
+		TagType::QuickTime
+	}
+
+	fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	fn contains<'a>(&'a self, key: Self::RefKey<'a>) -> bool {
+		self.items.iter().any(|item| &item.key == key)
+	}
+
+	fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	/// Dumps the `meta` atom, followed by a `udta` atom containing the text atoms
+	fn dump_to<W: Write>(
+		&self,
+		writer: &mut W,
+		write_options: WriteOptions,
+	) -> std::result::Result<(), TagEncodingError> {
+		let mut content = write::build_meta(self, true, write_options)?;
+
+		let user_data = write::build_user_data(self);
+		if !user_data.is_empty() {
+			content.extend(crate::mp4::write::atom(*b"udta", &user_data));
+		}
+
+		writer
+			.write_all(&content)
+			.map_err(|e| TagEncodingError::new(TagType::QuickTime, Box::new(e)))
+	}
+
+	fn clear(&mut self) {
+		self.items.clear();
+	}
+}
+
+impl TagWriteExt for QuickTimeMetadata {
+	fn save_to<F>(
+		&self,
+		file: VerifiedFile<'_, F>,
+		write_options: WriteOptions,
+	) -> std::result::Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		write::write_to(file, self, write_options)
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SplitTagRemainder {
+	metadata: QuickTimeMetadata,
+	// The keys (and `udta` languages) that were converted, so they can be put back in the same place
+	origins: Vec<(ItemKey, QuickTimeKey, u16)>,
+}
+
+impl SplitTag for QuickTimeMetadata {
+	type Remainder = SplitTagRemainder;
+
+	fn split_tag(mut self) -> (Self::Remainder, Tag) {
+		let mut tag = Tag::new(TagType::QuickTime);
+		let mut origins: Vec<(ItemKey, QuickTimeKey, u16)> = Vec::new();
+
+		self.items.retain_mut(|item| {
+			let Some(item_key) = item.key.item_key() else {
+				return true;
+			};
+
+			let (AtomData::UTF8(text) | AtomData::UTF16(text)) = &mut item.data else {
+				return true;
+			};
+
+			if !origins
+				.iter()
+				.any(|(key, origin, _)| *key == item_key && *origin == item.key)
+			{
+				origins.push((item_key, item.key.clone(), item.language));
+			}
+
+			// The same value is commonly stored in both `moov.meta` and `moov.udta`
+			let duplicate = tag
+				.get_strings(item_key)
+				.any(|existing| existing == text.as_str());
+			if !duplicate {
+				tag.items.push(TagItem::new(
+					item_key,
+					ItemValue::Text(std::mem::take(text)),
+				));
+			}
+
+			false
+		});
+
+		(
+			SplitTagRemainder {
+				metadata: self,
+				origins,
+			},
+			tag,
+		)
+	}
+}
+
+impl MergeTag for SplitTagRemainder {
+	type Merged = QuickTimeMetadata;
+
+	fn merge_tag(self, tag: Tag) -> Self::Merged {
+		let Self {
+			metadata: mut merged,
+			origins,
+		} = self;
+
+		for item in tag.items {
+			let ItemValue::Text(text) = item.item_value else {
+				continue;
+			};
+
+			let mut item_origins = origins
+				.iter()
+				.filter(|(key, _, _)| *key == item.item_key)
+				.peekable();
+
+			if item_origins.peek().is_none() {
+				let Some(key) = item.item_key.map_key(TagType::QuickTime) else {
+					continue;
+				};
+
+				merged.items.push(QuickTimeItem::new(
+					QuickTimeKey::from_str(key),
+					AtomData::UTF8(text),
+				));
+				continue;
+			}
+
+			for (_, key, language) in item_origins {
+				merged.items.push(QuickTimeItem {
+					key: key.clone(),
+					data: AtomData::UTF8(text.clone()),
+					language: *language,
+				});
+			}
+		}
+
+		merged
+	}
+}
+
+impl From<QuickTimeMetadata> for Tag {
+	fn from(input: QuickTimeMetadata) -> Self {
+		input.split_tag().1
+	}
+}
+
+impl From<Tag> for QuickTimeMetadata {
+	fn from(input: Tag) -> Self {
+		SplitTagRemainder::default().merge_tag(input)
+	}
+}
@@ -0,0 +1,247 @@
+use super::{QuickTimeItem, QuickTimeKey};
+use crate::config::ParsingMode;
+use crate::macros::try_vec;
+use crate::mp4::atom_info::{AtomIdent, AtomInfo};
+use crate::mp4::error::AtomParseError;
+use crate::mp4::ilst::read::{interpret_atom_content, parse_data_inner};
+use crate::mp4::read::{AtomReader, meta_is_full, seek_to_end};
+use crate::util::text::{latin1_decode, trim_end_nulls};
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+/// Parse a `moov.meta` atom with an `mdta` handler
+///
+/// NOTE: This expects the reader to be at the start of the atom's content. The reader will not
+///       be seeked to the end of the atom.
+pub(in crate::mp4) fn parse_mdta_meta<R>(
+	reader: &mut AtomReader<R>,
+	meta: &AtomInfo,
+	parsing_mode: ParsingMode,
+) -> Result<Vec<QuickTimeItem>, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut read = meta.header_size();
+	if meta_is_full(reader)? {
+		read += 4;
+	}
+
+	let mut keys = Vec::new();
+	let mut ilst = None;
+
+	while read < meta.len {
+		let Some(atom) = reader.next()? else { break };
+
+		read += atom.len;
+
+		match atom.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"hdlr" && atom.len >= 20 => {
+				// Version (1), flags (3), and pre-defined (4)
+				let _ = reader.read_u64()?;
+
+				let mut handler_type = [0; 4];
+				reader.read_exact(&mut handler_type)?;
+
+				if &handler_type != b"mdta" {
+					log::debug!(
+						"Skipping `meta` atom with handler type: {}",
+						handler_type.escape_ascii()
+					);
+					return Ok(Vec::new());
+				}
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"keys" => {
+				let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+				reader.read_exact(&mut content)?;
+
+				keys = parse_keys(&content).map_err(|e| e.with_ident(atom.ident.clone()))?;
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"ilst" => {
+				let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+				reader.read_exact(&mut content)?;
+
+				ilst = Some(content);
+			},
+			_ => {},
+		}
+
+		seek_to_end(reader, &atom)?;
+	}
+
+	let Some(ilst) = ilst else {
+		return Ok(Vec::new());
+	};
+
+	parse_keyed_ilst(ilst, &keys, parsing_mode)
+}
+
+/// Parse the content of a `keys` atom
+fn parse_keys(mut content: &[u8]) -> Result<Vec<Option<String>>, AtomParseError> {
+	let _version_and_flags = content.read_u32::<BigEndian>()?;
+	let entry_count = content.read_u32::<BigEndian>()?;
+
+	let mut keys = Vec::new();
+	for _ in 0..entry_count {
+		let key_size = content.read_u32::<BigEndian>()?;
+
+		let mut namespace = [0; 4];
+		content.read_exact(&mut namespace)?;
+
+		let mut key = try_vec![0; key_size.saturating_sub(8) as usize]?;
+		content.read_exact(&mut key)?;
+
+		// Keys in other namespaces (such as `udta`) have values we can't make sense of
+		if &namespace != b"mdta" {
+			log::warn!(
+				"Skipping key with unsupported namespace: {}",
+				namespace.escape_ascii()
+			);
+			keys.push(None);
+			continue;
+		}
+
+		keys.push(Some(String::from_utf8_lossy(&key).into_owned()));
+	}
+
+	Ok(keys)
+}
+
+/// Parse an `ilst` atom whose children are identified by their 1-based index into `keys`
+fn parse_keyed_ilst(
+	ilst: Vec<u8>,
+	keys: &[Option<String>],
+	parsing_mode: ParsingMode,
+) -> Result<Vec<QuickTimeItem>, AtomParseError> {
+	let len = ilst.len() as u64;
+	let mut cursor = Cursor::new(ilst);
+	let mut reader = AtomReader::new(&mut cursor, parsing_mode)?;
+
+	let mut items = Vec::new();
+
+	let mut pos = 0;
+	while pos + 8 <= len {
+		reader.seek(SeekFrom::Start(pos))?;
+
+		// The key indices aren't valid atom identifiers, so we can't use `AtomReader::next`
+		let atom_len = u64::from(reader.read_u32()?);
+		let key_index = reader.read_u32()?;
+
+		if atom_len < 8 || pos + atom_len > len {
+			if parsing_mode == ParsingMode::Strict {
+				return Err(AtomParseError::message(
+					None,
+					"invalid item size in `mdta` ilst",
+				));
+			}
+
+			log::warn!("Encountered an invalid item size in `mdta` ilst, stopping");
+			break;
+		}
+
+		let atom = AtomInfo {
+			start: pos,
+			len: atom_len,
+			extended: false,
+			ident: AtomIdent::Fourcc(key_index.to_be_bytes()),
+		};
+
+		pos += atom_len;
+
+		let key = (key_index as usize)
+			.checked_sub(1)
+			.and_then(|index| keys.get(index))
+			.cloned();
+
+		let Some(Some(key)) = key else {
+			log::warn!("Skipping `mdta` item with unknown key index: {key_index}");
+			continue;
+		};
+
+		let Some(atom_data) = parse_data_inner(&mut reader, parsing_mode, &atom)? else {
+			continue;
+		};
+
+		for (data_type, content) in atom_data {
+			match interpret_atom_content(data_type, content) {
+				Ok(data) => items.push(QuickTimeItem::new(QuickTimeKey::Mdta(key.clone()), data)),
+				Err(e) if parsing_mode == ParsingMode::Strict => return Err(e),
+				Err(e) => log::warn!("Skipping `mdta` item with invalid content: {e}"),
+			}
+		}
+	}
+
+	Ok(items)
+}
+
+/// Parse the content of a `udta` text atom (Ex. `©nam`)
+///
+/// These contain a list of strings, each with a 16-bit length and a language code. This returns
+/// `None` if the atom isn't in this format.
+pub(in crate::mp4) fn parse_user_data_text(mut content: &[u8]) -> Option<Vec<(u16, String)>> {
+	let mut strings = Vec::new();
+
+	while !content.is_empty() {
+		let text_len = usize::from(content.read_u16::<BigEndian>().ok()?);
+		let language = content.read_u16::<BigEndian>().ok()?;
+
+		if text_len > content.len() {
+			return None;
+		}
+
+		let (text, remaining) = content.split_at(text_len);
+		content = remaining;
+
+		// Macintosh language codes (below 0x400) may use a legacy encoding
+		let mut text = match std::str::from_utf8(text) {
+			Ok(text) => text.to_owned(),
+			Err(_) => latin1_decode(text),
+		};
+		trim_end_nulls(&mut text);
+
+		strings.push((language, text));
+	}
+
+	if strings.is_empty() {
+		return None;
+	}
+
+	Some(strings)
+}
+
+/// Parse a `udta` text atom into its items
+///
+/// NOTE: This expects the reader to be at the start of the atom's content, and will always read
+///       the entire atom.
+pub(in crate::mp4) fn parse_user_data_atom<R>(
+	reader: &mut R,
+	atom: &AtomInfo,
+) -> Result<Vec<QuickTimeItem>, AtomParseError>
+where
+	R: Read,
+{
+	let mut content = try_vec![0; (atom.len - atom.header_size()) as usize]?;
+	reader.read_exact(&mut content)?;
+
+	let AtomIdent::Fourcc(fourcc) = atom.ident else {
+		return Ok(Vec::new());
+	};
+
+	let Some(strings) = parse_user_data_text(&content) else {
+		log::warn!(
+			"Skipping `udta` atom with unexpected content: {}",
+			fourcc.escape_ascii()
+		);
+		return Ok(Vec::new());
+	};
+
+	Ok(strings
+		.into_iter()
+		.map(|(language, text)| QuickTimeItem {
+			key: QuickTimeKey::UserData(fourcc),
+			data: crate::mp4::AtomData::UTF8(text),
+			language,
+		})
+		.collect())
+}
@@ -0,0 +1,227 @@
+use super::read::{parse_mdta_meta, parse_user_data_text};
+use super::write::{build_meta, build_user_data};
+use super::{QuickTimeItem, QuickTimeKey, QuickTimeMetadata};
+use crate::config::{ParsingMode, WriteOptions};
+use crate::mp4::AtomData;
+use crate::mp4::read::AtomReader;
+use crate::tag::{Accessor, ItemKey, MergeTag, SplitTag, TagExt, TagType};
+
+use std::io::Cursor;
+
+fn mdta(key: &str) -> QuickTimeKey {
+	QuickTimeKey::Mdta(String::from(key))
+}
+
+fn text(value: &str) -> AtomData {
+	AtomData::UTF8(String::from(value))
+}
+
+fn read_meta(meta: &[u8]) -> Vec<QuickTimeItem> {
+	let mut cursor = Cursor::new(meta);
+	let mut reader = AtomReader::new(&mut cursor, ParsingMode::Strict).unwrap();
+
+	let atom = reader.next().unwrap().unwrap();
+	parse_mdta_meta(&mut reader, &atom, ParsingMode::Strict).unwrap()
+}
+
+#[test_log::test]
+fn mdta_round_trip() {
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.title"),
+		text("Foo title"),
+	));
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.location.ISO6709"),
+		text("+27.1281-100.2508+000.000/"),
+	));
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.title"),
+		text("Bar title"),
+	));
+
+	for full in [true, false] {
+		let meta = build_meta(&quicktime, full, WriteOptions::default()).unwrap();
+		let items = read_meta(&meta);
+
+		// Values of the same key are grouped together
+		assert_eq!(items.len(), 3);
+		assert_eq!(items[0].key(), &mdta("com.apple.quicktime.title"));
+		assert_eq!(items[0].data(), &text("Foo title"));
+		assert_eq!(items[1].key(), &mdta("com.apple.quicktime.title"));
+		assert_eq!(items[1].data(), &text("Bar title"));
+		assert_eq!(
+			items[2].key(),
+			&mdta("com.apple.quicktime.location.ISO6709")
+		);
+	}
+}
+
+#[test_log::test]
+fn skip_non_mdta_meta() {
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.title"),
+		text("Foo title"),
+	));
+
+	let mut meta = build_meta(&quicktime, true, WriteOptions::default()).unwrap();
+
+	// Version and flags (4), meta header (8), hdlr header (8), version and flags (4), pre-defined (4)
+	meta[28..32].copy_from_slice(b"mdir");
+	assert!(read_meta(&meta).is_empty());
+}
+
+#[test_log::test]
+fn user_data_text() {
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::UserData(*b"\xa9nam"),
+		text("Foo title"),
+	));
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::UserData(*b"\xa9nam"),
+		text("Bar title"),
+	));
+	// Not representable in a `udta` text atom
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::UserData(*b"\xa9cmt"),
+		AtomData::SignedInteger(1),
+	));
+
+	let user_data = build_user_data(&quicktime);
+
+	// A single `©nam` atom holding both strings
+	assert_eq!(&user_data[4..8], b"\xa9nam");
+	assert_eq!(user_data.len(), 8 + (4 + 9) * 2);
+
+	let strings = parse_user_data_text(&user_data[8..]).unwrap();
+	assert_eq!(
+		strings,
+		vec![
+			(super::UNDETERMINED_LANGUAGE, String::from("Foo title")),
+			(super::UNDETERMINED_LANGUAGE, String::from("Bar title")),
+		]
+	);
+}
+
+#[test_log::test]
+fn user_data_text_legacy_encoding() {
+	// Macintosh language code 0 (English), with a Latin-1 string and trailing null
+	let content = b"\x00\x05\x00\x00Caf\xe9\x00";
+	let strings = parse_user_data_text(content).unwrap();
+	assert_eq!(strings, vec![(0, String::from("Café"))]);
+
+	// Length exceeds the content
+	assert!(parse_user_data_text(b"\x00\xFF\x00\x00Foo").is_none());
+}
+
+#[test_log::test]
+fn key_mapping() {
+	assert_eq!(
+		mdta("com.apple.quicktime.title").item_key(),
+		Some(ItemKey::TrackTitle)
+	);
+	assert_eq!(
+		QuickTimeKey::UserData(*b"\xa9nam").item_key(),
+		Some(ItemKey::TrackTitle)
+	);
+	assert_eq!(
+		QuickTimeKey::from_str("©wrt"),
+		QuickTimeKey::UserData(*b"\xa9wrt")
+	);
+	assert_eq!(
+		QuickTimeKey::from_str("com.apple.quicktime.artist"),
+		mdta("com.apple.quicktime.artist")
+	);
+}
+
+#[test_log::test]
+fn accessor_updates_every_key() {
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.title"),
+		text("Foo title"),
+	));
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::UserData(*b"\xa9nam"),
+		text("Foo title"),
+	));
+
+	quicktime.set_title(String::from("Bar title"));
+	assert_eq!(quicktime.len(), 2);
+	assert!(
+		quicktime
+			.items()
+			.all(|item| item.data() == &text("Bar title"))
+	);
+
+	quicktime.remove_title();
+	assert!(quicktime.is_empty());
+
+	// No existing items, an `mdta` item is created
+	quicktime.set_artist(String::from("Foo artist"));
+	assert_eq!(
+		quicktime
+			.get(&mdta("com.apple.quicktime.artist"))
+			.unwrap()
+			.data(),
+		&text("Foo artist")
+	);
+
+	// `©wrt` has no `mdta` mapping
+	let mut tag = crate::tag::Tag::new(TagType::QuickTime);
+	tag.insert_text(ItemKey::Writer, String::from("Foo writer"));
+	let quicktime = QuickTimeMetadata::from(tag);
+	assert!(
+		quicktime
+			.get(&QuickTimeKey::UserData(*b"\xa9wrt"))
+			.is_some()
+	);
+}
+
+#[test_log::test]
+fn split_merge_keeps_origins() {
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.title"),
+		text("Foo title"),
+	));
+	quicktime.push(QuickTimeItem {
+		key: QuickTimeKey::UserData(*b"\xa9nam"),
+		data: text("Foo title"),
+		language: 0,
+	});
+	quicktime.push(QuickTimeItem::new(
+		mdta("com.apple.quicktime.location.ISO6709"),
+		text("+27.1281-100.2508+000.000/"),
+	));
+
+	let (remainder, mut tag) = quicktime.split_tag();
+
+	// The duplicate value is only converted once
+	assert_eq!(tag.len(), 1);
+	assert_eq!(tag.title().as_deref(), Some("Foo title"));
+
+	tag.set_title(String::from("Bar title"));
+
+	let merged = remainder.merge_tag(tag);
+	assert_eq!(merged.len(), 3);
+	assert_eq!(
+		merged
+			.get(&mdta("com.apple.quicktime.title"))
+			.unwrap()
+			.data(),
+		&text("Bar title")
+	);
+
+	let user_data_title = merged.get(&QuickTimeKey::UserData(*b"\xa9nam")).unwrap();
+	assert_eq!(user_data_title.data(), &text("Bar title"));
+	assert_eq!(user_data_title.language, 0);
+
+	assert!(
+		merged
+			.get(&mdta("com.apple.quicktime.location.ISO6709"))
+			.is_some()
+	);
+}
@@ -0,0 +1,316 @@
+use super::read::parse_user_data_text;
+use super::{QuickTimeKey, QuickTimeMetadata};
+use crate::config::WriteOptions;
+use crate::error::{FileEncodingError, FileParseError, TagEncodingError};
+use crate::io::VerifiedFile;
+use crate::mp4::AtomData;
+use crate::mp4::atom_info::AtomIdent;
+use crate::mp4::error::Mp4ParseError;
+use crate::mp4::ilst::r#ref::AtomRef;
+use crate::mp4::ilst::write::build_ilst;
+use crate::mp4::read::{AtomReader, verify_mp4};
+use crate::mp4::write::{
+	AtomWriter, ContextualAtom, atom, atom_bytes, atom_content, fast_start, read_atom, truncate_str,
+};
+use crate::tag::TagType;
+use crate::util::io::FileLike;
+
+use std::io::Seek;
+
+/// The `meta` children that a non-full `meta` atom can start with
+const META_CHILDREN: [&[u8; 4]; 3] = [b"hdlr", b"keys", b"ilst"];
+
+/// Write QuickTime metadata, replacing the `moov.meta` (`mdta`) atom and the `moov.udta` text atoms
+///
+/// Any existing items will be replaced. If `tag` is empty, they will be removed.
+pub(crate) fn write_to<F>(
+	file: VerifiedFile<'_, F>,
+	tag: &QuickTimeMetadata,
+	write_options: WriteOptions,
+) -> Result<(), FileEncodingError>
+where
+	F: FileLike,
+{
+	log::debug!("Attempting to write QuickTime metadata to file");
+
+	let parse_mode = write_options.parse_options.parsing_mode;
+
+	let file = file.into_inner();
+	let mut reader = AtomReader::new(file, parse_mode)?;
+	let major_brand = verify_mp4(&mut reader).map_err(FileParseError::from)?;
+
+	let mut file = reader.into_inner();
+	file.rewind()?;
+
+	let atom_writer =
+		AtomWriter::new_from_file(&mut file, parse_mode).map_err(Into::<FileParseError>::into)?;
+
+	let Some(moov) = atom_writer.find_contextual_atom(*b"moov") else {
+		return Err(FileParseError::from(Mp4ParseError::missing_moov()).into());
+	};
+
+	let moov_start = moov.info.start;
+	let moov_len = moov.info.len;
+
+	// QuickTime movies use a non-full `meta` atom, unlike ISO base media files
+	let context = BuildContext {
+		moov,
+		meta: build_meta_children(tag, write_options)?,
+		user_data: build_user_data(tag),
+		new_meta_is_full: major_brand != "qt  ",
+	};
+
+	let moov_bytes = read_atom(&atom_writer, &moov.info)?;
+	let new_moov = build_moov(&moov_bytes, &context);
+
+	if new_moov == moov_bytes {
+		log::debug!("QuickTime metadata is unchanged, nothing to do");
+		fast_start(&mut file, write_options)?;
+		return Ok(());
+	}
+
+	// Update the offsets before copying the `moov` atom, so our copy will have the new offsets
+	let difference = new_moov.len() as i64 - moov_len as i64;
+	let strategy = atom_writer.resize_moov(moov, difference)?;
+
+	let moov_bytes = read_atom(&atom_writer, &moov.info)?;
+	let new_moov = build_moov(&moov_bytes, &context);
+
+	let mut write_handle = atom_writer.start_write();
+	write_handle.splice(
+		moov_start as usize..(moov_start + moov_len) as usize,
+		new_moov,
+	);
+	drop(write_handle);
+
+	atom_writer.save_moov_to(&mut file, moov, strategy)?;
+	fast_start(&mut file, write_options)?;
+
+	Ok(())
+}
+
+struct BuildContext<'a> {
+	moov: &'a ContextualAtom,
+	// The content of the new `meta` atom, without the version and flags
+	meta: Vec<u8>,
+	// The new `udta` text atoms
+	user_data: Vec<u8>,
+	new_meta_is_full: bool,
+}
+
+fn build_moov(moov_bytes: &[u8], context: &BuildContext<'_>) -> Vec<u8> {
+	let moov_start = context.moov.info.start;
+
+	let mut content = Vec::with_capacity(moov_bytes.len());
+	let mut wrote_meta = false;
+	let mut found_udta = false;
+
+	for child in &context.moov.children {
+		let bytes = atom_bytes(moov_bytes, moov_start, &child.info);
+
+		match child.info.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"meta" => {
+				let meta_content = atom_content(moov_bytes, moov_start, &child.info);
+				let (children, is_full) = meta_children(meta_content);
+				if !is_mdta_meta(children) {
+					content.extend_from_slice(bytes);
+					continue;
+				}
+
+				// Replace the first `mdta` meta atom in place, keeping its style
+				if !wrote_meta {
+					content.extend(wrap_meta(&context.meta, is_full));
+					wrote_meta = true;
+				}
+			},
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"udta" => {
+				found_udta = true;
+
+				let mut udta = Vec::new();
+				for udta_child in &child.children {
+					if !is_user_data_text(moov_bytes, moov_start, udta_child) {
+						udta.extend_from_slice(atom_bytes(
+							moov_bytes,
+							moov_start,
+							&udta_child.info,
+						));
+					}
+				}
+
+				udta.extend_from_slice(&context.user_data);
+
+				if !udta.is_empty() {
+					content.extend(atom(*b"udta", &udta));
+				}
+			},
+			_ => content.extend_from_slice(bytes),
+		}
+	}
+
+	if !found_udta && !context.user_data.is_empty() {
+		content.extend(atom(*b"udta", &context.user_data));
+	}
+
+	if !wrote_meta {
+		content.extend(wrap_meta(&context.meta, context.new_meta_is_full));
+	}
+
+	atom(*b"moov", &content)
+}
+
+/// Split the content of a `meta` atom into its children, and whether it's a full atom
+fn meta_children(content: &[u8]) -> (&[u8], bool) {
+	match content.get(4..8) {
+		Some(ident) if META_CHILDREN.iter().any(|child| *child == ident) => (content, false),
+		_ => (content.get(4..).unwrap_or_default(), true),
+	}
+}
+
+/// Whether the `meta` atom has an `mdta` handler
+fn is_mdta_meta(mut children: &[u8]) -> bool {
+	while children.len() >= 8 {
+		let len = u32::from_be_bytes([children[0], children[1], children[2], children[3]]) as usize;
+		let len = len.clamp(8, children.len());
+
+		let (child, remaining) = children.split_at(len);
+		if &child[4..8] == b"hdlr" {
+			// Version (1), flags (3), and pre-defined (4)
+			return child.get(16..20) == Some(&b"mdta"[..]);
+		}
+
+		children = remaining;
+	}
+
+	false
+}
+
+fn is_user_data_text(moov_bytes: &[u8], moov_start: u64, atom: &ContextualAtom) -> bool {
+	match atom.info.ident {
+		AtomIdent::Fourcc(fourcc) if fourcc[0] == b'\xa9' => {
+			parse_user_data_text(atom_content(moov_bytes, moov_start, &atom.info)).is_some()
+		},
+		_ => false,
+	}
+}
+
+fn wrap_meta(children: &[u8], full: bool) -> Vec<u8> {
+	if children.is_empty() {
+		return Vec::new();
+	}
+
+	let mut content = Vec::with_capacity(children.len() + 4);
+	if full {
+		content.extend([0; 4]);
+	}
+
+	content.extend_from_slice(children);
+	atom(*b"meta", &content)
+}
+
+/// Group the values of each `mdta` key, in the order the keys first appear
+fn mdta_groups(tag: &QuickTimeMetadata) -> Vec<(&str, Vec<&AtomData>)> {
+	let mut groups: Vec<(&str, Vec<&AtomData>)> = Vec::new();
+	for item in &tag.items {
+		let QuickTimeKey::Mdta(key) = &item.key else {
+			continue;
+		};
+
+		match groups.iter_mut().find(|(existing, _)| existing == key) {
+			Some((_, values)) => values.push(&item.data),
+			None => groups.push((key, vec![&item.data])),
+		}
+	}
+
+	groups
+}
+
+/// Build the `hdlr`, `keys`, and `ilst` atoms
+fn build_meta_children(
+	tag: &QuickTimeMetadata,
+	write_options: WriteOptions,
+) -> Result<Vec<u8>, TagEncodingError> {
+	let groups = mdta_groups(tag);
+
+	if groups.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	// Version (1), flags (3), pre-defined (4), handler type (4), reserved (12), and an empty name
+	let mut hdlr = vec![0; 8];
+	hdlr.extend(b"mdta");
+	hdlr.extend([0; 13]);
+
+	let mut keys = vec![0; 4];
+	keys.extend((groups.len() as u32).to_be_bytes());
+	for (key, _) in &groups {
+		keys.extend((key.len() as u32 + 8).to_be_bytes());
+		keys.extend(b"mdta");
+		keys.extend(key.as_bytes());
+	}
+
+	// The items are identified by their 1-based index into `keys`
+	let mut atoms = groups
+		.iter()
+		.enumerate()
+		.map(|(index, (_, values))| AtomRef {
+			ident: AtomIdent::Fourcc((index as u32 + 1).to_be_bytes()),
+			data: values.iter().copied(),
+		});
+	let ilst = build_ilst(&mut atoms, write_options)
+		.map_err(|e| TagEncodingError::new(TagType::QuickTime, Box::new(e)))?;
+
+	let mut children = atom(*b"hdlr", &hdlr);
+	children.extend(atom(*b"keys", &keys));
+	children.extend(ilst);
+
+	Ok(children)
+}
+
+/// Build the `meta` atom, as it would appear in the file
+pub(super) fn build_meta(
+	tag: &QuickTimeMetadata,
+	full: bool,
+	write_options: WriteOptions,
+) -> Result<Vec<u8>, TagEncodingError> {
+	let children = build_meta_children(tag, write_options)?;
+	Ok(wrap_meta(&children, full))
+}
+
+/// Build the `udta` text atoms
+pub(super) fn build_user_data(tag: &QuickTimeMetadata) -> Vec<u8> {
+	let mut groups: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+	for item in &tag.items {
+		let QuickTimeKey::UserData(fourcc) = item.key else {
+			continue;
+		};
+
+		let Some(text) = item.text() else {
+			log::warn!(
+				"`udta` text atoms can only store text, discarding item: {}",
+				fourcc.escape_ascii()
+			);
+			continue;
+		};
+
+		let text = truncate_str(text, usize::from(u16::MAX));
+
+		let content = match groups.iter_mut().find(|(existing, _)| *existing == fourcc) {
+			Some((_, content)) => content,
+			None => {
+				groups.push((fourcc, Vec::new()));
+				&mut groups.last_mut().expect("just pushed").1
+			},
+		};
+
+		content.extend((text.len() as u16).to_be_bytes());
+		content.extend(item.language.to_be_bytes());
+		content.extend(text.as_bytes());
+	}
+
+	let mut user_data = Vec::new();
+	for (fourcc, content) in groups {
+		user_data.extend(atom(fourcc, &content));
+	}
+
+	user_data
+}
@@ -2,6 +2,7 @@ mod atom_reader;
 
 use super::Mp4File;
 use super::atom_info::{AtomIdent, AtomInfo};
+use super::chapters::read::read_chapter_track;
 use super::moov::Moov;
 use super::properties::Mp4Properties;
 use crate::config::{ParseOptions, ParsingMode};
@@ -65,12 +66,36 @@ where
 
 	let moov = Moov::parse(&mut reader, parse_options)?;
 
+	let mut chapters = Vec::new();
+	if parse_options.read_tags {
+		// Chapter tracks are preferred, as Nero chapters are limited to 255 entries
+		reader.reset_bounds(0, file_length);
+		match read_chapter_track(&mut reader, &moov.traks, parse_options.parsing_mode) {
+			Ok(Some(chapter_track)) => chapters = chapter_track.chapters,
+			Ok(None) => {},
+			Err(e) if parse_options.parsing_mode == ParsingMode::Strict => return Err(e.into()),
+			Err(_) => log::warn!("Unable to read chapter track, discarding"),
+		}
+
+		if chapters.is_empty()
+			&& let Some(chpl) = moov.chpl
+		{
+			chapters = chpl;
+		}
+	}
+
+	let mut tracks = Vec::new();
 	let mut properties = if parse_options.read_properties {
+		tracks = moov.traks.iter().map(|trak| trak.track.clone()).collect();
+
 		// Remove the length restriction
 		reader.reset_bounds(0, file_length);
 		super::properties::read_properties(
 			&mut reader,
 			&moov.traks,
+			moov.mvhd,
+			moov.mvex.as_ref(),
+			parse_options.mp4_audio_track,
 			file_length,
 			parse_options.parsing_mode,
 		)?
@@ -81,6 +106,9 @@ where
 
 	Ok(Mp4File {
 		ilst_tag: moov.ilst,
+		quicktime_tag: moov.quicktime,
+		chapters,
+		tracks,
 		properties,
 	})
 }
@@ -115,6 +143,20 @@ where
 	Ok(())
 }
 
+/// Seeks the reader to the end of the atom, regardless of how much of it has been read
+///
+/// NOTE: Unlike [`skip_atom`], this makes no assumptions about the reader's position within the atom.
+pub(super) fn seek_to_end<R>(reader: &mut AtomReader<R>, atom: &AtomInfo) -> std::io::Result<()>
+where
+	R: Read + Seek,
+{
+	let pos = reader.stream_position()?;
+	reader.seek(SeekFrom::Current(
+		(atom.start + atom.len) as i64 - pos as i64,
+	))?;
+	Ok(())
+}
+
 /// Finds the first child atom with the given fourcc
 ///
 /// * `len` is the length of the parent atom
@@ -207,7 +249,7 @@ where
 	reader.read_exact(&mut possible_ident)?;
 
 	match &possible_ident {
-		b"hdlr" | b"ilst" | b"mhdr" | b"ctry" | b"lang" => {
+		b"hdlr" | b"ilst" | b"keys" | b"mhdr" | b"ctry" | b"lang" => {
 			log::warn!("File contains a non-full 'meta' atom");
 
 			reader.seek(SeekFrom::Current(-8))?;
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+/// A track in an MP4 file
+///
+/// See [`Mp4File::tracks`](super::Mp4File::tracks).
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Mp4Track {
+	pub(crate) id: u32,
+	pub(crate) enabled: bool,
+	pub(crate) handler_type: [u8; 4],
+	pub(crate) codec: Option<[u8; 4]>,
+	pub(crate) language: Option<[u8; 3]>,
+	pub(crate) duration: Duration,
+}
+
+impl Mp4Track {
+	/// The track ID, from `tkhd`
+	pub fn id(&self) -> u32 {
+		self.id
+	}
+
+	/// Whether the track is enabled, from the `tkhd` flags
+	///
+	/// Disabled tracks, such as chapter tracks, are not meant to be played.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// The handler type, from `hdlr` (Ex. `soun` for audio, `vide` for video, or `text`)
+	pub fn handler_type(&self) -> [u8; 4] {
+		self.handler_type
+	}
+
+	/// The FOURCC of the track's first sample description, from `stsd` (Ex. `mp4a`, `alac`, or `avc1`)
+	pub fn codec(&self) -> Option<[u8; 4]> {
+		self.codec
+	}
+
+	/// The ISO 639-2/T language code, from `mdhd` (Ex. `eng`)
+	///
+	/// This will be `None` if the track uses a Macintosh language code.
+	pub fn language(&self) -> Option<&str> {
+		self.language
+			.as_ref()
+			.and_then(|language| std::str::from_utf8(language).ok())
+	}
+
+	/// The duration of the track, from `mdhd`
+	pub fn duration(&self) -> Duration {
+		self.duration
+	}
+}
+
+/// Selects the audio track used for an MP4 file's [`Mp4Properties`](super::Mp4Properties)
+///
+/// If no audio track matches, the first one will be used.
+///
+/// This can be set with [`ParseOptions::mp4_audio_track`](crate::config::ParseOptions::mp4_audio_track).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Mp4AudioTrack {
+	/// The first audio track
+	#[default]
+	First,
+	/// The audio track with the given ID
+	Id(u32),
+	/// The first audio track with the given ISO 639-2/T language code (Ex. `*b"eng"`)
+	Language([u8; 3]),
+}
@@ -1,8 +1,10 @@
-use crate::config::ParsingMode;
-use crate::error::FileEncodingError;
-use crate::mp4::atom_info::{AtomIdent, AtomInfo, IDENTIFIER_LEN};
+use crate::config::{ParsingMode, WriteOptions};
+use crate::error::{FileEncodingError, FileParseError};
+use crate::macros::try_vec;
+use crate::mp4::atom_info::{ATOM_HEADER_LEN, AtomIdent, AtomInfo, IDENTIFIER_LEN};
 use crate::mp4::error::{AtomParseError, Mp4ParseError};
-use crate::mp4::read::{meta_is_full, skip_atom};
+use crate::mp4::ilst::write::update_offsets;
+use crate::mp4::read::{AtomReader, meta_is_full, seek_to_end, skip_atom};
 use crate::util::io::FileLike;
 
 use std::cell::{RefCell, RefMut};
@@ -11,6 +13,30 @@ use std::ops::RangeBounds;
 
 use byteorder::{BigEndian, WriteBytesExt};
 
+/// How the atoms of an MP4 file were updated when writing
+///
+/// See [`Ilst::save_to_with_report`](crate::mp4::Ilst::save_to_with_report).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mp4WriteStrategy {
+	/// Nothing outside of the `moov` atom was moved
+	///
+	/// The changes fit in the existing space, making use of any `free` atoms next to the `ilst`
+	/// atom or directly after the `moov` atom. Only the `moov` atom (and the `free` atoms following
+	/// it) were rewritten.
+	///
+	/// This is also reported if there was nothing to write.
+	InPlace,
+	/// The `moov` atom changed size, but nothing else had to move, since it's at the end of the file
+	MoovResized,
+	/// The atoms after `moov` (typically `mdat`) were moved, and the chunk offsets (`stco`/`co64`)
+	/// were updated to match
+	///
+	/// This is also reported if `moov` was moved in front of `mdat`, see
+	/// [`WriteOptions::mp4_fast_start`].
+	DataMoved,
+}
+
 /// A wrapper around [`AtomInfo`] that allows us to track all of the children of containers we deem important
 #[derive(Debug)]
 pub(super) struct ContextualAtom {
@@ -25,10 +51,12 @@ const IMPORTANT_CONTAINERS: &[[u8; 4]] = &[
 	*b"moov",
 		*b"udta",
 		*b"moof",
+			*b"traf",
 		*b"trak",
 			*b"mdia",
 				*b"minf",
 					*b"stbl",
+	*b"mfra",
 ];
 impl ContextualAtom {
 	pub(super) fn read<R>(
@@ -157,6 +185,11 @@ impl AtomWriter {
 			.find(|atom| matches!(atom.info.ident, AtomIdent::Fourcc(ident) if ident == fourcc))
 	}
 
+	/// The top-level atoms of the file
+	pub(super) fn atoms(&self) -> &[ContextualAtom] {
+		&self.atoms
+	}
+
 	pub(super) fn into_contents(self) -> Vec<u8> {
 		self.contents.into_inner().into_inner()
 	}
@@ -167,16 +200,132 @@ impl AtomWriter {
 		}
 	}
 
-	pub(super) fn save_to<F>(&mut self, file: &mut F) -> Result<(), FileEncodingError>
+	/// Save the contents from `start` onwards to `file`, removing anything past the new end
+	pub(super) fn save_tail_to<F>(&self, file: &mut F, start: u64) -> Result<(), FileEncodingError>
 	where
 		F: FileLike,
 	{
-		file.rewind()?;
-		file.truncate(0)?;
-		file.write_all(self.contents.borrow().get_ref())?;
+		let contents = self.contents.borrow();
+		let contents = contents.get_ref();
+
+		file.seek(SeekFrom::Start(start))?;
+		file.write_all(&contents[start as usize..])?;
+		file.truncate(contents.len() as u64)?;
 
 		Ok(())
 	}
+
+	/// Save the contents to `file`, only writing what may have changed after `moov` was resized
+	/// with the given [`Mp4WriteStrategy`]
+	///
+	/// Everything before `moov` is left untouched.
+	pub(super) fn save_moov_to<F>(
+		&self,
+		file: &mut F,
+		moov: &ContextualAtom,
+		strategy: Mp4WriteStrategy,
+	) -> Result<(), FileEncodingError>
+	where
+		F: FileLike,
+	{
+		let contents = self.contents.borrow();
+		let contents = contents.get_ref();
+
+		let start = moov.info.start;
+		let end = match strategy {
+			Mp4WriteStrategy::InPlace => {
+				let (free_len, _) = self.free_after(moov);
+				start + moov.info.len + free_len
+			},
+			Mp4WriteStrategy::MoovResized | Mp4WriteStrategy::DataMoved => contents.len() as u64,
+		};
+
+		log::trace!("Writing bytes {start}..{end} of {}", contents.len());
+
+		file.seek(SeekFrom::Start(start))?;
+		file.write_all(&contents[start as usize..end as usize])?;
+
+		if end == contents.len() as u64 {
+			file.truncate(end)?;
+		}
+
+		Ok(())
+	}
+
+	/// The total size of the `free` atoms directly following `atom`, and whether they're the last
+	/// atoms in the file
+	fn free_after(&self, atom: &ContextualAtom) -> (u64, bool) {
+		let following = self
+			.atoms
+			.iter()
+			.skip_while(|top_level_atom| top_level_atom.info.start != atom.info.start)
+			.skip(1);
+
+		let mut free_len = 0;
+		for following_atom in following {
+			if following_atom.info.ident != AtomIdent::Fourcc(*b"free") {
+				return (free_len, false);
+			}
+
+			free_len += following_atom.info.len;
+		}
+
+		(free_len, true)
+	}
+
+	/// Whether growing `moov` by `growth` bytes would require moving the atoms after it
+	pub(super) fn growth_moves_data(&self, moov: &ContextualAtom, growth: u64) -> bool {
+		let (free_len, is_last) = self.free_after(moov);
+		!is_last && !free_space_fits(free_len, growth)
+	}
+
+	/// Make room for `moov` to change size by `difference` bytes
+	///
+	/// Growth will first try to use the `free` atoms directly after `moov`, and shrinking will leave
+	/// behind a `free` atom, so the rest of the file doesn't have to move. Otherwise, the chunk
+	/// offsets are updated.
+	///
+	/// NOTE: This must be called before `moov` itself is modified, as it relies on the original
+	///       positions of the offset atoms.
+	pub(super) fn resize_moov(
+		&self,
+		moov: &ContextualAtom,
+		difference: i64,
+	) -> Result<Mp4WriteStrategy, FileEncodingError> {
+		if difference == 0 {
+			return Ok(Mp4WriteStrategy::InPlace);
+		}
+
+		let (free_len, is_last) = self.free_after(moov);
+		let free_start = moov.info.start + moov.info.len;
+		let free_range = free_start as usize..(free_start + free_len) as usize;
+
+		let growth = difference.unsigned_abs();
+		if difference.is_positive() && free_space_fits(free_len, growth) {
+			log::trace!("Using {growth} bytes of the `free` atoms after `moov`");
+
+			let mut write_handle = self.start_write();
+			write_handle.splice(free_range, free_atom(free_len - growth)?);
+			return Ok(Mp4WriteStrategy::InPlace);
+		}
+
+		// Give the space back to the `free` atoms, so that it can be reused
+		if difference.is_negative() && (free_len > 0 || (!is_last && growth >= ATOM_HEADER_LEN)) {
+			log::trace!("Leaving {growth} bytes of padding after `moov`");
+
+			let mut write_handle = self.start_write();
+			write_handle.splice(free_range, free_atom(free_len + growth)?);
+			return Ok(Mp4WriteStrategy::InPlace);
+		}
+
+		if is_last {
+			log::trace!("`moov` is at the end of the file, no offsets to update");
+			return Ok(Mp4WriteStrategy::MoovResized);
+		}
+
+		update_offsets(self, moov, difference, free_start)?;
+		Ok(Mp4WriteStrategy::DataMoved)
+	}
 }
 
 /// The actual handler of the writing operations
@@ -316,3 +465,199 @@ impl<'a> Iterator for AtomFindAll<std::slice::Iter<'a, ContextualAtom>> {
 		}
 	}
 }
+
+/// Read an entire atom from the in-memory file
+pub(super) fn read_atom(atom_writer: &AtomWriter, atom: &AtomInfo) -> std::io::Result<Vec<u8>> {
+	let mut write_handle = atom_writer.start_write();
+	write_handle.seek(SeekFrom::Start(atom.start))?;
+
+	let mut bytes = vec![0; atom.len as usize];
+	write_handle.read_exact(&mut bytes)?;
+
+	Ok(bytes)
+}
+
+/// Get the bytes of `atom`, a child of the atom whose bytes start at `parent_start`
+pub(super) fn atom_bytes<'a>(
+	parent_bytes: &'a [u8],
+	parent_start: u64,
+	atom: &AtomInfo,
+) -> &'a [u8] {
+	let start = (atom.start - parent_start) as usize;
+	&parent_bytes[start..start + atom.len as usize]
+}
+
+/// Get the content of `atom`, a child of the atom whose bytes start at `parent_start`
+pub(super) fn atom_content<'a>(
+	parent_bytes: &'a [u8],
+	parent_start: u64,
+	atom: &AtomInfo,
+) -> &'a [u8] {
+	&atom_bytes(parent_bytes, parent_start, atom)[atom.header_size() as usize..]
+}
+
+/// Create an atom, using an extended size if necessary
+pub(super) fn atom(fourcc: [u8; 4], content: &[u8]) -> Vec<u8> {
+	let mut atom = Vec::with_capacity(content.len() + 16);
+
+	let len = content.len() as u64 + ATOM_HEADER_LEN;
+	match u32::try_from(len) {
+		Ok(len) => {
+			atom.extend(len.to_be_bytes());
+			atom.extend(fourcc);
+		},
+		Err(_) => {
+			atom.extend(1u32.to_be_bytes());
+			atom.extend(fourcc);
+			atom.extend((len + 8).to_be_bytes());
+		},
+	}
+
+	atom.extend_from_slice(content);
+	atom
+}
+
+/// Truncate a string to at most `max_len` bytes, on a character boundary
+pub(super) fn truncate_str(text: &str, max_len: usize) -> &str {
+	if text.len() <= max_len {
+		return text;
+	}
+
+	let mut end = max_len;
+	while !text.is_char_boundary(end) {
+		end -= 1;
+	}
+
+	&text[..end]
+}
+
+/// Whether `growth` bytes can be taken from `free_len` bytes of `free` atoms
+///
+/// The remaining space needs to be either empty, or large enough to hold a new `free` atom.
+fn free_space_fits(free_len: u64, growth: u64) -> bool {
+	free_len == growth || free_len >= growth + ATOM_HEADER_LEN
+}
+
+/// Create a `free` atom of `size` bytes, or nothing if `size` is 0
+pub(super) fn free_atom(size: u64) -> Result<Vec<u8>, FileEncodingError> {
+	if size == 0 {
+		return Ok(Vec::new());
+	}
+
+	let header_len = if u32::try_from(size).is_ok() {
+		ATOM_HEADER_LEN
+	} else {
+		ATOM_HEADER_LEN + 8
+	};
+
+	Ok(atom(*b"free", &try_vec![0; (size - header_len) as usize]?))
+}
+
+/// The `preferred_padding`, if it's large enough to hold a `free` atom
+pub(super) fn preferred_padding(write_options: WriteOptions) -> Option<u32> {
+	write_options
+		.preferred_padding
+		.map(std::num::NonZero::get)
+		.filter(|padding| u64::from(*padding) >= ATOM_HEADER_LEN)
+}
+
+/// Move `moov` in front of the first `mdat` atom, if [`WriteOptions::mp4_fast_start`] is enabled
+///
+/// The `free` atoms directly following `moov` are moved along with it, and if there are none,
+/// `preferred_padding` is added so future writes can happen in place. Returns `true` if `moov` was
+/// moved.
+pub(super) fn fast_start<F>(
+	file: &mut F,
+	write_options: WriteOptions,
+) -> Result<bool, FileEncodingError>
+where
+	F: FileLike,
+{
+	if !write_options.mp4_fast_start {
+		return Ok(false);
+	}
+
+	let parse_mode = write_options.parse_options.parsing_mode;
+
+	// Check the top-level atoms first, so we don't have to read the entire file if `moov` is
+	// already in place
+	file.rewind()?;
+	let mut reader = AtomReader::new(&mut *file, parse_mode)?;
+	if !moov_follows_mdat(&mut reader).map_err(FileParseError::from)? {
+		log::trace!("`moov` is already in front of `mdat`");
+		return Ok(false);
+	}
+
+	file.rewind()?;
+	let atom_writer =
+		AtomWriter::new_from_file(file, parse_mode).map_err(Into::<FileParseError>::into)?;
+
+	let (Some(moov), Some(mdat)) = (
+		atom_writer.find_contextual_atom(*b"moov"),
+		atom_writer.find_contextual_atom(*b"mdat"),
+	) else {
+		return Ok(false);
+	};
+
+	let moov_start = moov.info.start;
+	let mdat_start = mdat.info.start;
+
+	let (free_len, is_last) = atom_writer.free_after(moov);
+	let moved_end = moov_start + moov.info.len + free_len;
+
+	let padding = match preferred_padding(write_options) {
+		Some(padding) if free_len == 0 => free_atom(u64::from(padding))?,
+		_ => Vec::new(),
+	};
+
+	let moved_len = moved_end - moov_start;
+	let shift = moved_len + padding.len() as u64;
+
+	log::debug!("Moving `moov` from offset {moov_start} to {mdat_start}");
+
+	// Anything after the moved atoms only moves forward by the new padding, so take back their
+	// size first. This way the offsets never have to hold more than their final value.
+	if !is_last {
+		update_offsets(&atom_writer, moov, -(moved_len as i64), moved_end)?;
+	}
+
+	// Everything between `mdat` and `moov` (and now everything after) moves forward by the size of
+	// `moov`, its `free` atoms, and the new padding
+	update_offsets(&atom_writer, moov, shift as i64, mdat_start)?;
+
+	let mut write_handle = atom_writer.start_write();
+	write_handle.seek(SeekFrom::Start(moov_start))?;
+
+	let mut moved = try_vec![0; moved_len as usize]?;
+	write_handle.read_exact(&mut moved)?;
+	moved.extend(padding);
+
+	write_handle.splice(moov_start as usize..moved_end as usize, []);
+	write_handle.splice(mdat_start as usize..mdat_start as usize, moved);
+	drop(write_handle);
+
+	let contents = atom_writer.into_contents();
+	file.seek(SeekFrom::Start(mdat_start))?;
+	file.write_all(&contents[mdat_start as usize..])?;
+
+	Ok(true)
+}
+
+/// Whether the top-level `moov` atom comes after an `mdat` atom
+fn moov_follows_mdat<R>(reader: &mut AtomReader<R>) -> Result<bool, AtomParseError>
+where
+	R: Read + Seek,
+{
+	let mut found_mdat = false;
+	while let Some(atom) = reader.next()? {
+		match atom.ident {
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"moov" => return Ok(found_mdat),
+			AtomIdent::Fourcc(ref fourcc) if fourcc == b"mdat" => found_mdat = true,
+			_ => {},
+		}
+
+		seek_to_end(reader, &atom)?;
+	}
+
+	Ok(false)
+}
@@ -9,6 +9,7 @@ pub use header::{ChannelMode, Emphasis, Layer, MpegVersion};
 pub use properties::MpegProperties;
 
 use crate::ape::tag::ApeTag;
+use crate::id3::lyrics3::tag::Lyrics3Tag;
 use crate::id3::v1::tag::Id3v1Tag;
 use crate::id3::v2::tag::Id3v2Tag;
 
@@ -28,6 +29,9 @@ pub struct MpegFile {
 	/// An APEv1/v2 tag
 	#[lofty(tag_type = "Ape")]
 	pub(crate) ape_tag: Option<ApeTag>,
+	/// A Lyrics3v1/v2 tag (read only)
+	#[lofty(tag_type = "Lyrics3")]
+	pub(crate) lyrics3_tag: Option<Lyrics3Tag>,
 	/// The file's audio properties
 	pub(crate) properties: MpegProperties,
 }
@@ -3,9 +3,10 @@ use super::{MpegFile, MpegProperties};
 use crate::ape::tag::header::read_ape_header;
 use crate::config::{ParseOptions, ParsingMode};
 use crate::error::{FakeTagError, SizeMismatchError, TagParseError};
+use crate::id3::lyrics3::read::find_lyrics3;
 use crate::id3::v2::header::Id3v2Header;
 use crate::id3::v2::read::parse_id3v2;
-use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1, find_lyrics3v2};
+use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1};
 use crate::io::SeekStreamLen;
 use crate::mpeg::error::MpegParseError;
 use crate::mpeg::header::HEADER_MASK;
@@ -161,8 +162,13 @@ where
 		file.id3v1_tag = id3v1;
 	}
 
-	let _ = find_lyrics3v2(reader)?;
+	let ID3FindResults(lyrics3_header, lyrics3) =
+		find_lyrics3(reader, parse_options.read_tags, parse_options.parsing_mode)?;
+	if lyrics3_header.is_some() {
+		file.lyrics3_tag = lyrics3;
+	}
 
+	let ape_end = reader.stream_position()?;
 	reader.seek(SeekFrom::Current(-32))?;
 
 	match crate::ape::tag::read::read_ape_tag(reader, true, parse_options)
@@ -172,12 +178,20 @@ where
 			file.ape_tag = tag;
 
 			// Seek back to the start of the tag
-			let pos = reader.stream_position()?;
-			let Some(start_of_tag) = pos.checked_sub(u64::from(header.size)) else {
+			let Some(start_of_tag) = ape_end.checked_sub(u64::from(header.size)) else {
 				return Err(SizeMismatchError.into());
 			};
 
 			reader.seek(SeekFrom::Start(start_of_tag))?;
+
+			// The Lyrics3 tag may be between the APE and ID3v1 tags
+			if lyrics3_header.is_none() {
+				let ID3FindResults(lyrics3_header, lyrics3) =
+					find_lyrics3(reader, parse_options.read_tags, parse_options.parsing_mode)?;
+				if lyrics3_header.is_some() {
+					file.lyrics3_tag = lyrics3;
+				}
+			}
 		},
 		_ => {
 			// Correct the position (APE header - Preamble)
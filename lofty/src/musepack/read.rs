@@ -4,8 +4,9 @@ use super::sv8::MpcSv8Properties;
 use super::{MpcFile, MpcProperties, MpcStreamVersion};
 use crate::config::ParseOptions;
 use crate::error::{SizeMismatchError, TagParseError};
+use crate::id3::lyrics3::read::find_lyrics3;
 use crate::id3::v2::read::parse_id3v2;
-use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1, find_id3v2, find_lyrics3v2};
+use crate::id3::{FindId3v2Config, ID3FindResults, find_id3v1, find_id3v2};
 use crate::musepack::error::MpcParseError;
 use crate::util::io::SeekStreamLen;
 
@@ -67,12 +68,16 @@ where
 		stream_length = new_stream_length;
 	}
 
-	let ID3FindResults(_, lyrics3v2_size) = find_lyrics3v2(reader)?;
-	let Some(new_stream_length) = stream_length.checked_sub(u64::from(lyrics3v2_size)) else {
-		return Err(SizeMismatchError.into());
-	};
+	if let ID3FindResults(Some(lyrics3_header), _) =
+		find_lyrics3(reader, false, parse_options.parsing_mode)?
+	{
+		let Some(new_stream_length) = stream_length.checked_sub(u64::from(lyrics3_header.size))
+		else {
+			return Err(SizeMismatchError.into());
+		};
 
-	stream_length = new_stream_length;
+		stream_length = new_stream_length;
+	}
 
 	reader.seek(SeekFrom::Current(-32))?;
 
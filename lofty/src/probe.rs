@@ -357,7 +357,7 @@ impl<R: Read + Seek> Probe<R> {
 			FileTypeGuessResult::MaybePrecededById3(id3_len) => {
 				// `id3_len` is the size of the tag, not including the header (10 bytes)
 				log::debug!("Probe: ID3v2 tag detected, skipping {} bytes", 10 + id3_len);
-				let position_after_id3_block = self
+				let mut position_after_id3_block = self
 					.inner
 					.seek(SeekFrom::Current(i64::from(10 + id3_len)))?;
 
@@ -368,6 +368,31 @@ impl<R: Read + Seek> Probe<R> {
 					&mut Cursor::new(&mut ident[..]),
 				)?;
 
+				// An APE tag may (against spec) sit between the ID3v2 tag and the audio
+				if &ident == b"APET" {
+					let mut ape_header = [0; 28];
+					std::io::copy(
+						&mut self.inner.by_ref().take(ape_header.len() as u64),
+						&mut Cursor::new(&mut ape_header[..]),
+					)?;
+
+					if &ape_header[..4] == b"AGEX" {
+						// The size doesn't include the header
+						let ape_size = u32::from_le_bytes(ape_header[8..12].try_into().unwrap());
+						log::debug!("Probe: APE tag detected, skipping {} bytes", 32 + ape_size);
+
+						position_after_id3_block = self.inner.seek(SeekFrom::Start(
+							position_after_id3_block + 32 + u64::from(ape_size),
+						))?;
+
+						ident = [0; 4];
+						std::io::copy(
+							&mut self.inner.by_ref().take(ident.len() as u64),
+							&mut Cursor::new(&mut ident[..]),
+						)?;
+					}
+				}
+
 				self.inner.seek(SeekFrom::Start(position_after_id3_block))?;
 
 				let file_type_after_id3_block = match &ident {
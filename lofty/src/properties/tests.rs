@@ -5,7 +5,7 @@ use crate::file::AudioFile;
 use crate::flac::{FlacFile, FlacProperties};
 use crate::iff::aiff::{AiffFile, AiffProperties};
 use crate::iff::wav::{WavFile, WavFormat, WavProperties};
-use crate::mp4::{AudioObjectType, Mp4Codec, Mp4File, Mp4Properties};
+use crate::mp4::{AudioObjectType, Mp4Codec, Mp4File, Mp4Properties, Mp4Timing};
 use crate::mpeg::{ChannelMode, Layer, MpegFile, MpegProperties, MpegVersion};
 use crate::musepack::sv4to6::MpcSv4to6Properties;
 use crate::musepack::sv7::{Link, MpcSv7Properties, Profile};
@@ -27,6 +27,7 @@ use std::time::Duration;
 const AAC_PROPERTIES: AACProperties = AACProperties {
 	version: MpegVersion::V4,
 	audio_object_type: AudioObjectType::AacLowComplexity,
+	extension_audio_object_type: None,
 	duration: Duration::from_millis(1474), /* TODO: This is ~100ms greater than FFmpeg's report, can we do better? */
 	overall_bitrate: 117,                  // 9 less than FFmpeg reports
 	audio_bitrate: 117,                    // 9 less than FFmpeg reports
@@ -45,6 +46,7 @@ const AIFF_PROPERTIES: AiffProperties = AiffProperties {
 	sample_size: 16,
 	channels: 2,
 	compression_type: None,
+	compression_name: None,
 };
 
 const APE_PROPERTIES: ApeProperties = ApeProperties {
@@ -65,6 +67,11 @@ const FLAC_PROPERTIES: FlacProperties = FlacProperties {
 	bit_depth: 16,
 	channels: 2,
 	signature: 164_506_065_180_489_231_127_156_351_872_182_799_315,
+	min_block_size: 4608,
+	max_block_size: 4608,
+	min_frame_size: 783,
+	max_frame_size: 4744,
+	total_samples: 68546,
 };
 
 const MP1_PROPERTIES: MpegProperties = MpegProperties {
@@ -116,14 +123,29 @@ fn expected_mp4_aac_properties() -> Mp4Properties {
 	Mp4Properties {
 		codec: Some(Mp4Codec::AAC),
 		extended_audio_object_type: Some(AudioObjectType::AacLowComplexity),
+		extension_audio_object_type: None,
 		duration: Duration::from_millis(1449),
 		overall_bitrate: Some(135),
 		audio_bitrate: Some(124),
 		sample_rate: Some(48000),
 		bit_depth: None,
 		channels: Some(2),
+		channel_mask: None,
 		drm_protected: false,
+		protection: None,
 		ftyp: String::from("M4A "),
+		movie_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 1000,
+			duration: 1450,
+		}),
+		media_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 48000,
+			duration: 69570,
+		}),
 	}
 }
 
@@ -131,14 +153,29 @@ fn expected_mp4_alac_properties() -> Mp4Properties {
 	Mp4Properties {
 		codec: Some(Mp4Codec::ALAC),
 		extended_audio_object_type: None,
+		extension_audio_object_type: None,
 		duration: Duration::from_millis(1428),
 		overall_bitrate: Some(331),
 		audio_bitrate: Some(326),
 		sample_rate: Some(48000),
 		bit_depth: Some(16),
 		channels: Some(2),
+		channel_mask: None,
 		drm_protected: false,
+		protection: None,
 		ftyp: String::from("M4A "),
+		movie_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 1000,
+			duration: 1429,
+		}),
+		media_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 48000,
+			duration: 68546,
+		}),
 	}
 }
 
@@ -146,14 +183,29 @@ fn expected_mp4_als_properties() -> Mp4Properties {
 	Mp4Properties {
 		codec: Some(Mp4Codec::AAC),
 		extended_audio_object_type: Some(AudioObjectType::AudioLosslessCoding),
+		extension_audio_object_type: None,
 		duration: Duration::from_millis(1429),
 		overall_bitrate: Some(1083),
 		audio_bitrate: Some(1078),
 		sample_rate: Some(48000),
 		bit_depth: None,
 		channels: Some(2),
+		channel_mask: None,
 		drm_protected: false,
+		protection: None,
 		ftyp: String::from("mp42"),
+		movie_timing: Some(Mp4Timing {
+			creation_time: 3_729_632_675,
+			modification_time: 3_729_632_675,
+			timescale: 48000,
+			duration: 68608,
+		}),
+		media_timing: Some(Mp4Timing {
+			creation_time: 3_729_632_675,
+			modification_time: 3_729_632_675,
+			timescale: 48000,
+			duration: 68608,
+		}),
 	}
 }
 
@@ -161,14 +213,29 @@ fn expected_mp4_flac_properties() -> Mp4Properties {
 	Mp4Properties {
 		codec: Some(Mp4Codec::FLAC),
 		extended_audio_object_type: None,
+		extension_audio_object_type: None,
 		duration: Duration::from_millis(1428),
 		overall_bitrate: Some(280),
 		audio_bitrate: Some(275),
 		sample_rate: Some(48000),
 		bit_depth: Some(16),
 		channels: Some(2),
+		channel_mask: Some(ChannelMask::stereo()),
 		drm_protected: false,
+		protection: None,
 		ftyp: String::from("isom"),
+		movie_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 1000,
+			duration: 1429,
+		}),
+		media_timing: Some(Mp4Timing {
+			creation_time: 0,
+			modification_time: 0,
+			timescale: 48000,
+			duration: 68546,
+		}),
 	}
 }
 
@@ -280,6 +347,8 @@ const WAV_PROPERTIES: WavProperties = WavProperties {
 	bit_depth: 16,
 	channels: 2,
 	channel_mask: None,
+	block_align: 4,
+	sub_format: None,
 };
 
 const WAVPACK_PROPERTIES: WavPackProperties = WavPackProperties {
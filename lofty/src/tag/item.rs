@@ -259,6 +259,17 @@ gen_map!(
 	"MusicBrainz Album Release Country" 	=> ReleaseCountry,
 );
 
+gen_map!(
+	LYRICS3_MAP;
+
+	"LYR" => Lyrics,
+	"INF" => Comment,
+	"AUT" => Lyricist,
+	"EAL" => AlbumTitle,
+	"EAR" => TrackArtist,
+	"ETT" => TrackTitle
+);
+
 gen_map!(
 	ILST_MAP;
 
@@ -463,6 +474,31 @@ gen_map!(
 	"RELEASETYPE"							  => MusicBrainzReleaseType,
 );
 
+gen_map!(
+	QUICKTIME_MAP;
+
+	"com.apple.quicktime.title"          | "\u{a9}nam" => TrackTitle,
+	"com.apple.quicktime.album"          | "\u{a9}alb" => AlbumTitle,
+	"com.apple.quicktime.artist"         | "\u{a9}ART" => TrackArtist,
+	"com.apple.quicktime.originalartist"              => OriginalArtist,
+	"\u{a9}arg"                                       => Arranger,
+	"\u{a9}wrt"                                       => Writer,
+	"\u{a9}com"                                       => Composer,
+	"com.apple.quicktime.director"       | "\u{a9}dir" => Director,
+	"com.apple.quicktime.performer"      | "\u{a9}prf" => Performer,
+	"com.apple.quicktime.producer"       | "\u{a9}prd" => Producer,
+	"com.apple.quicktime.publisher"                   => Publisher,
+	"\u{a9}lab"                                       => Label,
+	"com.apple.quicktime.creationdate"   | "\u{a9}day" => RecordingDate,
+	"com.apple.quicktime.year"                        => Year,
+	"\u{a9}isr"                                       => Isrc,
+	"com.apple.quicktime.software"       | "\u{a9}swr" => EncoderSoftware,
+	"com.apple.quicktime.copyright"      | "\u{a9}cpy" => CopyrightMessage,
+	"com.apple.quicktime.genre"          | "\u{a9}gen" => Genre,
+	"com.apple.quicktime.comment"        | "\u{a9}cmt" => Comment,
+	"com.apple.quicktime.description"    | "\u{a9}des" => Description,
+);
+
 macro_rules! gen_item_keys {
 	(
 		MAPS => [
@@ -538,8 +574,12 @@ gen_item_keys!(
 
 		[TagType::Id3v2, ID3V2_MAP],
 
+		[TagType::Lyrics3, LYRICS3_MAP],
+
 		[TagType::Mp4Ilst, ILST_MAP],
 
+		[TagType::QuickTime, QUICKTIME_MAP],
+
 		[TagType::RiffInfo, RIFF_INFO_MAP],
 
 		[TagType::VorbisComments, VORBIS_MAP]
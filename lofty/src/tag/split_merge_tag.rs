@@ -70,6 +70,7 @@ pub trait MergeTag: private::Sealed {
 // https://rust-lang.github.io/api-guidelines/future-proofing.html#c-sealed
 mod private {
 	use crate::ape::ApeTag;
+	use crate::id3::lyrics3::Lyrics3Tag;
 	use crate::id3::v1::Id3v1Tag;
 	use crate::id3::v2::Id3v2Tag;
 	use crate::iff::aiff::AiffTextChunks;
@@ -91,9 +92,15 @@ mod private {
 	impl Sealed for Id3v2Tag {}
 	impl Sealed for crate::id3::v2::tag::SplitTagRemainder {}
 
+	impl Sealed for Lyrics3Tag {}
+	impl Sealed for crate::id3::lyrics3::tag::SplitTagRemainder {}
+
 	impl Sealed for crate::mp4::Ilst {}
 	impl Sealed for crate::mp4::ilst::SplitTagRemainder {}
 
+	impl Sealed for crate::mp4::QuickTimeMetadata {}
+	impl Sealed for crate::mp4::quicktime::SplitTagRemainder {}
+
 	impl Sealed for RiffInfoList {}
 	impl Sealed for crate::iff::wav::tag::SplitTagRemainder {}
 
@@ -176,11 +176,12 @@ pub(crate) trait TagWriteExt {
 // https://rust-lang.github.io/api-guidelines/future-proofing.html#c-sealed
 mod private {
 	use crate::ape::ApeTag;
+	use crate::id3::lyrics3::Lyrics3Tag;
 	use crate::id3::v1::Id3v1Tag;
 	use crate::id3::v2::Id3v2Tag;
 	use crate::iff::aiff::AiffTextChunks;
 	use crate::iff::wav::RiffInfoList;
-	use crate::mp4::Ilst;
+	use crate::mp4::{Ilst, QuickTimeMetadata};
 	use crate::ogg::tag::VorbisComments;
 	use crate::tag::Tag;
 
@@ -191,6 +192,8 @@ mod private {
 	impl Sealed for Id3v1Tag {}
 	impl Sealed for Id3v2Tag {}
 	impl Sealed for Ilst {}
+	impl Sealed for Lyrics3Tag {}
+	impl Sealed for QuickTimeMetadata {}
 	impl Sealed for RiffInfoList {}
 	impl Sealed for Tag {}
 	impl Sealed for VorbisComments {}
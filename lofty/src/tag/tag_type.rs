@@ -115,6 +115,12 @@ pub enum TagType {
 	RiffInfo,
 	/// Represents AIFF text chunks
 	AiffText,
+	/// This covers both Lyrics3v1 and Lyrics3v2
+	///
+	/// Lyrics3 tags are read only, they can only be removed.
+	Lyrics3,
+	/// Represents QuickTime metadata, stored in `moov.meta` (`mdta`) and `moov.udta` text atoms
+	QuickTime,
 }
 
 impl TagType {
@@ -5,9 +5,9 @@ use crate::id3::v1::tag::Id3v1TagRef;
 use crate::id3::v2::tag::conversion::Id3v2TagRef;
 use crate::id3::v2::{self, Id3v2TagFlags};
 use crate::io::VerifiedFile;
-use crate::mp4::Ilst;
+use crate::mp4::{Ilst, QuickTimeMetadata};
 use crate::ogg::tag::{VorbisCommentsRef, create_vorbis_comments_ref};
-use crate::tag::{Tag, TagType};
+use crate::tag::{Tag, TagExt, TagType};
 use crate::util::io::FileLike;
 use crate::{aac, ape, flac, iff, mpeg, musepack, wavpack};
 use ape::tag::ApeTagRef;
@@ -36,11 +36,17 @@ where
 		},
 		FileType::Mpc => musepack::write::write_to(file, tag, write_options),
 		FileType::Mpeg => mpeg::write::write_to(file, tag, write_options),
-		FileType::Mp4 => crate::mp4::ilst::write::write_to(
-			file,
-			&mut Into::<Ilst>::into(tag.clone()).as_ref(),
-			write_options,
-		),
+		FileType::Mp4 => match tag.tag_type() {
+			TagType::QuickTime => {
+				crate::mp4::quicktime::write::write_to(file, &tag.clone().into(), write_options)
+			},
+			_ => crate::mp4::ilst::write::write_to(
+				file,
+				&mut Into::<Ilst>::into(tag.clone()).as_ref(),
+				write_options,
+			)
+			.map(|_| ()),
+		},
 		FileType::Wav => iff::wav::write::write_to(file, tag, write_options),
 		FileType::WavPack => wavpack::write::write_to(file, tag, write_options),
 		FileType::Custom(_) => unreachable!("custom file types don't support writing"),
@@ -73,6 +79,7 @@ pub(crate) fn dump_tag<W: Write>(
 			.as_ref()
 			.dump_to(writer, write_options)
 			.map_err(Into::into),
+		TagType::QuickTime => QuickTimeMetadata::from(tag.clone()).dump_to(writer, write_options),
 		TagType::VorbisComments => {
 			let (vendor, items, pictures) = create_vorbis_comments_ref(tag);
 
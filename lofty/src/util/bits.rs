@@ -0,0 +1,163 @@
+use std::io::{Error, ErrorKind};
+
+/// A big-endian bit reader over a byte slice
+///
+/// This is used for the few formats that pack their fields tighter than a byte, such
+/// as MPEG-4 AudioSpecificConfigs and raw AAC data blocks.
+#[derive(Clone)]
+pub(crate) struct BitReader<'a> {
+	data: &'a [u8],
+	// Position in bits from the start of `data`
+	position: usize,
+}
+
+impl<'a> BitReader<'a> {
+	pub(crate) fn new(data: &'a [u8]) -> Self {
+		Self { data, position: 0 }
+	}
+
+	/// Move to an absolute bit position
+	///
+	/// Positions past the end are clamped, causing the next read to fail.
+	pub(crate) fn set_position(&mut self, position: usize) {
+		self.position = core::cmp::min(position, self.data.len() * 8);
+	}
+
+	/// The number of bits left to read
+	pub(crate) fn bits_left(&self) -> usize {
+		(self.data.len() * 8) - self.position
+	}
+
+	pub(crate) fn read_bit(&mut self) -> std::io::Result<bool> {
+		Ok(self.read_bits(1)? == 1)
+	}
+
+	/// Read up to 32 bits
+	pub(crate) fn read_bits(&mut self, count: u8) -> std::io::Result<u32> {
+		debug_assert!(count <= 32);
+
+		if usize::from(count) > self.bits_left() {
+			return Err(Error::from(ErrorKind::UnexpectedEof));
+		}
+
+		let mut value = 0u32;
+		let mut remaining = count;
+		while remaining > 0 {
+			let byte = self.data[self.position / 8];
+			let bit_offset = (self.position % 8) as u8;
+
+			let available = 8 - bit_offset;
+			let take = core::cmp::min(available, remaining);
+
+			let bits = (byte >> (available - take)) & (0xFF >> (8 - take));
+			value = (value << take) | u32::from(bits);
+
+			self.position += usize::from(take);
+			remaining -= take;
+		}
+
+		Ok(value)
+	}
+
+	pub(crate) fn skip_bits(&mut self, count: usize) -> std::io::Result<()> {
+		if count > self.bits_left() {
+			return Err(Error::from(ErrorKind::UnexpectedEof));
+		}
+
+		self.position += count;
+		Ok(())
+	}
+}
+
+#[cfg(feature = "flac_verification")]
+impl BitReader<'_> {
+	/// The current position in bits
+	pub(crate) fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Read up to 64 bits
+	pub(crate) fn read_bits_u64(&mut self, count: u8) -> std::io::Result<u64> {
+		debug_assert!(count <= 64);
+
+		if count <= 32 {
+			return self.read_bits(count).map(u64::from);
+		}
+
+		let high = u64::from(self.read_bits(count - 32)?);
+		let low = u64::from(self.read_bits(32)?);
+		Ok((high << 32) | low)
+	}
+
+	/// Read a two's complement signed integer of up to 64 bits
+	pub(crate) fn read_signed(&mut self, count: u8) -> std::io::Result<i64> {
+		if count == 0 {
+			return Ok(0);
+		}
+
+		let value = self.read_bits_u64(count)?;
+		let shift = 64 - u32::from(count);
+		Ok(((value << shift) as i64) >> shift)
+	}
+
+	/// Read a unary coded value, the number of `0` bits before the next `1` bit
+	pub(crate) fn read_unary(&mut self) -> std::io::Result<u32> {
+		let mut count = 0;
+
+		// Skip entire zero bytes when possible
+		while self.position.is_multiple_of(8)
+			&& let Some(0) = self.data.get(self.position / 8)
+		{
+			self.position += 8;
+			count += 8;
+		}
+
+		while !self.read_bit()? {
+			count += 1;
+		}
+
+		Ok(count)
+	}
+
+	/// Skip to the next byte boundary
+	pub(crate) fn align(&mut self) {
+		self.position = self.position.next_multiple_of(8);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BitReader;
+
+	#[test_log::test]
+	fn read_bits() {
+		let data = [0b1010_1100, 0b0101_1111, 0xFF];
+		let mut reader = BitReader::new(&data);
+
+		assert!(reader.read_bit().unwrap());
+		assert_eq!(reader.read_bits(3).unwrap(), 0b010);
+		assert_eq!(reader.read_bits(8).unwrap(), 0b1100_0101);
+		assert_eq!(reader.bits_left(), 12);
+		assert_eq!(reader.read_bits(12).unwrap(), 0xFFF);
+		assert!(reader.read_bit().is_err());
+
+		reader.set_position(4);
+		reader.skip_bits(4).unwrap();
+		assert_eq!(reader.read_bits(16).unwrap(), 0x5FFF);
+	}
+
+	#[test_log::test]
+	#[cfg(feature = "flac_verification")]
+	fn read_signed_and_unary() {
+		let data = [0b1110_0000, 0b0000_0000, 0b0001_1000, 0x80, 0, 0, 0, 0x01];
+		let mut reader = BitReader::new(&data);
+
+		assert_eq!(reader.read_signed(3).unwrap(), -1);
+		assert_eq!(reader.read_unary().unwrap(), 16);
+		assert_eq!(reader.read_unary().unwrap(), 0);
+		reader.align();
+		assert_eq!(reader.position(), 24);
+		assert_eq!(reader.read_signed(33).unwrap(), -(1 << 32));
+		assert_eq!(reader.read_bits_u64(7).unwrap(), 1);
+	}
+}
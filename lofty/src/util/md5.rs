@@ -0,0 +1,168 @@
+//! A minimal MD5 implementation (RFC 1321)
+//!
+//! This only exists to verify FLAC STREAMINFO signatures, and must not be used for anything
+//! security related.
+
+const SHIFTS: [u32; 64] = [
+	7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+	14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+	21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+#[rustfmt::skip]
+const K: [u32; 64] = [
+	0xD76A_A478, 0xE8C7_B756, 0x2420_70DB, 0xC1BD_CEEE,
+	0xF57C_0FAF, 0x4787_C62A, 0xA830_4613, 0xFD46_9501,
+	0x6980_98D8, 0x8B44_F7AF, 0xFFFF_5BB1, 0x895C_D7BE,
+	0x6B90_1122, 0xFD98_7193, 0xA679_438E, 0x49B4_0821,
+	0xF61E_2562, 0xC040_B340, 0x265E_5A51, 0xE9B6_C7AA,
+	0xD62F_105D, 0x0244_1453, 0xD8A1_E681, 0xE7D3_FBC8,
+	0x21E1_CDE6, 0xC337_07D6, 0xF4D5_0D87, 0x455A_14ED,
+	0xA9E3_E905, 0xFCEF_A3F8, 0x676F_02D9, 0x8D2A_4C8A,
+	0xFFFA_3942, 0x8771_F681, 0x6D9D_6122, 0xFDE5_380C,
+	0xA4BE_EA44, 0x4BDE_CFA9, 0xF6BB_4B60, 0xBEBF_BC70,
+	0x289B_7EC6, 0xEAA1_27FA, 0xD4EF_3085, 0x0488_1D05,
+	0xD9D4_D039, 0xE6DB_99E5, 0x1FA2_7CF8, 0xC4AC_5665,
+	0xF429_2244, 0x432A_FF97, 0xAB94_23A7, 0xFC93_A039,
+	0x655B_59C3, 0x8F0C_CC92, 0xFFEF_F47D, 0x8584_5DD1,
+	0x6FA8_7E4F, 0xFE2C_E6E0, 0xA301_4314, 0x4E08_11A1,
+	0xF753_7E82, 0xBD3A_F235, 0x2AD7_D2BB, 0xEB86_D391,
+];
+
+const BLOCK_SIZE: usize = 64;
+
+pub(crate) struct Md5 {
+	state: [u32; 4],
+	buffer: [u8; BLOCK_SIZE],
+	buffer_len: usize,
+	// Total message length in bytes
+	len: u64,
+}
+
+impl Default for Md5 {
+	fn default() -> Self {
+		Self {
+			state: [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476],
+			buffer: [0; BLOCK_SIZE],
+			buffer_len: 0,
+			len: 0,
+		}
+	}
+}
+
+impl Md5 {
+	pub(crate) fn update(&mut self, mut data: &[u8]) {
+		self.len = self.len.wrapping_add(data.len() as u64);
+
+		if self.buffer_len > 0 {
+			let take = core::cmp::min(BLOCK_SIZE - self.buffer_len, data.len());
+			self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+			self.buffer_len += take;
+			data = &data[take..];
+
+			if self.buffer_len < BLOCK_SIZE {
+				return;
+			}
+
+			let block = self.buffer;
+			self.process_block(&block);
+			self.buffer_len = 0;
+		}
+
+		let mut blocks = data.chunks_exact(BLOCK_SIZE);
+		for block in &mut blocks {
+			self.process_block(block.try_into().expect("chunk is exactly one block"));
+		}
+
+		let remainder = blocks.remainder();
+		self.buffer[..remainder.len()].copy_from_slice(remainder);
+		self.buffer_len = remainder.len();
+	}
+
+	/// Finish the digest, returning it as a big-endian integer
+	pub(crate) fn finalize(mut self) -> u128 {
+		let bit_len = self.len.wrapping_mul(8);
+
+		self.update(&[0x80]);
+		while self.buffer_len != BLOCK_SIZE - 8 {
+			self.update(&[0]);
+		}
+		self.update(&bit_len.to_le_bytes());
+
+		let mut digest = [0; 16];
+		for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+			chunk.copy_from_slice(&word.to_le_bytes());
+		}
+
+		u128::from_be_bytes(digest)
+	}
+
+	#[allow(clippy::many_single_char_names)]
+	fn process_block(&mut self, block: &[u8; BLOCK_SIZE]) {
+		let mut words = [0u32; 16];
+		for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+			*word = u32::from_le_bytes(bytes.try_into().expect("chunk is exactly 4 bytes"));
+		}
+
+		let [mut a, mut b, mut c, mut d] = self.state;
+		for i in 0..64 {
+			let (f, g) = match i {
+				0..=15 => ((b & c) | (!b & d), i),
+				16..=31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+				32..=47 => (b ^ c ^ d, (3 * i + 5) % 16),
+				_ => (c ^ (b | !d), (7 * i) % 16),
+			};
+
+			let rotated = a
+				.wrapping_add(f)
+				.wrapping_add(K[i])
+				.wrapping_add(words[g])
+				.rotate_left(SHIFTS[i]);
+
+			a = d;
+			d = c;
+			c = b;
+			b = b.wrapping_add(rotated);
+		}
+
+		self.state[0] = self.state[0].wrapping_add(a);
+		self.state[1] = self.state[1].wrapping_add(b);
+		self.state[2] = self.state[2].wrapping_add(c);
+		self.state[3] = self.state[3].wrapping_add(d);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Md5;
+
+	fn digest(data: &[u8]) -> u128 {
+		let mut md5 = Md5::default();
+		md5.update(data);
+		md5.finalize()
+	}
+
+	#[test_log::test]
+	fn rfc1321_test_suite() {
+		assert_eq!(digest(b""), 0xD41D_8CD9_8F00_B204_E980_0998_ECF8_427E);
+		assert_eq!(digest(b"abc"), 0x9001_5098_3CD2_4FB0_D696_3F7D_28E1_7F72);
+		assert_eq!(
+			digest(
+				b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+			),
+			0x57ED_F4A2_2BE3_C955_AC49_DA2E_2107_B67A
+		);
+	}
+
+	#[test_log::test]
+	fn split_updates() {
+		let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+
+		let mut md5 = Md5::default();
+		for chunk in data.chunks(7) {
+			md5.update(chunk);
+		}
+
+		assert_eq!(md5.finalize(), digest(&data));
+	}
+}
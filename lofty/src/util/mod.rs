@@ -1,6 +1,9 @@
 pub(crate) mod alloc;
+pub(crate) mod bits;
 pub mod io;
 pub(crate) mod math;
+#[cfg(feature = "flac_verification")]
+pub(crate) mod md5;
 pub(crate) mod text;
 
 pub(crate) fn flag_item(item: &str) -> Option<bool> {
@@ -2,7 +2,8 @@ use super::WavPackFile;
 use super::properties::WavPackProperties;
 use crate::config::ParseOptions;
 use crate::error::{SizeMismatchError, TagParseError};
-use crate::id3::{ID3FindResults, find_id3v1, find_lyrics3v2};
+use crate::id3::lyrics3::read::find_lyrics3;
+use crate::id3::{ID3FindResults, find_id3v1};
 use crate::wavpack::error::WavPackParseError;
 
 use std::io::{Read, Seek, SeekFrom};
@@ -34,13 +35,17 @@ where
 		stream_length = new_stream_length;
 	}
 
-	// Next, check for a Lyrics3v2 tag, and skip over it, as it's no use to us
-	let ID3FindResults(_, lyrics3v2_size) = find_lyrics3v2(reader)?;
-	let Some(new_stream_length) = stream_length.checked_sub(u64::from(lyrics3v2_size)) else {
-		return Err(SizeMismatchError.into());
-	};
+	// Next, check for a Lyrics3 tag, and skip over it, as it's no use to us
+	if let ID3FindResults(Some(lyrics3_header), _) =
+		find_lyrics3(reader, false, parse_options.parsing_mode)?
+	{
+		let Some(new_stream_length) = stream_length.checked_sub(u64::from(lyrics3_header.size))
+		else {
+			return Err(SizeMismatchError.into());
+		};
 
-	stream_length = new_stream_length;
+		stream_length = new_stream_length;
+	}
 
 	// Next, search for an APE tag footer
 	//
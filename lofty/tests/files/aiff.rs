@@ -1,10 +1,15 @@
-use lofty::config::ParseOptions;
+use crate::util::temp_file;
+use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::FileType;
+use lofty::iff::aiff::{
+	AiffCompressionType, AiffFile, InstrumentChunk, InstrumentLoop, Marker, PlayMode,
+};
 use lofty::prelude::*;
 use lofty::probe::Probe;
-use lofty::tag::TagType;
+use lofty::tag::{Tag, TagType};
 
-use std::io::Seek;
+use std::io::{Cursor, Seek};
+use std::time::Duration;
 
 #[test_log::test]
 fn read() {
@@ -89,6 +94,103 @@ fn remove_id3v2() {
 	crate::util::remove_tag_test("tests/files/assets/minimal/full_test.aiff", TagType::Id3v2);
 }
 
+// 44100 Hz as an 80-bit extended float
+const SAMPLE_RATE_44100: [u8; 10] = [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0];
+
+fn aifc(
+	channels: u16,
+	sample_frames: u32,
+	sample_size: u16,
+	compression: &[u8],
+	data_len: usize,
+) -> AiffFile {
+	let mut comm = Vec::new();
+	comm.extend(channels.to_be_bytes());
+	comm.extend(sample_frames.to_be_bytes());
+	comm.extend(sample_size.to_be_bytes());
+	comm.extend(SAMPLE_RATE_44100);
+	comm.extend(compression);
+	if comm.len() % 2 != 0 {
+		comm.push(0);
+	}
+
+	let mut chunks = Vec::new();
+	chunks.extend(b"COMM");
+	chunks.extend((comm.len() as u32).to_be_bytes());
+	chunks.extend(comm);
+	chunks.extend(b"SSND");
+	chunks.extend((data_len as u32 + 8).to_be_bytes());
+	chunks.resize(chunks.len() + 8 + data_len.next_multiple_of(2), 0);
+
+	let mut file = Vec::new();
+	file.extend(b"FORM");
+	file.extend((chunks.len() as u32 + 4).to_be_bytes());
+	file.extend(b"AIFC");
+	file.extend(chunks);
+
+	AiffFile::read_from(&mut Cursor::new(file), ParseOptions::new()).unwrap()
+}
+
+#[test_log::test]
+fn aifc_ima4_properties() {
+	// 689 packets of 64 sample frames each, 34 bytes per channel
+	let file = aifc(2, 689, 16, b"ima4\x07IMA 4:1", 689 * 68);
+	let properties = file.properties();
+
+	assert_eq!(
+		properties.compression_type(),
+		Some(&AiffCompressionType::ima4)
+	);
+	assert_eq!(properties.compression_name(), Some("IMA 4:1"));
+	assert_eq!(properties.duration(), Duration::from_millis(999));
+	assert_eq!(properties.audio_bitrate(), 375);
+	assert_eq!(properties.sample_size(), 16);
+}
+
+#[test_log::test]
+fn aifc_stream_length_properties() {
+	// No sample frame count, the duration is estimated from the stream
+	for (compression, bytes_per_frame) in [
+		(&b"sowt\x00"[..], 4),
+		(b"fl32\x0D32-bit float", 8),
+		(b"fl64\x0D64-bit float", 16),
+		(b"ulaw\x08\xB5Law 2:1", 2),
+	] {
+		let file = aifc(2, 0, 16, compression, 44100 * bytes_per_frame);
+		let properties = file.properties();
+
+		assert_eq!(properties.duration(), Duration::from_secs(1));
+	}
+
+	// The compression name is not required to be UTF-8
+	let file = aifc(2, 0, 16, b"ulaw\x08\xB5Law 2:1", 44100 * 2);
+	assert_eq!(file.properties().compression_name(), Some("µLaw 2:1"));
+
+	// 160 sample frames per 35 byte packet
+	let file = aifc(1, 0, 16, b"Qclp\x00", 35 * 100);
+	let properties = file.properties();
+	assert_eq!(
+		properties.compression_type(),
+		Some(&AiffCompressionType::Qclp)
+	);
+	assert_eq!(properties.compression_name(), None);
+	assert_eq!(properties.duration(), Duration::from_millis(362));
+}
+
+#[test_log::test]
+fn aifc_truncated_compression_name() {
+	// The name claims to be 7 bytes long, but the chunk ends early
+	let file = aifc(2, 689, 16, b"ima4\x07IMA", 689 * 68);
+	let properties = file.properties();
+
+	assert_eq!(
+		properties.compression_type(),
+		Some(&AiffCompressionType::ima4)
+	);
+	assert_eq!(properties.compression_name(), None);
+	assert_eq!(properties.duration(), Duration::from_millis(999));
+}
+
 #[test_log::test]
 fn read_no_properties() {
 	crate::util::no_properties_test("tests/files/assets/minimal/full_test.aiff");
@@ -109,3 +211,123 @@ fn empty_id3_chunk_skipped_644() {
 	assert_eq!(file.file_type(), FileType::Aiff);
 	assert!(file.tag(TagType::Id3v2).is_none());
 }
+
+#[test_log::test]
+fn write_instrument_and_markers() {
+	let mut file = temp_file("tests/files/assets/minimal/full_test.aiff");
+
+	let mut aiff = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert!(aiff.instrument().is_none());
+	assert!(aiff.markers().is_empty());
+
+	let markers = vec![
+		Marker::new(1, 0, String::from("Loop start")),
+		Marker::new(2, 100, String::from("Loop end")),
+	];
+
+	let instrument = InstrumentChunk {
+		base_note: 57,
+		sustain_loop: InstrumentLoop {
+			play_mode: PlayMode::ForwardBackward,
+			begin_marker: 1,
+			end_marker: 2,
+		},
+		..InstrumentChunk::new()
+	};
+
+	aiff.set_markers(markers.clone());
+	aiff.set_instrument(instrument);
+	aiff.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut aiff = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(aiff.markers(), markers.as_slice());
+	assert_eq!(aiff.instrument(), Some(instrument));
+	assert_eq!(aiff.properties().duration().as_millis(), 1428);
+
+	// Rewriting the tags keeps the chunks around
+	for tag_type in [TagType::AiffText, TagType::Id3v2] {
+		file.rewind().unwrap();
+		let mut tag = Tag::new(tag_type);
+		tag.set_title(String::from("A much longer title, to force a resize"));
+		tag.save_to(&mut file, WriteOptions::default()).unwrap();
+	}
+
+	file.rewind().unwrap();
+	let reread = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.markers(), markers.as_slice());
+	assert_eq!(reread.instrument(), Some(instrument));
+
+	aiff.remove_instrument();
+	aiff.remove_markers();
+
+	file.rewind().unwrap();
+	aiff.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let aiff = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(aiff.instrument().is_none());
+	assert!(aiff.markers().is_empty());
+}
+
+#[test_log::test]
+fn unparsed_chunks_preserved() {
+	// An `INST` chunk that is far too small to be valid
+	let mut aiff = std::fs::read("tests/files/assets/minimal/full_test.aiff").unwrap();
+	aiff.extend(b"INST");
+	aiff.extend(4_u32.to_be_bytes());
+	aiff.extend(b"junk");
+	let form_size = (aiff.len() - 8) as u32;
+	aiff[4..8].copy_from_slice(&form_size.to_be_bytes());
+
+	let mut file = Cursor::new(aiff);
+	let mut parsed = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(parsed.instrument().is_none());
+
+	file.rewind().unwrap();
+	parsed.set_markers(vec![Marker::new(1, 0, String::from("Start"))]);
+	parsed.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = file.get_ref();
+	assert!(content.windows(12).any(|w| w == b"INST\0\0\0\x04junk"));
+
+	file.rewind().unwrap();
+	let parsed = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(parsed.markers().len(), 1);
+	assert!(parsed.instrument().is_none());
+}
+
+#[test_log::test]
+fn unchanged_markers_preserved() {
+	// Claims to hold 3 markers, but only has room for 1
+	let mut mark = b"MARK\0\0\0\x0E\0\x03\0\x01\0\0\0\0\x04Caf\xE9\0".to_vec();
+
+	let mut aiff = std::fs::read("tests/files/assets/minimal/full_test.aiff").unwrap();
+	aiff.extend(&mark);
+	let form_size = (aiff.len() - 8) as u32;
+	aiff[4..8].copy_from_slice(&form_size.to_be_bytes());
+
+	let mut file = Cursor::new(aiff);
+	let mut parsed = AiffFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(
+		parsed.markers(),
+		[Marker::new(1, 0, String::from("Caf\u{e9}"))]
+	);
+
+	file.rewind().unwrap();
+	parsed.set_instrument(InstrumentChunk::new());
+	parsed.save_to(&mut file, WriteOptions::default()).unwrap();
+	assert!(file.get_ref().windows(mark.len()).any(|w| w == mark));
+
+	// Once changed, the chunk is rewritten
+	parsed.markers_mut()[0].position = 10;
+
+	file.rewind().unwrap();
+	parsed.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	mark[9] = 1;
+	mark[15] = 10;
+	assert!(file.get_ref().windows(mark.len()).any(|w| w == mark));
+}
@@ -1,10 +1,15 @@
-use lofty::config::ParseOptions;
+use crate::util::temp_file;
+use lofty::ape::{
+	ApeFile, ApeItem, ApeItemIssue, ApeItemIssueKind, ApeTag, ApeTagPlacement, ApeTagVersion,
+};
+use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::FileType;
+use lofty::id3::lyrics3::Lyrics3Version;
 use lofty::prelude::*;
 use lofty::probe::Probe;
-use lofty::tag::TagType;
+use lofty::tag::{ItemValue, TagType};
 
-use std::io::Seek;
+use std::io::{Cursor, Read, Seek};
 
 #[test_log::test]
 fn read() {
@@ -80,6 +85,240 @@ fn write() {
 	);
 }
 
+#[test_log::test]
+fn write_apev1() {
+	let mut file = temp_file("tests/files/assets/minimal/full_test.ape");
+
+	let mut tag = ApeTag::new();
+	tag.set_artist(String::from("Foo ártist"));
+	tag.set_title(String::from("Foo タイトル"));
+	tag.insert(
+		ApeItem::new(
+			String::from("Cover Art (Front)"),
+			ItemValue::Binary(vec![1, 2, 3]),
+		)
+		.unwrap(),
+	);
+
+	assert_eq!(
+		tag.validate(ApeTagVersion::V1),
+		vec![
+			ApeItemIssue {
+				key: String::from("Title"),
+				kind: ApeItemIssueKind::NonLatin1Text,
+			},
+			ApeItemIssue {
+				key: String::from("Cover Art (Front)"),
+				kind: ApeItemIssueKind::NonTextValue,
+			},
+		]
+	);
+
+	// The title can't be represented without lossy encoding
+	let write_options = WriteOptions::new().ape_tag_version(ApeTagVersion::V1);
+	assert!(
+		tag.save_to(
+			&mut file,
+			WriteOptions::new()
+				.ape_tag_version(ApeTagVersion::V1)
+				.lossy_text_encoding(false)
+		)
+		.is_err()
+	);
+
+	file.rewind().unwrap();
+	tag.save_to(&mut file, write_options).unwrap();
+
+	// APEv1 tags only have a footer
+	file.rewind().unwrap();
+	let mut bytes = Vec::new();
+	file.read_to_end(&mut bytes).unwrap();
+
+	let preambles = bytes
+		.windows(8)
+		.enumerate()
+		.filter(|(_, window)| window == b"APETAGEX")
+		.map(|(pos, _)| pos)
+		.collect::<Vec<_>>();
+	assert_eq!(preambles.len(), 1);
+	assert_eq!(
+		&bytes[preambles[0] + 8..preambles[0] + 12],
+		&1000_u32.to_le_bytes()
+	);
+
+	file.rewind().unwrap();
+	let ape_file = ApeFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	let ape_tag = ape_file.ape().unwrap();
+
+	assert_eq!(ape_tag.len(), 2);
+	assert_eq!(ape_tag.artist().as_deref(), Some("Foo ártist"));
+	assert_eq!(ape_tag.title().as_deref(), Some("Foo ????"));
+}
+
+fn preamble_positions(bytes: &[u8]) -> Vec<usize> {
+	bytes
+		.windows(8)
+		.enumerate()
+		.filter(|(_, window)| window == b"APETAGEX")
+		.map(|(pos, _)| pos)
+		.collect()
+}
+
+fn save_and_verify(bytes: Vec<u8>, write_options: WriteOptions) -> Vec<u8> {
+	let mut tag = ApeTag::new();
+	tag.set_title(String::from("Foo title"));
+
+	let mut file = Cursor::new(bytes);
+	tag.save_to(&mut file, write_options).unwrap();
+
+	file.rewind().unwrap();
+	let ape_file = ApeFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(
+		ape_file.ape().unwrap().title().as_deref(),
+		Some("Foo title")
+	);
+	assert!(ape_file.id3v1().is_some());
+
+	file.into_inner()
+}
+
+#[test_log::test]
+fn ape_tag_placement() {
+	let mut bytes = std::fs::read("tests/files/assets/minimal/full_test.ape").unwrap();
+
+	// Add a Lyrics3v2 tag between the APE and ID3v1 tags
+	let lyrics3 = b"LYRICSBEGININD0000200LYR00005hello000034LYRICS200";
+	let id3v1_start = bytes.len() - 128;
+	bytes.splice(id3v1_start..id3v1_start, lyrics3.iter().copied());
+
+	let bytes = save_and_verify(
+		bytes,
+		WriteOptions::new().ape_tag_placement(ApeTagPlacement::AfterLyrics3),
+	);
+
+	// [Lyrics3][APE][ID3v1], with the old tag removed
+	let id3v1_start = bytes.len() - 128;
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 2);
+	assert_eq!(preambles[1] + 32, id3v1_start);
+	assert_eq!(&bytes[preambles[0] - lyrics3.len()..preambles[0]], lyrics3);
+
+	// And back again
+	let bytes = save_and_verify(bytes, WriteOptions::new());
+
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 2);
+	assert_eq!(
+		&bytes[preambles[1] + 32..preambles[1] + 32 + lyrics3.len()],
+		lyrics3
+	);
+	assert_eq!(preambles[1] + 32 + lyrics3.len(), bytes.len() - 128);
+}
+
+#[test_log::test]
+fn ape_tag_header() {
+	let bytes = std::fs::read("tests/files/assets/minimal/full_test.ape").unwrap();
+
+	let bytes = save_and_verify(bytes, WriteOptions::new().ape_tag_header(false));
+
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 1);
+	assert_eq!(preambles[0] + 32, bytes.len() - 128);
+
+	// Bit 31 unset: tag doesn't contain a header
+	let flags = u32::from_le_bytes(
+		bytes[preambles[0] + 20..preambles[0] + 24]
+			.try_into()
+			.unwrap(),
+	);
+	assert_eq!(flags & (1 << 31), 0);
+}
+
+// Move the APE tag in front of the audio, after the ID3v2 tag, returning the tag's position
+fn with_leading_ape_tag(mut bytes: Vec<u8>) -> (Vec<u8>, usize) {
+	let id3v1_start = bytes.len() - 128;
+	let preambles = preamble_positions(&bytes);
+	let ape_tag = bytes.drain(preambles[0]..id3v1_start).collect::<Vec<_>>();
+
+	let audio_start = bytes.windows(4).position(|w| w == b"MAC ").unwrap();
+	bytes.splice(audio_start..audio_start, ape_tag);
+
+	(bytes, audio_start)
+}
+
+#[test_log::test]
+fn ape_leading_tag() {
+	let (bytes, audio_start) =
+		with_leading_ape_tag(std::fs::read("tests/files/assets/minimal/full_test.ape").unwrap());
+
+	// Update the tag where it is, even without a header requested
+	let bytes = save_and_verify(
+		bytes,
+		WriteOptions::new()
+			.ape_move_leading_tag(false)
+			.ape_tag_header(false),
+	);
+
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 2);
+	assert_eq!(preambles[0], audio_start);
+	assert_eq!(&bytes[preambles[1] + 32..preambles[1] + 36], b"MAC ");
+
+	// Now move it to the end
+	let bytes = save_and_verify(bytes, WriteOptions::new());
+
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 2);
+	assert_eq!(&bytes[audio_start..audio_start + 4], b"MAC ");
+	assert_eq!(preambles[1] + 32, bytes.len() - 128);
+}
+
+#[test_log::test]
+fn ape_leading_tag_v1() {
+	let (bytes, audio_start) =
+		with_leading_ape_tag(std::fs::read("tests/files/assets/minimal/full_test.ape").unwrap());
+
+	// APEv1 tags have no header, so they can't stay at the beginning of the file
+	let bytes = save_and_verify(
+		bytes,
+		WriteOptions::new()
+			.ape_move_leading_tag(false)
+			.ape_tag_version(ApeTagVersion::V1),
+	);
+
+	let preambles = preamble_positions(&bytes);
+	assert_eq!(preambles.len(), 1);
+	assert_eq!(&bytes[audio_start..audio_start + 4], b"MAC ");
+	assert_eq!(preambles[0] + 32, bytes.len() - 128);
+}
+
+#[test_log::test]
+fn lyrics3() {
+	let original = std::fs::read("tests/files/assets/minimal/full_test.ape").unwrap();
+
+	// [audio][Lyrics3v1][APE][ID3v1]
+	let lyrics3 = b"LYRICSBEGINFoo lyricsLYRICSEND";
+	let preambles = preamble_positions(&original);
+	assert_eq!(preambles.len(), 2);
+
+	let mut bytes = original.clone();
+	bytes.splice(preambles[0]..preambles[0], lyrics3.iter().copied());
+
+	let mut file = Cursor::new(bytes);
+	let ape_file = ApeFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(ape_file.ape().is_some());
+
+	let lyrics3_tag = ape_file.lyrics3().unwrap();
+	assert_eq!(lyrics3_tag.version, Lyrics3Version::V1);
+	assert_eq!(lyrics3_tag.lyrics.as_deref(), Some("Foo lyrics"));
+
+	file.rewind().unwrap();
+	TagType::Lyrics3
+		.remove_from(&mut file, WriteOptions::default())
+		.unwrap();
+	assert_eq!(file.into_inner(), original);
+}
+
 #[test_log::test]
 fn remove_ape() {
 	crate::util::remove_tag_test("tests/files/assets/minimal/full_test.ape", TagType::Ape);
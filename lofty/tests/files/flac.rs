@@ -1,10 +1,13 @@
 use crate::util::temp_file;
 
 use std::fs::File;
-use std::io::Seek;
+use std::io::{Read, Seek};
+use std::time::Duration;
 
 use lofty::config::{ParseOptions, ParsingMode, WriteOptions};
-use lofty::flac::FlacFile;
+use lofty::flac::{
+	ApplicationBlock, CueSheetTrack, FlacBlockOrder, FlacBlockType, FlacFile, SeekPoint, SeekTable,
+};
 use lofty::ogg::OggPictureStorage;
 use lofty::ogg::tag::VorbisComments;
 use lofty::picture::{Picture, PictureInformation, PictureType};
@@ -182,3 +185,467 @@ fn application_block_is_last() {
 		Some("Foo Artist")
 	);
 }
+
+#[test_log::test]
+fn read_stream_blocks() {
+	let mut file = File::open("tests/taglib/data/silence-44-s.flac").unwrap();
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	let seek_table = f.seek_table().unwrap();
+	assert_eq!(seek_table.points.len(), 6);
+	assert_eq!(
+		seek_table.points[1],
+		SeekPoint {
+			sample_number: 41472,
+			stream_offset: 11852,
+			frame_samples: 4608,
+		}
+	);
+	assert!(seek_table.points[5].is_placeholder());
+
+	let cue_sheet = f.cue_sheet().unwrap();
+	assert_eq!(cue_sheet.media_catalog_number, "1234567890123");
+	assert_eq!(cue_sheet.lead_in_samples, 88200);
+	assert!(cue_sheet.is_cd);
+	assert_eq!(cue_sheet.tracks.len(), 4);
+
+	let first_track = &cue_sheet.tracks[0];
+	assert_eq!(first_track.isrc.as_deref(), Some("123456789012"));
+	assert!(first_track.is_audio);
+
+	let second_track = &cue_sheet.tracks[1];
+	assert_eq!(second_track.offset, 44100);
+	assert!(!second_track.is_audio);
+	assert!(second_track.pre_emphasis);
+	assert_eq!(second_track.indices.len(), 2);
+	assert_eq!(second_track.indices[1].offset, 588);
+	assert_eq!(second_track.indices[1].number, 2);
+
+	let lead_out = cue_sheet.tracks.last().unwrap();
+	assert_eq!(lead_out.number, CueSheetTrack::CD_DA_LEAD_OUT);
+	assert!(lead_out.indices.is_empty());
+
+	let mut file = File::open("tests/files/assets/application_block_last.flac").unwrap();
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	assert_eq!(f.application_blocks().len(), 1);
+	assert_eq!(f.application_blocks()[0].id, *b"\x12\x00\x12\x00");
+}
+
+#[test_log::test]
+fn write_stream_blocks() {
+	let mut file = temp_file("tests/taglib/data/silence-44-s.flac");
+
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	let original_comments = f.vorbis_comments().cloned();
+	let original_cue_sheet = f.cue_sheet().cloned().unwrap();
+
+	f.set_seek_table(SeekTable::new(vec![
+		SeekPoint::placeholder(),
+		SeekPoint {
+			sample_number: 4608,
+			stream_offset: 1024,
+			frame_samples: 4608,
+		},
+	]));
+	f.cue_sheet_mut().unwrap().tracks[0].isrc = None;
+	f.push_application_block(ApplicationBlock::new(*b"test", vec![1, 2, 3]));
+
+	f.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	// Placeholders are sorted to the end
+	let seek_table = f.seek_table().unwrap();
+	assert_eq!(seek_table.points.len(), 2);
+	assert_eq!(seek_table.points[0].sample_number, 4608);
+	assert!(seek_table.points[1].is_placeholder());
+
+	let cue_sheet = f.cue_sheet().unwrap();
+	assert!(cue_sheet.tracks[0].isrc.is_none());
+	assert_eq!(cue_sheet.tracks[1..], original_cue_sheet.tracks[1..]);
+
+	assert_eq!(f.application_blocks().len(), 1);
+	assert_eq!(f.application_blocks()[0].data, [1, 2, 3]);
+
+	// The tag should be untouched
+	assert_eq!(f.vorbis_comments().cloned(), original_comments);
+
+	// And now remove them all
+	f.remove_seek_table();
+	f.remove_cue_sheet();
+	f.remove_application_blocks(*b"test");
+
+	file.rewind().unwrap();
+	f.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	assert!(f.seek_table().is_none());
+	assert!(f.cue_sheet().is_none());
+	assert!(f.application_blocks().is_empty());
+	assert_eq!(f.vorbis_comments().cloned(), original_comments);
+}
+
+#[test_log::test]
+fn tag_write_retains_stream_blocks() {
+	let mut file = temp_file("tests/taglib/data/silence-44-s.flac");
+
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	let mut tag = VorbisComments::new();
+	tag.set_artist(String::from("Foo Artist"));
+	tag.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let f2 = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	assert_eq!(f2.seek_table(), f.seek_table());
+	assert_eq!(f2.cue_sheet(), f.cue_sheet());
+	assert_eq!(f2.application_blocks(), f.application_blocks());
+}
+
+#[test_log::test]
+fn write_retains_unparsed_stream_blocks() {
+	// An APPLICATION block that is too small to hold an ID, directly after STREAMINFO
+	let mut data = std::fs::read("tests/files/assets/minimal/full_test.flac").unwrap();
+	let stream_info_end = data.windows(4).position(|w| w == b"fLaC").unwrap() + 4 + 4 + 34;
+	data.splice(stream_info_end..stream_info_end, [2, 0, 0, 2, 0xAB, 0xCD]);
+
+	let mut file = std::io::Cursor::new(data);
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(f.application_blocks().is_empty());
+
+	f.vorbis_comments_mut()
+		.unwrap()
+		.set_title(String::from("Bar title"));
+
+	file.rewind().unwrap();
+	f.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let data = file.into_inner();
+	let (blocks, _) = metadata_blocks(&data);
+	assert!(blocks.contains(&2));
+	assert!(data.windows(6).any(|w| w == [2, 0, 0, 2, 0xAB, 0xCD]));
+
+	let f = FlacFile::read_from(&mut std::io::Cursor::new(data), ParseOptions::new()).unwrap();
+	assert_eq!(
+		f.vorbis_comments().unwrap().title().as_deref(),
+		Some("Bar title")
+	);
+}
+
+#[test_log::test]
+fn write_retains_unchanged_cue_sheet() {
+	// A non-ASCII media catalog number, and an ISRC that is too short
+	let mut cue_sheet = vec![0; 128 + 8 + 259 + 1 + 36];
+	cue_sheet[..4].copy_from_slice(b"Caf\xE9");
+	cue_sheet[395] = 1;
+	cue_sheet[396 + 8] = 170;
+	cue_sheet[396 + 9..396 + 12].copy_from_slice(b"ABC");
+
+	// Block type 5 (CUESHEET), followed by the 24-bit size
+	let mut block = (cue_sheet.len() as u32).to_be_bytes().to_vec();
+	block[0] = 5;
+	block.extend(&cue_sheet);
+
+	let mut data = std::fs::read("tests/files/assets/minimal/full_test.flac").unwrap();
+	let stream_info_end = data.windows(4).position(|w| w == b"fLaC").unwrap() + 4 + 4 + 34;
+	data.splice(stream_info_end..stream_info_end, block.iter().copied());
+
+	let mut file = std::io::Cursor::new(data);
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(f.cue_sheet().unwrap().media_catalog_number.is_empty());
+	assert_eq!(
+		f.cue_sheet().unwrap().tracks[0].isrc.as_deref(),
+		Some("ABC")
+	);
+
+	f.vorbis_comments_mut()
+		.unwrap()
+		.set_title(String::from("Bar title"));
+
+	file.rewind().unwrap();
+	f.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let data = file.into_inner();
+	assert!(data.windows(block.len()).any(|w| w == block));
+}
+
+#[test_log::test]
+#[cfg(feature = "flac_verification")]
+fn verify_md5() {
+	for path in [
+		"tests/files/assets/minimal/full_test.flac",
+		"tests/files/assets/flac_with_id3v2.flac",
+		"tests/taglib/data/sinewave.flac",
+		"tests/taglib/data/silence-44-s.flac",
+	] {
+		let mut file = File::open(path).unwrap();
+		let verification = lofty::flac::verify_md5(&mut file).unwrap();
+
+		assert!(verification.is_valid(), "{path}");
+		assert!(verification.invalid_frames().is_empty(), "{path}");
+	}
+}
+
+#[test_log::test]
+#[cfg(feature = "flac_verification")]
+fn verify_md5_corrupt_frame() {
+	use lofty::flac::InvalidFrameKind;
+
+	let mut data = std::fs::read("tests/taglib/data/sinewave.flac").unwrap();
+
+	let mut file = std::io::Cursor::new(&data);
+	let verification = lofty::flac::verify_md5(&mut file).unwrap();
+	let total_samples = verification.decoded_samples();
+
+	// Flip a bit in the middle of the stream
+	let corrupt_offset = data.len() / 2;
+	data[corrupt_offset] ^= 0x10;
+
+	let mut file = std::io::Cursor::new(&data);
+	let verification = lofty::flac::verify_md5(&mut file).unwrap();
+
+	assert!(!verification.is_valid());
+	assert_ne!(
+		verification.expected_signature(),
+		verification.computed_signature()
+	);
+	assert!(verification.decoded_samples() < total_samples);
+
+	let [invalid_frame] = verification.invalid_frames() else {
+		panic!("expected exactly one invalid frame");
+	};
+
+	assert!(invalid_frame.offset <= corrupt_offset as u64);
+	assert!(matches!(
+		invalid_frame.kind,
+		InvalidFrameKind::CrcMismatch | InvalidFrameKind::Malformed
+	));
+}
+
+#[test_log::test]
+#[cfg(feature = "flac_verification")]
+fn verify_md5_prediction_overflow() {
+	let mut data = std::fs::read("tests/taglib/data/sinewave.flac").unwrap();
+
+	// Corrupts a subframe such that its prediction no longer fits in an `i64`
+	data[7051] ^= 0x10;
+
+	let mut file = std::io::Cursor::new(&data);
+	let verification = lofty::flac::verify_md5(&mut file).unwrap();
+
+	assert!(!verification.is_valid());
+	assert!(!verification.invalid_frames().is_empty());
+}
+
+#[test_log::test]
+fn scan_frames_for_total_samples() {
+	for path in [
+		"tests/files/assets/minimal/full_test.flac",
+		"tests/files/assets/flac_with_id3v2.flac",
+		"tests/taglib/data/sinewave.flac",
+	] {
+		let mut data = std::fs::read(path).unwrap();
+		let expected = *FlacFile::read_from(&mut std::io::Cursor::new(&data), ParseOptions::new())
+			.unwrap()
+			.properties();
+		assert_ne!(expected.total_samples(), 0);
+
+		// Zero out the total samples in STREAMINFO, as a streamed encode would
+		let stream_info_start = data.windows(4).position(|w| w == b"fLaC").unwrap() + 8;
+		data[stream_info_start + 13] &= 0xF0;
+		data[stream_info_start + 14..stream_info_start + 18].fill(0);
+
+		let f = FlacFile::read_from(&mut std::io::Cursor::new(&data), ParseOptions::new()).unwrap();
+		assert_eq!(f.properties().total_samples(), 0, "{path}");
+		assert_eq!(f.properties().duration(), Duration::ZERO, "{path}");
+
+		let f = FlacFile::read_from(
+			&mut std::io::Cursor::new(&data),
+			ParseOptions::new().scan_frames(true),
+		)
+		.unwrap();
+		assert_eq!(*f.properties(), expected, "{path}");
+	}
+}
+
+#[test_log::test]
+fn scan_frames_for_total_samples_false_sync() {
+	for path in [
+		"tests/files/assets/minimal/full_test.flac",
+		"tests/taglib/data/sinewave.flac",
+	] {
+		let mut data = std::fs::read(path).unwrap();
+		let expected = FlacFile::read_from(&mut std::io::Cursor::new(&data), ParseOptions::new())
+			.unwrap()
+			.properties()
+			.total_samples();
+
+		let stream_info_start = data.windows(4).position(|w| w == b"fLaC").unwrap() + 8;
+		data[stream_info_start + 13] &= 0xF0;
+		data[stream_info_start + 14..stream_info_start + 18].fill(0);
+
+		// A copy of the first frame's header at the end of the stream, which can't possibly be
+		// a real frame
+		let (_, first_frame) = metadata_blocks(&data);
+		let header = data[first_frame..first_frame + 16].to_vec();
+		data.extend(header);
+
+		let f = FlacFile::read_from(
+			&mut std::io::Cursor::new(&data),
+			ParseOptions::new().scan_frames(true),
+		)
+		.unwrap();
+		assert_eq!(f.properties().total_samples(), expected, "{path}");
+	}
+}
+
+// Returns the type of each metadata block, and the offset of the first frame
+fn metadata_blocks(data: &[u8]) -> (Vec<u8>, usize) {
+	let mut blocks = Vec::new();
+	let mut pos = data.windows(4).position(|w| w == b"fLaC").unwrap() + 4;
+	loop {
+		let header = data[pos];
+		let len = u32::from_be_bytes([0, data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+		blocks.push(header & 0x7F);
+		pos += 4 + len;
+
+		if header & 0x80 != 0 {
+			return (blocks, pos);
+		}
+	}
+}
+
+#[test_log::test]
+fn write_block_order() {
+	let mut file = temp_file("tests/taglib/data/silence-44-s.flac");
+
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	f.insert_picture(
+		Picture::unchecked(vec![0; 64])
+			.pic_type(PictureType::CoverFront)
+			.build(),
+		None,
+	)
+	.unwrap();
+	f.set_seek_table(SeekTable::new(vec![SeekPoint::placeholder()]));
+
+	let order = FlacBlockOrder::with_priority(&[
+		FlacBlockType::VorbisComments,
+		FlacBlockType::SeekTable,
+		FlacBlockType::Padding,
+		FlacBlockType::Picture,
+	]);
+	f.save_to(&mut file, WriteOptions::new().flac_block_order(Some(order)))
+		.unwrap();
+
+	file.rewind().unwrap();
+	let mut data = Vec::new();
+	file.read_to_end(&mut data).unwrap();
+	let (blocks, _) = metadata_blocks(&data);
+
+	// STREAMINFO, VORBIS_COMMENT, SEEKTABLE, PADDING, PICTURE (x2), CUESHEET
+	assert_eq!(blocks, [0, 4, 3, 1, 6, 6, 5]);
+
+	// The tag and stream blocks are untouched
+	file.rewind().unwrap();
+	let reread = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.vorbis_comments(), f.vorbis_comments());
+	assert_eq!(reread.pictures().len(), f.pictures().len());
+	assert_eq!(reread.seek_table(), f.seek_table());
+	assert_eq!(reread.cue_sheet(), f.cue_sheet());
+}
+
+#[test_log::test]
+fn regenerate_seek_table() {
+	let mut file = temp_file("tests/taglib/data/sinewave.flac");
+
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	let sample_rate = u64::from(f.properties().sample_rate());
+	let total_samples = f.properties().total_samples();
+
+	// 100ms intervals, smaller than a single frame
+	let interval = Duration::from_millis(100);
+	f.save_to(
+		&mut file,
+		WriteOptions::new().flac_seek_table_interval(Some(interval)),
+	)
+	.unwrap();
+
+	file.rewind().unwrap();
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	let seek_table = f.seek_table().unwrap();
+
+	file.rewind().unwrap();
+	let mut data = Vec::new();
+	file.read_to_end(&mut data).unwrap();
+	let (_, stream_start) = metadata_blocks(&data);
+
+	assert_eq!(seek_table.points[0].sample_number, 0);
+	assert_eq!(seek_table.points[0].stream_offset, 0);
+
+	let interval_samples = sample_rate / 10;
+	for (idx, point) in seek_table.points.iter().enumerate() {
+		assert!(!point.is_placeholder());
+
+		// Every point refers to a frame
+		let frame_start = stream_start + point.stream_offset as usize;
+		assert_eq!(data[frame_start], 0xFF);
+		assert_eq!(data[frame_start + 1] & 0xFE, 0xF8);
+
+		if let Some(next) = seek_table.points.get(idx + 1) {
+			assert!(next.sample_number > point.sample_number);
+			assert!(next.stream_offset > point.stream_offset);
+		}
+	}
+
+	// No gap between points is larger than the interval plus a frame
+	let max_frame_samples = u64::from(f.properties().max_block_size());
+	for pair in seek_table.points.windows(2) {
+		assert!(
+			pair[1].sample_number - pair[0].sample_number <= interval_samples + max_frame_samples
+		);
+	}
+
+	let last = seek_table.points.last().unwrap();
+	assert!(last.sample_number < total_samples);
+
+	// The audio is untouched
+	assert_eq!(f.properties().total_samples(), total_samples);
+}
+
+#[test_log::test]
+fn strip_unknown_application_blocks() {
+	let mut file = temp_file("tests/files/assets/application_block_last.flac");
+
+	let mut f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert_eq!(f.application_blocks().len(), 1);
+	assert!(!f.application_blocks()[0].is_registered());
+
+	f.push_application_block(ApplicationBlock::new(*b"riff", vec![1, 2, 3]));
+	f.save_to(
+		&mut file,
+		WriteOptions::new().flac_strip_unknown_application_blocks(true),
+	)
+	.unwrap();
+
+	file.rewind().unwrap();
+	let f = FlacFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	assert_eq!(f.application_blocks().len(), 1);
+	assert_eq!(f.application_blocks()[0].id, *b"riff");
+	assert_eq!(f.application_blocks()[0].data, [1, 2, 3]);
+}
@@ -1,10 +1,15 @@
-use lofty::config::ParseOptions;
+use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::FileType;
+use lofty::mp4::{
+	AtomData, Ilst, Mp4AudioTrack, Mp4Chapter, Mp4File, Mp4WriteStrategy, QuickTimeItem,
+	QuickTimeKey, QuickTimeMetadata,
+};
 use lofty::prelude::*;
 use lofty::probe::Probe;
-use lofty::tag::TagType;
+use lofty::tag::{ItemKey, TagType};
 
-use std::io::Seek;
+use std::io::{Cursor, Seek};
+use std::time::Duration;
 
 #[test_log::test]
 fn read() {
@@ -73,3 +78,481 @@ fn read_no_properties() {
 fn read_no_tags() {
 	crate::util::no_tag_test("tests/files/assets/minimal/m4a_codec_aac.m4a", None);
 }
+
+#[test_log::test]
+fn chapters() {
+	let original = std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap();
+	let mut file = Cursor::new(original.clone());
+
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(mp4.chapters().is_empty());
+
+	let original_duration = mp4.properties().duration();
+
+	let chapters = vec![
+		Mp4Chapter::new(Duration::ZERO, String::from("Introduction")),
+		Mp4Chapter::new(Duration::from_millis(1500), String::from("Chapter 1")),
+		Mp4Chapter::new(
+			Duration::from_secs(3),
+			String::from("Chapter 2 \u{2014} \u{1F4D6}"),
+		),
+	];
+	mp4.set_chapters(chapters.clone());
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.chapters(), chapters.as_slice());
+	assert_eq!(mp4.properties().duration(), original_duration);
+	assert_eq!(mp4.ilst().unwrap().artist().as_deref(), Some("Foo artist"));
+
+	// Replacing the chapters should reuse the space of the old chapter track
+	let written_len = file.get_ref().len();
+	mp4.chapters_mut()[1].title = String::from("Chapter 3");
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+	assert_eq!(file.get_ref().len(), written_len);
+
+	file.rewind().unwrap();
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.chapters()[1].title, "Chapter 3");
+
+	// And removing them should leave the file at its original size
+	mp4.remove_chapters();
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+	assert_eq!(file.get_ref().len(), original.len());
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(mp4.chapters().is_empty());
+	assert_eq!(mp4.properties().duration(), original_duration);
+}
+
+#[test_log::test]
+fn chapters_in_place() {
+	// The first chunk offset of the audio track
+	fn first_chunk_offset(bytes: &[u8]) -> u32 {
+		let stco = bytes
+			.windows(4)
+			.position(|window| window == b"stco")
+			.unwrap();
+		u32::from_be_bytes(bytes[stco + 12..stco + 16].try_into().unwrap())
+	}
+
+	// Move `moov` in front of `mdat`, with some padding after it
+	let mut file =
+		Cursor::new(std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap());
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::new().mp4_fast_start(true))
+		.unwrap();
+
+	let fast_start = file.get_ref().clone();
+	let audio_offset = first_chunk_offset(&fast_start);
+
+	file.rewind().unwrap();
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	let chapters = vec![
+		Mp4Chapter::new(Duration::ZERO, String::from("Introduction")),
+		Mp4Chapter::new(Duration::from_millis(1500), String::from("Chapter 1")),
+	];
+	mp4.set_chapters(chapters.clone());
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	// The new `moov` fits in the padding, so the audio stays where it is
+	let written = file.get_ref();
+	assert_eq!(first_chunk_offset(written), audio_offset);
+	assert_eq!(
+		written[audio_offset as usize..fast_start.len()],
+		fast_start[audio_offset as usize..]
+	);
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.chapters(), chapters.as_slice());
+}
+
+#[test_log::test]
+fn quicktime() {
+	let original = std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap();
+	let mut file = Cursor::new(original.clone());
+
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(mp4.quicktime().is_none());
+
+	let mut quicktime = QuickTimeMetadata::new();
+	quicktime.set_title(String::from("Foo title"));
+	// The `mdta` items are read before the `udta` text atoms
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::Mdta(String::from("com.apple.quicktime.location.ISO6709")),
+		AtomData::UTF8(String::from("+27.1281-100.2508+000.000/")),
+	));
+	quicktime.push(QuickTimeItem::new(
+		QuickTimeKey::UserData(*b"\xa9day"),
+		AtomData::UTF8(String::from("2024")),
+	));
+	mp4.set_quicktime(quicktime.clone());
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.quicktime(), Some(&quicktime));
+	assert_eq!(mp4.ilst().unwrap().artist().as_deref(), Some("Foo artist"));
+
+	// Generic `Tag` writes go through the same path
+	let mut file = Cursor::new(file.into_inner());
+	let mut tagged_file = Probe::new(&mut file)
+		.guess_file_type()
+		.unwrap()
+		.read()
+		.unwrap();
+
+	let tag = tagged_file.tag_mut(TagType::QuickTime).unwrap();
+	assert_eq!(tag.title().as_deref(), Some("Foo title"));
+	assert_eq!(tag.get_string(ItemKey::Year), None);
+	assert_eq!(tag.get_string(ItemKey::RecordingDate), Some("2024"));
+
+	tag.set_title(String::from("Bar title"));
+
+	file.rewind().unwrap();
+	tagged_file
+		.tag(TagType::QuickTime)
+		.unwrap()
+		.save_to(&mut file, WriteOptions::default())
+		.unwrap();
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	let quicktime = mp4.quicktime().unwrap();
+	assert_eq!(quicktime.title().as_deref(), Some("Bar title"));
+	assert_eq!(quicktime.len(), 2);
+
+	// Removing the tag should leave the file at its original size
+	file.rewind().unwrap();
+	TagType::QuickTime
+		.remove_from(&mut file, WriteOptions::default())
+		.unwrap();
+	assert_eq!(file.get_ref().len(), original.len());
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(mp4.quicktime().is_none());
+	assert_eq!(mp4.ilst().unwrap().artist().as_deref(), Some("Foo artist"));
+}
+
+#[test_log::test]
+fn tracks() {
+	let mut file =
+		Cursor::new(std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap());
+
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	let [audio_track] = mp4.tracks() else {
+		panic!("Expected a single track, got: {:?}", mp4.tracks());
+	};
+	assert_eq!(audio_track.handler_type(), *b"soun");
+	assert_eq!(audio_track.codec(), Some(*b"mp4a"));
+	assert!(audio_track.is_enabled());
+
+	let audio_track = audio_track.clone();
+	let duration = mp4.properties().duration();
+
+	// The chapter track should show up alongside the audio track
+	mp4.set_chapters(vec![Mp4Chapter::new(Duration::ZERO, String::from("Intro"))]);
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	let [first, chapter_track] = mp4.tracks() else {
+		panic!("Expected two tracks, got: {:?}", mp4.tracks());
+	};
+	assert_eq!(first, &audio_track);
+	assert_eq!(chapter_track.handler_type(), *b"text");
+	assert_eq!(chapter_track.codec(), Some(*b"text"));
+	assert_eq!(chapter_track.language(), Some("und"));
+	assert!(!chapter_track.is_enabled());
+
+	// Selecting the audio track by ID, or a missing one, should use the same track
+	for selection in [Mp4AudioTrack::Id(audio_track.id()), Mp4AudioTrack::Id(1234)] {
+		file.rewind().unwrap();
+		let mp4 =
+			Mp4File::read_from(&mut file, ParseOptions::new().mp4_audio_track(selection)).unwrap();
+		assert_eq!(mp4.properties().duration(), duration);
+	}
+}
+
+#[test_log::test]
+fn fragmented() {
+	// The same stream as `m4a_codec_aac.m4a`, split into two movie fragments, with a `mfra` index
+	let mut file = Cursor::new(std::fs::read("tests/files/assets/fragmented.m4a").unwrap());
+	let unfragmented = Mp4File::read_from(
+		&mut Cursor::new(std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap()),
+		ParseOptions::new(),
+	)
+	.unwrap();
+
+	let mut mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(
+		mp4.properties().duration(),
+		unfragmented.properties().duration()
+	);
+	assert_eq!(
+		mp4.properties().audio_bitrate(),
+		unfragmented.properties().audio_bitrate()
+	);
+
+	// Grow the tag, moving all of the fragments
+	let ilst = mp4.ilst_mut().unwrap();
+	ilst.set_title("Foo title ".repeat(200));
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(
+		mp4.ilst().unwrap().title().as_deref(),
+		Some(&*"Foo title ".repeat(200))
+	);
+	assert_eq!(
+		mp4.properties().duration(),
+		unfragmented.properties().duration()
+	);
+
+	let contents = file.into_inner();
+	let offset_at =
+		|pos: usize| u64::from_be_bytes(contents[pos..pos + 8].try_into().unwrap()) as usize;
+
+	// The first fragment has an absolute base data offset, pointing to the contents of its `mdat`
+	let tfhd = contents.windows(4).position(|w| w == b"tfhd").unwrap();
+	let base_data_offset = offset_at(tfhd + 12);
+	assert_eq!(&contents[base_data_offset - 4..base_data_offset], b"mdat");
+
+	// And every `tfra` entry should still point to a `moof`
+	let tfra = contents.windows(4).position(|w| w == b"tfra").unwrap();
+	for entry in 0..2 {
+		let moof_offset = offset_at(tfra + 20 + entry * 19 + 8);
+		assert_eq!(&contents[moof_offset + 4..moof_offset + 8], b"moof");
+	}
+}
+
+#[test_log::test]
+fn fragmented_huge_duration() {
+	let mut contents = std::fs::read("tests/files/assets/fragmented.m4a").unwrap();
+
+	// Give every sample in the first fragment the maximum `trex` duration
+	let trex = contents.windows(4).position(|w| w == b"trex").unwrap();
+	contents[trex + 16..trex + 20].copy_from_slice(&u32::MAX.to_be_bytes());
+
+	// And give it far more samples, with no per-sample fields
+	let trun = contents.windows(4).position(|w| w == b"trun").unwrap();
+	contents[trun + 5..trun + 8].copy_from_slice(&[0; 3]);
+	contents[trun + 8..trun + 12].copy_from_slice(&0x0100_0000_u32.to_be_bytes());
+
+	let mp4 = Mp4File::read_from(&mut Cursor::new(contents), ParseOptions::new()).unwrap();
+	// 0x0100_0000 * u32::MAX samples at 48 kHz, plus the rest of the stream
+	let first_fragment_millis = (0x0100_0000 * u64::from(u32::MAX)) / 48;
+	assert!(mp4.properties().duration() > Duration::from_millis(first_fragment_millis));
+}
+
+#[test_log::test]
+fn write_strategy() {
+	// The `moov` atom comes before the fragments, with an `ilst` followed by 990 bytes of padding
+	let original = std::fs::read("tests/files/assets/fragmented.m4a").unwrap();
+	let mut file = Cursor::new(original.clone());
+
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	let duration = mp4.properties().duration();
+	let mut ilst = mp4.ilst().unwrap().clone();
+
+	let save = |ilst: &Ilst, file: &mut Cursor<Vec<u8>>| {
+		file.rewind().unwrap();
+		let strategy = ilst
+			.save_to_with_report(file, WriteOptions::default())
+			.unwrap();
+
+		file.rewind().unwrap();
+		let mp4 = Mp4File::read_from(file, ParseOptions::new()).unwrap();
+		assert_eq!(mp4.properties().duration(), duration);
+		assert_eq!(mp4.ilst().cloned().unwrap_or_default(), *ilst);
+
+		strategy
+	};
+
+	// Small edits fit in the padding
+	ilst.set_title("Foo title ".repeat(50));
+	assert_eq!(save(&ilst, &mut file), Mp4WriteStrategy::InPlace);
+	assert_eq!(file.get_ref().len(), original.len());
+
+	// Large ones have to move the fragments, leaving padding behind for the next edit
+	ilst.set_title("Foo title ".repeat(200));
+	assert_eq!(save(&ilst, &mut file), Mp4WriteStrategy::DataMoved);
+
+	let padded_len = file.get_ref().len();
+	assert!(padded_len > original.len() + 1500);
+
+	ilst.set_title("Bar title ".repeat(200));
+	assert_eq!(save(&ilst, &mut file), Mp4WriteStrategy::InPlace);
+	assert_eq!(file.get_ref().len(), padded_len);
+
+	// Removing the tag leaves a `free` atom after `moov`, which the next write can make use of
+	assert_eq!(save(&Ilst::new(), &mut file), Mp4WriteStrategy::InPlace);
+	assert_eq!(file.get_ref().len(), padded_len);
+
+	ilst.set_title(String::from("Foo title"));
+	assert_eq!(save(&ilst, &mut file), Mp4WriteStrategy::InPlace);
+	assert_eq!(file.get_ref().len(), padded_len);
+
+	// The `moov` atom is at the end of this file, so it can always be resized
+	let original = std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap();
+	let mut file = Cursor::new(original.clone());
+
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	let duration = mp4.properties().duration();
+	let mut ilst = mp4.ilst().unwrap().clone();
+
+	ilst.set_title("Foo title ".repeat(200));
+
+	file.rewind().unwrap();
+	let strategy = ilst
+		.save_to_with_report(&mut file, WriteOptions::default())
+		.unwrap();
+	assert_eq!(strategy, Mp4WriteStrategy::MoovResized);
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.properties().duration(), duration);
+	assert_eq!(mp4.ilst(), Some(&ilst));
+}
+
+#[test_log::test]
+fn fast_start() {
+	fn top_level_atoms(bytes: &[u8]) -> Vec<&[u8]> {
+		let mut atoms = Vec::new();
+
+		let mut pos = 0;
+		while pos < bytes.len() {
+			let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+			atoms.push(&bytes[pos + 4..pos + 8]);
+			pos += len;
+		}
+
+		atoms
+	}
+
+	// The first chunk offset in the `stco` atom
+	fn first_chunk_offset(bytes: &[u8]) -> usize {
+		let stco = bytes
+			.windows(4)
+			.position(|window| window == b"stco")
+			.unwrap();
+		u32::from_be_bytes(bytes[stco + 12..stco + 16].try_into().unwrap()) as usize
+	}
+
+	// The `moov` atom is at the end of this file
+	let original = std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap();
+	assert_eq!(
+		top_level_atoms(&original),
+		[&b"ftyp"[..], b"free", b"mdat", b"moov"]
+	);
+
+	let mut file = Cursor::new(original.clone());
+
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	let duration = mp4.properties().duration();
+	let mut ilst = mp4.ilst().unwrap().clone();
+
+	let write_options = WriteOptions::new().mp4_fast_start(true);
+
+	ilst.set_title(String::from("Foo title"));
+
+	file.rewind().unwrap();
+	let strategy = ilst.save_to_with_report(&mut file, write_options).unwrap();
+	assert_eq!(strategy, Mp4WriteStrategy::DataMoved);
+
+	// `moov` is now in front of `mdat`, with padding for future writes
+	assert_eq!(
+		top_level_atoms(file.get_ref()),
+		[&b"ftyp"[..], b"free", b"moov", b"free", b"mdat"]
+	);
+
+	// The chunk offsets still point to the same data
+	let original_offset = first_chunk_offset(&original);
+	let new_offset = first_chunk_offset(file.get_ref());
+	assert_ne!(original_offset, new_offset);
+	assert_eq!(
+		original[original_offset..original_offset + 32],
+		file.get_ref()[new_offset..new_offset + 32]
+	);
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.properties().duration(), duration);
+	assert_eq!(mp4.ilst(), Some(&ilst));
+
+	// Now that `moov` is in place, writes can happen in place again
+	let len = file.get_ref().len();
+
+	ilst.set_title(String::from("Bar title"));
+
+	file.rewind().unwrap();
+	let strategy = ilst.save_to_with_report(&mut file, write_options).unwrap();
+	assert_eq!(strategy, Mp4WriteStrategy::InPlace);
+	assert_eq!(file.get_ref().len(), len);
+
+	file.rewind().unwrap();
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(mp4.properties().duration(), duration);
+	assert_eq!(mp4.ilst(), Some(&ilst));
+
+	// Saving the whole file works the same way
+	let mut file = Cursor::new(original.clone());
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	file.rewind().unwrap();
+	mp4.save_to(&mut file, write_options).unwrap();
+	assert_eq!(
+		top_level_atoms(file.get_ref()),
+		[&b"ftyp"[..], b"free", b"moov", b"free", b"mdat"]
+	);
+
+	file.rewind().unwrap();
+	let new_mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(new_mp4.properties().duration(), duration);
+	assert_eq!(new_mp4.ilst(), mp4.ilst());
+}
+
+#[test_log::test]
+fn fast_start_offset_overflow() {
+	let mut original = std::fs::read("tests/files/assets/minimal/m4a_codec_aac.m4a").unwrap();
+
+	// A chunk offset that can't be moved any further without overflowing the `stco` entry
+	let stco = original
+		.windows(4)
+		.position(|window| window == b"stco")
+		.unwrap();
+	original[stco + 12..stco + 16].copy_from_slice(&(u32::MAX - 16).to_be_bytes());
+
+	let mut file = Cursor::new(original.clone());
+	let mp4 = Mp4File::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	file.rewind().unwrap();
+	assert!(
+		mp4.save_to(&mut file, WriteOptions::new().mp4_fast_start(true))
+			.is_err()
+	);
+}
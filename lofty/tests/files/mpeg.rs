@@ -1,6 +1,7 @@
 use crate::util::temp_file;
 use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::{BoundTaggedFile, FileType};
+use lofty::id3::lyrics3::Lyrics3Version;
 use lofty::id3::v2::{Frame, FrameId, Id3v2Tag, KeyValueFrame};
 use lofty::mpeg::MpegFile;
 use lofty::prelude::*;
@@ -8,7 +9,7 @@ use lofty::probe::Probe;
 use lofty::tag::{Tag, TagType};
 
 use std::borrow::Cow;
-use std::io::Seek;
+use std::io::{Cursor, Seek};
 
 #[test_log::test]
 fn read() {
@@ -366,6 +367,82 @@ fn remove_ape() {
 	crate::util::remove_tag_test("tests/files/assets/minimal/full_test.mp3", TagType::Ape);
 }
 
+#[test_log::test]
+fn lyrics3() {
+	let original = std::fs::read("tests/files/assets/minimal/full_test.mp3").unwrap();
+
+	// [audio][APE][Lyrics3v2][ID3v1]
+	let lyrics3 = b"LYRICSBEGININD0000210LYR00010Foo lyricsEAR00010Bar artist000057LYRICS200";
+	let mut bytes = original.clone();
+	bytes.splice(
+		original.len() - 128..original.len() - 128,
+		lyrics3.iter().copied(),
+	);
+
+	let mut file = Cursor::new(bytes.clone());
+	let mpeg_file = MpegFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(mpeg_file.ape().is_some());
+	assert!(mpeg_file.id3v1().is_some());
+
+	let lyrics3_tag = mpeg_file.lyrics3().unwrap();
+	assert_eq!(lyrics3_tag.version, Lyrics3Version::V2);
+	assert_eq!(lyrics3_tag.lyrics.as_deref(), Some("Foo lyrics"));
+	assert_eq!(lyrics3_tag.artist().as_deref(), Some("Bar artist"));
+
+	// The Lyrics3 tag is read only, so it should be left alone when saving
+	file.rewind().unwrap();
+	mpeg_file
+		.save_to(&mut file, WriteOptions::default())
+		.unwrap();
+
+	file.rewind().unwrap();
+	let tagged_file = Probe::new(&mut file)
+		.guess_file_type()
+		.unwrap()
+		.read()
+		.unwrap();
+
+	let tag = tagged_file.tag(TagType::Lyrics3).unwrap();
+	assert_eq!(tag.get_string(ItemKey::Lyrics), Some("Foo lyrics"));
+	assert_eq!(tag.artist().as_deref(), Some("Bar artist"));
+
+	let mut file = Cursor::new(bytes);
+	TagType::Lyrics3
+		.remove_from(&mut file, WriteOptions::default())
+		.unwrap();
+	assert_eq!(file.into_inner(), original);
+}
+
+#[test_log::test]
+fn lyrics3_invalid_field_size() {
+	use lofty::config::ParsingMode;
+
+	let original = std::fs::read("tests/files/assets/minimal/full_test.mp3").unwrap();
+
+	let lyrics3 = b"LYRICSBEGINEAR00010Bar artistLYR000x0000037LYRICS200";
+	let mut bytes = original.clone();
+	bytes.splice(
+		original.len() - 128..original.len() - 128,
+		lyrics3.iter().copied(),
+	);
+
+	let mpeg_file = MpegFile::read_from(&mut Cursor::new(&bytes), ParseOptions::new()).unwrap();
+	assert!(mpeg_file.id3v1().is_some());
+
+	// Everything up to the invalid field is kept
+	let lyrics3_tag = mpeg_file.lyrics3().unwrap();
+	assert_eq!(lyrics3_tag.artist().as_deref(), Some("Bar artist"));
+	assert!(lyrics3_tag.lyrics.is_none());
+
+	assert!(
+		MpegFile::read_from(
+			&mut Cursor::new(&bytes),
+			ParseOptions::new().parsing_mode(ParsingMode::Strict)
+		)
+		.is_err()
+	);
+}
+
 #[test_log::test]
 fn read_and_write_tpil_frame() {
 	let key_value_pairs = vec![
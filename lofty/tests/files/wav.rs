@@ -1,10 +1,17 @@
-use lofty::config::ParseOptions;
+use crate::util::temp_file;
+use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::FileType;
+use lofty::iff::wav::{
+	AcidChunk, BextChunk, BextLoudness, CuePoint, CueRegion, InstrumentChunk, Ixml, SampleLoop,
+	SamplerChunk, WavContainer, WavFile, WavFormat,
+};
 use lofty::prelude::*;
 use lofty::probe::Probe;
-use lofty::tag::TagType;
+use lofty::tag::{ItemKey, Tag, TagType};
 
-use std::io::Seek;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, Write};
+use std::time::Duration;
 
 #[test_log::test]
 fn read() {
@@ -104,6 +111,144 @@ fn issue_174_divide_by_zero() {
 	assert_eq!(file.file_type(), FileType::Wav);
 }
 
+fn wav_with_fmt(fmt: &[u8], fact: Option<u32>, data_len: usize) -> WavFile {
+	let mut chunks = Vec::new();
+	chunks.extend(b"fmt ");
+	chunks.extend((fmt.len() as u32).to_le_bytes());
+	chunks.extend(fmt);
+	if let Some(fact) = fact {
+		chunks.extend(b"fact");
+		chunks.extend(4_u32.to_le_bytes());
+		chunks.extend(fact.to_le_bytes());
+	}
+	chunks.extend(b"data");
+	chunks.extend((data_len as u32).to_le_bytes());
+	chunks.resize(chunks.len() + data_len.next_multiple_of(2), 0);
+
+	let mut file = Vec::new();
+	file.extend(b"RIFF");
+	file.extend((chunks.len() as u32 + 4).to_le_bytes());
+	file.extend(b"WAVE");
+	file.extend(chunks);
+
+	WavFile::read_from(&mut Cursor::new(file), ParseOptions::new()).unwrap()
+}
+
+fn fmt_chunk(
+	format_tag: u16,
+	channels: u16,
+	sample_rate: u32,
+	bytes_per_second: u32,
+	block_align: u16,
+	bits_per_sample: u16,
+	extra: &[u8],
+) -> Vec<u8> {
+	let mut fmt = Vec::new();
+	fmt.extend(format_tag.to_le_bytes());
+	fmt.extend(channels.to_le_bytes());
+	fmt.extend(sample_rate.to_le_bytes());
+	fmt.extend(bytes_per_second.to_le_bytes());
+	fmt.extend(block_align.to_le_bytes());
+	fmt.extend(bits_per_sample.to_le_bytes());
+	fmt.extend((extra.len() as u16).to_le_bytes());
+	fmt.extend(extra);
+	fmt
+}
+
+#[test_log::test]
+fn mulaw_properties() {
+	// Telephony audio, which often lacks a `fact` chunk
+	let file = wav_with_fmt(&fmt_chunk(0x0007, 1, 8000, 8000, 1, 8, &[]), None, 8000);
+	let properties = file.properties();
+
+	assert_eq!(*properties.format(), WavFormat::MULAW);
+	assert_eq!(properties.duration(), Duration::from_secs(1));
+	assert_eq!(properties.bitrate(), 64);
+	assert_eq!(properties.bit_depth(), 8);
+	assert_eq!(properties.block_align(), 1);
+}
+
+#[test_log::test]
+fn adpcm_properties() {
+	// IMA ADPCM, 505 samples per 256 byte block
+	let fmt = fmt_chunk(0x0011, 1, 8000, 4055, 256, 4, &505_u16.to_le_bytes());
+
+	let file = wav_with_fmt(&fmt, None, 256 * 10);
+	let properties = file.properties();
+	assert_eq!(*properties.format(), WavFormat::IMA_ADPCM);
+	assert_eq!(properties.duration(), Duration::from_millis(631));
+	assert_eq!(properties.bitrate(), 32);
+	assert_eq!(properties.bit_depth(), 4);
+	assert_eq!(properties.block_align(), 256);
+
+	// The `fact` chunk takes precedence
+	let file = wav_with_fmt(&fmt, Some(5000), 256 * 10);
+	assert_eq!(file.properties().duration(), Duration::from_millis(625));
+
+	// GSM 06.10, 320 samples per 65 byte block
+	let fmt = fmt_chunk(0x0031, 1, 8000, 1625, 65, 0, &320_u16.to_le_bytes());
+
+	let file = wav_with_fmt(&fmt, None, 65 * 25);
+	let properties = file.properties();
+	assert_eq!(*properties.format(), WavFormat::GSM610);
+	assert_eq!(properties.duration(), Duration::from_secs(1));
+	assert_eq!(properties.bitrate(), 13);
+	assert_eq!(properties.bit_depth(), 0);
+}
+
+#[test_log::test]
+fn mpeg_properties() {
+	// No samples per block, the duration comes from the byte rate
+	let fmt = fmt_chunk(0x0055, 2, 44100, 16000, 1, 0, &[0; 12]);
+
+	let file = wav_with_fmt(&fmt, None, 32000);
+	let properties = file.properties();
+	assert_eq!(*properties.format(), WavFormat::MPEGLAYER3);
+	assert_eq!(properties.duration(), Duration::from_secs(2));
+	assert_eq!(properties.bitrate(), 128);
+}
+
+#[test_log::test]
+fn extensible_properties() {
+	const PCM_GUID: [u8; 16] = [
+		0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B,
+		0x71,
+	];
+
+	// 20-bit audio in 24-bit containers
+	let mut extra = Vec::new();
+	extra.extend(20_u16.to_le_bytes());
+	extra.extend(3_u32.to_le_bytes());
+	extra.extend(PCM_GUID);
+
+	let file = wav_with_fmt(
+		&fmt_chunk(0xFFFE, 2, 48000, 288_000, 6, 24, &extra),
+		None,
+		288_000,
+	);
+	let properties = file.properties();
+	assert_eq!(*properties.format(), WavFormat::PCM);
+	assert_eq!(properties.sub_format(), Some(PCM_GUID));
+	assert_eq!(properties.bit_depth(), 20);
+	assert_eq!(properties.duration(), Duration::from_secs(1));
+
+	// Unknown sub-formats are left for the caller
+	let mut guid = PCM_GUID;
+	guid[15] = 0;
+	extra.truncate(6);
+	extra.extend(guid);
+
+	let file = wav_with_fmt(
+		&fmt_chunk(0xFFFE, 2, 48000, 288_000, 6, 24, &extra),
+		Some(48000),
+		288_000,
+	);
+	let properties = file.properties();
+	assert_eq!(*properties.format(), WavFormat::Other(0xFFFE));
+	assert_eq!(properties.sub_format(), Some(guid));
+	assert_eq!(properties.duration(), Duration::from_secs(1));
+}
+
 #[test_log::test]
 fn read_no_properties() {
 	crate::util::no_properties_test("tests/files/assets/minimal/wav_format_pcm.wav");
@@ -124,3 +269,794 @@ fn empty_id3_chunk_skipped_644() {
 	assert_eq!(file.file_type(), FileType::Wav);
 	assert!(file.tag(TagType::Id3v2).is_none());
 }
+
+#[test_log::test]
+fn write_bext() {
+	let mut file = temp_file("tests/files/assets/minimal/wav_format_pcm.wav");
+
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert!(wav.bext().is_none());
+	let original_properties = *wav.properties();
+
+	let mut bext = BextChunk::new();
+	bext.description = String::from("Morning news");
+	bext.originator = String::from("Studio A");
+	bext.originator_reference = String::from("REF0001");
+	bext.origination_date = String::from("2024-01-02");
+	bext.origination_time = String::from("13:14:15");
+	bext.time_reference = 1_234_567;
+	bext.loudness = BextLoudness {
+		loudness_value: Some(-2300),
+		..BextLoudness::default()
+	};
+	bext.coding_history = String::from("A=PCM,F=44100,W=16,M=mono\r\n");
+	wav.set_bext(bext.clone());
+
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+
+	// The version is raised for the loudness values
+	assert_eq!(wav.bext(), Some(&BextChunk { version: 2, ..bext }));
+
+	// Everything else should be untouched
+	assert_eq!(wav.properties().duration(), original_properties.duration());
+	assert_eq!(wav.properties().bitrate(), original_properties.bitrate());
+	assert_eq!(wav.id3v2().unwrap().artist().as_deref(), Some("Foo artist"));
+	assert_eq!(
+		wav.riff_info().unwrap().artist().as_deref(),
+		Some("Bar artist")
+	);
+
+	// Writing a tag leaves the chunk alone
+	file.rewind().unwrap();
+	let mut tag = Tag::new(TagType::RiffInfo);
+	tag.set_artist(String::from("Baz artist"));
+	tag.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav_with_tag = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav_with_tag.bext(), wav.bext());
+	assert_eq!(
+		wav_with_tag.riff_info().unwrap().artist().as_deref(),
+		Some("Baz artist")
+	);
+
+	// And now remove it
+	wav.remove_bext();
+
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.bext().is_none());
+	assert_eq!(*wav.properties(), original_properties);
+}
+
+#[test_log::test]
+fn bext_tag_mappings() {
+	let mut bext = BextChunk::new();
+	bext.description = String::from("Morning news");
+	bext.originator = String::from("Studio A");
+	bext.origination_date = String::from("2024-01-02");
+	bext.origination_time = String::from("13:14:15");
+	bext.coding_history = String::from("A=PCM,F=44100,W=16,M=mono");
+
+	let mut tag = Tag::new(TagType::Id3v2);
+	bext.merge_into_tag(&mut tag);
+
+	assert_eq!(tag.get_string(ItemKey::Comment), Some("Morning news"));
+	assert_eq!(tag.get_string(ItemKey::EncodedBy), Some("Studio A"));
+	assert_eq!(
+		tag.get_string(ItemKey::RecordingDate),
+		Some("2024-01-02T13:14:15")
+	);
+	assert_eq!(
+		tag.get_string(ItemKey::EncoderSettings),
+		Some("A=PCM,F=44100,W=16,M=mono")
+	);
+
+	let mut new_bext = BextChunk::new();
+	new_bext.merge_from_tag(&tag);
+
+	assert_eq!(new_bext, bext);
+}
+
+#[test_log::test]
+fn write_xml_chunks() {
+	const AXML: &str =
+		"<ebuCoreMain><coreMetadata><title>News</title></coreMetadata></ebuCoreMain>";
+
+	let mut file = temp_file("tests/files/assets/minimal/wav_format_pcm.wav");
+
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert!(wav.ixml().is_none());
+	assert!(wav.axml().is_none());
+
+	let mut ixml = Ixml::new();
+	ixml.set("PROJECT", "Feature");
+	ixml.set("SCENE", "12A");
+	ixml.set("TAKE", "3");
+	ixml.set("NOTE", "Odd");
+	wav.set_ixml(ixml.clone());
+	wav.set_axml(String::from(AXML));
+
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.ixml(), Some(&ixml));
+	assert_eq!(wav.axml(), Some(AXML));
+
+	// Rewriting the tags keeps the XML chunks around
+	for tag_type in [TagType::RiffInfo, TagType::Id3v2] {
+		file.rewind().unwrap();
+		let mut tag = Tag::new(tag_type);
+		tag.set_title(String::from("A much longer title, to force a resize"));
+		tag.save_to(&mut file, WriteOptions::default()).unwrap();
+	}
+
+	file.rewind().unwrap();
+	let reread = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.ixml(), Some(&ixml));
+	assert_eq!(reread.axml(), Some(AXML));
+
+	// Update a single field
+	wav.ixml_mut().unwrap().set("TAKE", "4");
+	wav.remove_axml();
+
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	let ixml = wav.ixml().unwrap();
+	assert_eq!(ixml.take().as_deref(), Some("4"));
+	assert_eq!(ixml.scene().as_deref(), Some("12A"));
+	assert!(wav.axml().is_none());
+}
+
+#[test_log::test]
+fn write_cue_points() {
+	let mut file = temp_file("tests/files/assets/minimal/wav_format_pcm.wav");
+
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert!(wav.cue_points().is_empty());
+
+	let mut intro = CuePoint::new(1, 0);
+	intro.label = Some(String::from("Intro"));
+	intro.note = Some(String::from("Fade in"));
+
+	let mut chorus = CuePoint::new(2, 1000);
+	chorus.label = Some(String::from("Chorus"));
+	chorus.regions.push(CueRegion {
+		text: String::from("Loop"),
+		..CueRegion::new(500)
+	});
+
+	// No associated data
+	let outro = CuePoint::new(3, 2000);
+
+	let cue_points = vec![intro, chorus, outro];
+	wav.set_cue_points(cue_points.clone());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.cue_points(), cue_points.as_slice());
+
+	// The RIFF INFO LIST is separate from the adtl LIST
+	assert!(wav.riff_info().is_some());
+
+	// Rewriting the tags keeps the cue points around
+	for tag_type in [TagType::RiffInfo, TagType::Id3v2] {
+		file.rewind().unwrap();
+		let mut tag = Tag::new(tag_type);
+		tag.set_title(String::from("A much longer title, to force a resize"));
+		tag.save_to(&mut file, WriteOptions::default()).unwrap();
+	}
+
+	file.rewind().unwrap();
+	let reread = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.cue_points(), cue_points.as_slice());
+
+	// Duplicate IDs can't be written
+	wav.cue_points_mut().push(CuePoint::new(1, 3000));
+	file.rewind().unwrap();
+	assert!(wav.save_to(&mut file, WriteOptions::default()).is_err());
+
+	wav.remove_cue_points();
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.cue_points().is_empty());
+	assert!(wav.riff_info().is_some());
+}
+
+#[test_log::test]
+fn cue_associated_data_preserved() {
+	let mut adtl = Vec::new();
+	adtl.extend(b"adtl");
+	// A label for a cue point that doesn't exist
+	adtl.extend(b"labl");
+	adtl.extend(8_u32.to_le_bytes());
+	adtl.extend(7_u32.to_le_bytes());
+	adtl.extend(b"abc\0");
+	adtl.extend(b"file");
+	adtl.extend(4_u32.to_le_bytes());
+	adtl.extend([1, 2, 3, 4]);
+
+	// A `cue ` chunk that is too small to be valid
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	riff.extend(b"cue ");
+	riff.extend(2_u32.to_le_bytes());
+	riff.extend([0, 0]);
+	riff.extend(b"LIST");
+	riff.extend((adtl.len() as u32).to_le_bytes());
+	riff.extend(&adtl);
+	let riff_size = (riff.len() - 8) as u32;
+	riff[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+	let mut file = file_with_content(&riff);
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.cue_points().is_empty());
+
+	file.rewind().unwrap();
+	wav.set_acid(AcidChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	let chunks = riff_chunks(&content);
+	assert!(chunks.contains(&(*b"cue ", &[0, 0][..])));
+	assert!(chunks.contains(&(*b"LIST", &adtl[..])));
+
+	// Cue points relative to a block are kept intact
+	let mut cue_point = CuePoint::new(1, 100);
+	cue_point.chunk_id = *b"slnt";
+	cue_point.chunk_start = 12;
+	cue_point.block_start = 34;
+	cue_point.label = Some(String::from("Start"));
+
+	wav.set_cue_points(vec![cue_point.clone()]);
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.cue_points(), &[cue_point]);
+
+	let content = read_all(&mut file);
+	let (_, list) = riff_chunks(&content)
+		.into_iter()
+		.find(|(fourcc, list)| fourcc == b"LIST" && list.starts_with(b"adtl"))
+		.unwrap();
+	assert!(list.ends_with(&adtl[4..]));
+
+	// Only the cue point's label is removed
+	wav.remove_cue_points();
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	let chunks = riff_chunks(&content);
+	assert!(!chunks.iter().any(|(fourcc, _)| fourcc == b"cue "));
+	assert!(chunks.contains(&(*b"LIST", &adtl[..])));
+}
+
+#[test_log::test]
+fn write_sampler_chunks() {
+	let mut file = temp_file("tests/files/assets/minimal/wav_format_pcm.wav");
+
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	file.rewind().unwrap();
+
+	assert!(wav.sampler().is_none());
+	assert!(wav.instrument().is_none());
+	assert!(wav.acid().is_none());
+
+	let mut sampler = SamplerChunk::new();
+	sampler.midi_unity_note = 57;
+	sampler.loops.push(SampleLoop {
+		cue_point_id: 1,
+		..SampleLoop::new(0, 1000)
+	});
+
+	let instrument = InstrumentChunk {
+		unshifted_note: 57,
+		gain: -3,
+		..InstrumentChunk::new()
+	};
+
+	let mut acid = AcidChunk::new();
+	acid.root_note = Some(57);
+	acid.beats = 8;
+	acid.tempo = 120.0;
+
+	wav.set_sampler(sampler.clone());
+	wav.set_instrument(instrument);
+	wav.set_acid(acid);
+	wav.set_cue_points(vec![CuePoint::new(1, 0)]);
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.sampler(), Some(&sampler));
+	assert_eq!(wav.instrument(), Some(instrument));
+	assert_eq!(wav.acid(), Some(acid));
+
+	// Rewriting the tags keeps the chunks around
+	for tag_type in [TagType::RiffInfo, TagType::Id3v2] {
+		file.rewind().unwrap();
+		let mut tag = Tag::new(tag_type);
+		tag.set_title(String::from("A much longer title, to force a resize"));
+		tag.save_to(&mut file, WriteOptions::default()).unwrap();
+	}
+
+	file.rewind().unwrap();
+	let reread = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.sampler(), Some(&sampler));
+	assert_eq!(reread.acid(), Some(acid));
+
+	wav.acid_mut().unwrap().tempo = 140.0;
+	wav.remove_sampler();
+	wav.remove_instrument();
+
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.sampler().is_none());
+	assert!(wav.instrument().is_none());
+	assert_eq!(wav.acid().map(|acid| acid.tempo), Some(140.0));
+}
+
+#[test_log::test]
+fn unparsed_chunks_preserved() {
+	use lofty::config::ParsingMode;
+
+	// A `bext` chunk that is far too small to be valid
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	riff.extend(b"bext");
+	riff.extend(4_u32.to_le_bytes());
+	riff.extend(b"junk");
+	let riff_size = (riff.len() - 8) as u32;
+	riff[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+	let mut file = file_with_content(&riff);
+	assert!(
+		WavFile::read_from(
+			&mut file,
+			ParseOptions::new().parsing_mode(ParsingMode::Strict)
+		)
+		.is_err()
+	);
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.bext().is_none());
+
+	file.rewind().unwrap();
+	wav.set_acid(AcidChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	let chunks = riff_chunks(&content);
+	assert!(chunks.contains(&(*b"bext", &b"junk"[..])));
+	assert!(chunks.iter().any(|(fourcc, _)| fourcc == b"acid"));
+
+	// It can still be replaced
+	wav.set_bext(BextChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.bext(), Some(&BextChunk::new()));
+}
+
+#[test_log::test]
+fn unchanged_chunks_not_rewritten() {
+	// Only the `fmt ` and `data` chunks, followed by an `acid` chunk
+	let original = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+
+	let mut riff = original[..12].to_vec();
+	for (fourcc, content) in riff_chunks(&original) {
+		if &fourcc == b"fmt " || &fourcc == b"data" {
+			riff.extend(fourcc);
+			riff.extend((content.len() as u32).to_le_bytes());
+			riff.extend(content);
+		}
+	}
+	riff.extend(b"acid");
+	riff.extend(24_u32.to_le_bytes());
+	riff.extend([0; 24]);
+	let riff_size = (riff.len() - 8) as u32;
+	riff[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+	let mut file = file_with_content(&riff);
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.acid().is_some());
+
+	// Nothing changed, so nothing should be written
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+	assert_eq!(read_all(&mut file), riff);
+
+	// Existing chunks are replaced where they are
+	wav.acid_mut().unwrap().tempo = 120.0;
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	assert_eq!(content.len(), riff.len());
+
+	let chunks = riff_chunks(&content);
+	let original_chunks = riff_chunks(&riff);
+	assert_eq!(chunks.len(), original_chunks.len());
+	assert_eq!(chunks.last().unwrap().0, *b"acid");
+	assert_eq!(
+		chunks[..chunks.len() - 1],
+		original_chunks[..chunks.len() - 1]
+	);
+
+	file.rewind().unwrap();
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!((wav.acid().unwrap().tempo - 120.0).abs() < f32::EPSILON);
+}
+
+#[test_log::test]
+fn non_ascii_bext_preserved() {
+	let mut bext = vec![0; 602];
+	bext[..5].copy_from_slice(b"Caf\xE9!");
+	bext[256..264].copy_from_slice(b"Studio A");
+	bext.extend(b"A=PCM,T=\xA9\r\n");
+
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	append_chunk(&mut riff, *b"bext", &bext);
+
+	let mut file = file_with_content(&riff);
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.bext().unwrap().description, "Caf\u{e9}!");
+	assert_eq!(wav.bext().unwrap().coding_history, "A=PCM,T=\u{a9}\r\n");
+
+	// The chunk is untouched when only the tags change
+	wav.riff_info_mut()
+		.unwrap()
+		.set_title(String::from("Foo title"));
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	assert!(riff_chunks(&content).contains(&(*b"bext", &bext[..])));
+
+	// Only the changed fields are re-encoded
+	wav.bext_mut().unwrap().originator = String::from("Studio B");
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let mut expected = bext.clone();
+	expected[256..264].copy_from_slice(b"Studio B");
+
+	let content = read_all(&mut file);
+	assert!(riff_chunks(&content).contains(&(*b"bext", &expected[..])));
+}
+
+#[test_log::test]
+fn xml_chunks_preserved() {
+	use lofty::config::ParsingMode;
+
+	let ixml = b"<BWFXML><NOTE>Caf\xE9</NOTE></BWFXML>";
+	let axml = b"  \0\0";
+
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	append_chunk(&mut riff, *b"iXML", ixml);
+	append_chunk(&mut riff, *b"axml", axml);
+
+	let mut file = file_with_content(&riff);
+	assert!(
+		WavFile::read_from(
+			&mut file,
+			ParseOptions::new().parsing_mode(ParsingMode::Strict)
+		)
+		.is_err()
+	);
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert!(wav.ixml().is_none());
+	assert!(wav.axml().is_none());
+
+	file.rewind().unwrap();
+	wav.set_acid(AcidChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	let chunks = riff_chunks(&content);
+	assert!(chunks.contains(&(*b"iXML", &ixml[..])));
+	assert!(chunks.contains(&(*b"axml", &axml[..])));
+
+	// A padded document that isn't changed is left as-is
+	let ixml = b"<BWFXML><TAKE>3</TAKE></BWFXML>\0\0\0";
+
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	append_chunk(&mut riff, *b"iXML", ixml);
+
+	let mut file = file_with_content(&riff);
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.ixml().unwrap().take().as_deref(), Some("3"));
+
+	file.rewind().unwrap();
+	wav.set_acid(AcidChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	assert!(riff_chunks(&content).contains(&(*b"iXML", &ixml[..])));
+}
+
+#[test_log::test]
+fn truncated_cue_chunk_preserved() {
+	// Claims to hold 3 cue points, but only has room for 1
+	let mut cue = 3_u32.to_le_bytes().to_vec();
+	cue.extend(1_u32.to_le_bytes());
+	cue.extend(0_u32.to_le_bytes());
+	cue.extend(b"data");
+	cue.extend([0; 12]);
+
+	let mut riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+	append_chunk(&mut riff, *b"cue ", &cue);
+
+	let mut file = file_with_content(&riff);
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.cue_points(), [CuePoint::new(1, 0)]);
+
+	file.rewind().unwrap();
+	wav.set_acid(AcidChunk::new());
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	let content = read_all(&mut file);
+	assert!(riff_chunks(&content).contains(&(*b"cue ", &cue[..])));
+
+	// Until the cue points are changed
+	wav.cue_points_mut()[0].sample_offset = 10;
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let reread = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.cue_points(), wav.cue_points());
+}
+
+// Append a chunk to a RIFF file, updating the RIFF size
+fn append_chunk(riff: &mut Vec<u8>, fourcc: [u8; 4], content: &[u8]) {
+	riff.extend(fourcc);
+	riff.extend((content.len() as u32).to_le_bytes());
+	riff.extend(content);
+	riff.resize(riff.len().next_multiple_of(2), 0);
+
+	let riff_size = (riff.len() - 8) as u32;
+	riff[4..8].copy_from_slice(&riff_size.to_le_bytes());
+}
+
+// Split a RIFF file into its top-level chunks
+fn riff_chunks(riff: &[u8]) -> Vec<([u8; 4], &[u8])> {
+	let mut chunks = Vec::new();
+
+	let mut pos = 12;
+	while pos + 8 <= riff.len() {
+		let fourcc = riff[pos..pos + 4].try_into().unwrap();
+		let size = u32::from_le_bytes(riff[pos + 4..pos + 8].try_into().unwrap()) as usize;
+		chunks.push((fourcc, &riff[pos + 8..pos + 8 + size]));
+		pos += 8 + size.next_multiple_of(2);
+	}
+
+	chunks
+}
+
+// Convert a RIFF file to RF64/BW64, with placeholder sizes like a real >4 GiB file would have
+fn riff_to_rf64(riff: &[u8], id: [u8; 4]) -> Vec<u8> {
+	let chunks = riff_chunks(riff);
+	let data_size = chunks.iter().find(|(id, _)| id == b"data").unwrap().1.len() as u64;
+
+	let mut body = Vec::new();
+	for (fourcc, content) in chunks {
+		body.extend(fourcc);
+		if &fourcc == b"data" {
+			body.extend(u32::MAX.to_le_bytes());
+		} else {
+			body.extend((content.len() as u32).to_le_bytes());
+		}
+		body.extend(content);
+		if content.len() % 2 != 0 {
+			body.push(0);
+		}
+	}
+
+	let mut rf64 = Vec::new();
+	rf64.extend(id);
+	rf64.extend(u32::MAX.to_le_bytes());
+	rf64.extend(b"WAVE");
+	rf64.extend(b"ds64");
+	rf64.extend(28_u32.to_le_bytes());
+	rf64.extend((4 + 36 + body.len() as u64).to_le_bytes());
+	rf64.extend(data_size.to_le_bytes());
+	rf64.extend(0_u64.to_le_bytes());
+	rf64.extend(0_u32.to_le_bytes());
+	rf64.extend(body);
+	rf64
+}
+
+fn wave64_guid(fourcc: [u8; 4]) -> Vec<u8> {
+	match &fourcc {
+		b"LIST" => vec![
+			b'l', b'i', b's', b't', 0x2F, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1,
+			0x00, 0x00,
+		],
+		_ => [
+			&fourcc[..],
+			&[
+				0xF3, 0xAC, 0xD3, 0x11, 0x8C, 0xD1, 0x00, 0xC0, 0x4F, 0x8E, 0xDB, 0x8A,
+			],
+		]
+		.concat(),
+	}
+}
+
+fn riff_to_wave64(riff: &[u8]) -> Vec<u8> {
+	let mut w64 = vec![
+		b'r', b'i', b'f', b'f', 0x2E, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00,
+		0x00,
+	];
+	w64.extend(0_u64.to_le_bytes());
+	w64.extend(wave64_guid(*b"wave"));
+
+	for (fourcc, content) in riff_chunks(riff) {
+		w64.extend(wave64_guid(fourcc));
+		w64.extend((24 + content.len() as u64).to_le_bytes());
+		w64.extend(content);
+		w64.resize(w64.len().next_multiple_of(8), 0);
+	}
+
+	let len = w64.len() as u64;
+	w64[16..24].copy_from_slice(&len.to_le_bytes());
+	w64
+}
+
+fn file_with_content(content: &[u8]) -> File {
+	let mut file = tempfile::tempfile().unwrap();
+	file.write_all(content).unwrap();
+	file.rewind().unwrap();
+	file
+}
+
+fn read_all(file: &mut File) -> Vec<u8> {
+	let mut content = Vec::new();
+	file.rewind().unwrap();
+	file.read_to_end(&mut content).unwrap();
+	file.rewind().unwrap();
+	content
+}
+
+// Read a converted file, write both tags, and make sure everything is intact afterwards
+fn large_container_test(content: &[u8], container: WavContainer) -> Vec<u8> {
+	let riff = WavFile::read_from(
+		&mut file_with_content(
+			&std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap(),
+		),
+		ParseOptions::new(),
+	)
+	.unwrap();
+
+	let mut file = file_with_content(content);
+
+	let tagged_file = Probe::new(&mut file)
+		.guess_file_type()
+		.unwrap()
+		.read()
+		.unwrap();
+	assert_eq!(tagged_file.file_type(), FileType::Wav);
+	file.rewind().unwrap();
+
+	let wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.container(), container);
+	assert_eq!(wav.properties().duration(), riff.properties().duration());
+	assert_eq!(
+		wav.properties().sample_rate(),
+		riff.properties().sample_rate()
+	);
+	assert_eq!(wav.properties().channels(), riff.properties().channels());
+	assert_eq!(wav.properties().bit_depth(), riff.properties().bit_depth());
+	assert_eq!(
+		wav.riff_info().unwrap().artist().as_deref(),
+		Some("Bar artist")
+	);
+	assert_eq!(wav.id3v2().unwrap().artist().as_deref(), Some("Foo artist"));
+
+	for tag_type in [TagType::RiffInfo, TagType::Id3v2] {
+		file.rewind().unwrap();
+		let mut tag = Tag::new(tag_type);
+		tag.set_title(String::from("A much longer title, to force a resize"));
+		tag.save_to(&mut file, WriteOptions::default()).unwrap();
+	}
+
+	file.rewind().unwrap();
+	let mut wav = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(wav.container(), container);
+	assert_eq!(wav.properties().duration(), riff.properties().duration());
+	assert_eq!(
+		wav.riff_info().unwrap().title().as_deref(),
+		Some("A much longer title, to force a resize")
+	);
+	assert_eq!(
+		wav.id3v2().unwrap().title().as_deref(),
+		Some("A much longer title, to force a resize")
+	);
+
+	// And through `WavFile` itself
+	wav.set_cue_points(vec![CuePoint::new(1, 0)]);
+	file.rewind().unwrap();
+	wav.save_to(&mut file, WriteOptions::default()).unwrap();
+
+	file.rewind().unwrap();
+	let reread = WavFile::read_from(&mut file, ParseOptions::new()).unwrap();
+	assert_eq!(reread.cue_points(), wav.cue_points());
+	assert_eq!(reread.properties().duration(), riff.properties().duration());
+
+	read_all(&mut file)
+}
+
+#[test_log::test]
+fn rf64() {
+	let riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+
+	for (id, container) in [
+		(*b"RF64", WavContainer::Rf64),
+		(*b"BW64", WavContainer::Bw64),
+	] {
+		let written = large_container_test(&riff_to_rf64(&riff, id), container);
+
+		// The sizes are only stored in the `ds64` chunk
+		assert_eq!(written[..4], id);
+		assert_eq!(&written[4..8], &u32::MAX.to_le_bytes());
+
+		let riff_size = u64::from_le_bytes(written[20..28].try_into().unwrap());
+		assert_eq!(riff_size, written.len() as u64 - 8);
+	}
+}
+
+#[test_log::test]
+fn rf64_huge_sample_count() {
+	// IMA ADPCM, with the sample count deferred to the `ds64` chunk
+	let fmt = fmt_chunk(0x0011, 1, 8000, 4055, 256, 4, &505_u16.to_le_bytes());
+
+	let mut riff = Vec::new();
+	riff.extend(b"RIFF");
+	riff.extend(0_u32.to_le_bytes());
+	riff.extend(b"WAVE");
+	append_chunk(&mut riff, *b"fmt ", &fmt);
+	append_chunk(&mut riff, *b"fact", &u32::MAX.to_le_bytes());
+	append_chunk(&mut riff, *b"data", &[0; 256]);
+
+	let mut rf64 = riff_to_rf64(&riff, *b"RF64");
+	rf64[36..44].copy_from_slice(&u64::MAX.to_le_bytes());
+
+	let file = WavFile::read_from(&mut Cursor::new(rf64), ParseOptions::new()).unwrap();
+	// u64::MAX samples at 8 kHz, rounded to the nearest millisecond
+	assert_eq!(file.properties().duration(), Duration::from_millis(1 << 61));
+}
+
+#[test_log::test]
+fn wave64() {
+	let riff = std::fs::read("tests/files/assets/minimal/wav_format_pcm.wav").unwrap();
+
+	let written = large_container_test(&riff_to_wave64(&riff), WavContainer::Wave64);
+
+	let riff_size = u64::from_le_bytes(written[16..24].try_into().unwrap());
+	assert_eq!(riff_size, written.len() as u64);
+	assert_eq!(written.len() % 8, 0);
+}
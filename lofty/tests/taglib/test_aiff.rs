@@ -53,6 +53,7 @@ fn test_aifc_properties() {
 		properties.compression_type().unwrap().compression_name(),
 		"CCITT G.711 A-law"
 	);
+	assert_eq!(properties.compression_name(), Some("SGI CCITT G.711 A-law"));
 }
 
 #[test_log::test]
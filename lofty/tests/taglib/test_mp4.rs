@@ -6,7 +6,7 @@ use std::io::{Read, Seek};
 
 use lofty::config::{ParseOptions, WriteOptions};
 use lofty::file::AudioFile;
-use lofty::mp4::{Atom, AtomData, AtomIdent, Ilst, Mp4Codec, Mp4File};
+use lofty::mp4::{Atom, AtomData, AtomIdent, AudioObjectType, Ilst, Mp4Codec, Mp4File};
 use lofty::picture::{MimeType, Picture};
 use lofty::tag::{Accessor, TagExt, TagType};
 
@@ -403,7 +403,12 @@ fn test_repeated_save() {
 fn test_with_zero_length_atom() {
 	let f = get_file::<Mp4File>("tests/taglib/data/zero-length-mdat.m4a");
 	assert_eq!(f.properties().duration().as_millis(), 1115);
-	assert_eq!(f.properties().sample_rate(), Some(22050));
+	// TagLib reports the core sample rate (22050), this file is HE-AAC
+	assert_eq!(f.properties().sample_rate(), Some(44100));
+	assert_eq!(
+		f.properties().extension_audio_object_type(),
+		Some(AudioObjectType::SpectralBandReplication)
+	);
 }
 
 #[test_log::test]
@@ -461,6 +466,14 @@ fn test_remove_metadata() {
 		// Fix the length of the `udta` atom
 		new_file_bytes[2780] = 8;
 
+		// The space freed by removing the tag is given back to the trailing `free` atom, rather
+		// than shrinking the file, so give it the size of the `meta` atom back. The rewritten
+		// `free` atom is zero-filled, whereas the original holds leftover data.
+		assert_eq!(&new_file_bytes[2785 + 4..2785 + 8], b"free");
+		new_file_bytes[2785 + 3] += 45;
+		new_file_bytes.extend(std::iter::repeat_n(0, 45));
+		original_file_bytes[2785 + 8..].fill(0);
+
 		assert_eq!(original_file_bytes, new_file_bytes);
 	}
 }
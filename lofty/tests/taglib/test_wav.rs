@@ -31,7 +31,7 @@ fn test_alaw_properties() {
 	assert_eq!(f.properties().sample_rate(), 8000);
 	assert_eq!(f.properties().bit_depth(), 8);
 	// TODO: assert_eq!(f.properties().total_samples(), 28400);
-	assert_eq!(*f.properties().format(), WavFormat::Other(6));
+	assert_eq!(*f.properties().format(), WavFormat::ALAW);
 }
 
 #[test_log::test]
@@ -68,6 +68,10 @@ pub(crate) fn init_write_lookup(
 		});
 	}
 
+	insert!(map, Lyrics3, {
+		lofty::id3::lyrics3::write::remove(file, write_options)
+	});
+
 	insert!(map, RiffInfo, {
 		lofty::iff::wav::tag::RIFFInfoListRef::new(lofty::iff::wav::tag::tagitems_into_riff(
 			tag.items(),
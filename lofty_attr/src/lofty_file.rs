@@ -424,7 +424,7 @@ fn generate_audiofile_impl(file: &LoftyFile) -> syn::Result<proc_macro2::TokenSt
 
 	let tag_fields = &file.struct_info.fields.tags;
 
-	let save_to_body = get_save_to_body(file.audiofile_impl.write_fn.as_ref(), tag_fields);
+	let save_to_body = get_save_to_body(file, tag_fields);
 
 	let tag_exists = tag_exists_iter(tag_fields);
 	let tag_exists_2 = tag_exists_iter(tag_fields);
@@ -480,24 +480,32 @@ fn generate_audiofile_impl(file: &LoftyFile) -> syn::Result<proc_macro2::TokenSt
 	Ok(ret)
 }
 
-fn get_save_to_body(
-	write_fn: Option<&proc_macro2::TokenStream>,
-	tag_fields: &[FieldContents],
-) -> proc_macro2::TokenStream {
+fn get_save_to_body(file: &LoftyFile, tag_fields: &[FieldContents]) -> proc_macro2::TokenStream {
 	// Custom write fn
-	if let Some(write_fn) = write_fn {
+	if let Some(write_fn) = &file.audiofile_impl.write_fn {
 		return quote! {
 			#write_fn(&self, file, write_options)
 		};
 	}
 
+	// Read only tags are skipped rather than erroring, same as `TaggedFile`. This is only possible
+	// for internal file types, since the tag support of custom file types isn't known here.
+	let is_writable = if file.internal_details.has_internal_file_type {
+		let file_type = &file.file_type;
+		quote! { ::lofty::file::FileType::#file_type.tag_support(tag.tag_type()).is_writable() }
+	} else {
+		quote! { true }
+	};
+
 	let tag_field_save = tag_fields.iter().map(|f| {
 		let name = &f.name;
 		if f.needs_option {
 			quote! {
 				if let Some(ref tag) = self.#name {
-					file.rewind()?;
-					tag.save_to(file, write_options)?;
+					if #is_writable {
+						file.rewind()?;
+						tag.save_to(file, write_options)?;
+					}
 				}
 			}
 		} else {